@@ -0,0 +1,100 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use gyard::{op::Math, to_postfix, to_postfix_slice, InputToken};
+
+/// `1 + 2 * f ( 3 , ( 4 - 5 ) ) ^ 6 / 7` repeated `n` times, joined by `+`.
+fn expression(n: usize) -> Vec<InputToken<u32, u32, Math>> {
+    let mut infix = Vec::new();
+    for i in 0..n {
+        if i > 0 {
+            infix.push(InputToken::Operator(Math::Add));
+        }
+        infix.extend([
+            InputToken::Value(1),
+            InputToken::Operator(Math::Add),
+            InputToken::Value(2),
+            InputToken::Operator(Math::Mul),
+            InputToken::Function(0),
+            InputToken::LeftParen,
+            InputToken::Value(3),
+            InputToken::ArgSeparator,
+            InputToken::LeftParen,
+            InputToken::Value(4),
+            InputToken::Operator(Math::Sub),
+            InputToken::Value(5),
+            InputToken::RightParen,
+            InputToken::RightParen,
+            InputToken::Operator(Math::Exponent),
+            InputToken::Value(6),
+            InputToken::Operator(Math::Div),
+            InputToken::Value(7),
+        ]);
+    }
+    infix
+}
+
+/// Like `expression`, but with functions that are much larger than the operators.
+fn large_functions(n: usize) -> Vec<InputToken<u32, [u64; 16], Math>> {
+    expression(n)
+        .into_iter()
+        .map(|token| match token {
+            InputToken::Value(v) => InputToken::Value(v),
+            InputToken::LeftParen => InputToken::LeftParen,
+            InputToken::RightParen => InputToken::RightParen,
+            InputToken::Function(f) => InputToken::Function([u64::from(f); 16]),
+            InputToken::Operator(o) => InputToken::Operator(o),
+            _ => InputToken::ArgSeparator,
+        })
+        .collect()
+}
+
+/// `( ( ( ... 1 ... ) ) )` nested `n` times with an operator on every level.
+fn nested(n: usize) -> Vec<InputToken<u32, u32, Math>> {
+    let mut infix = Vec::new();
+    for _ in 0..n {
+        infix.push(InputToken::Value(1));
+        infix.push(InputToken::Operator(Math::Mul));
+        infix.push(InputToken::LeftParen);
+    }
+    infix.push(InputToken::Value(1));
+    infix.extend((0..n).map(|_| InputToken::RightParen));
+    infix
+}
+
+/// `1 ^ 1 ^ ... ^ 1` with `n` operators, which are all left on the stack until the end.
+fn chain(n: usize) -> Vec<InputToken<u32, u32, Math>> {
+    let mut infix = vec![InputToken::Value(1)];
+    for _ in 0..n {
+        infix.push(InputToken::Operator(Math::Exponent));
+        infix.push(InputToken::Value(1));
+    }
+    infix
+}
+
+fn convert(c: &mut Criterion) {
+    for (name, infix) in [
+        ("short", expression(1)),
+        ("long", expression(1000)),
+        ("nested", nested(1000)),
+    ] {
+        c.bench_function(name, |b| b.iter(|| to_postfix(black_box(infix.clone()))));
+    }
+    let infix = large_functions(1000);
+    c.bench_function("large_functions", |b| {
+        b.iter(|| to_postfix(black_box(infix.clone())))
+    });
+}
+
+/// Compares the slice fast path with the generic iterator path on the same borrowed input.
+fn slice(c: &mut Criterion) {
+    for (name, infix) in [("long", expression(1000)), ("chain", chain(1000))] {
+        c.bench_function(&format!("{name}_iter_cloned"), |b| {
+            b.iter(|| to_postfix(black_box(&infix).iter().cloned()))
+        });
+        c.bench_function(&format!("{name}_slice"), |b| {
+            b.iter(|| to_postfix_slice(black_box(&infix)))
+        });
+    }
+}
+
+criterion_group!(benches, convert, slice);
+criterion_main!(benches);
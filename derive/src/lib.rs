@@ -0,0 +1,157 @@
+//! Derive macros for `gyard`. Use them through the `derive` feature of `gyard`.
+#![forbid(unsafe_code)]
+#![warn(missing_docs)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Expr, Fields, Type};
+
+/// Implements `From<Self> for gyard::InputToken<V, F, O>`. See `gyard::IntoInputToken`.
+#[proc_macro_derive(IntoInputToken, attributes(token))]
+pub fn derive_into_input_token(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    into_input_token(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+const EXPECTED_KIND: &str =
+    "expected `value`, `function`, `operator`, `left_paren`, `right_paren` or `arg_separator`";
+
+/// The types of the generated `InputToken`
+struct Types {
+    value: Type,
+    function: Type,
+    operator: Type,
+}
+
+fn types(input: &DeriveInput) -> syn::Result<Types> {
+    let unit: Type = syn::parse_quote!(());
+    let mut types = Types {
+        value: unit.clone(),
+        function: unit.clone(),
+        operator: unit,
+    };
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("token"))
+    {
+        attr.parse_nested_meta(|meta| {
+            let ty = if meta.path.is_ident("value") {
+                &mut types.value
+            } else if meta.path.is_ident("function") {
+                &mut types.function
+            } else if meta.path.is_ident("operator") {
+                &mut types.operator
+            } else {
+                return Err(meta.error("expected `value`, `function` or `operator`"));
+            };
+            *ty = meta.value()?.parse()?;
+            Ok(())
+        })?;
+    }
+    Ok(types)
+}
+
+/// Returns the arm converting `variant`.
+fn arm(name: &syn::Ident, variant: &syn::Variant) -> syn::Result<TokenStream2> {
+    let ident = &variant.ident;
+    let mut arm = None;
+    for attr in variant
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("token"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if arm.is_some() {
+                return Err(meta.error("a variant can only be converted into one token"));
+            }
+            let kind = meta
+                .path
+                .get_ident()
+                .map(ToString::to_string)
+                .unwrap_or_default();
+            let token = match kind.as_str() {
+                "left_paren" => quote!(LeftParen),
+                "right_paren" => quote!(RightParen),
+                "arg_separator" => quote!(ArgSeparator),
+                "value" => quote!(Value),
+                "function" => quote!(Function),
+                "operator" => quote!(Operator),
+                _ => return Err(meta.error(EXPECTED_KIND)),
+            };
+            arm = Some(match kind.as_str() {
+                "left_paren" | "right_paren" | "arg_separator" => quote! {
+                    #name::#ident { .. } => ::gyard::InputToken::#token
+                },
+                _ if meta.input.peek(syn::Token![=]) => {
+                    let expr: Expr = meta.value()?.parse()?;
+                    quote! {
+                        #name::#ident { .. } => ::gyard::InputToken::#token(#expr)
+                    }
+                }
+                _ => {
+                    let pattern = match &variant.fields {
+                        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                            quote!(#name::#ident(field))
+                        }
+                        Fields::Named(fields) if fields.named.len() == 1 => {
+                            let field = &fields.named[0].ident;
+                            quote!(#name::#ident { #field: field })
+                        }
+                        _ => {
+                            return Err(meta.error(format!(
+                                "`{kind}` requires exactly one field, use `{kind} = <expr>` \
+                                 otherwise"
+                            )))
+                        }
+                    };
+                    quote! {
+                        #pattern => ::gyard::InputToken::#token(::core::convert::Into::into(field))
+                    }
+                }
+            });
+            Ok(())
+        })?;
+    }
+    arm.ok_or_else(|| {
+        Error::new(
+            variant.span(),
+            "every variant needs a `#[token(...)]` attribute",
+        )
+    })
+}
+
+fn into_input_token(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new(
+            input.span(),
+            "IntoInputToken can only be derived for enums",
+        ));
+    };
+    let Types {
+        value,
+        function,
+        operator,
+    } = types(input)?;
+    let name = &input.ident;
+    let arms = data
+        .variants
+        .iter()
+        .map(|variant| arm(name, variant))
+        .collect::<syn::Result<Vec<_>>>()?;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::core::convert::From<#name #ty_generics>
+            for ::gyard::InputToken<#value, #function, #operator> #where_clause
+        {
+            fn from(token: #name #ty_generics) -> Self {
+                match token {
+                    #(#arms,)*
+                }
+            }
+        }
+    })
+}
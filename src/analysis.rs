@@ -0,0 +1,517 @@
+//! Static analysis of postfix expressions.
+
+use alloc::vec::Vec;
+use core::hash::Hash;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, VecDeque};
+
+use crate::{ast::AstError, Call, Operator, OutputToken};
+
+/// A variable read by an expression. See `free_variables`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Variable<V> {
+    /// The variable itself.
+    pub value: V,
+    /// Positions of all tokens referencing this variable, in ascending order.
+    pub positions: Vec<usize>,
+}
+
+/// Returns all distinct values for which `is_variable` returns true, in order of their first
+/// occurrence. Use this to find out which inputs an expression depends on.
+///
+/// ```rust
+/// use gyard::{analysis::{free_variables, Variable}, op::Math, to_postfix, InputToken};
+///
+/// // price * amount + price
+/// let postfix = to_postfix::<_, (), _>([
+///     InputToken::Value("price"),
+///     InputToken::Operator(Math::Mul),
+///     InputToken::Value("amount"),
+///     InputToken::Operator(Math::Add),
+///     InputToken::Value("price"),
+/// ]).unwrap();
+/// let variables = free_variables(&postfix, |v| v.parse::<f64>().is_err());
+/// assert_eq!(variables, vec![
+///     Variable { value: &"price", positions: vec![0, 3] },
+///     Variable { value: &"amount", positions: vec![1] },
+/// ]);
+/// ```
+#[cfg(feature = "std")]
+pub fn free_variables<'a, V, F, O>(
+    postfix: impl IntoIterator<Item = &'a OutputToken<V, F, O>>,
+    mut is_variable: impl FnMut(&V) -> bool,
+) -> Vec<Variable<&'a V>>
+where
+    V: Eq + Hash + 'a,
+    F: 'a,
+    O: 'a,
+{
+    let mut variables: Vec<Variable<&V>> = Vec::new();
+    let mut index: HashMap<&V, usize> = HashMap::new();
+    for (pos, token) in postfix.into_iter().enumerate() {
+        let OutputToken::Value(value) = token else {
+            continue;
+        };
+        if let Some(&i) = index.get(value) {
+            variables[i].positions.push(pos);
+        } else if is_variable(value) {
+            index.insert(value, variables.len());
+            variables.push(Variable {
+                value,
+                positions: vec![pos],
+            });
+        }
+    }
+    variables
+}
+
+/// Size and complexity of an expression. See `metrics`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Metrics {
+    /// The number of nodes on the longest path from the root to a value, a single value has a
+    /// depth of 1.
+    pub depth: usize,
+    /// The number of tokens.
+    pub node_count: usize,
+    /// The number of operators.
+    pub operator_count: usize,
+    /// The number of function calls.
+    pub function_count: usize,
+    /// The maximum number of values on the stack while evaluating the expression.
+    pub required_stack_depth: usize,
+}
+
+/// Computes the `Metrics` of a postfix expression created by `to_postfix_with_arity`.
+///
+/// ```rust
+/// use gyard::{analysis::{metrics, Metrics}, op::Math, to_postfix_with_arity, InputToken};
+///
+/// // 1 + 2 * max ( 3 , 4 )
+/// let postfix = to_postfix_with_arity([
+///     InputToken::Value(1),
+///     InputToken::Operator(Math::Add),
+///     InputToken::Value(2),
+///     InputToken::Operator(Math::Mul),
+///     InputToken::Function("max"),
+///     InputToken::LeftParen,
+///     InputToken::Value(3),
+///     InputToken::ArgSeparator,
+///     InputToken::Value(4),
+///     InputToken::RightParen,
+/// ]).unwrap();
+/// assert_eq!(metrics(&postfix), Ok(Metrics {
+///     depth: 4,
+///     node_count: 7,
+///     operator_count: 2,
+///     function_count: 1,
+///     required_stack_depth: 4,
+/// }));
+/// ```
+pub fn metrics<V, F, O: Operator>(
+    postfix: &[OutputToken<V, Call<F>, O>],
+) -> Result<Metrics, AstError> {
+    let mut metrics = Metrics {
+        node_count: postfix.len(),
+        ..Metrics::default()
+    };
+    // The depth of every subtree on the stack
+    let mut stack: Vec<usize> = Vec::new();
+    for (pos, token) in postfix.iter().enumerate() {
+        let arity = match token {
+            OutputToken::Value(_) => 0,
+            OutputToken::Operator(operator) => {
+                metrics.operator_count += 1;
+                operator.arity()
+            }
+            OutputToken::Function(call) => {
+                metrics.function_count += 1;
+                call.arity
+            }
+        };
+        let Some(start) = stack.len().checked_sub(arity) else {
+            return Err(AstError::StackUnderflow {
+                pos,
+                required: arity,
+                available: stack.len(),
+            });
+        };
+        let depth = stack.drain(start..).max().unwrap_or(0) + 1;
+        stack.push(depth);
+        metrics.required_stack_depth = metrics.required_stack_depth.max(stack.len());
+    }
+    match stack[..] {
+        [] => Err(AstError::EmptyExpression),
+        [depth] => Ok(Metrics { depth, ..metrics }),
+        _ => Err(AstError::UnusedValues { count: stack.len() }),
+    }
+}
+
+/// Helpers for analyses over stored postfix expressions.
+///
+/// ```rust
+/// use gyard::{analysis::PostfixSlice, op::Math, OutputToken};
+///
+/// // 1 2 + 3 *
+/// let postfix: &[OutputToken<i32, (), Math>] = &[
+///     OutputToken::Value(1),
+///     OutputToken::Value(2),
+///     OutputToken::Operator(Math::Add),
+///     OutputToken::Value(3),
+///     OutputToken::Operator(Math::Mul),
+/// ];
+/// assert_eq!(postfix.values().sum::<i32>(), 6);
+/// assert_eq!(postfix.operators().count(), 2);
+/// assert!(postfix.ends_balanced(|_| 0));
+/// assert!(!postfix[..4].ends_balanced(|_| 0));
+/// ```
+pub trait PostfixSlice<V, F, O> {
+    /// Returns all values in postfix order.
+    fn values<'a>(&'a self) -> impl Iterator<Item = &'a V>
+    where
+        V: 'a;
+    /// Returns all functions in postfix order.
+    fn functions<'a>(&'a self) -> impl Iterator<Item = &'a F>
+    where
+        F: 'a;
+    /// Returns all operators in postfix order.
+    fn operators<'a>(&'a self) -> impl Iterator<Item = &'a O>
+    where
+        O: 'a;
+    /// Returns true if evaluating the expression never takes more operands than available and
+    /// leaves exactly one value. `function_arity` returns the number of arguments of a function.
+    fn ends_balanced(&self, function_arity: impl FnMut(&F) -> usize) -> bool
+    where
+        O: Operator;
+}
+
+impl<V, F, O> PostfixSlice<V, F, O> for [OutputToken<V, F, O>] {
+    fn values<'a>(&'a self) -> impl Iterator<Item = &'a V>
+    where
+        V: 'a,
+    {
+        self.iter().filter_map(|token| match token {
+            OutputToken::Value(value) => Some(value),
+            _ => None,
+        })
+    }
+
+    fn functions<'a>(&'a self) -> impl Iterator<Item = &'a F>
+    where
+        F: 'a,
+    {
+        self.iter().filter_map(|token| match token {
+            OutputToken::Function(function) => Some(function),
+            _ => None,
+        })
+    }
+
+    fn operators<'a>(&'a self) -> impl Iterator<Item = &'a O>
+    where
+        O: 'a,
+    {
+        self.iter().filter_map(|token| match token {
+            OutputToken::Operator(operator) => Some(operator),
+            _ => None,
+        })
+    }
+
+    fn ends_balanced(&self, mut function_arity: impl FnMut(&F) -> usize) -> bool
+    where
+        O: Operator,
+    {
+        let mut depth = 0usize;
+        for token in self {
+            let arity = match token {
+                OutputToken::Value(_) => 0,
+                OutputToken::Function(function) => function_arity(function),
+                OutputToken::Operator(operator) => operator.arity(),
+            };
+            let Some(remaining) = depth.checked_sub(arity) else {
+                return false;
+            };
+            depth = remaining + 1;
+        }
+        depth == 1
+    }
+}
+
+#[cfg(feature = "std")]
+/// The dependencies between a set of named expressions, i.e. the cells of a spreadsheet.
+#[derive(Debug, Clone)]
+pub struct DependencyGraph<K> {
+    keys: Vec<K>,
+    index: HashMap<K, usize>,
+    dependencies: Vec<Vec<K>>,
+    dependents: Vec<Vec<K>>,
+}
+
+/// This error is returned if the expressions of a `DependencyGraph` reference each other in a
+/// cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CycleError<K> {
+    /// All expressions that are part of a cycle or depend on one, in input order.
+    pub expressions: Vec<K>,
+    /// One of the cycles. Every expression references the next one and the first and the last
+    /// expression are the same.
+    pub path: Vec<K>,
+}
+
+impl<K: core::fmt::Debug> core::fmt::Display for CycleError<K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Circular reference")?;
+        for (index, key) in self.path.iter().enumerate() {
+            let separator = if index == 0 { ": " } else { " -> " };
+            write!(f, "{separator}{key:?}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<K: core::fmt::Debug> core::error::Error for CycleError<K> {}
+
+#[cfg(feature = "std")]
+impl<K: Clone + Eq + Hash> DependencyGraph<K> {
+    /// Builds the graph from pairs of names and postfix expressions. `reference` maps a value to
+    /// the name of the expression it refers to, or `None` if it is not a reference.
+    /// References to names without an expression are treated as external inputs.
+    ///
+    /// ```rust
+    /// use gyard::{analysis::DependencyGraph, op::Math, OutputToken};
+    ///
+    /// let expressions = [
+    ///     // C = A + B
+    ///     ("C", vec![OutputToken::<_, (), _>::Value("A"), OutputToken::Value("B"),
+    ///         OutputToken::Operator(Math::Add)]),
+    ///     // A = B * 2
+    ///     ("A", vec![OutputToken::Value("B"), OutputToken::Value("2"),
+    ///         OutputToken::Operator(Math::Mul)]),
+    ///     // B = 3
+    ///     ("B", vec![OutputToken::Value("3")]),
+    /// ];
+    /// let graph = DependencyGraph::new(
+    ///     expressions.iter().map(|(name, postfix)| (*name, postfix)),
+    ///     |value| value.parse::<f64>().is_err().then_some(*value),
+    /// );
+    /// assert_eq!(graph.recalculation_order(), Ok(vec!["B", "A", "C"]));
+    /// assert_eq!(graph.dependents(&"B"), ["C", "A"]);
+    /// ```
+    pub fn new<'a, V, F, O, I>(
+        expressions: impl IntoIterator<Item = (K, I)>,
+        mut reference: impl FnMut(&V) -> Option<K>,
+    ) -> Self
+    where
+        I: IntoIterator<Item = &'a OutputToken<V, F, O>>,
+        V: Eq + Hash + 'a,
+        F: 'a,
+        O: 'a,
+    {
+        let mut graph = Self {
+            keys: Vec::new(),
+            index: HashMap::new(),
+            dependencies: Vec::new(),
+            dependents: Vec::new(),
+        };
+        for (key, postfix) in expressions {
+            let dependencies = free_variables(postfix, |value| reference(value).is_some())
+                .into_iter()
+                .filter_map(|variable| reference(variable.value))
+                .collect();
+            graph.index.insert(key.clone(), graph.keys.len());
+            graph.keys.push(key);
+            graph.dependencies.push(dependencies);
+            graph.dependents.push(Vec::new());
+        }
+        for (i, dependencies) in graph.dependencies.iter().enumerate() {
+            for dependency in dependencies {
+                if let Some(&j) = graph.index.get(dependency) {
+                    graph.dependents[j].push(graph.keys[i].clone());
+                }
+            }
+        }
+        graph
+    }
+
+    /// Returns the names of all expressions in input order.
+    pub fn expressions(&self) -> &[K] {
+        &self.keys
+    }
+
+    /// Returns the names directly referenced by `key`, including external inputs.
+    pub fn dependencies(&self, key: &K) -> &[K] {
+        self.index
+            .get(key)
+            .map_or(&[], |&i| self.dependencies[i].as_slice())
+    }
+
+    /// Returns the expressions directly referencing `key`.
+    pub fn dependents(&self, key: &K) -> &[K] {
+        self.index
+            .get(key)
+            .map_or(&[], |&i| self.dependents[i].as_slice())
+    }
+
+    /// Returns all expressions ordered so that every expression comes after the expressions it
+    /// depends on. Independent expressions keep their input order.
+    pub fn recalculation_order(&self) -> Result<Vec<K>, CycleError<K>> {
+        let mut pending: Vec<usize> = self
+            .dependencies
+            .iter()
+            .map(|dependencies| {
+                dependencies
+                    .iter()
+                    .filter(|key| self.index.contains_key(key))
+                    .count()
+            })
+            .collect();
+        let mut queue: VecDeque<usize> =
+            (0..self.keys.len()).filter(|&i| pending[i] == 0).collect();
+        let mut order = Vec::with_capacity(self.keys.len());
+        while let Some(i) = queue.pop_front() {
+            order.push(self.keys[i].clone());
+            for dependent in &self.dependents[i] {
+                let j = self.index[dependent];
+                pending[j] -= 1;
+                if pending[j] == 0 {
+                    queue.push_back(j);
+                }
+            }
+        }
+        if order.len() < self.keys.len() {
+            return Err(CycleError {
+                expressions: (0..self.keys.len())
+                    .filter(|&i| pending[i] > 0)
+                    .map(|i| self.keys[i].clone())
+                    .collect(),
+                path: self.find_cycle().unwrap_or_default(),
+            });
+        }
+        Ok(order)
+    }
+
+    /// Returns the first cycle of references found, starting at the earliest expression in input
+    /// order that is part of a cycle or depends on one. Every expression of the path references
+    /// the next one and the first and the last expression are the same.
+    ///
+    /// ```rust
+    /// use gyard::{analysis::DependencyGraph, op::Math, OutputToken};
+    ///
+    /// let reference = |name| vec![OutputToken::<_, (), Math>::Value(name)];
+    /// let expressions = [("A", reference("B")), ("B", reference("C")), ("C", reference("B"))];
+    /// let graph = DependencyGraph::new(
+    ///     expressions.iter().map(|(name, postfix)| (*name, postfix)),
+    ///     |value| Some(*value),
+    /// );
+    /// assert_eq!(graph.find_cycle(), Some(vec!["B", "C", "B"]));
+    /// ```
+    pub fn find_cycle(&self) -> Option<Vec<K>> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Visit {
+            New,
+            OnPath,
+            Done,
+        }
+        let mut visits = vec![Visit::New; self.keys.len()];
+        for root in 0..self.keys.len() {
+            if visits[root] != Visit::New {
+                continue;
+            }
+            // The expressions on the current path and the next dependency to visit for each
+            let mut path = vec![(root, 0)];
+            visits[root] = Visit::OnPath;
+            while let Some((i, next)) = path.last_mut() {
+                let i = *i;
+                let Some(dependency) = self.dependencies[i].get(*next) else {
+                    visits[i] = Visit::Done;
+                    path.pop();
+                    continue;
+                };
+                *next += 1;
+                let Some(&j) = self.index.get(dependency) else {
+                    continue;
+                };
+                match visits[j] {
+                    Visit::New => {
+                        visits[j] = Visit::OnPath;
+                        path.push((j, 0));
+                    }
+                    Visit::OnPath => {
+                        let start = path.iter().position(|&(k, _)| k == j)?;
+                        return Some(
+                            path[start..]
+                                .iter()
+                                .chain([&(j, 0)])
+                                .map(|&(k, _)| self.keys[k].clone())
+                                .collect(),
+                        );
+                    }
+                    Visit::Done => {}
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::PostfixSlice;
+    #[cfg(feature = "std")]
+    use super::{free_variables, CycleError, DependencyGraph};
+    use crate::{op::Math, OutputToken};
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn constants_are_ignored() {
+        let postfix = [
+            OutputToken::<_, (), _>::Value(1),
+            OutputToken::Value(-1),
+            OutputToken::Operator(Math::Add),
+        ];
+        assert_eq!(free_variables(&postfix, |v| *v < 0)[0].positions, vec![1]);
+        assert!(free_variables(&postfix, |_| false).is_empty());
+    }
+
+    #[test]
+    fn function_arity_balance() {
+        // max(1, 2) with max taking two or three arguments
+        let postfix = [
+            OutputToken::<_, _, Math>::Value(1),
+            OutputToken::Value(2),
+            OutputToken::Function("max"),
+        ];
+        assert!(postfix.ends_balanced(|_| 2));
+        assert!(!postfix.ends_balanced(|_| 3));
+        assert!(!postfix.ends_balanced(|_| 1));
+        assert_eq!(postfix.functions().collect::<Vec<_>>(), [&"max"]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn cycle() {
+        let value = |v| vec![OutputToken::<_, (), Math>::Value(v)];
+        let expressions = [("a", value("b")), ("b", value("a")), ("c", value("x"))];
+        let graph = DependencyGraph::new(
+            expressions.iter().map(|(name, postfix)| (*name, postfix)),
+            |value| Some(*value),
+        );
+        assert_eq!(graph.dependencies(&"c"), ["x"]);
+        assert_eq!(
+            graph.recalculation_order(),
+            Err(CycleError {
+                expressions: vec!["a", "b"],
+                path: vec!["a", "b", "a"]
+            })
+        );
+        assert_eq!(
+            graph.recalculation_order().expect_err("cyclic").to_string(),
+            r#"Circular reference: "a" -> "b" -> "a""#
+        );
+    }
+}
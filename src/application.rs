@@ -0,0 +1,194 @@
+//! Functions applied without parentheses, like `sin x`.
+//!
+//! `to_postfix` lets a function without parentheses take everything up to the end of the
+//! expression or the enclosing parenthesis as its argument, so `sin x + 1` means `sin(x + 1)`.
+//! `to_postfix_with_application` instead applies such a function to a single operand and binds it
+//! like a prefix operator with the given precedence. With a precedence above `+`, `sin x + 1`
+//! means `sin(x) + 1`. Functions followed by a left parenthesis are converted as usual.
+//!
+//! ```rust
+//! use gyard::{application::to_postfix_with_application, op::Math, InputToken, OutputToken};
+//!
+//! // sin x + 1
+//! let infix = [
+//!     InputToken::Function("sin"),
+//!     InputToken::Value("x"),
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Value("1"),
+//! ];
+//! assert_eq!(to_postfix_with_application(infix.clone(), 14), Ok(vec![
+//!     OutputToken::Value("x"),
+//!     OutputToken::Function("sin"),
+//!     OutputToken::Value("1"),
+//!     OutputToken::Operator(Math::Add),
+//! ]));
+//! assert_eq!(to_postfix_with_application(infix, 10), Ok(vec![
+//!     OutputToken::Value("x"),
+//!     OutputToken::Value("1"),
+//!     OutputToken::Operator(Math::Add),
+//!     OutputToken::Function("sin"),
+//! ]));
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{growable, Growable, InputToken, Operator, OutputToken, ParenMissmatchError, Queue};
+
+/// An operator on the stack of `to_postfix_with_application`
+enum Applied<F, O> {
+    Operator(O),
+    /// A function without parentheses, converted as a prefix operator
+    Function {
+        function: F,
+        precedence: usize,
+    },
+}
+
+impl<F, O: Operator> Operator for Applied<F, O> {
+    fn precedence(&self) -> usize {
+        match self {
+            Applied::Operator(operator) => operator.precedence(),
+            Applied::Function { precedence, .. } => *precedence,
+        }
+    }
+
+    fn is_left_associative(&self) -> bool {
+        match self {
+            Applied::Operator(operator) => operator.is_left_associative(),
+            Applied::Function { .. } => false,
+        }
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            Applied::Operator(operator) => operator.arity(),
+            Applied::Function { .. } => 1,
+        }
+    }
+}
+
+/// Turns the applied functions back into functions.
+struct ApplicationQueue<V, F, O>(Vec<OutputToken<V, F, O>>);
+
+impl<V, F, O> Queue<OutputToken<V, F, Applied<F, O>>> for ApplicationQueue<V, F, O> {
+    fn push(&mut self, item: OutputToken<V, F, Applied<F, O>>) -> bool {
+        self.0.push(match item {
+            OutputToken::Value(value) => OutputToken::Value(value),
+            OutputToken::Function(function)
+            | OutputToken::Operator(Applied::Function { function, .. }) => {
+                OutputToken::Function(function)
+            }
+            OutputToken::Operator(Applied::Operator(operator)) => OutputToken::Operator(operator),
+        });
+        true
+    }
+}
+
+/// Like `to_postfix`, but a function that is not followed by a left parenthesis takes a single
+/// operand and binds like a prefix operator with `precedence`. See the module documentation.
+pub fn to_postfix_with_application<V, F, O>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+    precedence: usize,
+) -> Result<Vec<OutputToken<V, F, O>>, ParenMissmatchError>
+where
+    O: Operator,
+{
+    let mut infix = infix.into_iter().enumerate().peekable();
+    let mut conversion: Growable<_, F, Applied<F, O>> =
+        Growable::new(&infix, |len| ApplicationQueue(Vec::with_capacity(len)));
+    let identity = |func, _| func;
+    while let Some((pos, token)) = infix.next() {
+        match token {
+            InputToken::Function(function)
+                if !matches!(infix.peek(), Some((_, InputToken::LeftParen))) =>
+            {
+                let applied = Applied::Function {
+                    function,
+                    precedence,
+                };
+                growable(conversion.converter.push_prefix(pos, applied))?;
+            }
+            token => conversion.push(pos, token.map_operator(Applied::Operator), &identity)?,
+        }
+    }
+    conversion.finish(&identity)?;
+    Ok(conversion.output.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::to_postfix_with_application;
+    use crate::{op::Math, InputToken, OutputToken, ParenMissmatchError};
+
+    #[test]
+    fn application_binds_weaker_than_the_operator_before() {
+        // 2 * sin 3 + 1
+        let infix = [
+            InputToken::Value(2),
+            InputToken::Operator(Math::Mul),
+            InputToken::Function("sin"),
+            InputToken::Value(3),
+            InputToken::Operator(Math::Add),
+            InputToken::Value(1),
+        ];
+        assert_eq!(
+            to_postfix_with_application(infix, 10),
+            Ok(vec![
+                OutputToken::Value(2),
+                OutputToken::Value(3),
+                OutputToken::Value(1),
+                OutputToken::Operator(Math::Add),
+                OutputToken::Function("sin"),
+                OutputToken::Operator(Math::Mul),
+            ])
+        );
+    }
+
+    #[test]
+    fn calls_with_parentheses_are_unchanged() {
+        // f g 2 ** 3 * f ( 1 , 2 )
+        let infix = [
+            InputToken::Function("f"),
+            InputToken::Function("g"),
+            InputToken::Value(2),
+            InputToken::Operator(Math::Exponent),
+            InputToken::Value(3),
+            InputToken::Operator(Math::Mul),
+            InputToken::Function("f"),
+            InputToken::LeftParen,
+            InputToken::Value(1),
+            InputToken::ArgSeparator,
+            InputToken::Value(2),
+            InputToken::RightParen,
+        ];
+        assert_eq!(
+            to_postfix_with_application(infix, 12),
+            Ok(vec![
+                OutputToken::Value(2),
+                OutputToken::Value(3),
+                OutputToken::Operator(Math::Exponent),
+                OutputToken::Function("g"),
+                OutputToken::Function("f"),
+                OutputToken::Value(1),
+                OutputToken::Value(2),
+                OutputToken::Function("f"),
+                OutputToken::Operator(Math::Mul),
+            ])
+        );
+        // f 1 , 2
+        assert!(matches!(
+            to_postfix_with_application(
+                [
+                    InputToken::<_, _, Math>::Function("f"),
+                    InputToken::Value(1),
+                    InputToken::ArgSeparator,
+                    InputToken::Value(2),
+                ],
+                12
+            ),
+            Err(ParenMissmatchError::UnexpectedSeparator { pos: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,698 @@
+//! Expression trees.
+//! `to_ast` builds an `Expr` from an infix expression, `postfix_to_ast` from a postfix expression
+//! created by `to_postfix_with_arity`. `fold_postfix` walks a postfix expression like a tree
+//! without allocating one.
+//!
+//! ```rust
+//! use gyard::{ast::{to_ast, Expr}, op::Math, InputToken};
+//!
+//! // 1 + max ( 2 , 3 )
+//! let infix = [
+//!     InputToken::Value(1),
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Function("max"),
+//!     InputToken::LeftParen,
+//!     InputToken::Value(2),
+//!     InputToken::ArgSeparator,
+//!     InputToken::Value(3),
+//!     InputToken::RightParen,
+//! ];
+//! assert_eq!(to_ast(infix), Ok(Expr::Operator {
+//!     operator: Math::Add,
+//!     args: vec![
+//!         Expr::Value(1),
+//!         Expr::Function {
+//!             function: "max",
+//!             args: vec![Expr::Value(2), Expr::Value(3)],
+//!         },
+//!     ],
+//! }));
+//! ```
+
+pub mod canonical;
+#[cfg(feature = "std")]
+pub mod dag;
+#[cfg(feature = "diff")]
+pub mod diff;
+#[cfg(feature = "dot")]
+pub mod dot;
+pub mod flat;
+pub mod hash;
+#[cfg(feature = "jsonlogic")]
+pub mod jsonlogic;
+#[cfg(feature = "latex")]
+pub mod latex;
+#[cfg(feature = "mathml")]
+pub mod mathml;
+pub mod pretty;
+pub mod render;
+pub mod rewrite;
+pub mod sexpr;
+pub mod simplify;
+
+use alloc::{vec, vec::Vec};
+
+use crate::{
+    expected::Expected, to_postfix_with_arity, Call, ErrorCode, InputToken, Operator, OutputToken,
+    ParenMissmatchError,
+};
+
+/// A node of an expression tree
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Expr<V, F, O> {
+    /// A value
+    Value(V),
+    /// An operator applied to its operands
+    Operator {
+        /// The operator
+        operator: O,
+        /// The operands in infix order
+        args: Vec<Expr<V, F, O>>,
+    },
+    /// A function call
+    Function {
+        /// The function
+        function: F,
+        /// The arguments in infix order
+        args: Vec<Expr<V, F, O>>,
+    },
+}
+
+impl<V: Clone, F: Clone, O: Operator + Clone> Expr<V, F, O> {
+    /// Renders the tree as an infix expression. Parentheses are only inserted where precedence or
+    /// associativity require them, so `to_ast` turns the result into the same tree again.
+    /// Unary operators are rendered as prefix operators. Operators taking more than two operands
+    /// are placed between each pair of operands.
+    ///
+    /// ```rust
+    /// use gyard::{ast::Expr, op::Math, InputToken};
+    ///
+    /// // ( 1 - 2 ) - ( 3 - 4 )
+    /// let sub = |a, b| Expr::<_, (), _>::Operator { operator: Math::Sub, args: vec![a, b] };
+    /// let expr = sub(sub(Expr::Value(1), Expr::Value(2)), sub(Expr::Value(3), Expr::Value(4)));
+    /// assert_eq!(expr.to_infix(), [
+    ///     InputToken::Value(1),
+    ///     InputToken::Operator(Math::Sub),
+    ///     InputToken::Value(2),
+    ///     InputToken::Operator(Math::Sub),
+    ///     InputToken::LeftParen,
+    ///     InputToken::Value(3),
+    ///     InputToken::Operator(Math::Sub),
+    ///     InputToken::Value(4),
+    ///     InputToken::RightParen,
+    /// ]);
+    /// ```
+    pub fn to_infix(&self) -> Vec<InputToken<V, F, O>> {
+        render::render(self, &mut InfixTokens)
+    }
+}
+
+impl<V, F, O> Expr<V, F, O> {
+    /// Converts the tree into a postfix expression like the one `to_postfix_with_arity` creates
+    /// from `to_infix`.
+    ///
+    /// ```rust
+    /// use gyard::{ast::Expr, op::Math, Call, OutputToken};
+    ///
+    /// // f ( 1 - 2 )
+    /// let expr = Expr::Function {
+    ///     function: "f",
+    ///     args: vec![Expr::Operator {
+    ///         operator: Math::Sub,
+    ///         args: vec![Expr::Value(1), Expr::Value(2)],
+    ///     }],
+    /// };
+    /// assert_eq!(expr.into_postfix(), [
+    ///     OutputToken::Value(1),
+    ///     OutputToken::Value(2),
+    ///     OutputToken::Operator(Math::Sub),
+    ///     OutputToken::Function(Call { function: "f", arity: 1 }),
+    /// ]);
+    /// ```
+    pub fn into_postfix(self) -> Vec<OutputToken<V, Call<F>, O>> {
+        let mut postfix = Vec::new();
+        self.push_postfix(&mut postfix);
+        postfix
+    }
+
+    fn push_postfix(self, postfix: &mut Vec<OutputToken<V, Call<F>, O>>) {
+        match self {
+            Expr::Value(value) => postfix.push(OutputToken::Value(value)),
+            Expr::Operator { operator, args } => {
+                args.into_iter().for_each(|arg| arg.push_postfix(postfix));
+                postfix.push(OutputToken::Operator(operator));
+            }
+            Expr::Function { function, args } => {
+                let arity = args.len();
+                args.into_iter().for_each(|arg| arg.push_postfix(postfix));
+                postfix.push(OutputToken::Function(Call { function, arity }));
+            }
+        }
+    }
+}
+
+/// Renders a tree as infix tokens. See `Expr::to_infix`.
+struct InfixTokens;
+
+impl<V: Clone, F: Clone, O: Clone> render::Notation<V, F, O> for InfixTokens {
+    type Output = Vec<InputToken<V, F, O>>;
+
+    fn value(&mut self, value: &V) -> Self::Output {
+        vec![InputToken::Value(value.clone())]
+    }
+
+    fn parens(&mut self, output: Self::Output) -> Self::Output {
+        let mut infix = Vec::with_capacity(output.len() + 2);
+        infix.push(InputToken::LeftParen);
+        infix.extend(output);
+        infix.push(InputToken::RightParen);
+        infix
+    }
+
+    fn operator(
+        &mut self,
+        operator: &O,
+        operands: Vec<render::Operand<Self::Output>>,
+    ) -> Self::Output {
+        let mut infix = Vec::new();
+        let unary = operands.len() == 1;
+        for (i, operand) in operands.into_iter().enumerate() {
+            if i > 0 || unary {
+                infix.push(InputToken::Operator(operator.clone()));
+            }
+            infix.extend(operand.grouped::<V, F, O, _>(self));
+        }
+        infix
+    }
+
+    fn function(&mut self, function: &F, args: Vec<Self::Output>) -> Self::Output {
+        let mut infix = vec![
+            InputToken::Function(function.clone()),
+            InputToken::LeftParen,
+        ];
+        for (i, arg) in args.into_iter().enumerate() {
+            if i > 0 {
+                infix.push(InputToken::ArgSeparator);
+            }
+            infix.extend(arg);
+        }
+        infix.push(InputToken::RightParen);
+        infix
+    }
+}
+
+impl<V, F, O: Operator> Expr<V, F, O> {
+    /// Returns true if the expression stays the left operand of `parent` without parentheses.
+    fn binds_left_of(&self, parent: &O) -> bool {
+        match self {
+            Expr::Operator { operator, .. } => {
+                operator.precedence() > parent.precedence()
+                    || (operator.precedence() == parent.precedence()
+                        && parent.is_left_associative())
+            }
+            _ => true,
+        }
+    }
+
+    /// Returns true if the expression stays the right operand of `parent` without parentheses.
+    fn binds_right_of(&self, parent: &O) -> bool {
+        match self {
+            Expr::Operator { operator, .. } => {
+                operator.precedence() > parent.precedence()
+                    || (operator.precedence() == parent.precedence()
+                        && !operator.is_left_associative())
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Converts a postfix expression created by `to_postfix_with_arity` back into an infix expression
+/// with minimal parentheses. See `Expr::to_infix`.
+pub fn postfix_to_infix<V, F, O>(
+    postfix: impl IntoIterator<Item = OutputToken<V, Call<F>, O>>,
+) -> Result<Vec<InputToken<V, F, O>>, AstError>
+where
+    V: Clone,
+    F: Clone,
+    O: Operator + Clone,
+{
+    Ok(postfix_to_ast(postfix)?.to_infix())
+}
+
+/// This error is returned by `roundtrip_check` if an expression does not survive being converted
+/// back into infix notation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RoundtripError<V, F, O> {
+    /// The original infix expression can not be converted into a tree.
+    Convert(AstError),
+    /// The reconstructed infix expression can not be converted into a tree.
+    Reconvert {
+        /// The reconstructed infix expression
+        infix: Vec<InputToken<V, F, O>>,
+        /// The error returned by the conversion
+        error: AstError,
+    },
+    /// The reconstructed infix expression converts into a different postfix expression.
+    Mismatch {
+        /// The position of the first token that differs
+        pos: usize,
+        /// The reconstructed infix expression
+        infix: Vec<InputToken<V, F, O>>,
+        /// The postfix expression of the original infix expression
+        expected: Vec<OutputToken<V, Call<F>, O>>,
+        /// The postfix expression of the reconstructed infix expression
+        actual: Vec<OutputToken<V, Call<F>, O>>,
+    },
+}
+
+impl<V, F, O> core::fmt::Display for RoundtripError<V, F, O> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RoundtripError::Convert(error) => error.fmt(f),
+            RoundtripError::Reconvert { error, .. } => {
+                write!(f, "Reconstructed infix expression is invalid: {error}")
+            }
+            RoundtripError::Mismatch { pos, .. } => {
+                write!(f, "Postfix expressions differ at position {pos}")
+            }
+        }
+    }
+}
+
+impl<V: core::fmt::Debug, F: core::fmt::Debug, O: core::fmt::Debug> core::error::Error
+    for RoundtripError<V, F, O>
+{
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            RoundtripError::Convert(error) | RoundtripError::Reconvert { error, .. } => Some(error),
+            RoundtripError::Mismatch { .. } => None,
+        }
+    }
+}
+
+type RoundtripResult<V, F, O> = Result<Vec<OutputToken<V, Call<F>, O>>, RoundtripError<V, F, O>>;
+
+/// Converts `infix` into postfix, back into infix with `postfix_to_infix` and into postfix again
+/// and checks that both postfix expressions are equal. Returns the postfix expression on success.
+/// Use this as a regression test for a grammar. Unary operators are reconstructed as prefix
+/// operators, which converts into the same postfix expression as a postfix operator.
+///
+/// ```rust
+/// use gyard::{ast::{roundtrip_check, RoundtripError}, op::Math, InputToken};
+///
+/// // 1 - ( 2 - 3 )
+/// let infix = [
+///     InputToken::<_, (), _>::Value(1),
+///     InputToken::Operator(Math::Sub),
+///     InputToken::LeftParen,
+///     InputToken::Value(2),
+///     InputToken::Operator(Math::Sub),
+///     InputToken::Value(3),
+///     InputToken::RightParen,
+/// ];
+/// assert_eq!(roundtrip_check(infix.clone()).map(|postfix| postfix.len()), Ok(5));
+/// assert!(matches!(roundtrip_check(infix[..3].to_vec()), Err(RoundtripError::Convert(_))));
+/// ```
+pub fn roundtrip_check<V, F, O>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+) -> RoundtripResult<V, F, O>
+where
+    V: Clone + PartialEq,
+    F: Clone + PartialEq,
+    O: Operator + Clone + PartialEq,
+{
+    let to_postfix = |infix| {
+        to_postfix_with_arity(infix)
+            .map_err(AstError::from)
+            .and_then(|postfix| {
+                // Checks that the postfix expression is a single tree
+                borrowed_ast(&postfix)?;
+                Ok(postfix)
+            })
+    };
+    let expected = to_postfix(Vec::from_iter(infix)).map_err(RoundtripError::Convert)?;
+    let infix = borrowed_ast(&expected)
+        .map_err(RoundtripError::Convert)?
+        .to_infix()
+        .into_iter()
+        .map(|token| token.map(V::clone, F::clone, O::clone))
+        .collect::<Vec<_>>();
+    let actual = match to_postfix(infix.clone()) {
+        Ok(actual) => actual,
+        Err(error) => return Err(RoundtripError::Reconvert { infix, error }),
+    };
+    match expected.iter().zip(&actual).position(|(e, a)| e != a) {
+        None if expected.len() == actual.len() => Ok(expected),
+        pos => Err(RoundtripError::Mismatch {
+            pos: pos.unwrap_or(expected.len().min(actual.len())),
+            infix,
+            expected,
+            actual,
+        }),
+    }
+}
+
+/// This error is returned if an expression can not be converted into a tree.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AstError {
+    /// The parentheses of the infix expression do not match.
+    ParenMissmatch(ParenMissmatchError),
+    /// The operator or function at `pos` requires more operands than available.
+    StackUnderflow {
+        /// The position of the operator or function
+        pos: usize,
+        /// The number of operands the operator or function requires
+        required: usize,
+        /// The number of available operands
+        available: usize,
+    },
+    /// The expression does not contain any tokens.
+    EmptyExpression,
+    /// The expression consists of more than one tree.
+    UnusedValues {
+        /// The number of trees
+        count: usize,
+    },
+}
+
+impl core::fmt::Display for AstError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AstError::ParenMissmatch(error) => return error.fmt(f),
+            AstError::StackUnderflow {
+                pos,
+                required,
+                available,
+            } => write!(
+                f,
+                "Token at position {pos} requires {required} values but only {available} are available"
+            ),
+            AstError::EmptyExpression => write!(f, "Can not build a tree from an empty expression"),
+            AstError::UnusedValues { count } => {
+                write!(f, "Expression consists of {count} trees instead of one")
+            }
+        }?;
+        match self.expected() {
+            Some(expected) => write!(f, ", expected {expected}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl core::error::Error for AstError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            AstError::ParenMissmatch(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl AstError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AstError::ParenMissmatch(error) => error.code(),
+            AstError::StackUnderflow { .. } => ErrorCode::StackUnderflow,
+            AstError::EmptyExpression => ErrorCode::EmptyExpression,
+            AstError::UnusedValues { .. } => ErrorCode::UnusedValues,
+        }
+    }
+
+    /// Returns the tokens that would have been valid. Missing operands expect the start of an
+    /// operand, additional trees expect an operator between them.
+    pub fn expected(&self) -> Option<Expected> {
+        match self {
+            AstError::ParenMissmatch(error) => error.expected(),
+            AstError::StackUnderflow { .. } | AstError::EmptyExpression => Some(Expected::OPERAND),
+            AstError::UnusedValues { .. } => Some(Expected::OPERATOR),
+        }
+    }
+}
+
+impl From<ParenMissmatchError> for AstError {
+    fn from(error: ParenMissmatchError) -> Self {
+        Self::ParenMissmatch(error)
+    }
+}
+
+/// Converts an infix expression into a tree. Operators take `Operator::arity` operands, functions
+/// take the arguments they were called with.
+pub fn to_ast<V, F, O>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+) -> Result<Expr<V, F, O>, AstError>
+where
+    O: Operator,
+{
+    postfix_to_ast(to_postfix_with_arity(infix)?)
+}
+
+/// Converts a postfix expression created by `to_postfix_with_arity` into a tree.
+pub fn postfix_to_ast<V, F, O>(
+    postfix: impl IntoIterator<Item = OutputToken<V, Call<F>, O>>,
+) -> Result<Expr<V, F, O>, AstError>
+where
+    O: Operator,
+{
+    fold_postfix(
+        postfix,
+        Expr::Value,
+        |operator, args| Expr::Operator {
+            operator,
+            args: core::mem::take(args),
+        },
+        |function, args| Expr::Function {
+            function,
+            args: core::mem::take(args),
+        },
+    )
+}
+
+/// Builds a tree borrowing the tokens of a postfix expression created by `to_postfix_with_arity`.
+pub(crate) fn borrowed_ast<V, F, O>(
+    postfix: &[OutputToken<V, Call<F>, O>],
+) -> Result<Expr<&V, &F, &O>, AstError>
+where
+    O: Operator,
+{
+    postfix_to_ast(postfix.iter().map(|token| {
+        token.as_ref().map_function(|call| Call {
+            function: &call.function,
+            arity: call.arity,
+        })
+    }))
+}
+
+/// Like `borrowed_ast`, but clones the operators, so they can be converted into `op::All`.
+#[cfg(any(feature = "jsonlogic", feature = "latex", feature = "mathml"))]
+pub(crate) fn borrowed_ast_with_operators<V, F, O>(
+    postfix: &[OutputToken<V, Call<F>, O>],
+) -> Result<Expr<&V, &F, O>, AstError>
+where
+    O: Operator + Clone,
+{
+    postfix_to_ast(postfix.iter().map(|token| {
+        token.as_ref().map(
+            |value| value,
+            |call| Call {
+                function: &call.function,
+                arity: call.arity,
+            },
+            O::clone,
+        )
+    }))
+}
+
+/// Folds a postfix expression created by `to_postfix_with_arity` bottom up without building a
+/// tree. `apply_op` and `apply_fn` receive the already folded operands in infix order.
+///
+/// ```rust
+/// use gyard::{ast::fold_postfix, op::Math, to_postfix_with_arity, InputToken};
+///
+/// // 1 + max ( 2 , 3 )
+/// let postfix = to_postfix_with_arity([
+///     InputToken::Value(1),
+///     InputToken::Operator(Math::Add),
+///     InputToken::Function("max"),
+///     InputToken::LeftParen,
+///     InputToken::Value(2),
+///     InputToken::ArgSeparator,
+///     InputToken::Value(3),
+///     InputToken::RightParen,
+/// ])
+/// .unwrap();
+/// let depth = fold_postfix(
+///     postfix,
+///     |_| 1,
+///     |_, args| 1 + args.iter().max().unwrap(),
+///     |_, args| 1 + args.iter().max().unwrap_or(&0),
+/// );
+/// assert_eq!(depth, Ok(3));
+/// ```
+pub fn fold_postfix<V, F, O, A>(
+    postfix: impl IntoIterator<Item = OutputToken<V, Call<F>, O>>,
+    mut value: impl FnMut(V) -> A,
+    mut apply_op: impl FnMut(O, &mut Vec<A>) -> A,
+    mut apply_fn: impl FnMut(F, &mut Vec<A>) -> A,
+) -> Result<A, AstError>
+where
+    O: Operator,
+{
+    let mut stack: Vec<A> = Vec::new();
+    let mut args: Vec<A> = Vec::new();
+
+    for (pos, token) in postfix.into_iter().enumerate() {
+        let arity = match &token {
+            OutputToken::Value(_) => 0,
+            OutputToken::Operator(op) => op.arity(),
+            OutputToken::Function(call) => call.arity,
+        };
+        let Some(start) = stack.len().checked_sub(arity) else {
+            return Err(AstError::StackUnderflow {
+                pos,
+                required: arity,
+                available: stack.len(),
+            });
+        };
+        args.clear();
+        args.extend(stack.drain(start..));
+        stack.push(match token {
+            OutputToken::Value(v) => value(v),
+            OutputToken::Operator(operator) => apply_op(operator, &mut args),
+            OutputToken::Function(Call { function, .. }) => apply_fn(function, &mut args),
+        });
+    }
+
+    let Some(result) = stack.pop() else {
+        return Err(AstError::EmptyExpression);
+    };
+    if !stack.is_empty() {
+        return Err(AstError::UnusedValues {
+            count: stack.len() + 1,
+        });
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        format,
+        string::{String, ToString},
+        vec,
+    };
+
+    use super::{fold_postfix, postfix_to_ast, to_ast, AstError, Expr};
+    use crate::{
+        op::{Logical, Math},
+        Call, InputToken, OutputToken,
+    };
+
+    #[test]
+    fn unary_operator() {
+        // ! ( a && b )
+        let infix = [
+            InputToken::<_, (), _>::Operator(Logical::Not),
+            InputToken::LeftParen,
+            InputToken::Value('a'),
+            InputToken::Operator(Logical::And),
+            InputToken::Value('b'),
+            InputToken::RightParen,
+        ];
+        assert_eq!(
+            to_ast(infix),
+            Ok(Expr::Operator {
+                operator: Logical::Not,
+                args: vec![Expr::Operator {
+                    operator: Logical::And,
+                    args: vec![Expr::Value('a'), Expr::Value('b')]
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn fold_to_string() {
+        // f ( a , ! b )
+        let postfix = [
+            OutputToken::Value('a'),
+            OutputToken::Value('b'),
+            OutputToken::Operator(Logical::Not),
+            OutputToken::Function(Call {
+                function: 'f',
+                arity: 2,
+            }),
+        ];
+        let folded = fold_postfix(
+            postfix,
+            String::from,
+            |_, args| format!("!{}", args[0]),
+            |function, args| format!("{function}({})", args.join(", ")),
+        );
+        assert_eq!(folded, Ok("f(a, !b)".to_string()));
+    }
+
+    #[test]
+    fn infix_roundtrip() {
+        use InputToken::*;
+        // ! ( a && b ) || ! c && 2 ** ( 3 ** 4 ) ** 5 > f ( 1 - ( 2 - 3 ) ) ^ d
+        let infix = [
+            Operator(Logical::Not.into()),
+            LeftParen,
+            Value('a'),
+            Operator(Logical::And.into()),
+            Value('b'),
+            RightParen,
+            Operator(Logical::Or.into()),
+            Operator(Logical::Not.into()),
+            Value('c'),
+            Operator(Logical::And.into()),
+            Value('2'),
+            Operator(Math::Exponent.into()),
+            LeftParen,
+            Value('3'),
+            Operator(Math::Exponent.into()),
+            Value('4'),
+            RightParen,
+            Operator(Math::Exponent.into()),
+            Value('5'),
+            Operator(crate::op::Compare::Gt.into()),
+            Function("f"),
+            LeftParen,
+            Value('1'),
+            Operator(Math::Sub.into()),
+            LeftParen,
+            Value('2'),
+            Operator(Math::Sub.into()),
+            Value('3'),
+            RightParen,
+            RightParen,
+            Operator(Logical::Xor.into()),
+            Value('d'),
+        ];
+        let Ok(expr) = to_ast::<_, _, crate::op::All>(infix.clone()) else {
+            panic!("Failed to build tree")
+        };
+        assert_eq!(expr.to_infix(), infix);
+    }
+
+    #[test]
+    fn stack_underflow() {
+        let postfix = [
+            OutputToken::<_, Call<()>, _>::Value('a'),
+            OutputToken::Operator(Logical::And),
+        ];
+        assert_eq!(
+            postfix_to_ast(postfix),
+            Err(AstError::StackUnderflow {
+                pos: 1,
+                required: 2,
+                available: 1
+            })
+        );
+    }
+}
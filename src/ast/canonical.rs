@@ -0,0 +1,220 @@
+//! Canonical ordering of the operands of commutative operators.
+//! Two expressions that only differ in the order of commutative operands, like `a + b` and
+//! `b + a`, have the same canonical form.
+//!
+//! ```rust
+//! use gyard::{ast::{canonical::canonicalize, Expr}, op::Math};
+//!
+//! let op = |operator, a, b| Expr::<_, (), _>::Operator { operator, args: vec![a, b] };
+//! // b * a - c
+//! let a = op(Math::Sub, op(Math::Mul, Expr::Value('b'), Expr::Value('a')), Expr::Value('c'));
+//! // a * b - c
+//! let b = op(Math::Sub, op(Math::Mul, Expr::Value('a'), Expr::Value('b')), Expr::Value('c'));
+//! assert_eq!(canonicalize(a), canonicalize(b));
+//! ```
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use super::{postfix_to_ast, AstError, Expr};
+use crate::{Call, Operator, OutputToken};
+
+/// Sorts the operands of every operator for which `Operator::is_commutative` returns true.
+/// Operands are ordered by `Ord`, so values sort before operators and operators before functions.
+pub fn canonicalize<V: Ord, F: Ord, O: Operator + Ord>(expr: Expr<V, F, O>) -> Expr<V, F, O> {
+    canonicalize_by(expr, &mut Ord::cmp)
+}
+
+/// Sorts the operands of every commutative operator using `compare`. See `canonicalize`.
+/// Use this if the values do not implement `Ord`, e.g. `f64`.
+pub fn canonicalize_by<V, F, O: Operator>(
+    expr: Expr<V, F, O>,
+    compare: &mut impl FnMut(&Expr<V, F, O>, &Expr<V, F, O>) -> Ordering,
+) -> Expr<V, F, O> {
+    match expr {
+        Expr::Value(value) => Expr::Value(value),
+        Expr::Function { function, args } => Expr::Function {
+            function,
+            args: args
+                .into_iter()
+                .map(|arg| canonicalize_by(arg, compare))
+                .collect(),
+        },
+        Expr::Operator { operator, args } => {
+            let mut args: Vec<_> = args
+                .into_iter()
+                .map(|arg| canonicalize_by(arg, compare))
+                .collect();
+            if operator.is_commutative() {
+                args.sort_by(|a, b| compare(a, b));
+            }
+            Expr::Operator { operator, args }
+        }
+    }
+}
+
+/// Merges nested applications of the same associative operator into a single node with all
+/// operands, i.e. `( a + b ) + c` and `a + ( b + c )` both become `+ [a, b, c]`.
+/// The result can not be evaluated with `Operator::arity` anymore; use it for comparisons.
+pub fn flatten<V, F, O: PartialEq>(
+    expr: Expr<V, F, O>,
+    is_associative: &impl Fn(&O) -> bool,
+) -> Expr<V, F, O> {
+    match expr {
+        Expr::Value(value) => Expr::Value(value),
+        Expr::Function { function, args } => Expr::Function {
+            function,
+            args: args
+                .into_iter()
+                .map(|arg| flatten(arg, is_associative))
+                .collect(),
+        },
+        Expr::Operator { operator, args } => {
+            let associative = is_associative(&operator);
+            let mut flat = Vec::with_capacity(args.len());
+            for arg in args {
+                match flatten(arg, is_associative) {
+                    Expr::Operator {
+                        operator: inner,
+                        args,
+                    } if associative && inner == operator => flat.extend(args),
+                    arg => flat.push(arg),
+                }
+            }
+            Expr::Operator {
+                operator,
+                args: flat,
+            }
+        }
+    }
+}
+
+/// Returns true if two postfix expressions created by `to_postfix_with_arity` are equal after
+/// canonicalization, i.e. they only differ in the order of commutative operands.
+///
+/// ```rust
+/// use gyard::{ast::canonical::equivalent, op::Math, to_postfix_with_arity, InputToken};
+///
+/// // a * b + c
+/// let lhs = to_postfix_with_arity::<_, (), _>([
+///     InputToken::Value('a'),
+///     InputToken::Operator(Math::Mul),
+///     InputToken::Value('b'),
+///     InputToken::Operator(Math::Add),
+///     InputToken::Value('c'),
+/// ]).unwrap();
+/// // c + ( b * a )
+/// let rhs = to_postfix_with_arity([
+///     InputToken::Value('c'),
+///     InputToken::Operator(Math::Add),
+///     InputToken::LeftParen,
+///     InputToken::Value('b'),
+///     InputToken::Operator(Math::Mul),
+///     InputToken::Value('a'),
+///     InputToken::RightParen,
+/// ]).unwrap();
+/// assert_eq!(equivalent(lhs, rhs), Ok(true));
+/// ```
+pub fn equivalent<V, F, O>(
+    lhs: impl IntoIterator<Item = OutputToken<V, Call<F>, O>>,
+    rhs: impl IntoIterator<Item = OutputToken<V, Call<F>, O>>,
+) -> Result<bool, AstError>
+where
+    V: Ord,
+    F: Ord,
+    O: Operator + Ord,
+{
+    Ok(canonicalize(postfix_to_ast(lhs)?) == canonicalize(postfix_to_ast(rhs)?))
+}
+
+/// Like `equivalent`, but also ignores the grouping of operators for which `is_associative`
+/// returns true, so `( a + b ) + c` is equivalent to `a + ( b + c )`.
+pub fn equivalent_with<V, F, O>(
+    lhs: impl IntoIterator<Item = OutputToken<V, Call<F>, O>>,
+    rhs: impl IntoIterator<Item = OutputToken<V, Call<F>, O>>,
+    is_associative: impl Fn(&O) -> bool,
+) -> Result<bool, AstError>
+where
+    V: Ord,
+    F: Ord,
+    O: Operator + Ord,
+{
+    let canonical = |postfix| -> Result<_, AstError> {
+        Ok(canonicalize(flatten(
+            postfix_to_ast(postfix)?,
+            &is_associative,
+        )))
+    };
+    Ok(canonical(lhs.into_iter().collect::<Vec<_>>())?
+        == canonical(rhs.into_iter().collect::<Vec<_>>())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::{canonicalize, canonicalize_by, equivalent, equivalent_with};
+    use crate::{
+        ast::{postfix_to_ast, Expr},
+        op::Math,
+    };
+    use crate::{Call, OutputToken};
+
+    #[test]
+    fn float_values() {
+        let op = |operator, a, b| Expr::<f64, (), _>::Operator {
+            operator,
+            args: vec![a, b],
+        };
+        let compare = &mut |a: &Expr<f64, (), Math>, b: &Expr<f64, (), Math>| match (a, b) {
+            (Expr::Value(a), Expr::Value(b)) => a.total_cmp(b),
+            _ => core::cmp::Ordering::Equal,
+        };
+        let expr = op(
+            Math::Add,
+            Expr::Value(2.),
+            op(Math::Div, Expr::Value(1.), Expr::Value(0.5)),
+        );
+        assert_eq!(
+            canonicalize_by(expr, compare),
+            op(
+                Math::Add,
+                Expr::Value(2.),
+                op(Math::Div, Expr::Value(1.), Expr::Value(0.5))
+            )
+        );
+        let expr = op(Math::Mul, Expr::Value(2.), Expr::Value(1.));
+        assert_eq!(
+            canonicalize_by(expr, compare),
+            op(Math::Mul, Expr::Value(1.), Expr::Value(2.))
+        );
+    }
+
+    #[test]
+    fn swapped_sum() {
+        let value = |v| OutputToken::<_, Call<()>, _>::Value(v);
+        let add = || OutputToken::Operator(Math::Add);
+        // a + b
+        let lhs = [value('a'), value('b'), add()];
+        // b + a
+        let rhs = [value('b'), value('a'), add()];
+        let canonical = |postfix| postfix_to_ast(postfix).map(canonicalize);
+        assert_eq!(canonical(lhs), canonical(rhs));
+    }
+
+    #[test]
+    fn associative_grouping() {
+        let value = |v| OutputToken::<_, Call<()>, _>::Value(v);
+        let add = || OutputToken::Operator(Math::Add);
+        // a + b + c
+        let lhs = [value('a'), value('b'), add(), value('c'), add()];
+        // c + ( a + b )
+        let rhs = [value('c'), value('a'), value('b'), add(), add()];
+        // b + ( c + a )
+        let other = [value('b'), value('c'), value('a'), add(), add()];
+        assert_eq!(equivalent(lhs.clone(), rhs.clone()), Ok(true));
+        assert_eq!(equivalent(lhs.clone(), other.clone()), Ok(false));
+        let is_associative = |op: &Math| *op == Math::Add;
+        assert_eq!(equivalent_with(lhs, other, is_associative), Ok(true));
+    }
+}
@@ -0,0 +1,219 @@
+//! Common subexpression elimination.
+//! A `Dag` stores every distinct subtree of an expression only once, so repeated subterms are
+//! evaluated a single time.
+//!
+//! ```rust
+//! use gyard::{ast::dag::Dag, op::Math, to_postfix_with_arity, InputToken};
+//!
+//! // ( x * x ) + ( x * x )
+//! let mut infix = vec![InputToken::LeftParen, InputToken::Value('x'), InputToken::Operator(Math::Mul),
+//!     InputToken::Value('x'), InputToken::RightParen];
+//! infix.push(InputToken::Operator(Math::Add));
+//! infix.extend(infix[..5].to_vec());
+//! let postfix = to_postfix_with_arity::<_, (), _>(infix).unwrap();
+//! let dag = Dag::from_postfix(postfix, |_| true).unwrap();
+//! // x, x * x and the sum
+//! assert_eq!(dag.nodes().len(), 3);
+//! ```
+
+use std::{collections::HashMap, hash::Hash};
+
+use super::AstError;
+use crate::{eval::arity_error, eval::EvalError, eval::Evaluator, Call, Operator, OutputToken};
+
+/// A node of a `Dag`. Operands are indices of earlier nodes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Node<V, F, O> {
+    /// A value
+    Value(V),
+    /// An operator applied to its operands
+    Operator {
+        /// The operator
+        operator: O,
+        /// The indices of the operands in infix order
+        args: Vec<usize>,
+    },
+    /// A function applied to its arguments
+    Function {
+        /// The function
+        function: F,
+        /// The indices of the arguments in infix order
+        args: Vec<usize>,
+    },
+}
+
+/// An expression in which equal subtrees are shared. Nodes are stored in evaluation order, every
+/// node only references nodes with a lower index and the last node is the root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dag<V, F, O> {
+    nodes: Vec<Node<V, F, O>>,
+    positions: Vec<usize>,
+}
+
+impl<V, F, O> Dag<V, F, O>
+where
+    V: Clone + Eq + Hash,
+    F: Clone + Eq + Hash,
+    O: Operator + Clone + Eq + Hash,
+{
+    /// Builds a `Dag` from a postfix expression created by `to_postfix_with_arity`.
+    /// Calls of functions for which `is_pure` returns false are never shared.
+    pub fn from_postfix(
+        postfix: impl IntoIterator<Item = OutputToken<V, Call<F>, O>>,
+        mut is_pure: impl FnMut(&F) -> bool,
+    ) -> Result<Self, AstError> {
+        let mut nodes = Vec::new();
+        let mut positions = Vec::new();
+        let mut index = HashMap::new();
+        let mut stack: Vec<usize> = Vec::new();
+
+        for (pos, token) in postfix.into_iter().enumerate() {
+            let arity = match &token {
+                OutputToken::Value(_) => 0,
+                OutputToken::Operator(op) => op.arity(),
+                OutputToken::Function(call) => call.arity,
+            };
+            let Some(start) = stack.len().checked_sub(arity) else {
+                return Err(AstError::StackUnderflow {
+                    pos,
+                    required: arity,
+                    available: stack.len(),
+                });
+            };
+            let args = stack.split_off(start);
+            let (node, shareable) = match token {
+                OutputToken::Value(value) => (Node::Value(value), true),
+                OutputToken::Operator(operator) => (Node::Operator { operator, args }, true),
+                OutputToken::Function(Call { function, .. }) => {
+                    let pure = is_pure(&function);
+                    (Node::Function { function, args }, pure)
+                }
+            };
+            let id = match index.get(&node) {
+                Some(&id) if shareable => id,
+                _ => {
+                    let id = nodes.len();
+                    if shareable {
+                        index.insert(node.clone(), id);
+                    }
+                    nodes.push(node);
+                    positions.push(pos);
+                    id
+                }
+            };
+            stack.push(id);
+        }
+
+        match stack.len() {
+            0 => Err(AstError::EmptyExpression),
+            1 => Ok(Self { nodes, positions }),
+            count => Err(AstError::UnusedValues { count }),
+        }
+    }
+}
+
+impl<V, F, O> Dag<V, F, O> {
+    /// Returns all nodes in evaluation order.
+    pub fn nodes(&self) -> &[Node<V, F, O>] {
+        &self.nodes
+    }
+
+    /// Returns the position of the first token in the postfix expression that created `node`.
+    pub fn position(&self, node: usize) -> usize {
+        self.positions[node]
+    }
+
+    /// Evaluates every node once. Errors contain the position of the first token that created the
+    /// failing node.
+    pub fn eval<E>(&self, evaluator: &mut E) -> Result<E::Value, EvalError<E::Error>>
+    where
+        V: Clone,
+        F: Clone,
+        O: Clone,
+        E: Evaluator<V, F, O>,
+        E::Value: Clone,
+    {
+        let mut results: Vec<E::Value> = Vec::with_capacity(self.nodes.len());
+        let mut args = Vec::new();
+        for (node, &pos) in self.nodes.iter().zip(&self.positions) {
+            let collect = |args: &mut Vec<E::Value>, ids: &[usize]| {
+                args.clear();
+                args.extend(ids.iter().map(|&id| results[id].clone()));
+            };
+            let result = match node {
+                Node::Value(value) => evaluator.value(value.clone()),
+                Node::Operator {
+                    operator,
+                    args: ids,
+                } => {
+                    collect(&mut args, ids);
+                    evaluator.operator(operator.clone(), &mut args)
+                }
+                Node::Function {
+                    function,
+                    args: ids,
+                } => {
+                    let expected = evaluator.accepted_arity(function);
+                    if let Some(error) = arity_error(expected, ids.len(), pos) {
+                        return Err(error);
+                    }
+                    collect(&mut args, ids);
+                    evaluator.function(function.clone(), &mut args)
+                }
+            };
+            results.push(result.map_err(|error| EvalError::Evaluator { pos, error })?);
+        }
+        results.pop().ok_or(EvalError::EmptyExpression)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dag;
+    use crate::{eval::Evaluator, op::Math, Call, OutputToken};
+
+    struct Counter(usize);
+
+    impl Evaluator<i32, &str, Math> for Counter {
+        type Value = i32;
+        type Error = ();
+
+        fn value(&mut self, value: i32) -> Result<i32, ()> {
+            Ok(value)
+        }
+
+        fn operator(&mut self, _: Math, args: &mut Vec<i32>) -> Result<i32, ()> {
+            Ok(args.iter().sum())
+        }
+
+        fn function(&mut self, _: &str, _: &mut Vec<i32>) -> Result<i32, ()> {
+            self.0 += 1;
+            Ok(self.0 as i32)
+        }
+
+        fn function_arity(&self, _: &&str) -> usize {
+            0
+        }
+    }
+
+    #[test]
+    fn impure_calls_are_not_shared() {
+        let call = |function| OutputToken::Function(Call { function, arity: 0 });
+        // rand() + rand() + ( one() + one() )
+        let postfix = [
+            call("rand"),
+            call("rand"),
+            OutputToken::Operator(Math::Add),
+            call("one"),
+            call("one"),
+            OutputToken::Operator(Math::Add),
+            OutputToken::Operator(Math::Add),
+        ];
+        let dag =
+            Dag::<i32, _, _>::from_postfix(postfix, |f| *f == "one").expect("valid expression");
+        assert_eq!(dag.nodes().len(), 6);
+        let mut counter = Counter(0);
+        // rand() = 1, rand() = 2, one() = 3
+        assert_eq!(dag.eval(&mut counter), Ok(1 + 2 + 3 + 3));
+    }
+}
@@ -0,0 +1,223 @@
+//! Symbolic differentiation of expression trees over `op::Math`.
+//! The result is not simplified, apart from dropping terms whose derivative is zero.
+//!
+//! ```rust
+//! use gyard::{ast::{diff::diff, Expr}, op::Math};
+//!
+//! #[derive(Debug, Clone, PartialEq)]
+//! enum Token {
+//!     Number(f64),
+//!     Var(char),
+//! }
+//!
+//! impl From<f64> for Token {
+//!     fn from(value: f64) -> Self {
+//!         Token::Number(value)
+//!     }
+//! }
+//!
+//! type E = Expr<Token, (), Math>;
+//! let op = |operator, a, b| E::Operator { operator, args: vec![a, b] };
+//! let x = || Expr::Value(Token::Var('x'));
+//! let n = |n| Expr::Value(Token::Number(n));
+//!
+//! // d/dx 3 * x ** 2 = 3 * ( 2 * x ** ( 2 - 1 ) )
+//! let expr = op(Math::Mul, n(3.), op(Math::Exponent, x(), n(2.)));
+//! assert_eq!(
+//!     diff(&expr, |v| *v == Token::Var('x')),
+//!     Ok(op(Math::Mul, n(3.), op(Math::Mul, n(2.), op(Math::Exponent, x(), op(Math::Sub, n(2.), n(1.))))))
+//! );
+//! ```
+
+use alloc::vec;
+
+use super::Expr;
+use crate::op::Math;
+
+/// This error is returned if an expression can not be differentiated.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum DiffError {
+    /// The exponent depends on the variable.
+    VariableExponent,
+    /// A function argument depends on the variable. Functions are opaque to `diff`.
+    Function,
+    /// An operator has the wrong number of operands.
+    InvalidOperands,
+}
+
+impl core::fmt::Display for DiffError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DiffError::VariableExponent => {
+                write!(
+                    f,
+                    "Can not differentiate an exponent depending on the variable"
+                )
+            }
+            DiffError::Function => write!(f, "Can not differentiate a function of the variable"),
+            DiffError::InvalidOperands => write!(f, "Operator has the wrong number of operands"),
+        }
+    }
+}
+
+impl core::error::Error for DiffError {}
+
+/// Differentiates `expr` with respect to the variable. `is_var` returns true for value tokens
+/// that refer to the variable, all other values are constants. Constants in the result are
+/// created with `V::from`.
+pub fn diff<V, F>(
+    expr: &Expr<V, F, Math>,
+    is_var: impl Fn(&V) -> bool,
+) -> Result<Expr<V, F, Math>, DiffError>
+where
+    V: Clone + From<f64>,
+    F: Clone,
+{
+    Ok(derive(expr, &is_var)?.unwrap_or(Expr::Value(V::from(0.))))
+}
+
+/// Returns the derivative of `expr` or `None` if it is zero.
+fn derive<V, F>(
+    expr: &Expr<V, F, Math>,
+    is_var: &impl Fn(&V) -> bool,
+) -> Result<Option<Expr<V, F, Math>>, DiffError>
+where
+    V: Clone + From<f64>,
+    F: Clone,
+{
+    let (operator, a, b) = match expr {
+        Expr::Value(value) => return Ok(is_var(value).then(|| Expr::Value(V::from(1.)))),
+        Expr::Function { args, .. } => {
+            for arg in args {
+                if derive(arg, is_var)?.is_some() {
+                    return Err(DiffError::Function);
+                }
+            }
+            return Ok(None);
+        }
+        Expr::Operator { operator, args } => match args.as_slice() {
+            [a, b] => (*operator, a, b),
+            _ => return Err(DiffError::InvalidOperands),
+        },
+    };
+    let (da, db) = (derive(a, is_var)?, derive(b, is_var)?);
+
+    let derivative = match (operator, da, db) {
+        (_, None, None) => None,
+        (Math::Add, da, db) => sum(da, db),
+        (Math::Sub, Some(da), None) => Some(da),
+        (Math::Sub, da, Some(db)) => {
+            Some(op(Math::Sub, da.unwrap_or(Expr::Value(V::from(0.))), db))
+        }
+        // (a * b)' = a' * b + a * b'
+        (Math::Mul, da, db) => sum(
+            da.map(|da| times(b.clone(), da)),
+            db.map(|db| times(a.clone(), db)),
+        ),
+        // (a / b)' = (a' * b - a * b') / (b * b)
+        (Math::Div, Some(da), None) => Some(op(Math::Div, da, b.clone())),
+        (Math::Div, da, Some(db)) => {
+            let a_db = times(a.clone(), db);
+            let numerator = match da {
+                Some(da) => op(Math::Sub, times(b.clone(), da), a_db),
+                None => op(Math::Sub, Expr::Value(V::from(0.)), a_db),
+            };
+            Some(op(
+                Math::Div,
+                numerator,
+                op(Math::Mul, b.clone(), b.clone()),
+            ))
+        }
+        // (a ** b)' = b * a ** (b - 1) * a'
+        (Math::Exponent, Some(da), None) => {
+            let exponent = op(Math::Sub, b.clone(), Expr::Value(V::from(1.)));
+            let power = op(
+                Math::Mul,
+                b.clone(),
+                op(Math::Exponent, a.clone(), exponent),
+            );
+            Some(times(power, da))
+        }
+        (Math::Exponent, _, Some(_)) => return Err(DiffError::VariableExponent),
+    };
+    Ok(derivative)
+}
+
+/// Multiplies `expr` with a derivative, omitting derivatives that are one.
+fn times<V, F>(expr: Expr<V, F, Math>, derivative: Expr<V, F, Math>) -> Expr<V, F, Math> {
+    match derivative {
+        // `derive` only returns a lone value for the variable itself, whose derivative is one
+        Expr::Value(_) => expr,
+        derivative => op(Math::Mul, expr, derivative),
+    }
+}
+
+fn sum<V, F>(a: Option<Expr<V, F, Math>>, b: Option<Expr<V, F, Math>>) -> Option<Expr<V, F, Math>> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(op(Math::Add, a, b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn op<V, F>(operator: Math, a: Expr<V, F, Math>, b: Expr<V, F, Math>) -> Expr<V, F, Math> {
+    Expr::Operator {
+        operator,
+        args: vec![a, b],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::{diff, DiffError};
+    use crate::{ast::Expr, op::Math};
+
+    type E = Expr<f64, &'static str, Math>;
+
+    fn op(operator: Math, a: E, b: E) -> E {
+        Expr::Operator {
+            operator,
+            args: vec![a, b],
+        }
+    }
+
+    /// The variable is represented by NaN
+    fn is_x(value: &f64) -> bool {
+        value.is_nan()
+    }
+
+    #[test]
+    fn quotient() {
+        // d/dx 1 / x = ( 0 - 1 ) / ( x * x )
+        let x = Expr::Value(f64::NAN);
+        let expr = op(Math::Div, Expr::Value(1.), x);
+        let Ok(Expr::Operator {
+            operator: Math::Div,
+            args,
+        }) = diff(&expr, is_x)
+        else {
+            panic!("Expected a quotient")
+        };
+        assert_eq!(args[0], op(Math::Sub, Expr::Value(0.), Expr::Value(1.)));
+    }
+
+    #[test]
+    fn unsupported() {
+        let x = Expr::Value(f64::NAN);
+        let expr = op(Math::Exponent, Expr::Value(2.), x.clone());
+        assert_eq!(diff(&expr, is_x), Err(DiffError::VariableExponent));
+        let expr = Expr::Function {
+            function: "sin",
+            args: vec![x],
+        };
+        assert_eq!(diff(&expr, is_x), Err(DiffError::Function));
+        let expr = Expr::Function {
+            function: "sin",
+            args: vec![Expr::Value(1.)],
+        };
+        assert_eq!(diff(&expr, is_x), Ok(Expr::Value(0.)));
+    }
+}
@@ -0,0 +1,92 @@
+//! GraphViz DOT export of expression trees.
+//!
+//! ```rust
+//! use gyard::{ast::{dot::to_dot, Expr}, op::Math};
+//!
+//! let expr = Expr::<_, &str, _>::Operator {
+//!     operator: Math::Add,
+//!     args: vec![Expr::Value(1), Expr::Function { function: "sin", args: vec![Expr::Value(2)] }],
+//! };
+//! assert_eq!(to_dot(&expr), r#"digraph expr {
+//!     ordering=out;
+//!     n0 [label="+", shape=circle];
+//!     n1 [label="1", shape=plain];
+//!     n0 -> n1;
+//!     n2 [label="sin", shape=box];
+//!     n3 [label="2", shape=plain];
+//!     n2 -> n3;
+//!     n0 -> n2;
+//! }
+//! "#);
+//! ```
+
+use alloc::string::{String, ToString};
+use core::fmt::{Display, Write};
+
+use super::{borrowed_ast, AstError, Expr};
+use crate::{Call, Operator, OutputToken};
+
+/// Renders `expr` as a DOT graph. Values are drawn as plain text, operators as circles and
+/// functions as boxes. Operands are ordered from left to right.
+pub fn to_dot<V, F, O>(expr: &Expr<V, F, O>) -> String
+where
+    V: Display,
+    F: Display,
+    O: Display,
+{
+    let mut dot = String::from("digraph expr {\n    ordering=out;\n");
+    write_node(expr, &mut dot, &mut 0);
+    dot.push_str("}\n");
+    dot
+}
+
+/// Renders a postfix expression created by `to_postfix_with_arity` as a DOT graph. See `to_dot`.
+pub fn postfix_to_dot<V, F, O>(postfix: &[OutputToken<V, Call<F>, O>]) -> Result<String, AstError>
+where
+    V: Display,
+    F: Display,
+    O: Operator + Display,
+{
+    Ok(to_dot(&borrowed_ast(postfix)?))
+}
+
+fn write_node<V, F, O>(expr: &Expr<V, F, O>, dot: &mut String, next: &mut usize) -> usize
+where
+    V: Display,
+    F: Display,
+    O: Display,
+{
+    let id = *next;
+    *next += 1;
+    let (label, shape, args) = match expr {
+        Expr::Value(value) => (value.to_string(), "plain", &[][..]),
+        Expr::Operator { operator, args } => (operator.to_string(), "circle", &args[..]),
+        Expr::Function { function, args } => (function.to_string(), "box", &args[..]),
+    };
+    let label = label.replace('\\', "\\\\").replace('"', "\\\"");
+    // Writing to a String never fails
+    let _ = writeln!(dot, "    n{id} [label=\"{label}\", shape={shape}];");
+    for arg in args {
+        let child = write_node(arg, dot, next);
+        let _ = writeln!(dot, "    n{id} -> n{child};");
+    }
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::postfix_to_dot;
+    use crate::{op::Logical, Call, OutputToken};
+
+    #[test]
+    fn escape_labels() {
+        let postfix = [
+            OutputToken::<_, Call<&str>, _>::Value("\"a\""),
+            OutputToken::Operator(Logical::Not),
+        ];
+        assert_eq!(
+            postfix_to_dot(&postfix).as_deref(),
+            Ok("digraph expr {\n    ordering=out;\n    n0 [label=\"!\", shape=circle];\n    n1 [label=\"\\\"a\\\"\", shape=plain];\n    n0 -> n1;\n}\n")
+        );
+    }
+}
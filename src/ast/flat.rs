@@ -0,0 +1,237 @@
+//! Expression trees stored in one flat buffer.
+//! A `FlatExpr` keeps all nodes in a single `Vec` in postfix order and refers to children by
+//! index. Analyses over very large expressions touch a few contiguous buffers instead of one heap
+//! allocation per node of an `Expr`.
+//!
+//! ```rust
+//! use gyard::{ast::flat::{FlatExpr, Node}, op::Math, to_postfix_with_arity, InputToken};
+//!
+//! // 1 + max ( 2 , 3 )
+//! let postfix = to_postfix_with_arity([
+//!     InputToken::Value(1),
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Function("max"),
+//!     InputToken::LeftParen,
+//!     InputToken::Value(2),
+//!     InputToken::ArgSeparator,
+//!     InputToken::Value(3),
+//!     InputToken::RightParen,
+//! ])
+//! .unwrap();
+//! let tree = FlatExpr::from_postfix(postfix).unwrap();
+//! assert_eq!(tree.node(tree.root()), &Node::Operator(Math::Add));
+//! assert_eq!(tree.args(tree.root()), [0, 3]);
+//! assert_eq!(tree.node(3), &Node::Function("max"));
+//! let depth = tree.fold(|_, args: &[usize]| 1 + args.iter().max().unwrap_or(&0));
+//! assert_eq!(depth, 3);
+//! ```
+
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+use super::{AstError, Expr};
+use crate::{Call, Operator, OutputToken};
+
+/// A node of a `FlatExpr` without its children
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Node<V, F, O> {
+    /// A value
+    Value(V),
+    /// An operator
+    Operator(O),
+    /// A function
+    Function(F),
+}
+
+/// An expression tree whose nodes are stored in postfix order. Every node only references nodes
+/// with a lower index and the last node is the root.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FlatExpr<V, F, O> {
+    nodes: Vec<Node<V, F, O>>,
+    /// The range of every node in `args`
+    ranges: Vec<Range<usize>>,
+    /// The children of all nodes in infix order
+    args: Vec<usize>,
+}
+
+impl<V, F, O> FlatExpr<V, F, O> {
+    /// Builds a tree from a postfix expression created by `to_postfix_with_arity`.
+    pub fn from_postfix(
+        postfix: impl IntoIterator<Item = OutputToken<V, Call<F>, O>>,
+    ) -> Result<Self, AstError>
+    where
+        O: Operator,
+    {
+        let postfix = postfix.into_iter();
+        let capacity = postfix.size_hint().0;
+        let mut tree = Self {
+            nodes: Vec::with_capacity(capacity),
+            ranges: Vec::with_capacity(capacity),
+            args: Vec::with_capacity(capacity),
+        };
+        // The roots of the subtrees built so far
+        let mut stack = Vec::new();
+        for (pos, token) in postfix.enumerate() {
+            let (node, arity) = match token {
+                OutputToken::Value(value) => (Node::Value(value), 0),
+                OutputToken::Operator(operator) => {
+                    let arity = operator.arity();
+                    (Node::Operator(operator), arity)
+                }
+                OutputToken::Function(Call { function, arity }) => {
+                    (Node::Function(function), arity)
+                }
+            };
+            let Some(start) = stack.len().checked_sub(arity) else {
+                return Err(AstError::StackUnderflow {
+                    pos,
+                    required: arity,
+                    available: stack.len(),
+                });
+            };
+            let args_start = tree.args.len();
+            tree.args.extend(stack.drain(start..));
+            tree.ranges.push(args_start..tree.args.len());
+            tree.nodes.push(node);
+            stack.push(pos);
+        }
+        match stack.len() {
+            0 => Err(AstError::EmptyExpression),
+            1 => Ok(tree),
+            count => Err(AstError::UnusedValues { count }),
+        }
+    }
+
+    /// All nodes in postfix order
+    pub fn nodes(&self) -> &[Node<V, F, O>] {
+        &self.nodes
+    }
+
+    /// The node at `index`
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn node(&self, index: usize) -> &Node<V, F, O> {
+        &self.nodes[index]
+    }
+
+    /// The indices of the children of the node at `index` in infix order
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn args(&self, index: usize) -> &[usize] {
+        &self.args[self.ranges[index].clone()]
+    }
+
+    /// The index of the root node
+    pub fn root(&self) -> usize {
+        self.nodes.len() - 1
+    }
+
+    /// Returns the index of the parent of every node. The root has no parent.
+    pub fn parents(&self) -> Vec<Option<usize>> {
+        let mut parents = vec![None; self.nodes.len()];
+        for (parent, range) in self.ranges.iter().enumerate() {
+            for &child in &self.args[range.clone()] {
+                parents[child] = Some(parent);
+            }
+        }
+        parents
+    }
+
+    /// Returns the indices of all nodes in pre-order, i.e. every node before its children and
+    /// children in infix order.
+    pub fn preorder(&self) -> impl Iterator<Item = usize> + '_ {
+        let mut stack = vec![self.root()];
+        core::iter::from_fn(move || {
+            let index = stack.pop()?;
+            stack.extend(self.args(index).iter().rev());
+            Some(index)
+        })
+    }
+
+    /// Folds the tree bottom up. `f` receives every node with the already folded children in
+    /// infix order.
+    pub fn fold<A>(&self, mut f: impl FnMut(&Node<V, F, O>, &[A]) -> A) -> A {
+        let mut stack: Vec<A> = Vec::new();
+        for (node, range) in self.nodes.iter().zip(&self.ranges) {
+            let start = stack.len() - range.len();
+            let result = f(node, &stack[start..]);
+            stack.truncate(start);
+            stack.push(result);
+        }
+        stack.pop().expect("A FlatExpr is never empty")
+    }
+
+    /// Converts the flat tree into an `Expr`.
+    pub fn into_expr(self) -> Expr<V, F, O> {
+        let mut stack: Vec<Expr<V, F, O>> = Vec::new();
+        for (node, range) in self.nodes.into_iter().zip(self.ranges) {
+            let args = stack.split_off(stack.len() - range.len());
+            stack.push(match node {
+                Node::Value(value) => Expr::Value(value),
+                Node::Operator(operator) => Expr::Operator { operator, args },
+                Node::Function(function) => Expr::Function { function, args },
+            });
+        }
+        stack.pop().expect("A FlatExpr is never empty")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::FlatExpr;
+    use crate::{
+        ast::{to_ast, AstError},
+        op::{Logical, Math},
+        to_postfix_with_arity, Call, InputToken, OutputToken,
+    };
+
+    #[test]
+    fn traversal_matches_expr() {
+        // ! ( 1 ^ 2 ) || f ( )
+        let infix = [
+            InputToken::Operator(Logical::Not),
+            InputToken::LeftParen,
+            InputToken::Value(1),
+            InputToken::Operator(Logical::Xor),
+            InputToken::Value(2),
+            InputToken::RightParen,
+            InputToken::Operator(Logical::Or),
+            InputToken::Function("f"),
+            InputToken::LeftParen,
+            InputToken::RightParen,
+        ];
+        let tree =
+            FlatExpr::from_postfix(to_postfix_with_arity(infix.clone()).expect("valid expression"))
+                .expect("valid expression");
+        assert_eq!(tree.preorder().collect::<Vec<_>>(), [5, 3, 2, 0, 1, 4]);
+        assert_eq!(
+            tree.parents(),
+            [Some(2), Some(2), Some(3), Some(5), Some(5), None]
+        );
+        assert_eq!(tree.into_expr(), to_ast(infix).expect("valid expression"));
+    }
+
+    #[test]
+    fn malformed_postfix() {
+        let postfix = [
+            OutputToken::<_, Call<()>, _>::Value(1),
+            OutputToken::Operator(Math::Add),
+        ];
+        assert_eq!(
+            FlatExpr::from_postfix(postfix),
+            Err(AstError::StackUnderflow {
+                pos: 1,
+                required: 2,
+                available: 1
+            })
+        );
+        assert_eq!(
+            FlatExpr::<i32, (), Math>::from_postfix([]),
+            Err(AstError::EmptyExpression)
+        );
+    }
+}
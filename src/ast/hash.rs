@@ -0,0 +1,163 @@
+//! Stable content hashes of expressions.
+//! The hash only depends on the canonical form of an expression and is the same on every
+//! platform and in every process built with the same compiler and crate version. The expression
+//! is fed to the hasher through its `Hash` implementations, whose output the standard library does
+//! not promise to keep, so do not rely on the hash across upgrades.
+//!
+//! ```rust
+//! use gyard::{ast::{hash::canonical_hash, Expr}, op::Math};
+//!
+//! let op = |operator, a, b| Expr::<_, (), _>::Operator { operator, args: vec![a, b] };
+//! let a = op(Math::Add, Expr::Value(1u32), Expr::Value(2));
+//! let b = op(Math::Add, Expr::Value(2u32), Expr::Value(1));
+//! assert_eq!(canonical_hash(a), canonical_hash(b));
+//! ```
+
+use core::hash::{Hash, Hasher};
+
+use super::{canonical::canonicalize, Expr};
+use crate::Operator;
+
+const FNV_OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+const FNV_PRIME: u128 = 0x0000000001000000000000000000013b;
+
+/// A 128 bit FNV-1a hasher with a platform independent encoding.
+/// Integers are hashed as little endian and `usize`/`isize` are widened to 64 bit, so unlike
+/// `DefaultHasher` the result does not depend on the target or a random seed. It is stable for a
+/// given compiler and crate version, because `Hash` implementations may change between versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StableHasher {
+    state: u128,
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StableHasher {
+    /// Creates a new hasher.
+    pub fn new() -> Self {
+        Self { state: FNV_OFFSET }
+    }
+
+    /// Returns the full 128 bit digest.
+    pub fn finish128(&self) -> u128 {
+        self.state
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        (self.state ^ (self.state >> 64)) as u64
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.state ^= *byte as u128;
+            self.state = self.state.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write_u16(i as u16)
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32)
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write_u128(i as u128)
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as u64)
+    }
+}
+
+/// Returns a stable 64 bit hash of the canonical form of `expr`. See `canonical::canonicalize`.
+pub fn canonical_hash<V, F, O>(expr: Expr<V, F, O>) -> u64
+where
+    V: Ord + Hash,
+    F: Ord + Hash,
+    O: Operator + Ord + Hash,
+{
+    let mut hasher = StableHasher::new();
+    canonicalize(expr).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns a stable 128 bit hash of the canonical form of `expr`. See `canonical_hash`.
+pub fn canonical_hash128<V, F, O>(expr: Expr<V, F, O>) -> u128
+where
+    V: Ord + Hash,
+    F: Ord + Hash,
+    O: Operator + Ord + Hash,
+{
+    let mut hasher = StableHasher::new();
+    canonicalize(expr).hash(&mut hasher);
+    hasher.finish128()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use core::hash::Hasher;
+
+    use super::{canonical_hash, StableHasher};
+    use crate::{ast::Expr, op::Math};
+
+    #[test]
+    fn stable_across_platforms() {
+        let mut a = StableHasher::new();
+        a.write_usize(42);
+        let mut b = StableHasher::new();
+        b.write_u64(42);
+        assert_eq!(a.finish128(), b.finish128());
+        // FNV-1a 128 of the empty input is the offset basis
+        assert_eq!(
+            StableHasher::new().finish128(),
+            0x6c62272e07bb014262b821756295c58d
+        );
+    }
+
+    #[test]
+    fn structure_matters() {
+        let op = |operator, a, b| Expr::<_, (), _>::Operator {
+            operator,
+            args: vec![a, b],
+        };
+        let a = op(Math::Sub, Expr::Value(1u32), Expr::Value(2));
+        let b = op(Math::Sub, Expr::Value(2u32), Expr::Value(1));
+        let c = op(Math::Add, Expr::Value(1u32), Expr::Value(2));
+        assert_ne!(canonical_hash(a.clone()), canonical_hash(b));
+        assert_ne!(canonical_hash(a), canonical_hash(c));
+    }
+}
@@ -0,0 +1,192 @@
+//! Export of expression trees as JsonLogic rules.
+//! Every operator and function call becomes an object with a single key, i.e. `{"+": [1, 2]}`.
+//! Functions use their `Display` name, so a call of `if` or `max` maps to the JsonLogic operation
+//! of the same name. Values are converted by a callback, which writes variables as
+//! `{"var": "name"}`.
+//!
+//! ```rust
+//! use gyard::{
+//!     ast::jsonlogic::postfix_to_jsonlogic,
+//!     op::{All, Compare, Math},
+//!     to_postfix_with_arity, InputToken,
+//! };
+//! use serde_json::json;
+//!
+//! // price * 2 > 100
+//! let postfix = to_postfix_with_arity([
+//!     InputToken::<_, &str, All>::Value("price"),
+//!     InputToken::Operator(Math::Mul.into()),
+//!     InputToken::Value("2"),
+//!     InputToken::Operator(Compare::Gt.into()),
+//!     InputToken::Value("100"),
+//! ]).unwrap();
+//! let rule = postfix_to_jsonlogic(&postfix, |value| match value.parse::<f64>() {
+//!     Ok(number) => json!(number),
+//!     Err(_) => json!({ "var": value }),
+//! });
+//! assert_eq!(rule, Ok(json!({ ">": [{ "*": [{ "var": "price" }, 2.0] }, 100.0] })));
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Display;
+
+use serde_json::{Map, Value};
+
+use super::{borrowed_ast_with_operators, AstError, Expr};
+use crate::{
+    op::{All, Compare, Logical, Math},
+    Call, Operator, OutputToken,
+};
+
+/// This error is returned if an expression can not be exported as a JsonLogic rule.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum JsonLogicError {
+    /// The postfix expression does not form a tree.
+    Ast(AstError),
+    /// JsonLogic has no operation for the operator, i.e. for `Math::Exponent`.
+    UnsupportedOperator(All),
+}
+
+impl core::fmt::Display for JsonLogicError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            JsonLogicError::Ast(error) => error.fmt(f),
+            JsonLogicError::UnsupportedOperator(operator) => {
+                write!(f, "JsonLogic does not support the operator {operator}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for JsonLogicError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            JsonLogicError::Ast(error) => Some(error),
+            JsonLogicError::UnsupportedOperator(_) => None,
+        }
+    }
+}
+
+impl From<AstError> for JsonLogicError {
+    fn from(error: AstError) -> Self {
+        Self::Ast(error)
+    }
+}
+
+/// Converts `expr` into a JsonLogic rule. `value` converts a value into JSON.
+pub fn to_jsonlogic<V, F, O>(
+    expr: &Expr<V, F, O>,
+    mut value: impl FnMut(&V) -> Value,
+) -> Result<Value, JsonLogicError>
+where
+    F: Display,
+    O: Into<All> + Clone,
+{
+    write_rule(expr, &mut value)
+}
+
+/// Converts a postfix expression created by `to_postfix_with_arity` into a JsonLogic rule.
+/// `value` converts a value into JSON.
+pub fn postfix_to_jsonlogic<V, F, O>(
+    postfix: &[OutputToken<V, Call<F>, O>],
+    mut value: impl FnMut(&V) -> Value,
+) -> Result<Value, JsonLogicError>
+where
+    F: Display,
+    O: Operator + Into<All> + Clone,
+{
+    let expr = borrowed_ast_with_operators(postfix)?;
+    write_rule(&expr, &mut |v: &&V| value(v))
+}
+
+fn write_rule<V, F, O>(
+    expr: &Expr<V, F, O>,
+    value: &mut impl FnMut(&V) -> Value,
+) -> Result<Value, JsonLogicError>
+where
+    F: Display,
+    O: Into<All> + Clone,
+{
+    let (name, args) = match expr {
+        Expr::Value(v) => return Ok(value(v)),
+        Expr::Operator { operator, args } => (operation(operator.clone().into())?, args),
+        Expr::Function { function, args } => (function.to_string(), args),
+    };
+    let args = args
+        .iter()
+        .map(|arg| write_rule(arg, value))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut rule = Map::new();
+    rule.insert(name, Value::Array(args));
+    Ok(Value::Object(rule))
+}
+
+/// The name of the JsonLogic operation implementing `operator`
+fn operation(operator: All) -> Result<String, JsonLogicError> {
+    let name = match operator {
+        All::Math(Math::Add) => "+",
+        All::Math(Math::Sub) => "-",
+        All::Math(Math::Mul) => "*",
+        All::Math(Math::Div) => "/",
+        All::Compare(Compare::Lt) => "<",
+        All::Compare(Compare::Le) => "<=",
+        All::Compare(Compare::Eq) => "===",
+        All::Compare(Compare::Ne) => "!==",
+        All::Compare(Compare::Ge) => ">=",
+        All::Compare(Compare::Gt) => ">",
+        All::Logical(Logical::And) => "and",
+        All::Logical(Logical::Or) => "or",
+        All::Logical(Logical::Not) => "!",
+        All::Math(Math::Exponent) | All::Logical(Logical::Xor) => {
+            return Err(JsonLogicError::UnsupportedOperator(operator))
+        }
+    };
+    Ok(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use serde_json::json;
+
+    use super::{to_jsonlogic, JsonLogicError};
+    use crate::{
+        ast::Expr,
+        op::{All, Logical, Math},
+    };
+
+    #[test]
+    fn functions_and_unsupported_operators() {
+        // if ( ! 0 , 1 , 2 )
+        let expr = Expr::Function {
+            function: "if",
+            args: vec![
+                Expr::Operator {
+                    operator: Logical::Not,
+                    args: vec![Expr::Value(0)],
+                },
+                Expr::Value(1),
+                Expr::Value(2),
+            ],
+        };
+        assert_eq!(
+            to_jsonlogic(&expr, |v| json!(v)),
+            Ok(json!({ "if": [{ "!": [0] }, 1, 2] }))
+        );
+
+        let expr = Expr::<_, &str, _>::Operator {
+            operator: Math::Exponent,
+            args: vec![Expr::Value(2), Expr::Value(3)],
+        };
+        assert_eq!(
+            to_jsonlogic(&expr, |v| json!(v)),
+            Err(JsonLogicError::UnsupportedOperator(All::Math(
+                Math::Exponent
+            )))
+        );
+    }
+}
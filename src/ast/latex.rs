@@ -0,0 +1,229 @@
+//! LaTeX rendering of expression trees.
+//! Operators from `op` are written with their mathematical symbols, i.e. `\cdot` and `\frac`.
+//! Values are written verbatim using `Display`.
+//!
+//! ```rust
+//! use gyard::{ast::{latex::postfix_to_latex, to_ast}, op::Math, to_postfix_with_arity, InputToken};
+//!
+//! // ( a + 1 ) / sin ( b )
+//! let postfix = to_postfix_with_arity([
+//!     InputToken::LeftParen,
+//!     InputToken::Value("a"),
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Value("1"),
+//!     InputToken::RightParen,
+//!     InputToken::Operator(Math::Div),
+//!     InputToken::Function("sin"),
+//!     InputToken::LeftParen,
+//!     InputToken::Value("b"),
+//!     InputToken::RightParen,
+//! ]).unwrap();
+//! assert_eq!(
+//!     postfix_to_latex(&postfix).as_deref(),
+//!     Ok(r"\frac{a + 1}{\sin\left(b\right)}")
+//! );
+//! ```
+
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Display;
+
+use super::{
+    borrowed_ast_with_operators,
+    render::{render, Notation, Operand},
+    AstError, Expr,
+};
+use crate::{
+    op::{All, Compare, Logical, Math},
+    Call, Operator, OutputToken,
+};
+
+/// Functions LaTeX has a command for.
+const KNOWN_FUNCTIONS: &[&str] = &[
+    "arccos", "arcsin", "arctan", "cos", "cosh", "cot", "det", "exp", "gcd", "lg", "ln", "log",
+    "max", "min", "sin", "sinh", "tan", "tanh",
+];
+
+type FunctionHook<'a, F> = Box<dyn FnMut(&F, &[String]) -> Option<String> + 'a>;
+
+/// A `Notation` producing LaTeX math.
+///
+/// Functions with a LaTeX command like `sin` are written as `\sin`, `sqrt` and `abs` use `\sqrt`
+/// and `\left|..\right|` and all other functions use `\operatorname`. Use `with_function` to
+/// customize the output for your own functions.
+pub struct Latex<'a, F> {
+    function: Option<FunctionHook<'a, F>>,
+}
+
+impl<F> Default for Latex<'_, F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, F> Latex<'a, F> {
+    /// Creates a notation using the default function rendering.
+    pub fn new() -> Self {
+        Self { function: None }
+    }
+
+    /// Renders function calls using `hook`. If `hook` returns `None` the default rendering is
+    /// used. The arguments are already rendered.
+    pub fn with_function(hook: impl FnMut(&F, &[String]) -> Option<String> + 'a) -> Self {
+        Self {
+            function: Some(Box::new(hook)),
+        }
+    }
+}
+
+impl<V, F, O> Notation<V, F, O> for Latex<'_, F>
+where
+    V: Display,
+    F: Display,
+    O: Into<All> + Clone,
+{
+    type Output = String;
+
+    fn value(&mut self, value: &V) -> String {
+        value.to_string()
+    }
+
+    fn parens(&mut self, output: String) -> String {
+        format!(r"\left({output}\right)")
+    }
+
+    fn operator(&mut self, operator: &O, operands: Vec<Operand<String>>) -> String {
+        let operator: All = operator.clone().into();
+        let symbol = match operator {
+            All::Math(Math::Add) => "+",
+            All::Math(Math::Sub) => "-",
+            All::Math(Math::Mul) => r"\cdot",
+            All::Math(Math::Div) => "/",
+            All::Math(Math::Exponent) => "^",
+            All::Compare(Compare::Lt) => "<",
+            All::Compare(Compare::Le) => r"\leq",
+            All::Compare(Compare::Eq) => "=",
+            All::Compare(Compare::Ne) => r"\neq",
+            All::Compare(Compare::Ge) => r"\geq",
+            All::Compare(Compare::Gt) => ">",
+            All::Logical(Logical::Xor) => r"\oplus",
+            All::Logical(Logical::And) => r"\land",
+            All::Logical(Logical::Or) => r"\lor",
+            All::Logical(Logical::Not) => r"\lnot",
+        };
+        let mut operands = operands.into_iter();
+        match (operator, operands.next(), operands.next()) {
+            (All::Math(Math::Div), Some(a), Some(b)) => {
+                format!(r"\frac{{{}}}{{{}}}", a.output, b.output)
+            }
+            (All::Math(Math::Exponent), Some(a), Some(b)) => {
+                format!("{}^{{{}}}", a.grouped::<V, F, O, _>(self), b.output)
+            }
+            (_, Some(a), Some(b)) => format!(
+                "{} {symbol} {}",
+                a.grouped::<V, F, O, _>(self),
+                b.grouped::<V, F, O, _>(self)
+            ),
+            (_, Some(a), None) => format!("{symbol} {}", a.grouped::<V, F, O, _>(self)),
+            (_, None, _) => symbol.to_string(),
+        }
+    }
+
+    fn function(&mut self, function: &F, args: Vec<String>) -> String {
+        if let Some(output) = self
+            .function
+            .as_mut()
+            .and_then(|hook| hook(function, &args))
+        {
+            return output;
+        }
+        let name = function.to_string();
+        match (name.as_str(), args.as_slice()) {
+            ("sqrt", [arg]) => format!(r"\sqrt{{{arg}}}"),
+            ("abs", [arg]) => format!(r"\left|{arg}\right|"),
+            (name, args) if KNOWN_FUNCTIONS.contains(&name) => {
+                format!(r"\{name}\left({}\right)", args.join(", "))
+            }
+            (name, args) => format!(
+                r"\operatorname{{{}}}\left({}\right)",
+                name.replace('_', r"\_"),
+                args.join(", ")
+            ),
+        }
+    }
+}
+
+/// Renders `expr` as LaTeX math using the default `Latex` notation.
+pub fn to_latex<V, F, O>(expr: &Expr<V, F, O>) -> String
+where
+    V: Display,
+    F: Display,
+    O: Operator + Into<All> + Clone,
+{
+    render(expr, &mut Latex::new())
+}
+
+/// Renders a postfix expression created by `to_postfix_with_arity` as LaTeX math.
+pub fn postfix_to_latex<V, F, O>(postfix: &[OutputToken<V, Call<F>, O>]) -> Result<String, AstError>
+where
+    V: Display,
+    F: Display,
+    O: Operator + Into<All> + Clone,
+{
+    Ok(to_latex(&borrowed_ast_with_operators(postfix)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, vec};
+
+    use super::{to_latex, Latex};
+    use crate::{
+        ast::{render::render, Expr},
+        op::{Logical, Math},
+    };
+
+    #[test]
+    fn operators() {
+        let op = |operator, a, b| Expr::<_, &str, _>::Operator {
+            operator,
+            args: vec![a, b],
+        };
+        // 2 * ( x - 1 ) ^ 2
+        let expr = op(
+            Math::Mul,
+            Expr::Value(2),
+            op(
+                Math::Exponent,
+                op(Math::Sub, Expr::Value(3), Expr::Value(1)),
+                Expr::Value(2),
+            ),
+        );
+        assert_eq!(to_latex(&expr), r"2 \cdot \left(3 - 1\right)^{2}");
+        let not = Expr::<bool, &str, _>::Operator {
+            operator: Logical::Not,
+            args: vec![Expr::Value(true)],
+        };
+        assert_eq!(to_latex(&not), r"\lnot true");
+    }
+
+    #[test]
+    fn function_hook() {
+        let call = |function, args| Expr::<i32, _, Math>::Function { function, args };
+        let expr = call(
+            "my_avg",
+            vec![Expr::Value(1), call("root", vec![Expr::Value(2)])],
+        );
+        let mut latex = Latex::with_function(|f: &&str, args| {
+            (*f == "root").then(|| format!(r"\sqrt{{{}}}", args[0]))
+        });
+        assert_eq!(
+            render(&expr, &mut latex),
+            r"\operatorname{my\_avg}\left(1, \sqrt{2}\right)"
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Presentation MathML rendering of expression trees.
+//! Values that parse as numbers are written as `<mn>`, all other values as `<mi>`.
+//!
+//! ```rust
+//! use gyard::{ast::{mathml::to_mathml, Expr}, op::Math};
+//!
+//! // x ^ 2 / 2
+//! let op = |operator, a, b| Expr::<_, &str, _>::Operator { operator, args: vec![a, b] };
+//! let expr = op(Math::Div, op(Math::Exponent, Expr::Value("x"), Expr::Value("2")), Expr::Value("2"));
+//! assert_eq!(
+//!     to_mathml(&expr),
+//!     "<math><mfrac><msup><mi>x</mi><mn>2</mn></msup><mn>2</mn></mfrac></math>"
+//! );
+//! ```
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Display;
+
+use super::{
+    borrowed_ast_with_operators,
+    render::{render, Notation, Operand},
+    AstError, Expr,
+};
+use crate::{
+    op::{All, Compare, Logical, Math},
+    Call, Operator, OutputToken,
+};
+
+/// A `Notation` producing Presentation MathML elements without the surrounding `<math>` element.
+/// `sqrt` and `abs` are rendered as roots and absolute values, all other functions as a name
+/// followed by the arguments in parentheses.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MathMl;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl<V, F, O> Notation<V, F, O> for MathMl
+where
+    V: Display,
+    F: Display,
+    O: Into<All> + Clone,
+{
+    type Output = String;
+
+    fn value(&mut self, value: &V) -> String {
+        let value = value.to_string();
+        let tag = if value.parse::<f64>().is_ok() {
+            "mn"
+        } else {
+            "mi"
+        };
+        format!("<{tag}>{}</{tag}>", escape(&value))
+    }
+
+    fn parens(&mut self, output: String) -> String {
+        format!("<mrow><mo>(</mo>{output}<mo>)</mo></mrow>")
+    }
+
+    fn operator(&mut self, operator: &O, operands: Vec<Operand<String>>) -> String {
+        let operator: All = operator.clone().into();
+        let symbol = match operator {
+            All::Math(Math::Add) => "+",
+            All::Math(Math::Sub) => "-",
+            All::Math(Math::Mul) => "⋅",
+            All::Math(Math::Div) => "/",
+            All::Math(Math::Exponent) => "^",
+            All::Compare(Compare::Lt) => "&lt;",
+            All::Compare(Compare::Le) => "≤",
+            All::Compare(Compare::Eq) => "=",
+            All::Compare(Compare::Ne) => "≠",
+            All::Compare(Compare::Ge) => "≥",
+            All::Compare(Compare::Gt) => "&gt;",
+            All::Logical(Logical::Xor) => "⊕",
+            All::Logical(Logical::And) => "∧",
+            All::Logical(Logical::Or) => "∨",
+            All::Logical(Logical::Not) => "¬",
+        };
+        let mut operands = operands.into_iter();
+        match (operator, operands.next(), operands.next()) {
+            (All::Math(Math::Div), Some(a), Some(b)) => {
+                format!("<mfrac>{}{}</mfrac>", a.output, b.output)
+            }
+            (All::Math(Math::Exponent), Some(a), Some(b)) => {
+                format!("<msup>{}{}</msup>", a.grouped::<V, F, O, _>(self), b.output)
+            }
+            (_, Some(a), Some(b)) => format!(
+                "<mrow>{}<mo>{symbol}</mo>{}</mrow>",
+                a.grouped::<V, F, O, _>(self),
+                b.grouped::<V, F, O, _>(self)
+            ),
+            (_, Some(a), None) => format!(
+                "<mrow><mo>{symbol}</mo>{}</mrow>",
+                a.grouped::<V, F, O, _>(self)
+            ),
+            (_, None, _) => format!("<mo>{symbol}</mo>"),
+        }
+    }
+
+    fn function(&mut self, function: &F, args: Vec<String>) -> String {
+        let name = function.to_string();
+        match (name.as_str(), args.as_slice()) {
+            ("sqrt", [arg]) => format!("<msqrt>{arg}</msqrt>"),
+            ("abs", [arg]) => format!("<mrow><mo>|</mo>{arg}<mo>|</mo></mrow>"),
+            (name, args) => format!(
+                "<mrow><mi>{}</mi><mo>&#x2061;</mo><mrow><mo>(</mo>{}<mo>)</mo></mrow></mrow>",
+                escape(name),
+                args.join("<mo>,</mo>")
+            ),
+        }
+    }
+}
+
+/// Renders `expr` as a `<math>` element.
+pub fn to_mathml<V, F, O>(expr: &Expr<V, F, O>) -> String
+where
+    V: Display,
+    F: Display,
+    O: Operator + Into<All> + Clone,
+{
+    format!("<math>{}</math>", render(expr, &mut MathMl))
+}
+
+/// Renders a postfix expression created by `to_postfix_with_arity` as a `<math>` element.
+pub fn postfix_to_mathml<V, F, O>(
+    postfix: &[OutputToken<V, Call<F>, O>],
+) -> Result<String, AstError>
+where
+    V: Display,
+    F: Display,
+    O: Operator + Into<All> + Clone,
+{
+    Ok(to_mathml(&borrowed_ast_with_operators(postfix)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::postfix_to_mathml;
+    use crate::{op::Compare, Call, OutputToken};
+
+    #[test]
+    fn escaping_and_functions() {
+        // f ( a ) < 1
+        let postfix = [
+            OutputToken::Value("a"),
+            OutputToken::Function(Call {
+                function: "f",
+                arity: 1,
+            }),
+            OutputToken::Value("1"),
+            OutputToken::Operator(Compare::Lt),
+        ];
+        assert_eq!(
+            postfix_to_mathml(&postfix).as_deref(),
+            Ok("<math><mrow><mrow><mi>f</mi><mo>&#x2061;</mo><mrow><mo>(</mo><mi>a</mi><mo>)</mo></mrow></mrow><mo>&lt;</mo><mn>1</mn></mrow></math>")
+        );
+    }
+}
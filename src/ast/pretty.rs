@@ -0,0 +1,296 @@
+//! A configurable textual pretty-printer.
+//!
+//! ```rust
+//! use gyard::{ast::{pretty::{Parens, PrettyPrinter}, Expr}, op::Math};
+//!
+//! let op = |operator, a, b| Expr::<_, &str, _>::Operator { operator, args: vec![a, b] };
+//! // 1 + 2 * 3
+//! let expr = op(Math::Add, Expr::Value(1), op(Math::Mul, Expr::Value(2), Expr::Value(3)));
+//!
+//! assert_eq!(PrettyPrinter::new().print(&expr), "1 + 2 * 3");
+//! let printer = PrettyPrinter::new()
+//!     .with_spacing(false)
+//!     .with_parens(Parens::Always)
+//!     .with_symbols(|op: &Math| match op {
+//!         Math::Mul => "×".to_string(),
+//!         op => op.to_string(),
+//!     });
+//! assert_eq!(printer.print(&expr), "1+(2×3)");
+//! ```
+
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::Display;
+
+use super::{
+    render::{render, Notation, Operand},
+    Expr,
+};
+use crate::Operator;
+
+/// Controls where `PrettyPrinter` inserts parentheses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Parens {
+    /// Only where precedence or associativity require them.
+    #[default]
+    Minimal,
+    /// Around every operator that is the operand of another operator.
+    Always,
+}
+
+type Symbols<'a, O> = Box<dyn Fn(&O) -> String + 'a>;
+
+/// Renders expressions as text. Lines longer than `with_max_width` are broken before operators
+/// and after argument separators, nested parts are indented.
+pub struct PrettyPrinter<'a, O> {
+    spacing: bool,
+    parens: Parens,
+    max_width: Option<usize>,
+    indent: usize,
+    symbols: Symbols<'a, O>,
+}
+
+impl<O: Display> Default for PrettyPrinter<'_, O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<O: Display> PrettyPrinter<'_, O> {
+    /// Creates a printer with spaces around binary operators, minimal parentheses, no line limit
+    /// and operators written using `Display`.
+    pub fn new() -> Self {
+        Self {
+            spacing: true,
+            parens: Parens::Minimal,
+            max_width: None,
+            indent: 4,
+            symbols: Box::new(|operator| operator.to_string()),
+        }
+    }
+}
+
+impl<'a, O> PrettyPrinter<'a, O> {
+    /// Sets whether binary operators and argument separators are followed by spaces.
+    pub fn with_spacing(mut self, spacing: bool) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Sets the parenthesization policy.
+    pub fn with_parens(mut self, parens: Parens) -> Self {
+        self.parens = parens;
+        self
+    }
+
+    /// Breaks lines longer than `max_width` characters if possible.
+    pub fn with_max_width(mut self, max_width: usize) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    /// Sets the number of spaces continuation lines are indented by. Defaults to 4.
+    pub fn with_indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Sets the symbol table used to write operators.
+    pub fn with_symbols(mut self, symbols: impl Fn(&O) -> String + 'a) -> Self {
+        self.symbols = Box::new(symbols);
+        self
+    }
+
+    /// Renders `expr`.
+    pub fn print<V: Display, F: Display>(&self, expr: &Expr<V, F, O>) -> String
+    where
+        O: Operator,
+    {
+        let fragment = render(expr, &mut Printer(self));
+        let mut out = String::new();
+        let mut column = 0;
+        self.layout(&fragment.doc, &mut out, 0, false, &mut column);
+        out
+    }
+
+    fn layout(&self, doc: &Doc, out: &mut String, indent: usize, flat: bool, column: &mut usize) {
+        match doc {
+            Doc::Text(text) => {
+                out.push_str(text);
+                *column += text.chars().count();
+            }
+            Doc::Break(text) if flat => {
+                out.push_str(text);
+                *column += text.chars().count();
+            }
+            Doc::Break(_) => {
+                out.push('\n');
+                out.extend(core::iter::repeat_n(' ', indent));
+                *column = indent;
+            }
+            Doc::Concat(docs) => {
+                for doc in docs {
+                    self.layout(doc, out, indent, flat, column);
+                }
+            }
+            Doc::Nest(doc) => self.layout(doc, out, indent + self.indent, flat, column),
+            Doc::Group(doc) => {
+                let flat = flat
+                    || self
+                        .max_width
+                        .is_none_or(|max| *column + doc.width() <= max);
+                self.layout(doc, out, indent, flat, column)
+            }
+        }
+    }
+}
+
+/// A document describing all possible layouts of an expression.
+#[derive(Debug, Clone)]
+enum Doc {
+    Text(String),
+    /// Written as the text if the surrounding group fits on the line, as a line break otherwise
+    Break(&'static str),
+    Concat(Vec<Doc>),
+    Nest(Box<Doc>),
+    Group(Box<Doc>),
+}
+
+impl Doc {
+    fn width(&self) -> usize {
+        match self {
+            Doc::Text(text) => text.chars().count(),
+            Doc::Break(text) => text.len(),
+            Doc::Concat(docs) => docs.iter().map(Doc::width).sum(),
+            Doc::Nest(doc) | Doc::Group(doc) => doc.width(),
+        }
+    }
+}
+
+/// A partially rendered expression. See `PrettyPrinter`.
+#[derive(Debug, Clone)]
+pub struct Fragment {
+    doc: Doc,
+    operator: bool,
+}
+
+struct Printer<'p, 'a, O>(&'p PrettyPrinter<'a, O>);
+
+impl<V: Display, F: Display, O> Notation<V, F, O> for Printer<'_, '_, O> {
+    type Output = Fragment;
+
+    fn value(&mut self, value: &V) -> Fragment {
+        Fragment {
+            doc: Doc::Text(value.to_string()),
+            operator: false,
+        }
+    }
+
+    fn parens(&mut self, output: Fragment) -> Fragment {
+        Fragment {
+            doc: Doc::Concat(vec![
+                Doc::Text("(".to_string()),
+                output.doc,
+                Doc::Text(")".to_string()),
+            ]),
+            operator: false,
+        }
+    }
+
+    fn operator(&mut self, operator: &O, operands: Vec<Operand<Fragment>>) -> Fragment {
+        let symbol = (self.0.symbols)(operator);
+        let unary = operands.len() == 1;
+        let (space, separator) = if self.0.spacing && !unary {
+            (" ", format!("{symbol} "))
+        } else {
+            ("", symbol)
+        };
+        let mut operands = operands.into_iter().map(|mut operand| {
+            operand.needs_parens |= self.0.parens == Parens::Always && operand.output.operator;
+            operand.grouped::<V, F, O, _>(self).doc
+        });
+        let doc = if unary {
+            Doc::Concat(
+                vec![Doc::Text(separator)]
+                    .into_iter()
+                    .chain(operands)
+                    .collect(),
+            )
+        } else {
+            let mut docs: Vec<_> = operands.next().into_iter().collect();
+            for operand in operands {
+                docs.push(Doc::Nest(Box::new(Doc::Concat(vec![
+                    Doc::Break(space),
+                    Doc::Text(separator.clone()),
+                    operand,
+                ]))));
+            }
+            Doc::Group(Box::new(Doc::Concat(docs)))
+        };
+        Fragment {
+            doc,
+            operator: true,
+        }
+    }
+
+    fn function(&mut self, function: &F, args: Vec<Fragment>) -> Fragment {
+        let separator = if self.0.spacing { " " } else { "" };
+        let mut docs = Vec::new();
+        for (i, arg) in args.into_iter().enumerate() {
+            if i > 0 {
+                docs.push(Doc::Text(",".to_string()));
+                docs.push(Doc::Break(separator));
+            }
+            docs.push(arg.doc);
+        }
+        Fragment {
+            doc: Doc::Group(Box::new(Doc::Concat(vec![
+                Doc::Text(format!("{function}(")),
+                Doc::Nest(Box::new(Doc::Concat(docs))),
+                Doc::Text(")".to_string()),
+            ]))),
+            operator: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::PrettyPrinter;
+    use crate::{ast::Expr, op::Math};
+
+    #[test]
+    fn line_wrapping() {
+        let op = |operator, a, b| Expr::<_, &str, _>::Operator {
+            operator,
+            args: vec![a, b],
+        };
+        // price * amount + shipping - discount
+        let expr = op(
+            Math::Sub,
+            op(
+                Math::Add,
+                op(Math::Mul, Expr::Value("price"), Expr::Value("amount")),
+                Expr::Value("shipping"),
+            ),
+            Expr::Value("discount"),
+        );
+        let printer = PrettyPrinter::new().with_max_width(30);
+        assert_eq!(
+            printer.print(&expr),
+            "price * amount + shipping\n    - discount"
+        );
+        let printer = PrettyPrinter::new().with_max_width(20);
+        assert_eq!(
+            printer.print(&expr),
+            "price * amount\n    + shipping\n    - discount"
+        );
+    }
+}
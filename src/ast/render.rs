@@ -0,0 +1,106 @@
+//! Rendering expression trees into other notations.
+//! `render` walks the tree and decides which operands need parentheses, a `Notation` decides how
+//! values, operators, functions and parentheses are written.
+//!
+//! ```rust
+//! use gyard::{ast::{render::{render, Notation, Operand}, Expr}, op::Math};
+//!
+//! struct Plain;
+//!
+//! impl Notation<i32, &str, Math> for Plain {
+//!     type Output = String;
+//!
+//!     fn value(&mut self, value: &i32) -> String {
+//!         value.to_string()
+//!     }
+//!     fn parens(&mut self, output: String) -> String {
+//!         format!("({output})")
+//!     }
+//!     fn operator(&mut self, operator: &Math, operands: Vec<Operand<String>>) -> String {
+//!         let operands: Vec<_> = operands.into_iter().map(|o| o.grouped(self)).collect();
+//!         operands.join(&format!(" {operator} "))
+//!     }
+//!     fn function(&mut self, function: &&str, args: Vec<String>) -> String {
+//!         format!("{function}({})", args.join(", "))
+//!     }
+//! }
+//!
+//! let op = |operator, a, b| Expr::Operator { operator, args: vec![a, b] };
+//! let expr = op(Math::Mul, op(Math::Add, Expr::Value(1), Expr::Value(2)), Expr::Value(3));
+//! assert_eq!(render(&expr, &mut Plain), "(1 + 2) * 3");
+//! ```
+
+use alloc::vec::Vec;
+
+use super::Expr;
+use crate::Operator;
+
+/// An already rendered operand of an operator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operand<T> {
+    /// The rendered operand
+    pub output: T,
+    /// True if the operand has to be wrapped in parentheses when written next to the operator.
+    pub needs_parens: bool,
+}
+
+impl<T> Operand<T> {
+    /// Returns the output, wrapped in parentheses by `notation` if required.
+    pub fn grouped<V, F, O, N>(self, notation: &mut N) -> T
+    where
+        N: Notation<V, F, O, Output = T> + ?Sized,
+    {
+        if self.needs_parens {
+            notation.parens(self.output)
+        } else {
+            self.output
+        }
+    }
+}
+
+/// Describes how an expression is written. See `render`.
+pub trait Notation<V, F, O> {
+    /// The rendered expression, i.e. `String`
+    type Output;
+
+    /// Renders a value.
+    fn value(&mut self, value: &V) -> Self::Output;
+    /// Wraps an expression in parentheses.
+    fn parens(&mut self, output: Self::Output) -> Self::Output;
+    /// Renders an operator. Operands that would change their meaning when written next to the
+    /// operator without parentheses are marked with `Operand::needs_parens`.
+    fn operator(&mut self, operator: &O, operands: Vec<Operand<Self::Output>>) -> Self::Output;
+    /// Renders a function call.
+    fn function(&mut self, function: &F, args: Vec<Self::Output>) -> Self::Output;
+}
+
+/// Renders `expr` bottom up using `notation`.
+pub fn render<V, F, O, N>(expr: &Expr<V, F, O>, notation: &mut N) -> N::Output
+where
+    O: Operator,
+    N: Notation<V, F, O> + ?Sized,
+{
+    match expr {
+        Expr::Value(value) => notation.value(value),
+        Expr::Function { function, args } => {
+            let args = args.iter().map(|arg| render(arg, notation)).collect();
+            notation.function(function, args)
+        }
+        Expr::Operator { operator, args } => {
+            let unary = args.len() == 1;
+            let operands = args
+                .iter()
+                .enumerate()
+                .map(|(i, arg)| Operand {
+                    output: render(arg, notation),
+                    needs_parens: if i == 0 && !unary {
+                        !arg.binds_left_of(operator)
+                    } else {
+                        !arg.binds_right_of(operator)
+                    },
+                })
+                .collect();
+            notation.operator(operator, operands)
+        }
+    }
+}
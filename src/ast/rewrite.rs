@@ -0,0 +1,155 @@
+//! Rewriting of expression trees.
+//! A `Rewriter` applies rules to every subtree until no rule matches anymore. Rules are closures
+//! that match a subtree and return its replacement, or `None` if they do not apply.
+//!
+//! ```rust
+//! use gyard::{ast::{rewrite::Rewriter, Expr}, op::Math};
+//!
+//! type E = Expr<i64, (), Math>;
+//!
+//! // x * 1 → x
+//! let rewriter = Rewriter::new().with_rule(|expr: &E| match expr {
+//!     Expr::Operator { operator: Math::Mul, args } if args[1] == Expr::Value(1) => {
+//!         Some(args[0].clone())
+//!     }
+//!     _ => None,
+//! });
+//!
+//! // ( 5 * 1 ) * 1
+//! let mul = |a, b| E::Operator { operator: Math::Mul, args: vec![a, b] };
+//! let expr = mul(mul(Expr::Value(5), Expr::Value(1)), Expr::Value(1));
+//! assert_eq!(rewriter.rewrite(expr), Expr::Value(5));
+//! ```
+
+use alloc::{boxed::Box, vec::Vec};
+
+use super::Expr;
+
+/// A rewrite rule. Returns the replacement of `expr` or `None` if the rule does not apply.
+///
+/// This trait is implemented for all closures `Fn(&Expr<V, F, O>) -> Option<Expr<V, F, O>>`.
+pub trait Rule<V, F, O> {
+    /// Returns the replacement of `expr` or `None` if the rule does not apply
+    fn apply(&self, expr: &Expr<V, F, O>) -> Option<Expr<V, F, O>>;
+}
+
+impl<V, F, O, R> Rule<V, F, O> for R
+where
+    R: Fn(&Expr<V, F, O>) -> Option<Expr<V, F, O>>,
+{
+    fn apply(&self, expr: &Expr<V, F, O>) -> Option<Expr<V, F, O>> {
+        self(expr)
+    }
+}
+
+/// Applies rewrite rules to fixpoint.
+/// Children are rewritten before their parent. Rules are tried in the order they were added.
+pub struct Rewriter<'a, V, F, O> {
+    rules: Vec<Box<dyn Rule<V, F, O> + 'a>>,
+    max_rewrites: usize,
+}
+
+impl<V, F, O> Default for Rewriter<'_, V, F, O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, V, F, O> Rewriter<'a, V, F, O> {
+    /// Creates a rewriter without rules.
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            max_rewrites: 10_000,
+        }
+    }
+
+    /// Adds a rule.
+    pub fn with_rule(mut self, rule: impl Rule<V, F, O> + 'a) -> Self {
+        self.add_rule(rule);
+        self
+    }
+
+    /// Adds a rule.
+    pub fn add_rule(&mut self, rule: impl Rule<V, F, O> + 'a) {
+        self.rules.push(Box::new(rule));
+    }
+
+    /// Sets the maximum number of rule applications per call to `rewrite`. The default is 10000.
+    /// This stops rule sets that never reach a fixpoint, i.e. `a + b → b + a`.
+    pub fn with_max_rewrites(mut self, max_rewrites: usize) -> Self {
+        self.max_rewrites = max_rewrites;
+        self
+    }
+
+    /// Rewrites `expr` until no rule applies or the maximum number of rewrites is reached.
+    pub fn rewrite(&self, expr: Expr<V, F, O>) -> Expr<V, F, O> {
+        let mut budget = self.max_rewrites;
+        self.rewrite_node(expr, &mut budget)
+    }
+
+    fn rewrite_node(&self, expr: Expr<V, F, O>, budget: &mut usize) -> Expr<V, F, O> {
+        let mut expr = match expr {
+            Expr::Value(value) => Expr::Value(value),
+            Expr::Operator { operator, args } => Expr::Operator {
+                operator,
+                args: self.rewrite_all(args, budget),
+            },
+            Expr::Function { function, args } => Expr::Function {
+                function,
+                args: self.rewrite_all(args, budget),
+            },
+        };
+        while *budget > 0 {
+            let Some(replacement) = self.rules.iter().find_map(|rule| rule.apply(&expr)) else {
+                break;
+            };
+            *budget -= 1;
+            // The replacement may contain subtrees that match a rule
+            expr = self.rewrite_node(replacement, budget);
+        }
+        expr
+    }
+
+    fn rewrite_all(&self, args: Vec<Expr<V, F, O>>, budget: &mut usize) -> Vec<Expr<V, F, O>> {
+        args.into_iter()
+            .map(|arg| self.rewrite_node(arg, budget))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::Rewriter;
+    use crate::{ast::Expr, op::Math};
+
+    type E = Expr<char, (), Math>;
+
+    fn add(a: E, b: E) -> E {
+        Expr::Operator {
+            operator: Math::Add,
+            args: vec![a, b],
+        }
+    }
+
+    #[test]
+    fn stops_after_max_rewrites() {
+        // a + b → b + a never reaches a fixpoint
+        let rewriter = Rewriter::new()
+            .with_rule(|expr: &E| match expr {
+                Expr::Operator {
+                    operator: Math::Add,
+                    args,
+                } => Some(add(args[1].clone(), args[0].clone())),
+                _ => None,
+            })
+            .with_max_rewrites(3);
+        let expr = add(Expr::Value('a'), Expr::Value('b'));
+        assert_eq!(
+            rewriter.rewrite(expr),
+            add(Expr::Value('b'), Expr::Value('a'))
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Lisp style s-expressions.
+//! Every operator and function call is written as a list of the operator or function followed by
+//! its operands, so the output does not depend on precedence and is easy to diff.
+//!
+//! ```rust
+//! use gyard::{ast::sexpr::postfix_to_sexpr, op::Math, to_postfix_with_arity, InputToken};
+//!
+//! // 5 + 2 * sin ( 123 )
+//! let postfix = to_postfix_with_arity([
+//!     InputToken::Value(5),
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Value(2),
+//!     InputToken::Operator(Math::Mul),
+//!     InputToken::Function("sin"),
+//!     InputToken::LeftParen,
+//!     InputToken::Value(123),
+//!     InputToken::RightParen,
+//! ]).unwrap();
+//! assert_eq!(postfix_to_sexpr(&postfix).as_deref(), Ok("(+ 5 (* 2 (sin 123)))"));
+//! ```
+
+use alloc::string::String;
+use core::fmt::{Display, Write};
+
+use super::{borrowed_ast, AstError, Expr};
+use crate::{Call, Operator, OutputToken};
+
+/// Renders `expr` as an s-expression.
+pub fn to_sexpr<V, F, O>(expr: &Expr<V, F, O>) -> String
+where
+    V: Display,
+    F: Display,
+    O: Display,
+{
+    let mut sexpr = String::new();
+    write_sexpr(expr, &mut sexpr);
+    sexpr
+}
+
+/// Renders a postfix expression created by `to_postfix_with_arity` as an s-expression.
+pub fn postfix_to_sexpr<V, F, O>(postfix: &[OutputToken<V, Call<F>, O>]) -> Result<String, AstError>
+where
+    V: Display,
+    F: Display,
+    O: Operator + Display,
+{
+    Ok(to_sexpr(&borrowed_ast(postfix)?))
+}
+
+fn write_sexpr<V, F, O>(expr: &Expr<V, F, O>, sexpr: &mut String)
+where
+    V: Display,
+    F: Display,
+    O: Display,
+{
+    // Writing to a String never fails
+    let args = match expr {
+        Expr::Value(value) => {
+            let _ = write!(sexpr, "{value}");
+            return;
+        }
+        Expr::Operator { operator, args } => {
+            let _ = write!(sexpr, "({operator}");
+            args
+        }
+        Expr::Function { function, args } => {
+            let _ = write!(sexpr, "({function}");
+            args
+        }
+    };
+    for arg in args {
+        sexpr.push(' ');
+        write_sexpr(arg, sexpr);
+    }
+    sexpr.push(')');
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::to_sexpr;
+    use crate::{ast::Expr, op::Logical};
+
+    #[test]
+    fn functions_without_arguments() {
+        let expr = Expr::Operator {
+            operator: Logical::Not,
+            args: vec![Expr::<bool, _, _>::Function {
+                function: "random",
+                args: vec![],
+            }],
+        };
+        assert_eq!(to_sexpr(&expr), "(! (random))");
+    }
+}
@@ -0,0 +1,327 @@
+//! Algebraic simplification of expressions using the predefined operators.
+//!
+//! - `x + -0`, `-0 + x`, `x - 0`, `x * 1`, `1 * x`, `x / 1` and `x ** 1` become `x`. `x + 0` is
+//!   kept, because it turns `-0` into `0`.
+//! - `!!x` becomes `x` and `!` of a constant is folded.
+//! - `&&` and `||` with a constant operand are reduced.
+//! - Comparisons of two constant numbers are folded.
+//!
+//! Rules that change the result for NaN or infinite operands, like `x * 0 → 0`, are not applied.
+//! The rules assume that operands have the types their operators expect, so an expression that
+//! would fail with a type error may simplify to a valid one.
+//!
+//! ```rust
+//! use gyard::{ast::{simplify::simplify, Expr}, op::{All, Compare, Logical, Math}};
+//!
+//! type E = Expr<f64, (), All>;
+//! let op = |operator: All, args| E::Operator { operator, args };
+//!
+//! // ! ! ( x * 1 + -0 )
+//! let x = Expr::Value(f64::NAN);
+//! let sum = op(Math::Add.into(), vec![op(Math::Mul.into(), vec![x.clone(), Expr::Value(1.)]), Expr::Value(-0.)]);
+//! let expr = op(Logical::Not.into(), vec![op(Logical::Not.into(), vec![sum])]);
+//! assert!(matches!(simplify(expr), Expr::Value(v) if v.is_nan()));
+//! ```
+
+use super::{rewrite::Rewriter, Expr};
+use crate::op::{All, Compare, Logical, Math};
+
+/// Values that can be constants of the predefined operators
+pub trait Literal: Sized {
+    /// Returns the number this value represents, if it is a constant number
+    fn as_number(&self) -> Option<f64>;
+    /// Returns the boolean this value represents, if it is a constant boolean
+    fn as_bool(&self) -> Option<bool>;
+    /// Creates a constant boolean. Returns `None` if booleans can not be represented.
+    fn from_bool(value: bool) -> Option<Self>;
+}
+
+/// Numbers are constants, NaN is treated as an unknown value.
+impl Literal for f64 {
+    fn as_number(&self) -> Option<f64> {
+        (!self.is_nan()).then_some(*self)
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        None
+    }
+
+    fn from_bool(_: bool) -> Option<Self> {
+        None
+    }
+}
+
+#[cfg(feature = "value")]
+impl Literal for crate::value::Value {
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    fn from_bool(value: bool) -> Option<Self> {
+        Some(Self::Bool(value))
+    }
+}
+
+/// Simplifies `expr` to fixpoint. See the module documentation for the applied rules.
+pub fn simplify<V, F, O>(expr: Expr<V, F, O>) -> Expr<V, F, O>
+where
+    V: Literal + Clone,
+    F: Clone,
+    O: Into<All> + Clone,
+{
+    Rewriter::new().with_rule(simplify_rule).rewrite(expr)
+}
+
+/// A rewrite rule performing a single simplification step. Add it to a `Rewriter` to combine it
+/// with your own rules.
+pub fn simplify_rule<V, F, O>(expr: &Expr<V, F, O>) -> Option<Expr<V, F, O>>
+where
+    V: Literal + Clone,
+    F: Clone,
+    O: Into<All> + Clone,
+{
+    let Expr::Operator { operator, args } = expr else {
+        return None;
+    };
+    let number = |i: usize| match &args[i] {
+        Expr::Value(value) => value.as_number(),
+        _ => None,
+    };
+    let boolean = |i: usize| match &args[i] {
+        Expr::Value(value) => value.as_bool(),
+        _ => None,
+    };
+    // Only `x + -0` and `x - 0` are `x` for every `x`, `-0 + 0` is `0`
+    let zero = |i: usize, negative: bool| {
+        number(i).is_some_and(|n| n == 0. && n.is_sign_negative() == negative)
+    };
+    let arg = |i: usize| Some(args[i].clone());
+
+    match (operator.clone().into(), args.len()) {
+        (All::Math(Math::Add), 2) if zero(1, true) => arg(0),
+        (All::Math(Math::Add), 2) if zero(0, true) => arg(1),
+        (All::Math(Math::Sub), 2) if zero(1, false) => arg(0),
+        (All::Math(Math::Mul), 2) if number(1) == Some(1.) => arg(0),
+        (All::Math(Math::Mul), 2) if number(0) == Some(1.) => arg(1),
+        (All::Math(Math::Div | Math::Exponent), 2) if number(1) == Some(1.) => arg(0),
+        (All::Logical(Logical::Not), 1) => match &args[0] {
+            Expr::Operator { operator, args } if args.len() == 1 => {
+                matches!(operator.clone().into(), All::Logical(Logical::Not))
+                    .then(|| args[0].clone())
+            }
+            _ => Some(Expr::Value(V::from_bool(!boolean(0)?)?)),
+        },
+        (All::Logical(Logical::And), 2) => match (boolean(0), boolean(1)) {
+            (Some(true), _) => arg(1),
+            (_, Some(true)) => arg(0),
+            (Some(false), _) => arg(0),
+            (_, Some(false)) => arg(1),
+            _ => None,
+        },
+        (All::Logical(Logical::Or), 2) => match (boolean(0), boolean(1)) {
+            (Some(false), _) => arg(1),
+            (_, Some(false)) => arg(0),
+            (Some(true), _) => arg(0),
+            (_, Some(true)) => arg(1),
+            _ => None,
+        },
+        (All::Compare(compare), 2) => {
+            let (a, b) = (number(0)?, number(1)?);
+            let result = match compare {
+                Compare::Lt => a < b,
+                Compare::Le => a <= b,
+                Compare::Eq => a == b,
+                Compare::Ne => a != b,
+                Compare::Ge => a >= b,
+                Compare::Gt => a > b,
+            };
+            Some(Expr::Value(V::from_bool(result)?))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::{simplify, Literal};
+    use crate::{
+        ast::Expr,
+        op::{All, Compare, Logical, Math},
+    };
+
+    /// A constant or a variable
+    #[derive(Debug, Clone, PartialEq)]
+    enum Lit {
+        Num(f64),
+        Bool(bool),
+        Var,
+    }
+
+    impl Literal for Lit {
+        fn as_number(&self) -> Option<f64> {
+            match self {
+                Lit::Num(n) => Some(*n),
+                _ => None,
+            }
+        }
+
+        fn as_bool(&self) -> Option<bool> {
+            match self {
+                Lit::Bool(b) => Some(*b),
+                _ => None,
+            }
+        }
+
+        fn from_bool(value: bool) -> Option<Self> {
+            Some(Lit::Bool(value))
+        }
+    }
+
+    type E = Expr<Lit, (), All>;
+
+    fn op(operator: impl Into<All>, args: Vec<E>) -> E {
+        Expr::Operator {
+            operator: operator.into(),
+            args,
+        }
+    }
+
+    fn num(n: f64) -> E {
+        Expr::Value(Lit::Num(n))
+    }
+
+    fn boolean(b: bool) -> E {
+        Expr::Value(Lit::Bool(b))
+    }
+
+    const X: E = Expr::Value(Lit::Var);
+
+    #[test]
+    fn neutral_elements() {
+        for (operator, args) in [
+            (Math::Add, [X, num(-0.)]),
+            (Math::Add, [num(-0.), X]),
+            (Math::Sub, [X, num(0.)]),
+            (Math::Mul, [X, num(1.)]),
+            (Math::Mul, [num(1.), X]),
+            (Math::Div, [X, num(1.)]),
+            (Math::Exponent, [X, num(1.)]),
+        ] {
+            assert_eq!(simplify(op(operator, args.to_vec())), X, "{operator:?}");
+        }
+        for (operator, args) in [
+            (Math::Add, [X, num(0.)]),
+            (Math::Add, [num(0.), X]),
+            (Math::Sub, [X, num(-0.)]),
+            (Math::Sub, [num(0.), X]),
+            (Math::Div, [num(1.), X]),
+            (Math::Mul, [X, num(0.)]),
+        ] {
+            let expr = op(operator, args.to_vec());
+            assert_eq!(simplify(expr.clone()), expr, "{operator:?}");
+        }
+    }
+
+    #[test]
+    fn signed_zero() {
+        // -0 + 0 is 0, not -0
+        let Expr::Value(Lit::Num(sum)) = simplify(op(Math::Add, vec![num(-0.), num(0.)])) else {
+            panic!("a constant");
+        };
+        assert!(sum == 0. && sum.is_sign_positive());
+        // -0 - 0 is -0
+        let Expr::Value(Lit::Num(difference)) = simplify(op(Math::Sub, vec![num(-0.), num(0.)]))
+        else {
+            panic!("a constant");
+        };
+        assert!(difference == 0. && difference.is_sign_negative());
+    }
+
+    #[test]
+    fn negation() {
+        assert_eq!(
+            simplify(op(Logical::Not, vec![op(Logical::Not, vec![X])])),
+            X
+        );
+        assert_eq!(
+            simplify(op(Logical::Not, vec![boolean(true)])),
+            boolean(false)
+        );
+        let not = op(Logical::Not, vec![X]);
+        assert_eq!(simplify(not.clone()), not);
+    }
+
+    #[test]
+    fn constant_operands_of_and_and_or() {
+        for (operator, args, expected) in [
+            (Logical::And, [boolean(true), X], X),
+            (Logical::And, [X, boolean(true)], X),
+            (Logical::And, [boolean(false), X], boolean(false)),
+            (Logical::And, [X, boolean(false)], boolean(false)),
+            (Logical::Or, [boolean(false), X], X),
+            (Logical::Or, [X, boolean(false)], X),
+            (Logical::Or, [boolean(true), X], boolean(true)),
+            (Logical::Or, [X, boolean(true)], boolean(true)),
+        ] {
+            assert_eq!(
+                simplify(op(operator, args.to_vec())),
+                expected,
+                "{operator:?}"
+            );
+        }
+        let and = op(Logical::And, vec![X, X]);
+        assert_eq!(simplify(and.clone()), and);
+    }
+
+    #[test]
+    fn constant_comparisons() {
+        for (compare, expected) in [
+            (Compare::Lt, true),
+            (Compare::Le, true),
+            (Compare::Eq, false),
+            (Compare::Ne, true),
+            (Compare::Ge, false),
+            (Compare::Gt, false),
+        ] {
+            let expr = op(compare, vec![num(1.), num(2.)]);
+            assert_eq!(simplify(expr), boolean(expected), "{compare:?}");
+        }
+        let expr = op(Compare::Lt, vec![X, num(2.)]);
+        assert_eq!(simplify(expr.clone()), expr);
+    }
+
+    #[cfg(feature = "value")]
+    #[test]
+    fn constant_comparison() {
+        use alloc::string::ToString;
+
+        use crate::value::Value;
+
+        type E = Expr<Value, (), All>;
+        let op = |operator: All, args: Vec<E>| E::Operator { operator, args };
+
+        // x || 1 < 2
+        let x = Expr::Value(Value::Str("x".to_string()));
+        let cmp = op(
+            Compare::Lt.into(),
+            vec![Expr::Value(1f64.into()), Expr::Value(2f64.into())],
+        );
+        let expr = op(Logical::Or.into(), vec![x.clone(), cmp.clone()]);
+        assert_eq!(simplify(expr), Expr::Value(true.into()));
+        // x && 1 < 2
+        let expr = op(Logical::And.into(), vec![x.clone(), cmp]);
+        assert_eq!(simplify(expr), x);
+    }
+}
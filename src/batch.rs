@@ -0,0 +1,265 @@
+//! Conversion of many expressions.
+//!
+//! ```rust
+//! use gyard::{batch::ShuntingYard, op::Math, InputToken, OutputToken};
+//!
+//! let expressions = (1..=3).map(|i| {
+//!     // i * 2
+//!     [
+//!         InputToken::<_, (), _>::Value(i),
+//!         InputToken::Operator(Math::Mul),
+//!         InputToken::Value(2),
+//!     ]
+//! });
+//! let mut yard = ShuntingYard::new();
+//! let postfix = yard.convert_all(expressions);
+//! assert_eq!(postfix.len(), 3);
+//! assert_eq!(postfix[2], Ok(vec![
+//!     OutputToken::Value(3),
+//!     OutputToken::Value(2),
+//!     OutputToken::Operator(Math::Mul),
+//! ]));
+//! ```
+
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::{collections::HashSet, hash::Hash, sync::Arc};
+
+use crate::{
+    convert_into, growable, Call, InputToken, Operator, OutputToken, ParenMissmatchError, Stack,
+};
+
+type ConversionResult<V, F, O> = Result<Vec<OutputToken<V, F, O>>, ParenMissmatchError>;
+
+/// A converter that keeps its operator stack between conversions. Converting an expression only
+/// allocates the output.
+pub struct ShuntingYard<F, O> {
+    stack: Stack<F, O>,
+}
+
+impl<F, O> Default for ShuntingYard<F, O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F, O> ShuntingYard<F, O> {
+    /// Creates a converter. The stack is allocated by the first conversion.
+    pub fn new() -> Self {
+        Self {
+            stack: Stack::default(),
+        }
+    }
+}
+
+impl<F, O: Operator> ShuntingYard<F, O> {
+    /// Like `to_postfix`, but reuses the stack of previous conversions.
+    pub fn convert<V>(
+        &mut self,
+        infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+    ) -> ConversionResult<V, F, O> {
+        self.stack.clear();
+        growable(convert_into(
+            infix,
+            |func, _| func,
+            None,
+            Vec::with_capacity,
+            |_| &mut self.stack,
+        ))
+    }
+
+    /// Like `to_postfix_with_arity`, but reuses the stack of previous conversions.
+    pub fn convert_with_arity<V>(
+        &mut self,
+        infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+    ) -> ConversionResult<V, Call<F>, O> {
+        self.stack.clear();
+        growable(convert_into(
+            infix,
+            |function, arity| Call { function, arity },
+            None,
+            Vec::with_capacity,
+            |_| &mut self.stack,
+        ))
+    }
+
+    /// Converts every expression. The results are in the same order as `expressions`.
+    pub fn convert_all<V, I>(
+        &mut self,
+        expressions: impl IntoIterator<Item = I>,
+    ) -> Vec<ConversionResult<V, F, O>>
+    where
+        I: IntoIterator<Item = InputToken<V, F, O>>,
+    {
+        expressions
+            .into_iter()
+            .map(|infix| self.convert(infix))
+            .collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<F: Eq + Hash, O: Operator> ShuntingYard<Arc<F>, O> {
+    /// Like `convert_all`, but replaces every function with the equal function stored in `pool`.
+    /// Expressions calling the same function share a single allocation.
+    ///
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use gyard::{batch::{FunctionPool, ShuntingYard}, InputToken, OutputToken, op::Math};
+    ///
+    /// // sum ( 1 )
+    /// let expression = || [
+    ///     InputToken::<_, _, Math>::Function("sum".to_string()),
+    ///     InputToken::LeftParen,
+    ///     InputToken::Value(1),
+    ///     InputToken::RightParen,
+    /// ];
+    /// let mut pool = FunctionPool::new();
+    /// let postfix = ShuntingYard::new().convert_all_interned([expression(), expression()], &mut pool);
+    /// let [Ok(first), Ok(second)] = &postfix[..] else { panic!() };
+    /// let (OutputToken::Function(a), OutputToken::Function(b)) = (&first[1], &second[1]) else {
+    ///     panic!()
+    /// };
+    /// assert!(Arc::ptr_eq(a, b));
+    /// assert_eq!(pool.len(), 1);
+    /// ```
+    pub fn convert_all_interned<V, I>(
+        &mut self,
+        expressions: impl IntoIterator<Item = I>,
+        pool: &mut FunctionPool<F>,
+    ) -> Vec<ConversionResult<V, Arc<F>, O>>
+    where
+        I: IntoIterator<Item = InputToken<V, F, O>>,
+    {
+        expressions
+            .into_iter()
+            .map(|infix| self.convert(infix.into_iter().map(|token| pool.intern_token(token))))
+            .collect()
+    }
+}
+
+/// Like `ShuntingYard::convert_all`, but converts the expressions in parallel. Every thread
+/// reuses its own stack. The results are in the same order as `expressions`.
+///
+/// ```rust
+/// use gyard::{batch::convert_par, op::Math, InputToken, OutputToken};
+///
+/// let expressions: Vec<_> = (0..1000)
+///     .map(|i| vec![
+///         InputToken::<_, (), _>::Value(i),
+///         InputToken::Operator(Math::Add),
+///         InputToken::Value(1),
+///     ])
+///     .collect();
+/// let postfix = convert_par(expressions);
+/// assert_eq!(postfix[999], Ok(vec![
+///     OutputToken::Value(999),
+///     OutputToken::Value(1),
+///     OutputToken::Operator(Math::Add),
+/// ]));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn convert_par<V, F, O, I>(
+    expressions: impl rayon::iter::IntoParallelIterator<Item = I>,
+) -> Vec<ConversionResult<V, F, O>>
+where
+    V: Send,
+    F: Send,
+    O: Operator + Send,
+    I: IntoIterator<Item = InputToken<V, F, O>> + Send,
+{
+    use rayon::prelude::*;
+
+    expressions
+        .into_par_iter()
+        .map_init(ShuntingYard::new, |yard, infix| yard.convert(infix))
+        .collect()
+}
+
+#[cfg(feature = "std")]
+/// Stores one shared copy of every distinct function. Unlike `intern::Interner`, which replaces
+/// functions with integer handles, the functions stay in the expressions and share their
+/// allocation through `Arc`.
+#[derive(Debug, Clone)]
+pub struct FunctionPool<F> {
+    functions: HashSet<Arc<F>>,
+}
+
+#[cfg(feature = "std")]
+impl<F> Default for FunctionPool<F> {
+    fn default() -> Self {
+        Self {
+            functions: HashSet::new(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<F: Eq + Hash> FunctionPool<F> {
+    /// Creates an empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the stored copy of `function`, storing it first if necessary.
+    pub fn intern(&mut self, function: F) -> Arc<F> {
+        if let Some(function) = self.functions.get(&function) {
+            return Arc::clone(function);
+        }
+        let function = Arc::new(function);
+        self.functions.insert(Arc::clone(&function));
+        function
+    }
+
+    /// Replaces the function of `token` with its stored copy.
+    pub fn intern_token<V, O>(&mut self, token: InputToken<V, F, O>) -> InputToken<V, Arc<F>, O> {
+        match token {
+            InputToken::Function(function) => InputToken::Function(self.intern(function)),
+            InputToken::Value(value) => InputToken::Value(value),
+            InputToken::LeftParen => InputToken::LeftParen,
+            InputToken::RightParen => InputToken::RightParen,
+            #[expect(deprecated, reason = "")]
+            InputToken::ArgSeperator => InputToken::ArgSeperator,
+            InputToken::ArgSeparator => InputToken::ArgSeparator,
+            InputToken::Operator(operator) => InputToken::Operator(operator),
+        }
+    }
+
+    /// The number of distinct functions
+    pub fn len(&self) -> usize {
+        self.functions.len()
+    }
+
+    /// Returns true if no function has been interned.
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::ShuntingYard;
+    use crate::{op::Math, to_postfix, InputToken, ParenMissmatchError};
+
+    #[test]
+    fn error_does_not_affect_next_expression() {
+        // ( 1 + 2
+        let broken = vec![
+            InputToken::<_, (), _>::LeftParen,
+            InputToken::Value(1),
+            InputToken::Operator(Math::Add),
+            InputToken::Value(2),
+        ];
+        let valid = broken[1..].to_vec();
+        let mut yard = ShuntingYard::new();
+        assert_eq!(
+            yard.convert_all([broken, valid.clone()]),
+            [
+                Err(ParenMissmatchError::UnclosedOpening { open_pos: 0 }),
+                to_postfix(valid)
+            ]
+        );
+    }
+}
@@ -0,0 +1,417 @@
+//! A versioned binary format for postfix expressions and compiled programs.
+//!
+//! Use it to cache expressions on disk. Every encoding starts with the magic bytes `GYRD`, the
+//! format version and the kind of the content (`0` postfix expression, `1` program). Numbers are
+//! unsigned LEB128 numbers.
+//!
+//! - A postfix expression is the number of tokens followed by the tokens. Every token is its
+//!   opcode (see `tape::Opcode`), the arity for functions, the length of the payload and the
+//!   payload.
+//! - A program is the number of instructions followed by the instructions and the number of
+//!   values followed by the values. Every instruction is a tag (`0` value, `1` operator,
+//!   `2` function, `3` lazy function), its index and, unless it is a value, its arity. Every
+//!   value is the length of the payload and the payload.
+//!
+//! Payloads are produced and read by the caller. Decoding never trusts the input, programs are
+//! verified again like during compilation.
+//!
+//! ```rust
+//! use gyard::{binary, op::Math, to_postfix_with_arity, InputToken, OutputToken};
+//!
+//! // 1 + 2
+//! let postfix = to_postfix_with_arity([
+//!     InputToken::<u8, (), _>::Value(1),
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Value(2),
+//! ]).unwrap();
+//! let bytes = binary::encode_postfix(&postfix, |token, out| match token {
+//!     OutputToken::Value(value) => out.push(*value),
+//!     OutputToken::Function(()) => {}
+//!     OutputToken::Operator(op) => out.extend(op.to_string().bytes()),
+//! });
+//! let decoded = binary::decode_postfix(&bytes, |token| match token {
+//!     OutputToken::Value([value]) => Ok(OutputToken::Value(*value)),
+//!     OutputToken::Operator(b"+") => Ok(OutputToken::Operator(Math::Add)),
+//!     _ => Err("unknown token"),
+//! });
+//! assert_eq!(decoded, Ok(postfix));
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{
+    compile::{CompileError, Instr, Program},
+    tape::Opcode,
+    Call, OutputToken,
+};
+
+/// The magic bytes at the start of every encoding
+const MAGIC: &[u8; 4] = b"GYRD";
+/// The current format version. Decoding accepts all versions up to this one.
+pub const VERSION: u8 = 1;
+
+type PostfixResult<V, F, O, E> = Result<Vec<OutputToken<V, Call<F>, O>>, DecodeError<E>>;
+
+const POSTFIX: u8 = 0;
+const PROGRAM: u8 = 1;
+
+/// This error is returned if bytes can not be decoded.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DecodeError<E> {
+    /// The bytes do not start with the magic bytes.
+    InvalidHeader,
+    /// The bytes were written by a newer version of the format.
+    UnsupportedVersion(u8),
+    /// The bytes contain a program where a postfix expression was expected or the other way
+    /// around.
+    WrongKind,
+    /// The bytes are truncated or corrupt at byte `offset`.
+    Malformed {
+        /// The byte offset of the error
+        offset: usize,
+    },
+    /// The decoded instructions do not form a valid program.
+    Program(CompileError),
+    /// The payload at byte `offset` was rejected by the caller.
+    Payload {
+        /// The byte offset of the payload
+        offset: usize,
+        /// The error returned by the caller
+        error: E,
+    },
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for DecodeError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeError::InvalidHeader => write!(f, "Data is not an encoded expression"),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported format version {version}")
+            }
+            DecodeError::WrongKind => write!(f, "Data contains a different kind of expression"),
+            DecodeError::Malformed { offset } => write!(f, "Malformed data at offset {offset}"),
+            DecodeError::Program(error) => error.fmt(f),
+            DecodeError::Payload { offset, error } => {
+                write!(f, "Invalid payload at offset {offset}: {error}")
+            }
+        }
+    }
+}
+
+impl<E: core::error::Error + 'static> core::error::Error for DecodeError<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            DecodeError::Program(error) => Some(error),
+            DecodeError::Payload { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Encodes a postfix expression created by `to_postfix_with_arity`. `encode` appends the payload
+/// of a token to the given buffer.
+pub fn encode_postfix<V, F, O>(
+    postfix: &[OutputToken<V, Call<F>, O>],
+    mut encode: impl FnMut(OutputToken<&V, &F, &O>, &mut Vec<u8>),
+) -> Vec<u8> {
+    let mut out = header(POSTFIX);
+    write_len(&mut out, postfix.len());
+    let mut payload = Vec::new();
+    for token in postfix {
+        payload.clear();
+        match token {
+            OutputToken::Value(value) => {
+                out.push(Opcode::Value as u8);
+                encode(OutputToken::Value(value), &mut payload);
+            }
+            OutputToken::Function(Call { function, arity }) => {
+                out.push(Opcode::Function as u8);
+                write_len(&mut out, *arity);
+                encode(OutputToken::Function(function), &mut payload);
+            }
+            OutputToken::Operator(operator) => {
+                out.push(Opcode::Operator as u8);
+                encode(OutputToken::Operator(operator), &mut payload);
+            }
+        }
+        write_len(&mut out, payload.len());
+        out.extend_from_slice(&payload);
+    }
+    out
+}
+
+/// Decodes a postfix expression written by `encode_postfix`. `decode` receives the payload of
+/// every token inside the variant of its kind.
+pub fn decode_postfix<V, F, O, E>(
+    bytes: &[u8],
+    mut decode: impl FnMut(OutputToken<&[u8], &[u8], &[u8]>) -> Result<OutputToken<V, F, O>, E>,
+) -> PostfixResult<V, F, O, E> {
+    let mut reader = Reader::new(bytes, POSTFIX)?;
+    let len = reader.len()?;
+    let mut postfix = Vec::with_capacity(len.min(bytes.len()));
+    for _ in 0..len {
+        let offset = reader.pos;
+        let token = match reader.byte()? {
+            0 => OutputToken::Value(()),
+            1 => OutputToken::Function(reader.len()?),
+            2 => OutputToken::Operator(()),
+            _ => return Err(DecodeError::Malformed { offset }),
+        };
+        let offset = reader.pos;
+        let payload = reader.payload()?;
+        let decoded = decode(match token {
+            OutputToken::Value(()) => OutputToken::Value(payload),
+            OutputToken::Function(_) => OutputToken::Function(payload),
+            OutputToken::Operator(()) => OutputToken::Operator(payload),
+        })
+        .map_err(|error| DecodeError::Payload { offset, error })?;
+        postfix.push(match (token, decoded) {
+            (OutputToken::Value(()), OutputToken::Value(value)) => OutputToken::Value(value),
+            (OutputToken::Function(arity), OutputToken::Function(function)) => {
+                OutputToken::Function(Call { function, arity })
+            }
+            (OutputToken::Operator(()), OutputToken::Operator(operator)) => {
+                OutputToken::Operator(operator)
+            }
+            // The caller returned a token of a different kind
+            _ => return Err(DecodeError::Malformed { offset }),
+        });
+    }
+    reader.finish()?;
+    Ok(postfix)
+}
+
+/// Encodes a compiled program. `encode` appends the payload of a value to the given buffer.
+pub fn encode_program<V>(
+    program: &Program<V>,
+    mut encode: impl FnMut(&V, &mut Vec<u8>),
+) -> Vec<u8> {
+    let mut out = header(PROGRAM);
+    write_len(&mut out, program.instructions().len());
+    for instr in program.instructions() {
+        let (tag, index, arity) = match *instr {
+            Instr::Value(index) => (0, index, None),
+            Instr::Operator { index, arity } => (1, index, Some(arity)),
+            Instr::Function { index, arity } => (2, index, Some(arity)),
+            Instr::Lazy { index, arity, .. } => (3, index, Some(arity)),
+        };
+        out.push(tag);
+        write_len(&mut out, index as usize);
+        if let Some(arity) = arity {
+            write_len(&mut out, arity as usize);
+        }
+    }
+    write_len(&mut out, program.values().len());
+    let mut payload = Vec::new();
+    for value in program.values() {
+        payload.clear();
+        encode(value, &mut payload);
+        write_len(&mut out, payload.len());
+        out.extend_from_slice(&payload);
+    }
+    out
+}
+
+/// Decodes a program written by `encode_program`. `decode` converts the payload of a value.
+pub fn decode_program<V, E>(
+    bytes: &[u8],
+    mut decode: impl FnMut(&[u8]) -> Result<V, E>,
+) -> Result<Program<V>, DecodeError<E>> {
+    let mut reader = Reader::new(bytes, PROGRAM)?;
+    let len = reader.len()?;
+    let mut instructions = Vec::with_capacity(len.min(bytes.len()));
+    // The offset of the instruction using the largest value index
+    let mut largest_value: Option<(u32, usize)> = None;
+    for _ in 0..len {
+        let offset = reader.pos;
+        let tag = reader.byte()?;
+        let index = reader.u32()?;
+        instructions.push(match tag {
+            0 => {
+                if largest_value.is_none_or(|(largest, _)| index > largest) {
+                    largest_value = Some((index, offset));
+                }
+                Instr::Value(index)
+            }
+            1 => Instr::Operator {
+                index,
+                arity: reader.u32()?,
+            },
+            2 => Instr::Function {
+                index,
+                arity: reader.u32()?,
+            },
+            3 => Instr::Lazy {
+                index,
+                arity: reader.u32()?,
+                thunks: 0,
+            },
+            _ => return Err(DecodeError::Malformed { offset }),
+        });
+    }
+    let len = reader.len()?;
+    let mut values = Vec::with_capacity(len.min(bytes.len()));
+    for _ in 0..len {
+        let offset = reader.pos;
+        let payload = reader.payload()?;
+        values.push(decode(payload).map_err(|error| DecodeError::Payload { offset, error })?);
+    }
+    reader.finish()?;
+    if let Some((index, offset)) = largest_value {
+        if index as usize >= values.len() {
+            return Err(DecodeError::Malformed { offset });
+        }
+    }
+    Program::from_instructions(instructions, values).map_err(DecodeError::Program)
+}
+
+fn header(kind: u8) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    out.extend([VERSION, kind]);
+    out
+}
+
+/// Appends `len` as an unsigned LEB128 number.
+pub(crate) fn write_len(out: &mut Vec<u8>, mut len: usize) {
+    while len >= 0x80 {
+        out.push((len as u8 & 0x7f) | 0x80);
+        len >>= 7;
+    }
+    out.push(len as u8);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Checks the header and returns a reader positioned after it.
+    fn new<E>(bytes: &'a [u8], kind: u8) -> Result<Self, DecodeError<E>> {
+        let Some(rest) = bytes.strip_prefix(MAGIC) else {
+            return Err(DecodeError::InvalidHeader);
+        };
+        match *rest {
+            [version, ..] if version > VERSION || version == 0 => {
+                Err(DecodeError::UnsupportedVersion(version))
+            }
+            [_, found, ..] if found != kind => Err(DecodeError::WrongKind),
+            [_, _, ..] => Ok(Self {
+                bytes,
+                pos: MAGIC.len() + 2,
+            }),
+            _ => Err(DecodeError::InvalidHeader),
+        }
+    }
+
+    fn byte<E>(&mut self) -> Result<u8, DecodeError<E>> {
+        let byte = *self
+            .bytes
+            .get(self.pos)
+            .ok_or(DecodeError::Malformed { offset: self.pos })?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn len<E>(&mut self) -> Result<usize, DecodeError<E>> {
+        let offset = self.pos;
+        let mut len = 0usize;
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.byte()?;
+            len |= usize::from(byte & 0x7f)
+                .checked_shl(shift)
+                .filter(|part| part >> shift == usize::from(byte & 0x7f))
+                .ok_or(DecodeError::Malformed { offset })?;
+            if byte & 0x80 == 0 {
+                return Ok(len);
+            }
+        }
+        Err(DecodeError::Malformed { offset })
+    }
+
+    fn u32<E>(&mut self) -> Result<u32, DecodeError<E>> {
+        let offset = self.pos;
+        u32::try_from(self.len()?).map_err(|_| DecodeError::Malformed { offset })
+    }
+
+    fn payload<E>(&mut self) -> Result<&'a [u8], DecodeError<E>> {
+        let offset = self.pos;
+        let len = self.len()?;
+        let payload = self
+            .pos
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(self.pos..end))
+            .ok_or(DecodeError::Malformed { offset })?;
+        self.pos += len;
+        Ok(payload)
+    }
+
+    /// Rejects trailing bytes.
+    fn finish<E>(&self) -> Result<(), DecodeError<E>> {
+        match self.pos == self.bytes.len() {
+            true => Ok(()),
+            false => Err(DecodeError::Malformed { offset: self.pos }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{decode_program, encode_program, DecodeError, VERSION};
+    use crate::{
+        compile::{compile_lazy, CompileError, FunctionIndex},
+        op::Math,
+        to_postfix_with_arity, InputToken,
+    };
+
+    #[test]
+    fn program_roundtrip_and_corrupt_data() {
+        // if ( 1 , 2 + 300 , 4 )
+        let postfix = to_postfix_with_arity([
+            InputToken::Function("if"),
+            InputToken::LeftParen,
+            InputToken::Value(1u32),
+            InputToken::ArgSeparator,
+            InputToken::Value(2),
+            InputToken::Operator(Math::Add),
+            InputToken::Value(300),
+            InputToken::ArgSeparator,
+            InputToken::Value(4),
+            InputToken::RightParen,
+        ])
+        .expect("valid expression");
+        let program = compile_lazy(postfix, |_| Some(0), |_, _| Some(FunctionIndex::Lazy(0)))
+            .expect("valid program");
+        let encode = |value: &u32, out: &mut Vec<u8>| out.extend(value.to_le_bytes());
+        let decode = |bytes: &[u8]| bytes.try_into().map(u32::from_le_bytes).map_err(|_| ());
+        let bytes = encode_program(&program, encode);
+        assert_eq!(decode_program(&bytes, decode), Ok(program));
+
+        let mut newer = bytes.clone();
+        newer[4] = VERSION + 1;
+        assert_eq!(
+            decode_program(&newer, decode),
+            Err(DecodeError::UnsupportedVersion(VERSION + 1))
+        );
+        assert_eq!(
+            decode_program(&bytes[..bytes.len() - 1], decode),
+            Err(DecodeError::Malformed {
+                offset: bytes.len() - 5
+            })
+        );
+        // Call the lazy function with two arguments
+        let mut corrupt = bytes.clone();
+        corrupt[20] = 2;
+        assert_eq!(
+            decode_program(&corrupt, decode),
+            Err(DecodeError::Program(CompileError::UnusedValues {
+                count: 2
+            }))
+        );
+    }
+}
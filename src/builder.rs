@@ -0,0 +1,285 @@
+//! Build infix token streams without writing parentheses and separators by hand.
+//!
+//! `ExpressionBuilder` adds the `LeftParen`, `RightParen` and `ArgSeparator` tokens of function
+//! calls and groups itself and checks that operands and operators alternate. Errors are reported
+//! by `build`.
+//!
+//! ```rust
+//! use gyard::{builder::ExpressionBuilder, op::Math, to_postfix, InputToken};
+//!
+//! // 5 + sin(123) * max(1, 2)
+//! let infix = ExpressionBuilder::new()
+//!     .value(5)
+//!     .op(Math::Add)
+//!     .call("sin", |args| args.value(123))
+//!     .op(Math::Mul)
+//!     .call("max", |args| args.value(1).next_arg().value(2))
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(infix[2], InputToken::Function("sin"));
+//! assert_eq!(to_postfix(infix).unwrap().len(), 8);
+//!
+//! assert!(ExpressionBuilder::<i32, &str, _>::new()
+//!     .value(5)
+//!     .op(Math::Add)
+//!     .build()
+//!     .is_err());
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{InputToken, Operator};
+
+/// This error is returned by `ExpressionBuilder::build`.
+/// `pos` is the index of the token in the built stream where the error was detected.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BuildError {
+    /// An operand was expected, i.e. `1 +` or `max(1, )`.
+    MissingOperand {
+        /// The position where the operand is missing
+        pos: usize,
+    },
+    /// An operator was expected, i.e. `1 2`.
+    MissingOperator {
+        /// The position of the second operand
+        pos: usize,
+    },
+    /// `next_arg` was called outside of a function call.
+    UnexpectedSeparator {
+        /// The position of the separator
+        pos: usize,
+    },
+}
+
+impl BuildError {
+    fn offset(self, offset: usize) -> Self {
+        match self {
+            BuildError::MissingOperand { pos } => BuildError::MissingOperand { pos: pos + offset },
+            BuildError::MissingOperator { pos } => {
+                BuildError::MissingOperator { pos: pos + offset }
+            }
+            BuildError::UnexpectedSeparator { pos } => {
+                BuildError::UnexpectedSeparator { pos: pos + offset }
+            }
+        }
+    }
+}
+
+impl core::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BuildError::MissingOperand { pos } => write!(f, "Missing operand at position {pos}"),
+            BuildError::MissingOperator { pos } => {
+                write!(f, "Missing operator at position {pos}")
+            }
+            BuildError::UnexpectedSeparator { pos } => {
+                write!(
+                    f,
+                    "Argument separator outside of function at position {pos}"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for BuildError {}
+
+/// A fluent builder for infix token streams.
+/// Operators with an arity of one may be used as prefix or postfix operators, all other
+/// operators are binary.
+#[derive(Debug, Clone)]
+pub struct ExpressionBuilder<V, F, O> {
+    tokens: Vec<InputToken<V, F, O>>,
+    expect_operand: bool,
+    in_call: bool,
+    error: Option<BuildError>,
+}
+
+impl<V, F, O> Default for ExpressionBuilder<V, F, O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V, F, O> ExpressionBuilder<V, F, O> {
+    /// Creates an empty builder.
+    pub fn new() -> Self {
+        Self {
+            tokens: Vec::new(),
+            expect_operand: true,
+            in_call: false,
+            error: None,
+        }
+    }
+
+    fn fail(&mut self, error: BuildError) {
+        self.error.get_or_insert(error);
+    }
+
+    fn operand(&mut self) {
+        if !self.expect_operand {
+            self.fail(BuildError::MissingOperator {
+                pos: self.tokens.len(),
+            });
+        }
+        self.expect_operand = false;
+    }
+
+    /// Checks the end of the builder. Only the arguments of a function may be empty.
+    fn finish(mut self) -> Result<Vec<InputToken<V, F, O>>, BuildError> {
+        if self.expect_operand && !(self.in_call && self.tokens.is_empty()) {
+            self.fail(BuildError::MissingOperand {
+                pos: self.tokens.len(),
+            });
+        }
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.tokens),
+        }
+    }
+
+    /// Appends the tokens of a nested builder surrounded by `prefix` and `)`.
+    fn nest(&mut self, prefix: impl IntoIterator<Item = InputToken<V, F, O>>, inner: Self) {
+        self.operand();
+        self.tokens.extend(prefix);
+        let offset = self.tokens.len();
+        match inner.finish() {
+            Ok(tokens) => self.tokens.extend(tokens),
+            Err(error) => self.fail(error.offset(offset)),
+        }
+        self.tokens.push(InputToken::RightParen);
+    }
+
+    /// Appends a value.
+    pub fn value(mut self, value: V) -> Self {
+        self.operand();
+        self.tokens.push(InputToken::Value(value));
+        self
+    }
+
+    /// Appends a function call. `args` receives an empty builder for the arguments, separate
+    /// them with `next_arg`.
+    pub fn call(mut self, function: F, args: impl FnOnce(Self) -> Self) -> Self {
+        let args = args(Self {
+            in_call: true,
+            ..Self::new()
+        });
+        self.nest(
+            [InputToken::Function(function), InputToken::LeftParen],
+            args,
+        );
+        self
+    }
+
+    /// Appends a parenthesized sub expression.
+    pub fn group(mut self, inner: impl FnOnce(Self) -> Self) -> Self {
+        let inner = inner(Self::new());
+        self.nest([InputToken::LeftParen], inner);
+        self
+    }
+
+    /// Starts the next argument of the surrounding function call.
+    pub fn next_arg(mut self) -> Self {
+        let pos = self.tokens.len();
+        if !self.in_call {
+            self.fail(BuildError::UnexpectedSeparator { pos });
+        } else if self.expect_operand {
+            self.fail(BuildError::MissingOperand { pos });
+        }
+        self.expect_operand = true;
+        self.tokens.push(InputToken::ArgSeparator);
+        self
+    }
+
+    /// Returns the built tokens or the first error.
+    pub fn build(self) -> Result<Vec<InputToken<V, F, O>>, BuildError> {
+        self.finish()
+    }
+}
+
+impl<V, F, O: Operator> ExpressionBuilder<V, F, O> {
+    /// Appends an operator.
+    pub fn op(mut self, operator: O) -> Self {
+        let pos = self.tokens.len();
+        match (operator.arity(), self.expect_operand) {
+            (1, _) => {}
+            (_, true) => self.fail(BuildError::MissingOperand { pos }),
+            (_, false) => self.expect_operand = true,
+        }
+        self.tokens.push(InputToken::Operator(operator));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BuildError, ExpressionBuilder};
+    use crate::{
+        op::{Logical, Math},
+        InputToken,
+    };
+
+    #[test]
+    fn nested_calls_and_groups() {
+        // !(1 || f()) && g(2, h(3))
+        let infix = ExpressionBuilder::new()
+            .op(Logical::Not)
+            .group(|inner| inner.value(1).op(Logical::Or).call("f", |args| args))
+            .op(Logical::And)
+            .call("g", |args| {
+                args.value(2).next_arg().call("h", |args| args.value(3))
+            })
+            .build()
+            .expect("Valid expression");
+        assert_eq!(
+            infix,
+            [
+                InputToken::Operator(Logical::Not),
+                InputToken::LeftParen,
+                InputToken::Value(1),
+                InputToken::Operator(Logical::Or),
+                InputToken::Function("f"),
+                InputToken::LeftParen,
+                InputToken::RightParen,
+                InputToken::RightParen,
+                InputToken::Operator(Logical::And),
+                InputToken::Function("g"),
+                InputToken::LeftParen,
+                InputToken::Value(2),
+                InputToken::ArgSeparator,
+                InputToken::Function("h"),
+                InputToken::LeftParen,
+                InputToken::Value(3),
+                InputToken::RightParen,
+                InputToken::RightParen,
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_first_error() {
+        let missing_operand = ExpressionBuilder::<_, &str, _>::new()
+            .value(1)
+            .op(Math::Add)
+            .call("f", |args| args.value(2).next_arg())
+            .build();
+        assert_eq!(missing_operand, Err(BuildError::MissingOperand { pos: 6 }));
+        let missing_operator = ExpressionBuilder::<_, &str, Math>::new()
+            .value(1)
+            .group(|inner| inner.value(2))
+            .build();
+        assert_eq!(
+            missing_operator,
+            Err(BuildError::MissingOperator { pos: 1 })
+        );
+        let separator = ExpressionBuilder::<_, &str, Math>::new()
+            .value(1)
+            .next_arg()
+            .value(2)
+            .build();
+        assert_eq!(separator, Err(BuildError::UnexpectedSeparator { pos: 1 }));
+    }
+}
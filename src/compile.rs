@@ -0,0 +1,1155 @@
+//! Compilation of postfix expressions into a flat `Program`.
+//! Operators and functions are resolved to indices into user supplied tables once, the resulting
+//! program can then be evaluated many times without matching tokens again.
+//!
+//! ```rust
+//! use gyard::{compile::compile, op::Math, to_postfix_with_arity, InputToken};
+//!
+//! fn add(args: &mut [f64]) -> Result<f64, String> {
+//!     Ok(args[0] + args[1])
+//! }
+//! fn mul(args: &mut [f64]) -> Result<f64, String> {
+//!     Ok(args[0] * args[1])
+//! }
+//! fn max(args: &mut [f64]) -> Result<f64, String> {
+//!     Ok(args.iter().copied().fold(f64::MIN, f64::max))
+//! }
+//! let operators = [add, mul];
+//! let functions = [max];
+//!
+//! // 1 + max ( 2 , x ) * 4
+//! let infix = [
+//!     InputToken::Value("1"),
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Function("max"),
+//!     InputToken::LeftParen,
+//!     InputToken::Value("2"),
+//!     InputToken::ArgSeparator,
+//!     InputToken::Value("x"),
+//!     InputToken::RightParen,
+//!     InputToken::Operator(Math::Mul),
+//!     InputToken::Value("4"),
+//! ];
+//! let postfix = to_postfix_with_arity(infix).unwrap();
+//! let program = compile(
+//!     postfix,
+//!     |op| match op {
+//!         Math::Add => Some(0),
+//!         Math::Mul => Some(1),
+//!         _ => None,
+//!     },
+//!     |func, _| (*func == "max").then_some(0),
+//! )
+//! .unwrap();
+//!
+//! for x in [1., 3.] {
+//!     let context = |value: &&str| match *value {
+//!         "x" => Ok(x),
+//!         v => v.parse::<f64>().map_err(|e| e.to_string()),
+//!     };
+//!     assert_eq!(program.eval(&operators, &functions, &context), Ok(1. + 2f64.max(x) * 4.));
+//! }
+//! ```
+
+pub mod columns;
+
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{
+    eval::{Context, EvalError, StackMachine},
+    trace, Call, Operator, OutputToken,
+};
+
+/// A single instruction of a `Program`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub enum Instr {
+    /// Push the value at this index of `Program::values` onto the stack.
+    Value(u32),
+    /// Apply an entry of the operator table to the topmost values of the stack.
+    Operator {
+        /// The index into the operator table
+        index: u32,
+        /// The number of values the operator takes
+        arity: u32,
+    },
+    /// Apply an entry of the function table to the topmost values of the stack.
+    Function {
+        /// The index into the function table
+        index: u32,
+        /// The number of values the function takes
+        arity: u32,
+    },
+    /// Apply an entry of the lazy function table to unevaluated arguments.
+    Lazy {
+        /// The index into the lazy function table
+        index: u32,
+        /// The number of arguments the function takes
+        arity: u32,
+        /// The index of the first argument in `Program::thunks`
+        thunks: u32,
+    },
+}
+
+/// The index of a function returned during compilation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub enum FunctionIndex {
+    /// An index into the function table. The arguments are evaluated before the call.
+    Strict(usize),
+    /// An index into the lazy function table. The function decides which arguments to evaluate.
+    Lazy(usize),
+}
+
+/// A compiled postfix expression.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub struct Program<V> {
+    instructions: Vec<Instr>,
+    values: Vec<V>,
+    thunks: Vec<(u32, u32)>,
+    skips: Vec<(u32, u32)>,
+    max_depth: usize,
+}
+
+/// This error is returned if a postfix expression can not be compiled.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CompileError {
+    /// The operator at `pos` has no entry in the operator table.
+    UnknownOperator {
+        /// The position of the operator
+        pos: usize,
+    },
+    /// The function at `pos` has no entry in the function table.
+    UnknownFunction {
+        /// The position of the function
+        pos: usize,
+    },
+    /// The operator or function at `pos` requires more values than there are on the stack.
+    StackUnderflow {
+        /// The position of the operator or function
+        pos: usize,
+        /// The number of values the operator or function requires
+        required: usize,
+        /// The number of values on the stack
+        available: usize,
+    },
+    /// The expression does not contain any tokens.
+    EmptyExpression,
+    /// The expression would leave more than one value on the stack.
+    UnusedValues {
+        /// The number of values left on the stack
+        count: usize,
+    },
+    /// The expression contains more tokens or larger indices than a program can address.
+    TooLarge,
+}
+
+impl core::fmt::Display for CompileError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CompileError::UnknownOperator { pos } => write!(f, "Unknown operator at position {pos}"),
+            CompileError::UnknownFunction { pos } => write!(f, "Unknown function at position {pos}"),
+            CompileError::StackUnderflow {
+                pos,
+                required,
+                available,
+            } => write!(
+                f,
+                "Token at position {pos} requires {required} values but only {available} are available"
+            ),
+            CompileError::EmptyExpression => write!(f, "Can not compile an empty expression"),
+            CompileError::UnusedValues { count } => {
+                write!(f, "Expression evaluates to {count} values instead of one")
+            }
+            CompileError::TooLarge => write!(f, "Expression is too large to be compiled"),
+        }
+    }
+}
+
+impl core::error::Error for CompileError {}
+
+/// Compiles a postfix expression created by `to_postfix_with_arity` into a `Program`.
+/// `operator_index` and `function_index` map operators and functions (together with the number of
+/// arguments they are called with) to indices into the tables passed to `Program::eval`.
+///
+/// The stack effect of the expression is verified during compilation.
+pub fn compile<V, F, O>(
+    postfix: impl IntoIterator<Item = OutputToken<V, Call<F>, O>>,
+    operator_index: impl FnMut(&O) -> Option<usize>,
+    mut function_index: impl FnMut(&F, usize) -> Option<usize>,
+) -> Result<Program<V>, CompileError>
+where
+    O: Operator,
+{
+    compile_lazy(postfix, operator_index, |func, arity| {
+        function_index(func, arity).map(FunctionIndex::Strict)
+    })
+}
+
+/// Compiles a postfix expression created by `to_postfix_with_arity` into a `Program` that may
+/// contain lazy functions. See `compile`.
+///
+/// The arguments of a lazy function are not evaluated before the call. Instead the function
+/// receives `Thunks` and evaluates only the arguments it needs.
+///
+/// ```rust
+/// use gyard::compile::{compile_lazy, FunctionIndex, Thunks};
+/// use gyard::eval::EvalError;
+/// use gyard::{op::Math, to_postfix_with_arity, InputToken};
+///
+/// fn div(args: &mut [f64]) -> Result<f64, String> {
+///     match args[1] {
+///         0. => Err("division by zero".to_string()),
+///         b => Ok(args[0] / b),
+///     }
+/// }
+/// fn if_(args: &mut Thunks<'_, f64, String>) -> Result<f64, EvalError<String>> {
+///     match args.eval(0)? {
+///         0. => args.eval(2),
+///         _ => args.eval(1),
+///     }
+/// }
+///
+/// // if ( x , 1 / x , 0 )
+/// let infix = [
+///     InputToken::Function("if"),
+///     InputToken::LeftParen,
+///     InputToken::Value("x"),
+///     InputToken::ArgSeparator,
+///     InputToken::Value("1"),
+///     InputToken::Operator(Math::Div),
+///     InputToken::Value("x"),
+///     InputToken::ArgSeparator,
+///     InputToken::Value("0"),
+///     InputToken::RightParen,
+/// ];
+/// let postfix = to_postfix_with_arity(infix).unwrap();
+/// let program = compile_lazy(
+///     postfix,
+///     |op| (*op == Math::Div).then_some(0),
+///     |func, arity| (*func == "if" && arity == 3).then_some(FunctionIndex::Lazy(0)),
+/// )
+/// .unwrap();
+///
+/// for x in [0., 4.] {
+///     let context = |value: &&str| match *value {
+///         "x" => Ok(x),
+///         v => v.parse::<f64>().map_err(|e| e.to_string()),
+///     };
+///     let result = program.eval_lazy(&[div], &[] as &[fn(&mut [f64]) -> _], &[if_], &context);
+///     assert_eq!(result, Ok(if x == 0. { 0. } else { 1. / x }));
+/// }
+/// ```
+pub fn compile_lazy<V, F, O>(
+    postfix: impl IntoIterator<Item = OutputToken<V, Call<F>, O>>,
+    operator_index: impl FnMut(&O) -> Option<usize>,
+    function_index: impl FnMut(&F, usize) -> Option<FunctionIndex>,
+) -> Result<Program<V>, CompileError>
+where
+    O: Operator,
+{
+    let span = trace::Span::compilation();
+    let result = compile_tokens(postfix, operator_index, function_index);
+    match &result {
+        Ok(program) => span.finish(program.instructions.len(), Some(program.max_depth), true),
+        Err(_) => span.finish(0, None, false),
+    }
+    result
+}
+
+fn compile_tokens<V, F, O>(
+    postfix: impl IntoIterator<Item = OutputToken<V, Call<F>, O>>,
+    mut operator_index: impl FnMut(&O) -> Option<usize>,
+    mut function_index: impl FnMut(&F, usize) -> Option<FunctionIndex>,
+) -> Result<Program<V>, CompileError>
+where
+    O: Operator,
+{
+    let mut builder = Builder::default();
+    let mut values = Vec::new();
+
+    for (pos, token) in postfix.into_iter().enumerate() {
+        let instr = match token {
+            OutputToken::Value(value) => {
+                let index = to_u32(values.len())?;
+                values.push(value);
+                Instr::Value(index)
+            }
+            OutputToken::Operator(op) => {
+                let index = operator_index(&op).ok_or(CompileError::UnknownOperator { pos })?;
+                Instr::Operator {
+                    index: to_u32(index)?,
+                    arity: to_u32(op.arity())?,
+                }
+            }
+            OutputToken::Function(Call { function, arity }) => {
+                let index = function_index(&function, arity)
+                    .ok_or(CompileError::UnknownFunction { pos })?;
+                match index {
+                    FunctionIndex::Strict(index) => Instr::Function {
+                        index: to_u32(index)?,
+                        arity: to_u32(arity)?,
+                    },
+                    FunctionIndex::Lazy(index) => Instr::Lazy {
+                        index: to_u32(index)?,
+                        arity: to_u32(arity)?,
+                        thunks: 0,
+                    },
+                }
+            }
+        };
+        builder.push(instr)?;
+    }
+    builder.finish(values)
+}
+
+/// Verifies the stack effect of instructions and records the arguments of lazy functions.
+#[derive(Default)]
+struct Builder {
+    instructions: Vec<Instr>,
+    thunks: Vec<(u32, u32)>,
+    skips: Vec<(u32, u32)>,
+    /// The position of the first instruction of every value on the stack
+    starts: Vec<usize>,
+    max_depth: usize,
+}
+
+impl Builder {
+    /// Appends an instruction. The `thunks` index of lazy functions is assigned by the builder.
+    fn push(&mut self, mut instr: Instr) -> Result<(), CompileError> {
+        let pos = self.instructions.len();
+        let arity = match &mut instr {
+            Instr::Value(_) => 0,
+            Instr::Operator { arity, .. } | Instr::Function { arity, .. } => *arity as usize,
+            Instr::Lazy { arity, thunks, .. } => {
+                *thunks = to_u32(self.thunks.len())?;
+                *arity as usize
+            }
+        };
+        let starts = &mut self.starts;
+        let Some(first) = starts.len().checked_sub(arity) else {
+            return Err(CompileError::StackUnderflow {
+                pos,
+                required: arity,
+                available: starts.len(),
+            });
+        };
+        let start = starts.get(first).copied().unwrap_or(pos);
+        if matches!(instr, Instr::Lazy { .. }) {
+            for (i, &arg_start) in starts[first..].iter().enumerate() {
+                let arg_end = starts.get(first + i + 1).copied().unwrap_or(pos);
+                self.thunks.push((to_u32(arg_start)?, to_u32(arg_end)?));
+            }
+            if arity > 0 {
+                self.skips.push((to_u32(start)?, to_u32(pos)?));
+            }
+        }
+        starts.truncate(first);
+        starts.push(start);
+        self.max_depth = self.max_depth.max(starts.len());
+        self.instructions.push(instr);
+        Ok(())
+    }
+
+    fn finish<V>(mut self, values: Vec<V>) -> Result<Program<V>, CompileError> {
+        // Arguments of nested lazy functions start at the same position, the outermost one comes
+        // first
+        self.skips
+            .sort_unstable_by_key(|&(start, end)| (start, core::cmp::Reverse(end)));
+        match self.starts.len() {
+            0 => Err(CompileError::EmptyExpression),
+            1 => Ok(Program {
+                instructions: self.instructions,
+                values,
+                thunks: self.thunks,
+                skips: self.skips,
+                max_depth: self.max_depth,
+            }),
+            count => Err(CompileError::UnusedValues { count }),
+        }
+    }
+}
+
+/// The unevaluated arguments of a lazy function
+pub struct Thunks<'a, T, E> {
+    pos: usize,
+    len: usize,
+    eval: &'a mut dyn FnMut(usize) -> Result<T, EvalError<E>>,
+}
+
+impl<T, E> Thunks<'_, T, E> {
+    /// Evaluates the argument at `index`. Every call evaluates the argument again.
+    ///
+    /// # Panics
+    /// Panics if `index` is not less than `len`.
+    pub fn eval(&mut self, index: usize) -> Result<T, EvalError<E>> {
+        assert!(index < self.len, "argument index out of bounds");
+        (self.eval)(index)
+    }
+
+    /// The number of arguments
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the function was called without arguments.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Creates an error at the position of the function.
+    pub fn error(&self, error: E) -> EvalError<E> {
+        EvalError::Evaluator {
+            pos: self.pos,
+            error,
+        }
+    }
+}
+
+/// The signature of an empty lazy function table
+type NoLazy<T, E> = fn(&mut Thunks<'_, T, E>) -> Result<T, EvalError<E>>;
+
+struct Tables<'a, P, Q, R, C> {
+    operators: &'a [P],
+    functions: &'a [Q],
+    lazy: &'a [R],
+    context: &'a C,
+}
+
+/// A compiled expression returned by `compile_to_fn`.
+pub type CompiledFn<'a, C, T, E> = Box<dyn Fn(&C) -> Result<T, EvalError<E>> + Send + Sync + 'a>;
+
+/// Compiles a postfix expression created by `to_postfix_with_arity` into a closure evaluating the
+/// expression against a context. `operator` and `function` return the procedure that implements
+/// an operator or function.
+///
+/// ```rust
+/// use gyard::{compile::compile_to_fn, op::Math, Call, OutputToken};
+///
+/// fn add(args: &mut [i64]) -> Result<i64, String> {
+///     Ok(args[0] + args[1])
+/// }
+///
+/// let x = 41;
+/// let context = |value: &&str| match *value {
+///     "x" => Ok(x),
+///     v => v.parse().map_err(|_| format!("invalid value {v}")),
+/// };
+///
+/// // 1 + x
+/// let postfix = [
+///     OutputToken::<_, Call<()>, _>::Value("1"),
+///     OutputToken::Value("x"),
+///     OutputToken::Operator(Math::Add),
+/// ];
+/// let formula = compile_to_fn(
+///     postfix,
+///     |op| (*op == Math::Add).then_some(add as fn(&mut [i64]) -> Result<i64, String>),
+///     |_, _| None::<fn(&mut [i64]) -> Result<i64, String>>,
+/// )
+/// .unwrap();
+/// assert_eq!(formula(&context), Ok(42));
+/// ```
+pub fn compile_to_fn<'a, V, F, O, T, E, P, Q, C>(
+    postfix: impl IntoIterator<Item = OutputToken<V, Call<F>, O>>,
+    mut operator: impl FnMut(&O) -> Option<P>,
+    mut function: impl FnMut(&F, usize) -> Option<Q>,
+) -> Result<CompiledFn<'a, C, T, E>, CompileError>
+where
+    O: Operator,
+    V: Send + Sync + 'a,
+    P: Fn(&mut [T]) -> Result<T, E> + Send + Sync + 'a,
+    Q: Fn(&mut [T]) -> Result<T, E> + Send + Sync + 'a,
+    C: for<'v> Context<&'v V, Value = T, Error = E>,
+{
+    let mut operators = Vec::new();
+    let mut functions = Vec::new();
+    let program = compile(
+        postfix,
+        |op| {
+            operators.push(operator(op)?);
+            Some(operators.len() - 1)
+        },
+        |func, arity| {
+            functions.push(function(func, arity)?);
+            Some(functions.len() - 1)
+        },
+    )?;
+    Ok(Box::new(move |context: &C| {
+        program.eval(&operators, &functions, context)
+    }))
+}
+
+/// Evaluates many independent programs in parallel. Returns the results in the order of
+/// `programs`. See `Program::eval`.
+///
+/// ```rust
+/// use gyard::{compile::{compile, eval_many}, op::Math, Call, OutputToken};
+///
+/// fn add(args: &mut [i64]) -> Result<i64, ()> {
+///     Ok(args[0] + args[1])
+/// }
+///
+/// let programs: Vec<_> = (0..100)
+///     .map(|i| {
+///         // i + x
+///         let postfix = [
+///             OutputToken::<_, Call<()>, _>::Value(Some(i)),
+///             OutputToken::Value(None),
+///             OutputToken::Operator(Math::Add),
+///         ];
+///         compile(postfix, |_| Some(0), |_, _| None).unwrap()
+///     })
+///     .collect();
+/// let context = |value: &Option<i64>| Ok(value.unwrap_or(1));
+/// let results = eval_many(&programs, &[add], &[] as &[fn(&mut [i64]) -> _], &context);
+/// assert_eq!(results, (1..=100).map(Ok).collect::<Vec<_>>());
+/// ```
+///
+/// # Panics
+/// Panics if an index returned during compilation is out of bounds of its table.
+#[cfg(feature = "rayon")]
+pub fn eval_many<V, T, E, P, Q, C>(
+    programs: &[Program<V>],
+    operators: &[P],
+    functions: &[Q],
+    context: &C,
+) -> Vec<Result<T, EvalError<E>>>
+where
+    V: Sync,
+    T: Send,
+    E: Send,
+    P: Fn(&mut [T]) -> Result<T, E> + Sync,
+    Q: Fn(&mut [T]) -> Result<T, E> + Sync,
+    C: for<'v> Context<&'v V, Value = T, Error = E> + Sync,
+{
+    use rayon::prelude::*;
+
+    programs
+        .par_iter()
+        .map_init(StackMachine::new, |machine, program| {
+            program.eval_with(machine, operators, functions, context)
+        })
+        .collect()
+}
+
+fn to_u32(n: usize) -> Result<u32, CompileError> {
+    u32::try_from(n).map_err(|_| CompileError::TooLarge)
+}
+
+impl<V> Program<V> {
+    /// Rebuilds a program from its instructions and values. The stack effect is verified again.
+    pub(crate) fn from_instructions(
+        instructions: impl IntoIterator<Item = Instr>,
+        values: Vec<V>,
+    ) -> Result<Self, CompileError> {
+        let mut builder = Builder::default();
+        for instr in instructions {
+            builder.push(instr)?;
+        }
+        builder.finish(values)
+    }
+
+    /// The instructions of the program
+    pub fn instructions(&self) -> &[Instr] {
+        &self.instructions
+    }
+
+    /// The values referenced by `Instr::Value`
+    pub fn values(&self) -> &[V] {
+        &self.values
+    }
+
+    /// The argument ranges of lazy functions. Each entry holds the first and one past the last
+    /// instruction of an argument.
+    pub fn thunks(&self) -> &[(u32, u32)] {
+        &self.thunks
+    }
+
+    /// The largest number of values on the stack during evaluation
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Evaluates the program. Values are resolved with `context`, operators and functions are
+    /// looked up in the given tables. Each table entry receives exactly `arity` arguments.
+    ///
+    /// # Panics
+    /// Panics if an index returned during compilation is out of bounds of its table.
+    pub fn eval<T, E, P, Q, C>(
+        &self,
+        operators: &[P],
+        functions: &[Q],
+        context: &C,
+    ) -> Result<T, EvalError<E>>
+    where
+        P: Fn(&mut [T]) -> Result<T, E>,
+        Q: Fn(&mut [T]) -> Result<T, E>,
+        C: for<'v> Context<&'v V, Value = T, Error = E>,
+    {
+        self.eval_with(
+            &mut StackMachine::with_capacity(self.max_depth),
+            operators,
+            functions,
+            context,
+        )
+    }
+
+    /// Evaluates the program using the stack of `machine`. See `Program::eval`.
+    ///
+    /// # Panics
+    /// Panics if an index returned during compilation is out of bounds of its table.
+    pub fn eval_with<T, E, P, Q, C>(
+        &self,
+        machine: &mut StackMachine<T>,
+        operators: &[P],
+        functions: &[Q],
+        context: &C,
+    ) -> Result<T, EvalError<E>>
+    where
+        P: Fn(&mut [T]) -> Result<T, E>,
+        Q: Fn(&mut [T]) -> Result<T, E>,
+        C: for<'v> Context<&'v V, Value = T, Error = E>,
+    {
+        self.eval_lazy_with(
+            machine,
+            operators,
+            functions,
+            &[] as &[NoLazy<T, E>],
+            context,
+        )
+    }
+
+    /// Evaluates a program created by `compile_lazy`. `lazy` is the table of lazy functions.
+    /// See `Program::eval`.
+    ///
+    /// # Panics
+    /// Panics if an index returned during compilation is out of bounds of its table.
+    pub fn eval_lazy<T, E, P, Q, R, C>(
+        &self,
+        operators: &[P],
+        functions: &[Q],
+        lazy: &[R],
+        context: &C,
+    ) -> Result<T, EvalError<E>>
+    where
+        P: Fn(&mut [T]) -> Result<T, E>,
+        Q: Fn(&mut [T]) -> Result<T, E>,
+        R: Fn(&mut Thunks<'_, T, E>) -> Result<T, EvalError<E>>,
+        C: for<'v> Context<&'v V, Value = T, Error = E>,
+    {
+        self.eval_lazy_with(
+            &mut StackMachine::with_capacity(self.max_depth),
+            operators,
+            functions,
+            lazy,
+            context,
+        )
+    }
+
+    /// Evaluates a program created by `compile_lazy` using the stack of `machine`. See
+    /// `Program::eval_lazy`.
+    ///
+    /// # Panics
+    /// Panics if an index returned during compilation is out of bounds of its table.
+    pub fn eval_lazy_with<T, E, P, Q, R, C>(
+        &self,
+        machine: &mut StackMachine<T>,
+        operators: &[P],
+        functions: &[Q],
+        lazy: &[R],
+        context: &C,
+    ) -> Result<T, EvalError<E>>
+    where
+        P: Fn(&mut [T]) -> Result<T, E>,
+        Q: Fn(&mut [T]) -> Result<T, E>,
+        R: Fn(&mut Thunks<'_, T, E>) -> Result<T, EvalError<E>>,
+        C: for<'v> Context<&'v V, Value = T, Error = E>,
+    {
+        eval_code(
+            self,
+            machine,
+            &Tables {
+                operators,
+                functions,
+                lazy,
+                context,
+            },
+        )
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<V: rkyv::Archive> ArchivedProgram<V> {
+    /// Evaluates an archived program without deserializing it. Values are resolved from their
+    /// archived form. See `Program::eval`.
+    ///
+    /// ```rust
+    /// use gyard::{compile::{compile, ArchivedProgram}, op::Math, Call, OutputToken};
+    ///
+    /// fn add(args: &mut [i64]) -> Result<i64, ()> {
+    ///     Ok(args[0] + args[1])
+    /// }
+    ///
+    /// // 1 + 2
+    /// let postfix = [
+    ///     OutputToken::<i64, Call<()>, _>::Value(1),
+    ///     OutputToken::Value(2),
+    ///     OutputToken::Operator(Math::Add),
+    /// ];
+    /// let program = compile(postfix, |_| Some(0), |_, _| None).unwrap();
+    /// let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&program).unwrap();
+    ///
+    /// let archived = rkyv::access::<ArchivedProgram<i64>, rkyv::rancor::Error>(&bytes).unwrap();
+    /// let context = |value: &rkyv::Archived<i64>| Ok(value.to_native());
+    /// assert_eq!(archived.eval(&[add], &[add], &context), Ok(3));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if an index returned during compilation is out of bounds of its table.
+    pub fn eval<T, E, P, Q, C>(
+        &self,
+        operators: &[P],
+        functions: &[Q],
+        context: &C,
+    ) -> Result<T, EvalError<E>>
+    where
+        P: Fn(&mut [T]) -> Result<T, E>,
+        Q: Fn(&mut [T]) -> Result<T, E>,
+        C: for<'v> Context<&'v rkyv::Archived<V>, Value = T, Error = E>,
+    {
+        self.eval_lazy_with(
+            &mut StackMachine::with_capacity(self.max_depth()),
+            operators,
+            functions,
+            &[] as &[NoLazy<T, E>],
+            context,
+        )
+    }
+
+    /// Evaluates an archived program created by `compile_lazy` using the stack of `machine`. See
+    /// `Program::eval_lazy_with`.
+    ///
+    /// # Panics
+    /// Panics if an index returned during compilation is out of bounds of its table.
+    pub fn eval_lazy_with<T, E, P, Q, R, C>(
+        &self,
+        machine: &mut StackMachine<T>,
+        operators: &[P],
+        functions: &[Q],
+        lazy: &[R],
+        context: &C,
+    ) -> Result<T, EvalError<E>>
+    where
+        P: Fn(&mut [T]) -> Result<T, E>,
+        Q: Fn(&mut [T]) -> Result<T, E>,
+        R: Fn(&mut Thunks<'_, T, E>) -> Result<T, EvalError<E>>,
+        C: for<'v> Context<&'v rkyv::Archived<V>, Value = T, Error = E>,
+    {
+        eval_code(
+            self,
+            machine,
+            &Tables {
+                operators,
+                functions,
+                lazy,
+                context,
+            },
+        )
+    }
+}
+
+/// The parts of a program read during evaluation. Implemented by `Program` and, with the `rkyv`
+/// feature, by `ArchivedProgram`.
+trait Code {
+    type Value;
+
+    fn len(&self) -> usize;
+
+    fn max_depth(&self) -> usize;
+
+    fn instruction(&self, pos: usize) -> Instr;
+
+    fn value(&self, index: u32) -> &Self::Value;
+
+    fn thunk(&self, index: usize) -> (u32, u32);
+
+    /// The skipped ranges starting at or after `pos`
+    fn skips_from(&self, pos: usize) -> impl Iterator<Item = (u32, u32)> + '_;
+}
+
+impl<V> Code for Program<V> {
+    type Value = V;
+
+    fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    fn instruction(&self, pos: usize) -> Instr {
+        self.instructions[pos]
+    }
+
+    fn value(&self, index: u32) -> &V {
+        &self.values[index as usize]
+    }
+
+    fn thunk(&self, index: usize) -> (u32, u32) {
+        self.thunks[index]
+    }
+
+    fn skips_from(&self, pos: usize) -> impl Iterator<Item = (u32, u32)> + '_ {
+        let first = self
+            .skips
+            .partition_point(|&(start, _)| (start as usize) < pos);
+        self.skips[first..].iter().copied()
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<V: rkyv::Archive> Code for ArchivedProgram<V> {
+    type Value = rkyv::Archived<V>;
+
+    fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    fn max_depth(&self) -> usize {
+        self.max_depth.to_native() as usize
+    }
+
+    fn instruction(&self, pos: usize) -> Instr {
+        match &self.instructions[pos] {
+            ArchivedInstr::Value(index) => Instr::Value(index.to_native()),
+            ArchivedInstr::Operator { index, arity } => Instr::Operator {
+                index: index.to_native(),
+                arity: arity.to_native(),
+            },
+            ArchivedInstr::Function { index, arity } => Instr::Function {
+                index: index.to_native(),
+                arity: arity.to_native(),
+            },
+            ArchivedInstr::Lazy {
+                index,
+                arity,
+                thunks,
+            } => Instr::Lazy {
+                index: index.to_native(),
+                arity: arity.to_native(),
+                thunks: thunks.to_native(),
+            },
+        }
+    }
+
+    fn value(&self, index: u32) -> &Self::Value {
+        &self.values[index as usize]
+    }
+
+    fn thunk(&self, index: usize) -> (u32, u32) {
+        let thunk = &self.thunks[index];
+        (thunk.0.to_native(), thunk.1.to_native())
+    }
+
+    fn skips_from(&self, pos: usize) -> impl Iterator<Item = (u32, u32)> + '_ {
+        let first = self
+            .skips
+            .partition_point(|skip| (skip.0.to_native() as usize) < pos);
+        self.skips[first..]
+            .iter()
+            .map(|skip| (skip.0.to_native(), skip.1.to_native()))
+    }
+}
+
+fn eval_code<K, T, E, P, Q, R, C>(
+    code: &K,
+    machine: &mut StackMachine<T>,
+    tables: &Tables<'_, P, Q, R, C>,
+) -> Result<T, EvalError<E>>
+where
+    K: Code,
+    P: Fn(&mut [T]) -> Result<T, E>,
+    Q: Fn(&mut [T]) -> Result<T, E>,
+    R: Fn(&mut Thunks<'_, T, E>) -> Result<T, EvalError<E>>,
+    C: for<'v> Context<&'v K::Value, Value = T, Error = E>,
+{
+    let span = trace::Span::evaluation();
+    machine.start();
+    machine.stack.reserve(code.max_depth());
+    let result = run(code, 0..code.len(), machine, tables)
+        .and_then(|()| machine.stack.pop().ok_or(EvalError::EmptyExpression));
+    span.finish(machine.executed, Some(machine.peak_depth), result.is_ok());
+    result
+}
+
+/// Executes the instructions in `range`, skipping the arguments of lazy functions.
+fn run<K, T, E, P, Q, R, C>(
+    code: &K,
+    range: core::ops::Range<usize>,
+    machine: &mut StackMachine<T>,
+    tables: &Tables<'_, P, Q, R, C>,
+) -> Result<(), EvalError<E>>
+where
+    K: Code,
+    P: Fn(&mut [T]) -> Result<T, E>,
+    Q: Fn(&mut [T]) -> Result<T, E>,
+    R: Fn(&mut Thunks<'_, T, E>) -> Result<T, EvalError<E>>,
+    C: for<'v> Context<&'v K::Value, Value = T, Error = E>,
+{
+    let mut skips = code.skips_from(range.start).peekable();
+    let mut pos = range.start;
+
+    while pos < range.end {
+        // Drop the arguments of skipped functions and the function being evaluated
+        while skips
+            .next_if(|&(start, end)| {
+                (start as usize) < pos || (start as usize == pos && end as usize >= range.end)
+            })
+            .is_some()
+        {}
+        if let Some((_, end)) = skips.next_if(|&(start, _)| start as usize == pos) {
+            pos = end as usize;
+        }
+
+        machine.step(pos)?;
+        let value = match code.instruction(pos) {
+            Instr::Value(index) => tables.context.resolve(code.value(index)),
+            Instr::Operator { index, arity } => {
+                machine.pop_args(arity as usize, pos)?;
+                tables.operators[index as usize](&mut machine.args)
+            }
+            Instr::Function { index, arity } => {
+                machine.pop_args(arity as usize, pos)?;
+                tables.functions[index as usize](&mut machine.args)
+            }
+            Instr::Lazy {
+                index,
+                arity,
+                thunks,
+            } => {
+                let mut eval = |i: usize| {
+                    let (start, end) = code.thunk(thunks as usize + i);
+                    run(code, start as usize..end as usize, machine, tables)?;
+                    machine.stack.pop().ok_or(EvalError::EmptyExpression)
+                };
+                let mut thunks = Thunks {
+                    pos,
+                    len: arity as usize,
+                    eval: &mut eval,
+                };
+                let value = tables.lazy[index as usize](&mut thunks)?;
+                machine.push(value, pos)?;
+                pos += 1;
+                continue;
+            }
+        }
+        .map_err(|error| EvalError::Evaluator { pos, error })?;
+        machine.push(value, pos)?;
+        pos += 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compile, compile_lazy, compile_to_fn, CompileError, FunctionIndex, Instr, Thunks};
+    use crate::{
+        eval::EvalError,
+        op::{Logical, Math},
+        Call, OutputToken,
+    };
+
+    fn sub(args: &mut [i64]) -> Result<i64, ()> {
+        Ok(args[0] - args[1])
+    }
+
+    fn neg(args: &mut [i64]) -> Result<i64, ()> {
+        Ok(-args[0])
+    }
+
+    #[test]
+    fn compile_and_eval() {
+        // neg ( 5 - 3 )
+        let postfix = [
+            OutputToken::Value(5),
+            OutputToken::Value(3),
+            OutputToken::Operator(Math::Sub),
+            OutputToken::Function(Call {
+                function: "neg",
+                arity: 1,
+            }),
+        ];
+        let program = compile(postfix, |_| Some(0), |_, _| Some(1));
+        let Ok(program) = program else {
+            panic!("Failed to compile {program:?}")
+        };
+        assert_eq!(
+            program.instructions(),
+            &[
+                Instr::Value(0),
+                Instr::Value(1),
+                Instr::Operator { index: 0, arity: 2 },
+                Instr::Function { index: 1, arity: 1 },
+            ]
+        );
+        assert_eq!(program.max_depth(), 2);
+        assert_eq!(program.eval(&[sub], &[sub, neg], &|v: &i64| Ok(*v)), Ok(-2));
+    }
+
+    #[test]
+    fn unknown_operator() {
+        let postfix = [
+            OutputToken::<_, Call<()>, _>::Value(true),
+            OutputToken::Operator(Logical::Not),
+        ];
+        let program = compile(postfix, |_| None, |_, _| None);
+        assert_eq!(program, Err(CompileError::UnknownOperator { pos: 1 }));
+    }
+
+    #[test]
+    fn stack_underflow() {
+        let postfix = [
+            OutputToken::<_, Call<()>, _>::Value(1),
+            OutputToken::Operator(Math::Add),
+        ];
+        let program = compile(postfix, |_| Some(0), |_, _| None);
+        assert_eq!(
+            program,
+            Err(CompileError::StackUnderflow {
+                pos: 1,
+                required: 2,
+                available: 1
+            })
+        );
+    }
+
+    #[test]
+    fn unused_values() {
+        let postfix = [
+            OutputToken::<_, Call<()>, Math>::Value(1),
+            OutputToken::Value(2),
+        ];
+        let program = compile(postfix, |_| Some(0), |_, _| None);
+        assert_eq!(program, Err(CompileError::UnusedValues { count: 2 }));
+    }
+
+    #[test]
+    fn compile_to_closure() {
+        // neg ( a - b )
+        let postfix = [
+            OutputToken::Value('a'),
+            OutputToken::Value('b'),
+            OutputToken::Operator(Math::Sub),
+            OutputToken::Function(Call {
+                function: "neg",
+                arity: 1,
+            }),
+        ];
+        let formula = compile_to_fn(
+            postfix,
+            |_| Some(sub),
+            |name, arity| (*name == "neg" && arity == 1).then_some(neg),
+        );
+        let Ok(formula) = formula else {
+            panic!("Failed to compile")
+        };
+        let context = |v: &char| match v {
+            'a' => Ok(1),
+            'b' => Ok(4),
+            _ => Err(()),
+        };
+        assert_eq!(formula(&context), Ok(3));
+    }
+
+    #[test]
+    fn nested_lazy_functions() {
+        fn if_(args: &mut Thunks<'_, i64, ()>) -> Result<i64, EvalError<()>> {
+            match args.eval(0)? {
+                0 => args.eval(2),
+                _ => args.eval(1),
+            }
+        }
+        fn fail(_: &mut [i64]) -> Result<i64, ()> {
+            Err(())
+        }
+        let call = |function| OutputToken::Function(Call { function, arity: 3 });
+
+        // if ( if ( a , b , fail ( ) ) , neg ( a ) , fail ( ) )
+        let postfix = [
+            OutputToken::Value('a'),
+            OutputToken::Value('b'),
+            OutputToken::Function(Call {
+                function: "fail",
+                arity: 0,
+            }),
+            call("if"),
+            OutputToken::Value('a'),
+            OutputToken::Function(Call {
+                function: "neg",
+                arity: 1,
+            }),
+            OutputToken::Function(Call {
+                function: "fail",
+                arity: 0,
+            }),
+            call("if"),
+        ];
+        let program = compile_lazy::<_, _, Math>(
+            postfix,
+            |_| None,
+            |name, _| match *name {
+                "if" => Some(FunctionIndex::Lazy(0)),
+                "neg" => Some(FunctionIndex::Strict(0)),
+                _ => Some(FunctionIndex::Strict(1)),
+            },
+        );
+        let Ok(program) = program else {
+            panic!("Failed to compile")
+        };
+        assert_eq!(program.thunks().len(), 6);
+
+        let functions = [neg, fail];
+        let context = |v: &char| match v {
+            'a' => Ok(0),
+            _ => Ok(1),
+        };
+        assert_eq!(
+            program.eval_lazy(&[sub], &functions, &[if_], &context),
+            Err(EvalError::Evaluator { pos: 2, error: () })
+        );
+        let context = |v: &char| Ok(if *v == 'a' { 2 } else { 1 });
+        assert_eq!(
+            program.eval_lazy(&[sub], &functions, &[if_], &context),
+            Ok(-2)
+        );
+
+        #[cfg(feature = "rkyv")]
+        {
+            let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&program).expect("serializable");
+            let archived =
+                rkyv::access::<super::ArchivedProgram<char>, rkyv::rancor::Error>(&bytes)
+                    .expect("valid archive");
+            let context = |v: &rkyv::Archived<char>| Ok(if *v == 'a' { 2 } else { 1 });
+            let mut machine = crate::eval::StackMachine::new();
+            assert_eq!(
+                archived.eval_lazy_with(&mut machine, &[sub], &functions, &[if_], &context),
+                Ok(-2)
+            );
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Evaluation of a `Program` over columns of values.
+//! Instead of evaluating the program once per row, every instruction is applied to a block of rows
+//! at a time. Operators and functions are kernels that fill an output column from their argument
+//! columns, which keeps the inner loops simple enough for the compiler to vectorize.
+//!
+//! ```rust
+//! use gyard::compile::{compile, columns::{binary, Column}};
+//! use gyard::{op::Math, Call, OutputToken};
+//!
+//! // price * ( 1 + tax )
+//! let postfix = [
+//!     OutputToken::<_, Call<()>, _>::Value("price"),
+//!     OutputToken::Value("1"),
+//!     OutputToken::Value("tax"),
+//!     OutputToken::Operator(Math::Add),
+//!     OutputToken::Operator(Math::Mul),
+//! ];
+//! let program = compile(
+//!     postfix,
+//!     |op| match op {
+//!         Math::Add => Some(0),
+//!         Math::Mul => Some(1),
+//!         _ => None,
+//!     },
+//!     |_, _| None,
+//! )
+//! .unwrap();
+//!
+//! let price = [10., 20., 30.];
+//! let tax = [0.5, 0.25, 0.];
+//! let operators = [binary(|a, b| a + b), binary(|a, b| a * b)];
+//! let result = program.eval_columns(&operators, &[] as &[fn(&[&[f64]], &mut [f64]) -> _], 3, |value| {
+//!     match *value {
+//!         "price" => Ok(Column::Slice(&price)),
+//!         "tax" => Ok(Column::Slice(&tax)),
+//!         v => v.parse().map(Column::Scalar).map_err(|_| format!("unknown value {v}")),
+//!     }
+//! });
+//! assert_eq!(result, Ok(vec![15., 25., 30.]));
+//! ```
+
+use alloc::{vec, vec::Vec};
+
+use super::{Instr, Program};
+use crate::eval::EvalError;
+
+/// The number of rows every instruction is applied to at once
+const BLOCK_SIZE: usize = 256;
+
+/// The values of a value token
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Column<'a> {
+    /// One value per row
+    Slice(&'a [f64]),
+    /// The same value for every row
+    Scalar(f64),
+}
+
+/// An operator or function applied to a block of rows
+type Kernel<'k, E> = &'k dyn Fn(&[&[f64]], &mut [f64]) -> Result<(), E>;
+
+/// A stack entry during columnar evaluation
+enum Slot {
+    /// The column of the value at this index of `Program::values`
+    Value(usize),
+    /// A column computed by an operator or function
+    Buffer(Vec<f64>),
+}
+
+/// Creates a kernel applying `f` to every row of a single argument column.
+pub fn unary<E>(f: fn(f64) -> f64) -> impl Fn(&[&[f64]], &mut [f64]) -> Result<(), E> {
+    move |args, out| {
+        for (out, a) in out.iter_mut().zip(args[0]) {
+            *out = f(*a);
+        }
+        Ok(())
+    }
+}
+
+/// Creates a kernel applying `f` to every row of two argument columns.
+pub fn binary<E>(f: fn(f64, f64) -> f64) -> impl Fn(&[&[f64]], &mut [f64]) -> Result<(), E> {
+    move |args, out| {
+        for ((out, a), b) in out.iter_mut().zip(args[0]).zip(args[1]) {
+            *out = f(*a, *b);
+        }
+        Ok(())
+    }
+}
+
+/// Returns the rows of `block` of a stack entry.
+fn column<'s>(
+    slot: &'s Slot,
+    columns: &'s [Option<(Column<'_>, Vec<f64>)>],
+    block: &core::ops::Range<usize>,
+) -> &'s [f64] {
+    match slot {
+        Slot::Value(index) => match &columns[*index] {
+            Some((Column::Slice(slice), _)) => &slice[block.clone()],
+            Some((Column::Scalar(_), buffer)) => &buffer[..block.len()],
+            None => unreachable!("Every value on the stack is resolved before the evaluation"),
+        },
+        Slot::Buffer(buffer) => buffer,
+    }
+}
+
+impl<V> Program<V> {
+    /// Evaluates the program for `rows` rows and returns the result of every row.
+    /// `context` returns the column of a value token. Operators and functions are kernels which
+    /// receive one column per argument and write their result into the output column. All columns
+    /// passed to a kernel have the same length.
+    ///
+    /// # Panics
+    /// Panics if an index returned during compilation is out of bounds of its table, if the
+    /// program contains lazy functions or if a column returned by `context` has less than `rows`
+    /// values.
+    pub fn eval_columns<'a, E, P, Q>(
+        &self,
+        operators: &[P],
+        functions: &[Q],
+        rows: usize,
+        mut context: impl FnMut(&V) -> Result<Column<'a>, E>,
+    ) -> Result<Vec<f64>, EvalError<E>>
+    where
+        P: Fn(&[&[f64]], &mut [f64]) -> Result<(), E>,
+        Q: Fn(&[&[f64]], &mut [f64]) -> Result<(), E>,
+    {
+        let block_size = rows.min(BLOCK_SIZE);
+        // The column of every value, indexed like `Program::values`. Values are resolved once,
+        // when the first instruction using them is reached.
+        let mut columns: Vec<Option<(Column<'a>, Vec<f64>)>> = vec![None; self.values.len()];
+        for (pos, instr) in self.instructions.iter().enumerate() {
+            let Instr::Value(index) = *instr else {
+                continue;
+            };
+            let slot = &mut columns[index as usize];
+            if slot.is_some() {
+                continue;
+            }
+            let column = context(&self.values[index as usize])
+                .map_err(|error| EvalError::Evaluator { pos, error })?;
+            // Scalars are broadcast to a full block once
+            let buffer = match column {
+                Column::Slice(slice) => {
+                    assert!(slice.len() >= rows, "column has less than {rows} rows");
+                    vec![]
+                }
+                Column::Scalar(value) => vec![value; block_size],
+            };
+            *slot = Some((column, buffer));
+        }
+
+        let mut result = Vec::with_capacity(rows);
+        let mut stack: Vec<Slot> = Vec::with_capacity(self.max_depth);
+        let mut args: Vec<Slot> = Vec::new();
+        let mut free: Vec<Vec<f64>> = Vec::new();
+
+        for start in (0..rows).step_by(BLOCK_SIZE) {
+            let block = start..rows.min(start + BLOCK_SIZE);
+
+            for (pos, instr) in self.instructions.iter().enumerate() {
+                let (kernel, arity): (Kernel<'_, E>, _) = match *instr {
+                    Instr::Value(index) => {
+                        stack.push(Slot::Value(index as usize));
+                        continue;
+                    }
+                    Instr::Operator { index, arity } => (&operators[index as usize], arity),
+                    Instr::Function { index, arity } => (&functions[index as usize], arity),
+                    Instr::Lazy { .. } => {
+                        panic!("lazy functions can not be evaluated over columns")
+                    }
+                };
+                let Some(first) = stack.len().checked_sub(arity as usize) else {
+                    return Err(EvalError::StackUnderflow {
+                        pos,
+                        required: arity as usize,
+                        available: stack.len(),
+                    });
+                };
+                args.extend(stack.drain(first..));
+                let mut out = free.pop().unwrap_or_default();
+                out.resize(block.len(), 0.);
+                let arg_columns: Vec<&[f64]> = args
+                    .iter()
+                    .map(|slot| column(slot, &columns, &block))
+                    .collect();
+                kernel(&arg_columns, &mut out)
+                    .map_err(|error| EvalError::Evaluator { pos, error })?;
+                for slot in args.drain(..) {
+                    if let Slot::Buffer(buffer) = slot {
+                        free.push(buffer);
+                    }
+                }
+                stack.push(Slot::Buffer(out));
+            }
+
+            let Some(slot) = stack.pop() else {
+                return Err(EvalError::EmptyExpression);
+            };
+            result.extend_from_slice(column(&slot, &columns, &block));
+            if let Slot::Buffer(buffer) = slot {
+                free.push(buffer);
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::{binary, unary, Column};
+    use crate::{
+        binary::{decode_program, encode_program},
+        compile::{compile, Instr, Program},
+        op::Math,
+        Call, OutputToken,
+    };
+
+    #[test]
+    fn multiple_blocks() {
+        // neg ( x - 1 )
+        let postfix = [
+            OutputToken::Value('x'),
+            OutputToken::Value('1'),
+            OutputToken::Operator(Math::Sub),
+            OutputToken::Function(Call {
+                function: "neg",
+                arity: 1,
+            }),
+        ];
+        let Ok(program) = compile(postfix, |_| Some(0), |_, _| Some(0)) else {
+            panic!("Failed to compile")
+        };
+        let x: Vec<f64> = (0..1000).map(f64::from).collect();
+        let result = program.eval_columns::<(), _, _>(
+            &[binary(|a, b| a - b)],
+            &[unary(|a: f64| -a)],
+            x.len(),
+            |value| match value {
+                'x' => Ok(Column::Slice(&x)),
+                _ => Ok(Column::Scalar(1.)),
+            },
+        );
+        let expected: Vec<f64> = x.iter().map(|x| 1. - x).collect();
+        assert_eq!(result, Ok(expected));
+    }
+
+    #[test]
+    fn value_indices_in_any_order() {
+        // y - x * x, the value `z` is never used
+        let program = Program::from_instructions(
+            [
+                Instr::Value(1),
+                Instr::Value(0),
+                Instr::Value(0),
+                Instr::Operator { index: 1, arity: 2 },
+                Instr::Operator { index: 0, arity: 2 },
+            ],
+            vec!['x', 'y', 'z'],
+        )
+        .expect("valid program");
+        let bytes = encode_program(&program, |value, out| out.push(*value as u8));
+        let program = decode_program(&bytes, |bytes| Ok::<_, ()>(char::from(bytes[0])))
+            .expect("valid encoding");
+
+        let x = [1., 2., 3.];
+        let mut resolved = Vec::new();
+        let result = program.eval_columns::<(), _, _>(
+            &[binary(|a, b| a - b), binary(|a, b| a * b)],
+            &[] as &[fn(&[&[f64]], &mut [f64]) -> _],
+            x.len(),
+            |value| {
+                resolved.push(*value);
+                match value {
+                    'x' => Ok(Column::Slice(&x)),
+                    _ => Ok(Column::Scalar(10.)),
+                }
+            },
+        );
+        assert_eq!(result, Ok(vec![9., 6., 1.]));
+        assert_eq!(resolved, ['y', 'x']);
+    }
+}
@@ -0,0 +1,303 @@
+//! Operators and functions defined in TOML or JSON.
+//!
+//! A `Config` lists operators with their symbol, precedence, associativity and arity and
+//! functions with the number of arguments they accept. Use `Config::operators` to create
+//! `op::DynOp`s for your tokenizer and `Config::registry` to create a `FunctionRegistry` with the
+//! implementations of your application. This lets users extend the expression language without
+//! recompiling.
+//!
+//! ```rust
+//! use gyard::{config::Config, eval::registry::Callback, Operator};
+//!
+//! let config = Config::from_toml(r#"
+//!     [[operators]]
+//!     symbol = "+"
+//!     precedence = 1
+//!
+//!     [[operators]]
+//!     symbol = "**"
+//!     precedence = 2
+//!     associativity = "Right"
+//!
+//!     [[functions]]
+//!     name = "max"
+//!     min_arity = 1
+//! "#).unwrap();
+//! let pow = config.operator("**").unwrap();
+//! assert!(!pow.is_left_associative());
+//! assert_eq!(pow.arity(), 2);
+//!
+//! let registry = config
+//!     .registry(|name| match name {
+//!         "max" => Some(Box::new(|args: &mut Vec<f64>| {
+//!             Ok::<_, ()>(args.iter().copied().fold(f64::MIN, f64::max))
+//!         }) as Callback<f64, ()>),
+//!         _ => None,
+//!     })
+//!     .unwrap();
+//! assert!(registry.accepts(&"max".to_string(), 3));
+//! assert!(!registry.accepts(&"max".to_string(), 0));
+//! ```
+
+use alloc::{string::String, vec::Vec};
+use std::collections::HashSet;
+
+use crate::{
+    eval::registry::{Callback, FunctionRegistry},
+    op::{Associativity, DynOp},
+};
+
+/// An operator of a `Config`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct OperatorConfig {
+    /// The symbol of the operator
+    pub symbol: String,
+    /// The precedence of the operator
+    pub precedence: usize,
+    /// The associativity of the operator, `Left` if it is missing
+    #[serde(default = "left")]
+    pub associativity: Associativity,
+    /// The number of operands, 2 if it is missing
+    #[serde(default = "two")]
+    pub arity: usize,
+}
+
+fn left() -> Associativity {
+    Associativity::Left
+}
+
+fn two() -> usize {
+    2
+}
+
+impl From<&OperatorConfig> for DynOp {
+    fn from(config: &OperatorConfig) -> Self {
+        DynOp {
+            name: config.symbol.clone(),
+            precedence: config.precedence,
+            associativity: config.associativity,
+            arity: config.arity,
+        }
+    }
+}
+
+/// A function of a `Config`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct FunctionConfig {
+    /// The name of the function
+    pub name: String,
+    /// The minimum number of arguments, 0 if it is missing
+    #[serde(default)]
+    pub min_arity: usize,
+    /// The maximum number of arguments. The number of arguments is unbounded if it is missing.
+    #[serde(default)]
+    pub max_arity: Option<usize>,
+}
+
+impl FunctionConfig {
+    /// Returns the range of accepted arguments.
+    pub fn arity(&self) -> core::ops::RangeInclusive<usize> {
+        self.min_arity..=self.max_arity.unwrap_or(usize::MAX)
+    }
+}
+
+/// Operator and function definitions. See the module documentation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Config {
+    /// The operators
+    #[serde(default)]
+    pub operators: Vec<OperatorConfig>,
+    /// The functions
+    #[serde(default)]
+    pub functions: Vec<FunctionConfig>,
+}
+
+/// This error is returned if a `Config` can not be loaded or used.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The TOML document is invalid.
+    Toml(toml::de::Error),
+    /// The JSON document is invalid.
+    Json(serde_json::Error),
+    /// Two operators with the same symbol and arity or two functions with the same name are
+    /// defined.
+    Duplicate {
+        /// The symbol or name
+        name: String,
+    },
+    /// The maximum number of arguments of a function is smaller than the minimum.
+    InvalidArity {
+        /// The name of the function
+        name: String,
+    },
+    /// `Config::registry` did not get an implementation for the function.
+    MissingImplementation {
+        /// The name of the function
+        name: String,
+    },
+}
+
+impl core::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ConfigError::Toml(error) => write!(f, "Invalid TOML: {error}"),
+            ConfigError::Json(error) => write!(f, "Invalid JSON: {error}"),
+            ConfigError::Duplicate { name } => write!(f, "{name} is defined twice"),
+            ConfigError::InvalidArity { name } => {
+                write!(
+                    f,
+                    "The minimum arity of {name} is larger than its maximum arity"
+                )
+            }
+            ConfigError::MissingImplementation { name } => {
+                write!(f, "Function {name} is not implemented")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            ConfigError::Toml(error) => Some(error),
+            ConfigError::Json(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl Config {
+    /// Parses and validates a TOML document.
+    pub fn from_toml(input: &str) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(input).map_err(ConfigError::Toml)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Parses and validates a JSON document.
+    pub fn from_json(input: &str) -> Result<Self, ConfigError> {
+        let config: Self = serde_json::from_str(input).map_err(ConfigError::Json)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks for duplicate definitions and invalid arities.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut operators = HashSet::new();
+        for operator in &self.operators {
+            if !operators.insert((&operator.symbol, operator.arity)) {
+                return Err(ConfigError::Duplicate {
+                    name: operator.symbol.clone(),
+                });
+            }
+        }
+        let mut functions = HashSet::new();
+        for function in &self.functions {
+            if !functions.insert(&function.name) {
+                return Err(ConfigError::Duplicate {
+                    name: function.name.clone(),
+                });
+            }
+            if function.arity().is_empty() {
+                return Err(ConfigError::InvalidArity {
+                    name: function.name.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns all operators in the order they are defined.
+    pub fn operators(&self) -> impl Iterator<Item = DynOp> + '_ {
+        self.operators.iter().map(DynOp::from)
+    }
+
+    /// Returns the first operator with `symbol`.
+    pub fn operator(&self, symbol: &str) -> Option<DynOp> {
+        self.operators().find(|operator| operator.name == symbol)
+    }
+
+    /// Creates a registry of all functions. `implementation` returns the callback of a function
+    /// or `None` if the application does not implement it.
+    pub fn registry<'a, T: 'a, E: 'a>(
+        &self,
+        mut implementation: impl FnMut(&str) -> Option<Callback<'a, T, E>>,
+    ) -> Result<FunctionRegistry<'a, String, T, E>, ConfigError> {
+        self.functions
+            .iter()
+            .try_fold(FunctionRegistry::new(), |registry, function| {
+                let callback = implementation(&function.name).ok_or_else(|| {
+                    ConfigError::MissingImplementation {
+                        name: function.name.clone(),
+                    }
+                })?;
+                Ok(registry.with_function(function.name.clone(), function.arity(), callback))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, ConfigError};
+    use crate::{to_postfix, InputToken, OutputToken};
+
+    #[test]
+    fn json_operators_convert() {
+        let config = Config::from_json(
+            r#"{
+                "operators": [
+                    { "symbol": "+", "precedence": 1 },
+                    { "symbol": "*", "precedence": 2 },
+                    { "symbol": "-", "precedence": 3, "associativity": "Right", "arity": 1 }
+                ]
+            }"#,
+        )
+        .expect("valid config");
+        let op =
+            |symbol| InputToken::<_, (), _>::Operator(config.operator(symbol).expect("defined"));
+        // 1 + 2 * 3
+        let postfix = to_postfix([
+            InputToken::Value(1),
+            op("+"),
+            InputToken::Value(2),
+            op("*"),
+            InputToken::Value(3),
+        ])
+        .expect("valid expression");
+        assert!(matches!(&postfix[4], OutputToken::Operator(operator) if operator.name == "+"));
+        assert_eq!(config.operator("-").map(|operator| operator.arity), Some(1));
+    }
+
+    #[test]
+    fn invalid_configs() {
+        let duplicate = r#"
+            [[functions]]
+            name = "f"
+            [[functions]]
+            name = "f"
+        "#;
+        assert!(matches!(
+            Config::from_toml(duplicate),
+            Err(ConfigError::Duplicate { name }) if name == "f"
+        ));
+        let arity = r#"{ "functions": [{ "name": "g", "min_arity": 2, "max_arity": 1 }] }"#;
+        assert!(matches!(
+            Config::from_json(arity),
+            Err(ConfigError::InvalidArity { name }) if name == "g"
+        ));
+        assert!(matches!(
+            Config::from_toml("operators = 1"),
+            Err(ConfigError::Toml(_))
+        ));
+        let missing = Config::from_toml("[[functions]]\nname = \"h\"")
+            .expect("valid config")
+            .registry::<f64, ()>(|_| None);
+        assert!(matches!(
+            missing,
+            Err(ConfigError::MissingImplementation { name }) if name == "h"
+        ));
+    }
+}
@@ -0,0 +1,164 @@
+//! Many named expressions that share their resources.
+//!
+//! A `Document` converts the expressions of a workbook or rule set with one reused operator
+//! stack, stores every distinct function once in a `FunctionPool` and keeps the validated results
+//! as a map of `PostfixExpr`s. Operators are shared by borrowing them, i.e. from an
+//! `op::set::OperatorSet`.
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use gyard::{
+//!     document::Document,
+//!     op::{set::OperatorSetBuilder, Associativity},
+//!     InputToken, OutputToken,
+//! };
+//!
+//! let set = OperatorSetBuilder::new()
+//!     .infix("+", 1, Associativity::Left)
+//!     .build()
+//!     .unwrap();
+//! let plus = set.get("+", false).unwrap();
+//! // sum ( a ) + 1
+//! let formula = |cell| [
+//!     InputToken::Function("sum".to_string()),
+//!     InputToken::LeftParen,
+//!     InputToken::Value(cell),
+//!     InputToken::RightParen,
+//!     InputToken::Operator(plus),
+//!     InputToken::Value("1"),
+//! ];
+//!
+//! let mut document = Document::new();
+//! document.insert("B1", formula("A1")).unwrap();
+//! document.insert("B2", formula("A2")).unwrap();
+//! assert_eq!(document.len(), 2);
+//! assert_eq!(document.function_pool().len(), 1);
+//!
+//! let (Some(b1), Some(b2)) = (document.get(&"B1"), document.get(&"B2")) else { panic!() };
+//! let (OutputToken::Function(sum1), OutputToken::Function(sum2)) = (&b1.tokens()[1], &b2.tokens()[1])
+//! else {
+//!     panic!()
+//! };
+//! assert!(Arc::ptr_eq(&sum1.function, &sum2.function));
+//! ```
+
+use std::{borrow::Borrow, collections::BTreeMap, hash::Hash, sync::Arc};
+
+use crate::{
+    ast::AstError,
+    batch::{FunctionPool, ShuntingYard},
+    postfix::PostfixExpr,
+    InputToken, Operator,
+};
+
+/// Named expressions sharing a function pool and an operator stack. See the module
+/// documentation.
+pub struct Document<K, V, F, O> {
+    yard: ShuntingYard<Arc<F>, O>,
+    functions: FunctionPool<F>,
+    expressions: BTreeMap<K, PostfixExpr<V, Arc<F>, O>>,
+}
+
+impl<K, V, F, O> Default for Document<K, V, F, O> {
+    fn default() -> Self {
+        Self {
+            yard: ShuntingYard::new(),
+            functions: FunctionPool::default(),
+            expressions: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K: Ord, V, F: Eq + Hash, O: Operator> Document<K, V, F, O> {
+    /// Creates an empty document.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Converts and validates `infix` and stores it as `name`. Returns the previous expression
+    /// with the same name. The document is not changed if the expression is invalid.
+    pub fn insert(
+        &mut self,
+        name: K,
+        infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+    ) -> Result<Option<PostfixExpr<V, Arc<F>, O>>, AstError> {
+        let functions = &mut self.functions;
+        let postfix = self
+            .yard
+            .convert_with_arity(infix.into_iter().map(|token| functions.intern_token(token)))?;
+        let expression = PostfixExpr::new(postfix)?;
+        Ok(self.expressions.insert(name, expression))
+    }
+}
+
+impl<K: Ord, V, F, O> Document<K, V, F, O> {
+    /// Returns the expression `name`.
+    pub fn get<Q: Ord + ?Sized>(&self, name: &Q) -> Option<&PostfixExpr<V, Arc<F>, O>>
+    where
+        K: Borrow<Q>,
+    {
+        self.expressions.get(name)
+    }
+
+    /// Removes the expression `name` and returns it. Its functions stay in the pool.
+    pub fn remove<Q: Ord + ?Sized>(&mut self, name: &Q) -> Option<PostfixExpr<V, Arc<F>, O>>
+    where
+        K: Borrow<Q>,
+    {
+        self.expressions.remove(name)
+    }
+
+    /// Returns all expressions ordered by name.
+    pub fn expressions(&self) -> &BTreeMap<K, PostfixExpr<V, Arc<F>, O>> {
+        &self.expressions
+    }
+
+    /// Returns all expressions ordered by name and drops the shared resources.
+    pub fn into_expressions(self) -> BTreeMap<K, PostfixExpr<V, Arc<F>, O>> {
+        self.expressions
+    }
+
+    /// Returns the pool storing the functions of all expressions.
+    pub fn function_pool(&self) -> &FunctionPool<F> {
+        &self.functions
+    }
+
+    /// The number of expressions
+    pub fn len(&self) -> usize {
+        self.expressions.len()
+    }
+
+    /// Returns true if the document does not contain any expression.
+    pub fn is_empty(&self) -> bool {
+        self.expressions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Document;
+    use crate::{ast::AstError, op::Math, InputToken};
+
+    #[test]
+    fn invalid_expression_keeps_previous() {
+        let mut document = Document::<_, _, (), _>::new();
+        let valid = [
+            InputToken::Value(1),
+            InputToken::Operator(Math::Add),
+            InputToken::Value(2),
+        ];
+        assert!(document
+            .insert("a", valid)
+            .expect("valid expression")
+            .is_none());
+        assert_eq!(
+            document.insert("a", [InputToken::Operator(Math::Add), InputToken::Value(2)]),
+            Err(AstError::StackUnderflow {
+                pos: 1,
+                required: 2,
+                available: 1
+            })
+        );
+        assert_eq!(document.get("a").map(|a| a.tokens().len()), Some(3));
+    }
+}
@@ -0,0 +1,182 @@
+//! A conversion core that is compiled once for all token types.
+//!
+//! With the `dynamic` feature `to_postfix` and `to_postfix_with_arity` replace every token with a
+//! handle holding its position, run the conversion on the handles and move the tokens into the
+//! output afterwards. Only the thin wrapper is instantiated for every `V`, `F` and `O`, which
+//! reduces compile time and binary size for crates using many token types.
+
+use alloc::vec::Vec;
+
+use crate::{convert_slice, InputToken, Operator, OutputToken, ParenMissmatchError};
+
+/// An operator replaced by its position and the properties the conversion needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Handle {
+    pos: usize,
+    precedence: usize,
+    left_associative: bool,
+    arity: usize,
+    commutative: bool,
+    takes_list: bool,
+}
+
+impl Handle {
+    /// Creates the handle of `operator` at `pos`.
+    fn new(pos: usize, operator: &impl Operator) -> Self {
+        Self {
+            pos,
+            precedence: operator.precedence(),
+            left_associative: operator.is_left_associative(),
+            arity: operator.arity(),
+            commutative: operator.is_commutative(),
+            takes_list: operator.takes_list(),
+        }
+    }
+}
+
+impl Operator for Handle {
+    fn precedence(&self) -> usize {
+        self.precedence
+    }
+
+    fn is_left_associative(&self) -> bool {
+        self.left_associative
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn is_commutative(&self) -> bool {
+        self.commutative
+    }
+
+    fn takes_list(&self) -> bool {
+        self.takes_list
+    }
+}
+
+type HandleOutput = Vec<OutputToken<usize, (usize, usize), Handle>>;
+
+/// The only instantiation of the conversion. Functions are returned with their position and
+/// number of arguments.
+#[inline(never)]
+fn convert_handles(
+    handles: Vec<InputToken<usize, usize, Handle>>,
+) -> Result<HandleOutput, ParenMissmatchError> {
+    convert_slice(&handles, |pos, arity| (pos, arity))
+}
+
+/// Converts `infix` using `convert_handles`.
+pub(crate) fn convert_dynamic<V, F, G, O>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+    call: impl Fn(F, usize) -> G,
+) -> Result<Vec<OutputToken<V, G, O>>, ParenMissmatchError>
+where
+    O: Operator,
+{
+    let mut tokens: Vec<_> = infix.into_iter().map(Some).collect();
+    let handles = tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(pos, token)| {
+            #[expect(deprecated, reason = "")]
+            let handle = match token.as_ref()? {
+                InputToken::Value(_) => InputToken::Value(pos),
+                InputToken::LeftParen => InputToken::LeftParen,
+                InputToken::RightParen => InputToken::RightParen,
+                InputToken::Function(_) => InputToken::Function(pos),
+                InputToken::ArgSeperator => InputToken::ArgSeperator,
+                InputToken::ArgSeparator => InputToken::ArgSeparator,
+                InputToken::Operator(operator) => InputToken::Operator(Handle::new(pos, operator)),
+            };
+            Some(handle)
+        })
+        .collect();
+    let postfix = convert_handles(handles)?;
+    // Every position is part of the output at most once
+    let mut output = Vec::with_capacity(tokens.len());
+    output.extend(postfix.into_iter().filter_map(|handle| match handle {
+        OutputToken::Value(pos) => match tokens[pos].take()? {
+            InputToken::Value(value) => Some(OutputToken::Value(value)),
+            _ => None,
+        },
+        OutputToken::Function((pos, arity)) => match tokens[pos].take()? {
+            InputToken::Function(function) => Some(OutputToken::Function(call(function, arity))),
+            _ => None,
+        },
+        OutputToken::Operator(Handle { pos, .. }) => match tokens[pos].take()? {
+            InputToken::Operator(operator) => Some(OutputToken::Operator(operator)),
+            _ => None,
+        },
+    }));
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::{convert_dynamic, Handle};
+    use crate::{
+        convert,
+        op::{All, Logical, Math, Sql},
+        Call, InputToken, Operator,
+    };
+
+    #[test]
+    fn matches_generic_conversion() {
+        // max ( 1 , 2 ^ 3 ^ 4 ) * ( 5 - 6 )
+        let infix = vec![
+            InputToken::Function("max"),
+            InputToken::LeftParen,
+            InputToken::Value(1),
+            InputToken::ArgSeparator,
+            InputToken::Value(2),
+            InputToken::Operator(Math::Exponent),
+            InputToken::Value(3),
+            InputToken::Operator(Math::Exponent),
+            InputToken::Value(4),
+            InputToken::RightParen,
+            InputToken::Operator(Math::Mul),
+            InputToken::LeftParen,
+            InputToken::Value(5),
+            InputToken::Operator(Math::Sub),
+            InputToken::Value(6),
+            InputToken::RightParen,
+        ];
+        let call = |function, arity| Call { function, arity };
+        assert_eq!(
+            convert_dynamic(infix.clone(), call),
+            convert(infix, call, None, Vec::with_capacity)
+        );
+    }
+
+    #[test]
+    fn unary_operators() {
+        // ! ! a && ( ! b || c )
+        let not = || InputToken::<_, (), _>::Operator(All::Logical(Logical::Not));
+        let infix = vec![
+            not(),
+            not(),
+            InputToken::Value('a'),
+            InputToken::Operator(All::Logical(Logical::And)),
+            InputToken::LeftParen,
+            not(),
+            InputToken::Value('b'),
+            InputToken::Operator(All::Logical(Logical::Or)),
+            InputToken::Value('c'),
+            InputToken::RightParen,
+        ];
+        let call = |function, arity| Call { function, arity };
+        assert_eq!(
+            convert_dynamic(infix.clone(), call),
+            convert(infix, call, None, Vec::with_capacity)
+        );
+
+        let handle = Handle::new(5, &All::Logical(Logical::Not));
+        assert_eq!(handle.arity(), 1);
+        assert!(Handle::new(0, &Sql::In).takes_list());
+        assert!(Handle::new(0, &Math::Add).is_commutative());
+    }
+}
@@ -0,0 +1,1533 @@
+//! Evaluation of postfix expressions.
+//! Implement the `Evaluator` trait for your value, function and operator types and pass the output
+//! of `to_postfix` to `eval_postfix`.
+//!
+//! ```rust
+//! use gyard::{eval::{eval_postfix, Evaluator}, op::Math, to_postfix, InputToken};
+//!
+//! struct Calculator;
+//! impl Evaluator<i64, &str, Math> for Calculator {
+//!     type Value = i64;
+//!     type Error = String;
+//!
+//!     fn value(&mut self, value: i64) -> Result<i64, String> {
+//!         Ok(value)
+//!     }
+//!
+//!     fn operator(&mut self, operator: Math, args: &mut Vec<i64>) -> Result<i64, String> {
+//!         let &[a, b] = args.as_slice() else {
+//!             unreachable!()
+//!         };
+//!         match operator {
+//!             Math::Add => Ok(a + b),
+//!             Math::Sub => Ok(a - b),
+//!             Math::Mul => Ok(a * b),
+//!             Math::Div => a.checked_div(b).ok_or_else(|| "division by zero".to_string()),
+//!             Math::Exponent => Ok(a.pow(b as u32)),
+//!         }
+//!     }
+//!
+//!     fn function(&mut self, function: &str, args: &mut Vec<i64>) -> Result<i64, String> {
+//!         match function {
+//!             "max" => Ok(args[0].max(args[1])),
+//!             _ => Err(format!("unknown function {function}")),
+//!         }
+//!     }
+//!
+//!     fn function_arity(&self, function: &&str) -> usize {
+//!         match *function {
+//!             "max" => 2,
+//!             _ => 0,
+//!         }
+//!     }
+//! }
+//!
+//! // 1 + max ( 2 , 3 ) * 4
+//! let infix = [
+//!     InputToken::Value(1),
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Function("max"),
+//!     InputToken::LeftParen,
+//!     InputToken::Value(2),
+//!     InputToken::ArgSeparator,
+//!     InputToken::Value(3),
+//!     InputToken::RightParen,
+//!     InputToken::Operator(Math::Mul),
+//!     InputToken::Value(4),
+//! ];
+//! let postfix = to_postfix(infix).unwrap();
+//! assert_eq!(eval_postfix(postfix, &mut Calculator), Ok(13));
+//! ```
+
+use alloc::vec::Vec;
+use core::{
+    iter::{Enumerate, Peekable},
+    ops::RangeInclusive,
+    task::Poll,
+};
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+use crate::{
+    growable, trace, Call, Converter, Error, ErrorCode, InputToken, Operator, OutputToken, Queue,
+    Stack,
+};
+
+#[cfg(any(feature = "bigint", feature = "decimal", feature = "fixed"))]
+pub mod exact;
+pub mod explain;
+#[cfg(feature = "std")]
+pub mod memo;
+#[cfg(feature = "num")]
+pub mod num;
+pub mod params;
+#[cfg(feature = "std")]
+pub mod registry;
+
+/// Evaluates the tokens of a postfix expression into values of type `Self::Value`.
+///
+/// The arguments passed to `operator` and `function` are in the same order as they appeared in the
+/// infix expression. `eval_postfix` guarantees that `args` contains exactly `Operator::arity` or
+/// `Evaluator::function_arity` values.
+pub trait Evaluator<V, F, O> {
+    /// The type expressions evaluate to
+    type Value;
+    /// The error returned if a token can not be evaluated
+    type Error;
+
+    /// Evaluates a value token
+    fn value(&mut self, value: V) -> Result<Self::Value, Self::Error>;
+    /// Applies an operator to its operands
+    fn operator(
+        &mut self,
+        operator: O,
+        args: &mut Vec<Self::Value>,
+    ) -> Result<Self::Value, Self::Error>;
+    /// Applies a function to its arguments.
+    /// When evaluating with `eval_postfix_with_arity` `args` contains exactly the arguments the
+    /// function was called with.
+    fn function(
+        &mut self,
+        function: F,
+        args: &mut Vec<Self::Value>,
+    ) -> Result<Self::Value, Self::Error>;
+    /// Returns the number of arguments a function takes
+    fn function_arity(&self, function: &F) -> usize;
+    /// Returns the numbers of arguments `function` can be called with, by default exactly
+    /// `function_arity`. This is only used by `eval_postfix_with_arity` and `memo::Memo`, which
+    /// report it in `EvalError::ArityMismatch`. Override it to support functions with a variable
+    /// number of arguments, or return an empty range to reject an unknown function with
+    /// `EvalError::UnknownFunction`.
+    fn accepted_arity(&self, function: &F) -> RangeInclusive<usize> {
+        let arity = self.function_arity(function);
+        arity..=arity
+    }
+    /// Returns true if `function` always returns the same result for the same arguments and has no
+    /// side effects. Only calls to pure functions are cached by `memo::Memo`.
+    fn is_pure(&self, function: &F) -> bool {
+        let _ = function;
+        false
+    }
+    /// Returns true if the operands of `operator` can be swapped without changing the result.
+    /// `memo::Memo` only shares results between swapped operands if this returns `true`. Override
+    /// it if the evaluator applies an operator that `Operator::is_commutative` declares
+    /// commutative in an order dependent way.
+    fn is_commutative(&self, operator: &O) -> bool
+    where
+        O: Operator,
+    {
+        operator.is_commutative()
+    }
+}
+
+/// Resolves value tokens before they are passed to an `Evaluator`.
+/// Use this to evaluate expressions containing variables against an environment.
+///
+/// This trait is implemented for all closures `Fn(V) -> Result<T, E>`.
+pub trait Context<V> {
+    /// The type values are resolved to
+    type Value;
+    /// The error returned if a value can not be resolved, i.e. an unknown variable
+    type Error;
+
+    /// Resolves a value token
+    fn resolve(&self, value: V) -> Result<Self::Value, Self::Error>;
+}
+
+impl<V, T, E, C> Context<V> for C
+where
+    C: Fn(V) -> Result<T, E>,
+{
+    type Value = T;
+    type Error = E;
+
+    fn resolve(&self, value: V) -> Result<T, E> {
+        self(value)
+    }
+}
+
+/// This error is returned if a postfix expression can not be evaluated.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EvalError<E> {
+    /// The operator or function at `pos` requires more values than there are on the stack.
+    StackUnderflow {
+        /// The position of the operator or function
+        pos: usize,
+        /// The number of values the operator or function requires
+        required: usize,
+        /// The number of values on the stack
+        available: usize,
+    },
+    /// The function at `pos` was called with the wrong number of arguments.
+    ArityMismatch {
+        /// The position of the function
+        pos: usize,
+        /// The numbers of arguments the function takes
+        expected: RangeInclusive<usize>,
+        /// The number of arguments the function was called with
+        found: usize,
+    },
+    /// The function at `pos` does not exist, i.e. `Evaluator::accepted_arity` is empty.
+    UnknownFunction {
+        /// The position of the function
+        pos: usize,
+    },
+    /// The expression does not contain any tokens.
+    EmptyExpression,
+    /// More than one value is left on the stack after all tokens have been evaluated.
+    UnusedValues {
+        /// The number of values left on the stack
+        count: usize,
+    },
+    /// The evaluation exceeded a limit of its `Budget` at `pos`.
+    BudgetExceeded {
+        /// The position of the token that exceeded the limit
+        pos: usize,
+        /// The exceeded limit
+        limit: Limit,
+    },
+    /// The evaluator returned an error for the token at `pos`.
+    Evaluator {
+        /// The position of the token
+        pos: usize,
+        /// The error returned by the evaluator
+        error: E,
+    },
+}
+
+impl<E> EvalError<E> {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            EvalError::StackUnderflow { .. } => ErrorCode::StackUnderflow,
+            EvalError::ArityMismatch { .. } => ErrorCode::ArityMismatch,
+            EvalError::UnknownFunction { .. } => ErrorCode::UnknownFunction,
+            EvalError::EmptyExpression => ErrorCode::EmptyExpression,
+            EvalError::UnusedValues { .. } => ErrorCode::UnusedValues,
+            EvalError::BudgetExceeded { .. } => ErrorCode::BudgetExceeded,
+            EvalError::Evaluator { .. } => ErrorCode::Evaluator,
+        }
+    }
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for EvalError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EvalError::StackUnderflow {
+                pos,
+                required,
+                available,
+            } => write!(
+                f,
+                "Token at position {pos} requires {required} values but only {available} are available"
+            ),
+            EvalError::ArityMismatch {
+                pos,
+                expected,
+                found,
+            } => {
+                write!(f, "Function at position {pos} takes ")?;
+                match (*expected.start(), *expected.end()) {
+                    (start, end) if start == end => write!(f, "{start}")?,
+                    (start, usize::MAX) => write!(f, "at least {start}")?,
+                    (start, end) => write!(f, "{start} to {end}")?,
+                }
+                write!(f, " arguments but {found} were given")
+            }
+            EvalError::UnknownFunction { pos } => write!(f, "Unknown function at position {pos}"),
+            EvalError::EmptyExpression => write!(f, "Can not evaluate an empty expression"),
+            EvalError::UnusedValues { count } => {
+                write!(f, "Expression evaluated to {count} values instead of one")
+            }
+            EvalError::BudgetExceeded { pos, limit } => {
+                write!(f, "Exceeded {limit} at position {pos}")
+            }
+            EvalError::Evaluator { pos, error } => {
+                write!(f, "Failed to evaluate token at position {pos}: {error}")
+            }
+        }
+    }
+}
+
+impl<E: core::error::Error + 'static> core::error::Error for EvalError<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            EvalError::Evaluator { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Evaluates a postfix expression.
+/// To evaluate a stored expression without consuming it use `OutputToken::as_ref`.
+///
+/// ```rust
+/// # use gyard::{eval::{eval_postfix, Evaluator}, op::Math, OutputToken};
+/// # struct Sum;
+/// # impl Evaluator<&i64, &(), &Math> for Sum {
+/// #     type Value = i64;
+/// #     type Error = ();
+/// #     fn value(&mut self, value: &i64) -> Result<i64, ()> { Ok(*value) }
+/// #     fn operator(&mut self, _: &Math, args: &mut Vec<i64>) -> Result<i64, ()> {
+/// #         Ok(args.iter().sum())
+/// #     }
+/// #     fn function(&mut self, _: &(), _: &mut Vec<i64>) -> Result<i64, ()> { Err(()) }
+/// #     fn function_arity(&self, _: &&()) -> usize { 0 }
+/// # }
+/// let postfix: Vec<OutputToken<i64, (), Math>> = vec![
+///     OutputToken::Value(1),
+///     OutputToken::Value(2),
+///     OutputToken::Operator(Math::Add),
+/// ];
+/// for _ in 0..2 {
+///     assert_eq!(eval_postfix(postfix.iter().map(OutputToken::as_ref), &mut Sum), Ok(3));
+/// }
+/// ```
+pub fn eval_postfix<V, F, O, E>(
+    postfix: impl IntoIterator<Item = OutputToken<V, F, O>>,
+    evaluator: &mut E,
+) -> Result<E::Value, EvalError<E::Error>>
+where
+    O: Operator,
+    E: Evaluator<V, F, O>,
+{
+    StackMachine::new().eval(postfix, evaluator)
+}
+
+/// Evaluates a postfix expression created by `to_postfix_with_arity`.
+/// Every function receives exactly the arguments it was called with. If the number of arguments is
+/// not accepted by `Evaluator::accepted_arity` `EvalError::ArityMismatch` is returned.
+///
+/// ```rust
+/// use std::ops::RangeInclusive;
+/// use gyard::{eval::{eval_postfix_with_arity, EvalError, Evaluator}, op::Math, to_postfix_with_arity, InputToken};
+///
+/// struct Calculator;
+/// impl Evaluator<i64, &str, Math> for Calculator {
+///     type Value = i64;
+///     type Error = String;
+///     fn value(&mut self, value: i64) -> Result<i64, String> {
+///         Ok(value)
+///     }
+///     fn operator(&mut self, _: Math, args: &mut Vec<i64>) -> Result<i64, String> {
+///         Ok(args[0] + args[1])
+///     }
+///     fn function(&mut self, function: &str, args: &mut Vec<i64>) -> Result<i64, String> {
+///         match function {
+///             "neg" => Ok(-args[0]),
+///             "max" => args.iter().copied().max().ok_or_else(|| "max of nothing".to_string()),
+///             _ => Err(format!("unknown function {function}")),
+///         }
+///     }
+///     fn function_arity(&self, function: &&str) -> usize {
+///         match *function {
+///             "neg" => 1,
+///             _ => 0,
+///         }
+///     }
+///     fn accepted_arity(&self, function: &&str) -> RangeInclusive<usize> {
+///         match *function {
+///             "max" => 1..=usize::MAX,
+///             "neg" => 1..=1,
+///             _ => 0..=0,
+///         }
+///     }
+/// }
+///
+/// // max ( 1 , 4 , 2 )
+/// let postfix = to_postfix_with_arity([
+///     InputToken::<_, _, Math>::Function("max"),
+///     InputToken::LeftParen,
+///     InputToken::Value(1),
+///     InputToken::ArgSeparator,
+///     InputToken::Value(4),
+///     InputToken::ArgSeparator,
+///     InputToken::Value(2),
+///     InputToken::RightParen,
+/// ]).unwrap();
+/// assert_eq!(eval_postfix_with_arity(postfix, &mut Calculator), Ok(4));
+///
+/// // neg ( 1 , 2 )
+/// let postfix = to_postfix_with_arity([
+///     InputToken::<_, _, Math>::Function("neg"),
+///     InputToken::LeftParen,
+///     InputToken::Value(1),
+///     InputToken::ArgSeparator,
+///     InputToken::Value(2),
+///     InputToken::RightParen,
+/// ]).unwrap();
+/// assert_eq!(
+///     eval_postfix_with_arity(postfix, &mut Calculator),
+///     Err(EvalError::ArityMismatch { pos: 2, expected: 1..=1, found: 2 })
+/// );
+/// ```
+pub fn eval_postfix_with_arity<V, F, O, E>(
+    postfix: impl IntoIterator<Item = OutputToken<V, Call<F>, O>>,
+    evaluator: &mut E,
+) -> Result<E::Value, EvalError<E::Error>>
+where
+    O: Operator,
+    E: Evaluator<V, F, O>,
+{
+    StackMachine::new().eval_with_arity(postfix, evaluator)
+}
+
+/// Converts and evaluates an infix expression in a single pass, like `to_postfix_with_arity`
+/// followed by `eval_postfix_with_arity`. Operators and functions are applied when the conversion
+/// moves them into the output, so the postfix expression is never stored and memory only grows
+/// with the nesting depth. The positions of `EvalError`s are positions in the postfix expression.
+/// Use `StackMachine::eval_infix` to reuse the value stack.
+///
+/// ```rust
+/// use gyard::{eval::{eval_infix, Evaluator}, op::Math, Error, InputToken};
+///
+/// struct Calculator;
+/// impl Evaluator<i64, (), Math> for Calculator {
+///     type Value = i64;
+///     type Error = ();
+///     fn value(&mut self, value: i64) -> Result<i64, ()> {
+///         Ok(value)
+///     }
+///     fn operator(&mut self, operator: Math, args: &mut Vec<i64>) -> Result<i64, ()> {
+///         match operator {
+///             Math::Add => Ok(args[0] + args[1]),
+///             Math::Mul => Ok(args[0] * args[1]),
+///             _ => Err(()),
+///         }
+///     }
+///     fn function(&mut self, _: (), _: &mut Vec<i64>) -> Result<i64, ()> {
+///         Err(())
+///     }
+///     fn function_arity(&self, _: &()) -> usize {
+///         0
+///     }
+/// }
+///
+/// // ( 1 + 2 ) * 3
+/// let infix = [
+///     InputToken::LeftParen,
+///     InputToken::Value(1),
+///     InputToken::Operator(Math::Add),
+///     InputToken::Value(2),
+///     InputToken::RightParen,
+///     InputToken::Operator(Math::Mul),
+///     InputToken::Value(3),
+/// ];
+/// assert_eq!(eval_infix(infix.clone(), &mut Calculator), Ok(9));
+/// assert!(matches!(
+///     eval_infix(infix[1..].to_vec(), &mut Calculator),
+///     Err(Error::ParenMissmatch(_))
+/// ));
+/// ```
+pub fn eval_infix<V, F, O, E>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+    evaluator: &mut E,
+) -> Result<E::Value, Error<E::Error>>
+where
+    O: Operator,
+    E: Evaluator<V, F, O>,
+{
+    StackMachine::new().eval_infix(infix, evaluator)
+}
+
+/// Resource limits of a `StackMachine`.
+/// Use this to stop the evaluation of hostile expressions. `Budget::default` sets no limit.
+///
+/// ```rust
+/// # use std::time::{Duration, Instant};
+/// # use gyard::eval::{Budget, StackMachine};
+/// let budget = Budget::default()
+///     .with_max_instructions(10_000)
+///     .with_max_stack_depth(64)
+///     .with_deadline(Instant::now() + Duration::from_millis(5));
+/// let machine = StackMachine::<f64>::with_budget(budget);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Budget {
+    max_instructions: Option<usize>,
+    max_stack_depth: Option<usize>,
+    #[cfg(feature = "std")]
+    deadline: Option<Instant>,
+}
+
+impl Budget {
+    /// Limits the number of tokens evaluated per expression.
+    pub fn with_max_instructions(mut self, max_instructions: usize) -> Self {
+        self.max_instructions = Some(max_instructions);
+        self
+    }
+
+    /// Limits the number of values on the stack.
+    pub fn with_max_stack_depth(mut self, max_stack_depth: usize) -> Self {
+        self.max_stack_depth = Some(max_stack_depth);
+        self
+    }
+
+    /// Aborts the evaluation once `deadline` has passed.
+    #[cfg(feature = "std")]
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// The maximum number of tokens evaluated per expression
+    pub fn max_instructions(&self) -> Option<usize> {
+        self.max_instructions
+    }
+
+    /// The maximum number of values on the stack
+    pub fn max_stack_depth(&self) -> Option<usize> {
+        self.max_stack_depth
+    }
+
+    /// The point in time at which evaluation is aborted
+    #[cfg(feature = "std")]
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns true if the deadline is set and has passed. Without the `std` feature there is no
+    /// clock and the deadline never passes.
+    fn deadline_passed(&self) -> bool {
+        #[cfg(feature = "std")]
+        return self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline);
+        #[cfg(not(feature = "std"))]
+        false
+    }
+}
+
+/// A limit of a `Budget`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Limit {
+    /// `Budget::max_instructions`
+    Instructions,
+    /// `Budget::max_stack_depth`
+    StackDepth,
+    /// `Budget::deadline`
+    Deadline,
+}
+
+impl core::fmt::Display for Limit {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Limit::Instructions => write!(f, "instruction limit"),
+            Limit::StackDepth => write!(f, "stack depth limit"),
+            Limit::Deadline => write!(f, "deadline"),
+        }
+    }
+}
+
+/// The deadline is only checked every `DEADLINE_INTERVAL` instructions.
+const DEADLINE_INTERVAL: usize = 64;
+
+/// Holds the value stack used during evaluation.
+/// Reuse one machine to evaluate many expressions without allocating a new stack every time.
+///
+/// ```rust
+/// # use gyard::{eval::{StackMachine, Evaluator}, op::Math, OutputToken};
+/// # struct Sum;
+/// # impl Evaluator<i64, (), Math> for Sum {
+/// #     type Value = i64;
+/// #     type Error = ();
+/// #     fn value(&mut self, value: i64) -> Result<i64, ()> { Ok(value) }
+/// #     fn operator(&mut self, _: Math, args: &mut Vec<i64>) -> Result<i64, ()> {
+/// #         Ok(args.iter().sum())
+/// #     }
+/// #     fn function(&mut self, _: (), _: &mut Vec<i64>) -> Result<i64, ()> { Err(()) }
+/// #     fn function_arity(&self, _: &()) -> usize { 0 }
+/// # }
+/// let mut machine = StackMachine::new();
+/// for i in 0..3 {
+///     // 1 2 i + +
+///     let postfix = [
+///         OutputToken::Value(1),
+///         OutputToken::Value(2),
+///         OutputToken::Value(i),
+///         OutputToken::Operator(Math::Add),
+///         OutputToken::Operator(Math::Add),
+///     ];
+///     assert_eq!(machine.eval(postfix, &mut Sum), Ok(3 + i));
+/// }
+/// assert_eq!(machine.peak_depth(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StackMachine<T> {
+    pub(crate) stack: Vec<T>,
+    pub(crate) args: Vec<T>,
+    pub(crate) peak_depth: usize,
+    pub(crate) executed: usize,
+    budget: Budget,
+}
+
+impl<T> Default for StackMachine<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> StackMachine<T> {
+    /// Creates a new machine with an empty stack.
+    pub fn new() -> Self {
+        Self {
+            stack: Vec::new(),
+            args: Vec::new(),
+            peak_depth: 0,
+            executed: 0,
+            budget: Budget::default(),
+        }
+    }
+
+    /// Creates a new machine with space for `capacity` values on the stack.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            stack: Vec::with_capacity(capacity),
+            args: Vec::new(),
+            peak_depth: 0,
+            executed: 0,
+            budget: Budget::default(),
+        }
+    }
+
+    /// Creates a new machine that stops evaluation once a limit of `budget` is exceeded.
+    pub fn with_budget(budget: Budget) -> Self {
+        Self {
+            budget,
+            ..Self::new()
+        }
+    }
+
+    /// Returns the resource limits of the machine.
+    pub fn budget(&self) -> &Budget {
+        &self.budget
+    }
+
+    /// Sets the resource limits of the machine.
+    pub fn set_budget(&mut self, budget: Budget) {
+        self.budget = budget;
+    }
+
+    /// Returns the largest number of values that have been on the stack at the same time since the
+    /// machine was created or `reset_peak_depth` was called.
+    pub fn peak_depth(&self) -> usize {
+        self.peak_depth
+    }
+
+    /// Resets the peak stack depth to zero.
+    pub fn reset_peak_depth(&mut self) {
+        self.peak_depth = 0;
+    }
+
+    /// Evaluates a postfix expression. See `eval_postfix`.
+    pub fn eval<V, F, O, E>(
+        &mut self,
+        postfix: impl IntoIterator<Item = OutputToken<V, F, O>>,
+        evaluator: &mut E,
+    ) -> Result<T, EvalError<E::Error>>
+    where
+        O: Operator,
+        E: Evaluator<V, F, O, Value = T>,
+    {
+        self.run(postfix, evaluator, resolve_arity)
+    }
+
+    /// Evaluates a postfix expression created by `to_postfix_with_arity`. See
+    /// `eval_postfix_with_arity`.
+    pub fn eval_with_arity<V, F, O, E>(
+        &mut self,
+        postfix: impl IntoIterator<Item = OutputToken<V, Call<F>, O>>,
+        evaluator: &mut E,
+    ) -> Result<T, EvalError<E::Error>>
+    where
+        O: Operator,
+        E: Evaluator<V, F, O, Value = T>,
+    {
+        self.run(postfix, evaluator, check_arity)
+    }
+
+    /// Evaluates a postfix expression resolving values with `context`. See
+    /// `eval_postfix_with_context`.
+    pub fn eval_with_context<V, F, O, C, E>(
+        &mut self,
+        postfix: impl IntoIterator<Item = OutputToken<V, F, O>>,
+        evaluator: &mut E,
+        context: &C,
+    ) -> Result<T, EvalError<E::Error>>
+    where
+        O: Operator,
+        C: Context<V>,
+        E: Evaluator<C::Value, F, O, Value = T>,
+        E::Error: From<C::Error>,
+    {
+        self.eval(postfix, &mut WithContext { evaluator, context })
+    }
+
+    /// Converts and evaluates an infix expression in a single pass. See `eval_infix`.
+    pub fn eval_infix<V, F, O, E>(
+        &mut self,
+        infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+        evaluator: &mut E,
+    ) -> Result<T, Error<E::Error>>
+    where
+        O: Operator,
+        E: Evaluator<V, F, O, Value = T>,
+    {
+        let span = trace::Span::evaluation();
+        self.start();
+        let mut converter: Converter<Stack<F, O>> = Converter::new(Stack::default());
+        let mut queue = Evaluating {
+            machine: self,
+            evaluator,
+            pos: 0,
+            error: None,
+        };
+        let call = |function, arity| Call { function, arity };
+        let result = infix
+            .into_iter()
+            .enumerate()
+            .try_for_each(|(pos, token)| converter.push(pos, token, &mut queue, &call))
+            .and_then(|()| converter.finish(&mut queue, &call));
+        let result = match queue.error {
+            Some(error) => Err(Error::Eval(error)),
+            None => growable(result)
+                .map_err(Error::from)
+                .and_then(|()| self.finish().map_err(Error::Eval)),
+        };
+        span.finish(self.executed, Some(self.peak_depth), result.is_ok());
+        result
+    }
+
+    fn run<V, G, F, O, E>(
+        &mut self,
+        postfix: impl IntoIterator<Item = OutputToken<V, G, O>>,
+        evaluator: &mut E,
+        resolve_call: impl Fn(&E, G, usize) -> Result<(F, usize), EvalError<E::Error>>,
+    ) -> Result<T, EvalError<E::Error>>
+    where
+        O: Operator,
+        E: Evaluator<V, F, O, Value = T>,
+    {
+        let span = trace::Span::evaluation();
+        self.start();
+        let result = postfix
+            .into_iter()
+            .enumerate()
+            .try_for_each(|(pos, token)| self.exec(pos, token, evaluator, &resolve_call))
+            .and_then(|()| self.finish());
+        span.finish(self.executed, Some(self.peak_depth), result.is_ok());
+        result
+    }
+
+    /// Evaluates a single token and pushes its result onto the stack.
+    fn exec<V, G, F, O, E>(
+        &mut self,
+        pos: usize,
+        token: OutputToken<V, G, O>,
+        evaluator: &mut E,
+        resolve_call: impl Fn(&E, G, usize) -> Result<(F, usize), EvalError<E::Error>>,
+    ) -> Result<(), EvalError<E::Error>>
+    where
+        O: Operator,
+        E: Evaluator<V, F, O, Value = T>,
+    {
+        self.step(pos)?;
+        let value = match token {
+            OutputToken::Value(value) => evaluator.value(value),
+            OutputToken::Operator(op) => {
+                self.pop_args(op.arity(), pos)?;
+                evaluator.operator(op, &mut self.args)
+            }
+            OutputToken::Function(func) => {
+                let (func, arity) = resolve_call(evaluator, func, pos)?;
+                self.pop_args(arity, pos)?;
+                evaluator.function(func, &mut self.args)
+            }
+        }
+        .map_err(|error| EvalError::Evaluator { pos, error })?;
+        self.push(value, pos)
+    }
+
+    /// Pops the result after all tokens have been evaluated.
+    fn finish<E>(&mut self) -> Result<T, EvalError<E>> {
+        let Some(result) = self.stack.pop() else {
+            return Err(EvalError::EmptyExpression);
+        };
+        if !self.stack.is_empty() {
+            return Err(EvalError::UnusedValues {
+                count: self.stack.len() + 1,
+            });
+        }
+        Ok(result)
+    }
+
+    /// Prepares the machine for the evaluation of a new expression.
+    pub(crate) fn start(&mut self) {
+        self.stack.clear();
+        self.executed = 0;
+    }
+
+    /// Checks the instruction limit and deadline before the token at `pos` is evaluated.
+    pub(crate) fn step<E>(&mut self, pos: usize) -> Result<(), EvalError<E>> {
+        let executed = self.executed;
+        self.executed += 1;
+        let exceeded = if self
+            .budget
+            .max_instructions
+            .is_some_and(|max| executed >= max)
+        {
+            Some(Limit::Instructions)
+        } else if executed.is_multiple_of(DEADLINE_INTERVAL) && self.budget.deadline_passed() {
+            Some(Limit::Deadline)
+        } else {
+            None
+        };
+        match exceeded {
+            Some(limit) => Err(EvalError::BudgetExceeded { pos, limit }),
+            None => Ok(()),
+        }
+    }
+
+    /// Pushes the result of the token at `pos` onto the stack.
+    pub(crate) fn push<E>(&mut self, value: T, pos: usize) -> Result<(), EvalError<E>> {
+        self.args.clear();
+        self.stack.push(value);
+        self.peak_depth = self.peak_depth.max(self.stack.len());
+        if self
+            .budget
+            .max_stack_depth
+            .is_some_and(|max| self.stack.len() > max)
+        {
+            return Err(EvalError::BudgetExceeded {
+                pos,
+                limit: Limit::StackDepth,
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn pop_args<E>(&mut self, arity: usize, pos: usize) -> Result<(), EvalError<E>> {
+        self.args.clear();
+        let Some(start) = self.stack.len().checked_sub(arity) else {
+            return Err(EvalError::StackUnderflow {
+                pos,
+                required: arity,
+                available: self.stack.len(),
+            });
+        };
+        self.args.extend(self.stack.drain(start..));
+        Ok(())
+    }
+}
+
+/// Receives the output of `StackMachine::eval_infix` and evaluates every token right away.
+struct Evaluating<'a, T, E, Err> {
+    machine: &'a mut StackMachine<T>,
+    evaluator: &'a mut E,
+    /// The position of the next token in the postfix expression
+    pos: usize,
+    /// The first error. Stops the conversion by reporting a full queue.
+    error: Option<EvalError<Err>>,
+}
+
+impl<V, F, O, E> Queue<OutputToken<V, Call<F>, O>> for Evaluating<'_, E::Value, E, E::Error>
+where
+    O: Operator,
+    E: Evaluator<V, F, O>,
+{
+    fn push(&mut self, item: OutputToken<V, Call<F>, O>) -> bool {
+        let pos = self.pos;
+        self.pos += 1;
+        match self.machine.exec(pos, item, self.evaluator, check_arity) {
+            Ok(()) => true,
+            Err(error) => {
+                self.error = Some(error);
+                false
+            }
+        }
+    }
+}
+
+fn check_arity<V, F, O, E>(
+    evaluator: &E,
+    call: Call<F>,
+    pos: usize,
+) -> Result<(F, usize), EvalError<E::Error>>
+where
+    E: Evaluator<V, F, O>,
+{
+    match arity_error(evaluator.accepted_arity(&call.function), call.arity, pos) {
+        Some(error) => Err(error),
+        None => Ok((call.function, call.arity)),
+    }
+}
+
+/// Returns the error for a call of the function at `pos` with `found` arguments if `expected` does
+/// not contain `found`.
+pub(crate) fn arity_error<E>(
+    expected: RangeInclusive<usize>,
+    found: usize,
+    pos: usize,
+) -> Option<EvalError<E>> {
+    if expected.is_empty() {
+        Some(EvalError::UnknownFunction { pos })
+    } else if !expected.contains(&found) {
+        Some(EvalError::ArityMismatch {
+            pos,
+            expected,
+            found,
+        })
+    } else {
+        None
+    }
+}
+
+fn resolve_arity<V, F, O, E>(
+    evaluator: &E,
+    func: F,
+    _: usize,
+) -> Result<(F, usize), EvalError<E::Error>>
+where
+    E: Evaluator<V, F, O>,
+{
+    let arity = evaluator.function_arity(&func);
+    Ok((func, arity))
+}
+
+/// A single step of `Steps`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step<V, F, O, T> {
+    /// The position of the evaluated token
+    pub pos: usize,
+    /// The evaluated token
+    pub token: OutputToken<V, F, O>,
+    /// The value stack after the token has been evaluated
+    pub stack: Vec<T>,
+}
+
+/// Evaluates a postfix expression one token at a time.
+/// Every step yields the evaluated token together with a snapshot of the value stack.
+/// The iterator ends after the last token or the first error. `Steps::finish` returns the result or
+/// the error.
+///
+/// ```rust
+/// # use gyard::{eval::{Evaluator, Step, Steps}, op::Math, OutputToken};
+/// # struct Sum;
+/// # impl Evaluator<i64, (), Math> for Sum {
+/// #     type Value = i64;
+/// #     type Error = ();
+/// #     fn value(&mut self, value: i64) -> Result<i64, ()> { Ok(value) }
+/// #     fn operator(&mut self, _: Math, args: &mut Vec<i64>) -> Result<i64, ()> {
+/// #         Ok(args.iter().sum())
+/// #     }
+/// #     fn function(&mut self, _: (), _: &mut Vec<i64>) -> Result<i64, ()> { Err(()) }
+/// #     fn function_arity(&self, _: &()) -> usize { 0 }
+/// # }
+/// // 1 2 +
+/// let postfix = [
+///     OutputToken::Value(1),
+///     OutputToken::Value(2),
+///     OutputToken::Operator(Math::Add),
+/// ];
+/// let mut sum = Sum;
+/// let mut steps = Steps::new(postfix, &mut sum);
+/// let stacks: Vec<_> = steps.by_ref().map(|step| step.stack).collect();
+/// assert_eq!(stacks, [vec![1], vec![1, 2], vec![3]]);
+/// assert_eq!(steps.finish(), Ok(3));
+/// ```
+pub struct Steps<'a, I: Iterator, E, T, Err> {
+    tokens: Peekable<Enumerate<I>>,
+    evaluator: &'a mut E,
+    machine: StackMachine<T>,
+    error: Option<EvalError<Err>>,
+}
+
+impl<'a, I: Iterator, E, T, Err> Steps<'a, I, E, T, Err> {
+    /// Creates a new step-wise evaluation of `postfix`.
+    pub fn new<V, F, O>(postfix: impl IntoIterator<IntoIter = I>, evaluator: &'a mut E) -> Self
+    where
+        I: Iterator<Item = OutputToken<V, F, O>>,
+        E: Evaluator<V, F, O, Value = T, Error = Err>,
+    {
+        Self::with_machine(postfix, evaluator, StackMachine::new())
+    }
+
+    /// Creates a new step-wise evaluation of `postfix` using `machine`, e.g. to apply a `Budget`.
+    pub fn with_machine<V, F, O>(
+        postfix: impl IntoIterator<IntoIter = I>,
+        evaluator: &'a mut E,
+        mut machine: StackMachine<T>,
+    ) -> Self
+    where
+        I: Iterator<Item = OutputToken<V, F, O>>,
+        E: Evaluator<V, F, O, Value = T, Error = Err>,
+    {
+        machine.start();
+        Self {
+            tokens: postfix.into_iter().enumerate().peekable(),
+            evaluator,
+            machine,
+            error: None,
+        }
+    }
+
+    /// Returns the current value stack.
+    pub fn stack(&self) -> &[T] {
+        &self.machine.stack
+    }
+
+    /// Evaluates the remaining tokens and returns the result of the expression or the error that
+    /// ended the iteration.
+    pub fn finish<V, F, O>(mut self) -> Result<T, EvalError<Err>>
+    where
+        I: Iterator<Item = OutputToken<V, F, O>>,
+        O: Operator,
+        E: Evaluator<V, F, O, Value = T, Error = Err>,
+    {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        for (pos, token) in self.tokens.by_ref() {
+            self.machine
+                .exec(pos, token, self.evaluator, resolve_arity)?;
+        }
+        self.machine.finish()
+    }
+
+    /// Evaluates up to `fuel` tokens without taking snapshots of the stack.
+    /// Returns `Poll::Pending` if there are tokens left and the result or error otherwise.
+    /// After the result has been returned the evaluation must not be resumed again.
+    ///
+    /// ```rust
+    /// # use std::task::Poll;
+    /// # use gyard::{eval::{Evaluator, Steps}, op::Math, OutputToken};
+    /// # struct Sum;
+    /// # impl Evaluator<i64, (), Math> for Sum {
+    /// #     type Value = i64;
+    /// #     type Error = ();
+    /// #     fn value(&mut self, value: i64) -> Result<i64, ()> { Ok(value) }
+    /// #     fn operator(&mut self, _: Math, args: &mut Vec<i64>) -> Result<i64, ()> {
+    /// #         Ok(args.iter().sum())
+    /// #     }
+    /// #     fn function(&mut self, _: (), _: &mut Vec<i64>) -> Result<i64, ()> { Err(()) }
+    /// #     fn function_arity(&self, _: &()) -> usize { 0 }
+    /// # }
+    /// // 1 2 +
+    /// let postfix = [
+    ///     OutputToken::Value(1),
+    ///     OutputToken::Value(2),
+    ///     OutputToken::Operator(Math::Add),
+    /// ];
+    /// let mut sum = Sum;
+    /// let mut steps = Steps::new(postfix, &mut sum);
+    /// assert_eq!(steps.resume(2), Poll::Pending);
+    /// assert_eq!(steps.resume(2), Poll::Ready(Ok(3)));
+    /// ```
+    pub fn resume<V, F, O>(&mut self, fuel: usize) -> Poll<Result<T, EvalError<Err>>>
+    where
+        I: Iterator<Item = OutputToken<V, F, O>>,
+        O: Operator,
+        E: Evaluator<V, F, O, Value = T, Error = Err>,
+    {
+        if let Some(error) = self.error.take() {
+            return Poll::Ready(Err(error));
+        }
+        for _ in 0..fuel {
+            let Some((pos, token)) = self.tokens.next() else {
+                break;
+            };
+            if let Err(error) = self.machine.exec(pos, token, self.evaluator, resolve_arity) {
+                return Poll::Ready(Err(error));
+            }
+        }
+        if self.tokens.peek().is_some() {
+            return Poll::Pending;
+        }
+        Poll::Ready(self.machine.finish())
+    }
+}
+
+impl<I, V, F, O, E, T, Err> Iterator for Steps<'_, I, E, T, Err>
+where
+    I: Iterator<Item = OutputToken<V, F, O>>,
+    V: Clone,
+    F: Clone,
+    O: Operator + Clone,
+    E: Evaluator<V, F, O, Value = T, Error = Err>,
+    T: Clone,
+{
+    type Item = Step<V, F, O, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.error.is_some() {
+            return None;
+        }
+        let (pos, token) = self.tokens.next()?;
+        let result = self
+            .machine
+            .exec(pos, token.clone(), self.evaluator, resolve_arity);
+        if let Err(error) = result {
+            self.error = Some(error);
+            return None;
+        }
+        Some(Step {
+            pos,
+            token,
+            stack: self.machine.stack.clone(),
+        })
+    }
+}
+
+/// Evaluates a postfix expression, resolving every value token with `context` before passing it to
+/// the evaluator.
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use gyard::{eval::{eval_postfix_with_context, Evaluator}, op::Math, to_postfix, InputToken};
+///
+/// enum Token {
+///     Number(f64),
+///     Variable(&'static str),
+/// }
+///
+/// struct Calculator;
+/// impl Evaluator<f64, (), Math> for Calculator {
+///     type Value = f64;
+///     type Error = String;
+///     fn value(&mut self, value: f64) -> Result<f64, String> {
+///         Ok(value)
+///     }
+///     fn operator(&mut self, operator: Math, args: &mut Vec<f64>) -> Result<f64, String> {
+///         match operator {
+///             Math::Add => Ok(args[0] + args[1]),
+///             Math::Mul => Ok(args[0] * args[1]),
+///             _ => Err("unsupported operator".to_string()),
+///         }
+///     }
+///     fn function(&mut self, _: (), _: &mut Vec<f64>) -> Result<f64, String> {
+///         Err("functions are not supported".to_string())
+///     }
+///     fn function_arity(&self, _: &()) -> usize {
+///         0
+///     }
+/// }
+///
+/// // x + 2 * y
+/// let infix = [
+///     InputToken::Value(Token::Variable("x")),
+///     InputToken::Operator(Math::Add),
+///     InputToken::Value(Token::Number(2.)),
+///     InputToken::Operator(Math::Mul),
+///     InputToken::Value(Token::Variable("y")),
+/// ];
+/// let postfix = gyard::to_postfix(infix).unwrap();
+///
+/// let env = HashMap::from([("x", 1.), ("y", 3.)]);
+/// let context = |token: Token| match token {
+///     Token::Number(n) => Ok(n),
+///     Token::Variable(name) => env.get(name).copied().ok_or(format!("unknown variable {name}")),
+/// };
+/// let result = eval_postfix_with_context(postfix, &mut Calculator, &context);
+/// assert_eq!(result, Ok(7.));
+/// ```
+pub fn eval_postfix_with_context<V, F, O, C, E>(
+    postfix: impl IntoIterator<Item = OutputToken<V, F, O>>,
+    evaluator: &mut E,
+    context: &C,
+) -> Result<E::Value, EvalError<E::Error>>
+where
+    O: Operator,
+    C: Context<V>,
+    E: Evaluator<C::Value, F, O>,
+    E::Error: From<C::Error>,
+{
+    StackMachine::new().eval_with_context(postfix, evaluator, context)
+}
+
+struct WithContext<'a, E, C> {
+    evaluator: &'a mut E,
+    context: &'a C,
+}
+
+impl<V, F, O, C, E> Evaluator<V, F, O> for WithContext<'_, E, C>
+where
+    C: Context<V>,
+    E: Evaluator<C::Value, F, O>,
+    E::Error: From<C::Error>,
+{
+    type Value = E::Value;
+    type Error = E::Error;
+
+    fn value(&mut self, value: V) -> Result<Self::Value, Self::Error> {
+        let value = self.context.resolve(value)?;
+        self.evaluator.value(value)
+    }
+
+    fn operator(
+        &mut self,
+        operator: O,
+        args: &mut Vec<Self::Value>,
+    ) -> Result<Self::Value, Self::Error> {
+        self.evaluator.operator(operator, args)
+    }
+
+    fn function(
+        &mut self,
+        function: F,
+        args: &mut Vec<Self::Value>,
+    ) -> Result<Self::Value, Self::Error> {
+        self.evaluator.function(function, args)
+    }
+
+    fn function_arity(&self, function: &F) -> usize {
+        self.evaluator.function_arity(function)
+    }
+
+    fn accepted_arity(&self, function: &F) -> RangeInclusive<usize> {
+        self.evaluator.accepted_arity(function)
+    }
+
+    fn is_pure(&self, function: &F) -> bool {
+        self.evaluator.is_pure(function)
+    }
+
+    fn is_commutative(&self, operator: &O) -> bool
+    where
+        O: Operator,
+    {
+        self.evaluator.is_commutative(operator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::{
+        eval_postfix, eval_postfix_with_arity, eval_postfix_with_context, Budget, EvalError,
+        Evaluator, Limit, StackMachine, Steps,
+    };
+    use crate::{
+        op::{All, Logical, Math},
+        to_postfix_with_arity, Error, InputToken, OutputToken,
+    };
+
+    struct Calculator;
+
+    impl Evaluator<i64, &str, All> for Calculator {
+        type Value = i64;
+        type Error = &'static str;
+
+        fn value(&mut self, value: i64) -> Result<i64, Self::Error> {
+            Ok(value)
+        }
+
+        fn operator(&mut self, operator: All, args: &mut Vec<i64>) -> Result<i64, Self::Error> {
+            match (operator, args.as_slice()) {
+                (All::Math(Math::Add), &[a, b]) => Ok(a + b),
+                (All::Math(Math::Sub), &[a, b]) => Ok(a - b),
+                (All::Math(Math::Div), &[_, 0]) => Err("division by zero"),
+                (All::Math(Math::Div), &[a, b]) => Ok(a / b),
+                (All::Logical(Logical::Not), &[a]) => Ok((a == 0) as i64),
+                _ => Err("unsupported operator"),
+            }
+        }
+
+        fn function(&mut self, function: &str, args: &mut Vec<i64>) -> Result<i64, Self::Error> {
+            match function {
+                "sum" => Ok(args.iter().sum()),
+                _ => Err("unknown function"),
+            }
+        }
+
+        fn function_arity(&self, function: &&str) -> usize {
+            match *function {
+                "sum" => 3,
+                _ => 0,
+            }
+        }
+    }
+
+    #[test]
+    fn infix_in_a_single_pass() {
+        // sum ( 1 , ! 0 , 3 ) - 4 / 2
+        let infix = [
+            InputToken::Function("sum"),
+            InputToken::LeftParen,
+            InputToken::Value(1),
+            InputToken::ArgSeparator,
+            InputToken::Operator(Logical::Not.into()),
+            InputToken::Value(0),
+            InputToken::ArgSeparator,
+            InputToken::Value(3),
+            InputToken::RightParen,
+            InputToken::Operator(Math::Sub.into()),
+            InputToken::Value(4),
+            InputToken::Operator(Math::Div.into()),
+            InputToken::Value(2),
+        ];
+        let postfix = to_postfix_with_arity(infix.clone()).expect("balanced");
+        let mut machine = StackMachine::new();
+        assert_eq!(
+            machine.eval_infix(infix.clone(), &mut Calculator),
+            eval_postfix_with_arity(postfix, &mut Calculator).map_err(Error::from)
+        );
+        assert_eq!(machine.eval_infix(infix, &mut Calculator), Ok(3));
+        assert_eq!(machine.peak_depth(), 3);
+        // 1 / 0 + (
+        let result = machine.eval_infix(
+            [
+                InputToken::Value(1),
+                InputToken::Operator(Math::Div.into()),
+                InputToken::Value(0),
+                InputToken::Operator(Math::Add.into()),
+                InputToken::LeftParen,
+            ],
+            &mut Calculator,
+        );
+        assert_eq!(
+            result,
+            Err(Error::Eval(EvalError::Evaluator {
+                pos: 2,
+                error: "division by zero"
+            }))
+        );
+    }
+
+    #[test]
+    fn operand_order() {
+        // 5 - 3
+        let result = eval_postfix(
+            [
+                OutputToken::Value(5),
+                OutputToken::Value(3),
+                OutputToken::Operator(Math::Sub.into()),
+            ],
+            &mut Calculator,
+        );
+        assert_eq!(result, Ok(2));
+    }
+
+    #[test]
+    fn unary_operator_and_function() {
+        // sum ( 1 , ! 0 , 3 )
+        let result = eval_postfix(
+            [
+                OutputToken::Value(1),
+                OutputToken::Value(0),
+                OutputToken::Operator(Logical::Not.into()),
+                OutputToken::Value(3),
+                OutputToken::Function("sum"),
+            ],
+            &mut Calculator,
+        );
+        assert_eq!(result, Ok(5));
+    }
+
+    #[test]
+    fn stack_underflow() {
+        let result = eval_postfix(
+            [
+                OutputToken::Value(1),
+                OutputToken::Operator(Math::Add.into()),
+            ],
+            &mut Calculator,
+        );
+        assert_eq!(
+            result,
+            Err(EvalError::StackUnderflow {
+                pos: 1,
+                required: 2,
+                available: 1
+            })
+        );
+    }
+
+    #[test]
+    fn steps_stop_at_error() {
+        // 1 + 2
+        let postfix = [
+            OutputToken::Value(1),
+            OutputToken::Operator(Math::Add.into()),
+            OutputToken::Value(2),
+        ];
+        let mut calculator = Calculator;
+        let mut steps = Steps::new(postfix, &mut calculator);
+        assert_eq!(steps.next().map(|step| step.stack), Some(vec![1]));
+        assert_eq!(steps.next(), None);
+        assert_eq!(
+            steps.finish(),
+            Err(EvalError::StackUnderflow {
+                pos: 1,
+                required: 2,
+                available: 1
+            })
+        );
+    }
+
+    #[test]
+    fn empty_expression() {
+        let result = eval_postfix([], &mut Calculator);
+        assert_eq!(result, Err(EvalError::EmptyExpression));
+    }
+
+    #[test]
+    fn unused_values() {
+        let result = eval_postfix(
+            [OutputToken::Value(1), OutputToken::Value(2)],
+            &mut Calculator,
+        );
+        assert_eq!(result, Err(EvalError::UnusedValues { count: 2 }));
+    }
+
+    #[test]
+    fn evaluator_error() {
+        let result = eval_postfix(
+            [
+                OutputToken::Value(1),
+                OutputToken::Value(0),
+                OutputToken::Operator(Math::Div.into()),
+            ],
+            &mut Calculator,
+        );
+        assert_eq!(
+            result,
+            Err(EvalError::Evaluator {
+                pos: 2,
+                error: "division by zero"
+            })
+        );
+    }
+
+    #[test]
+    fn resolve_variables() {
+        // a - b
+        let postfix = [
+            OutputToken::Value("a"),
+            OutputToken::Value("b"),
+            OutputToken::Operator(Math::Sub.into()),
+        ];
+        let context = |name: &str| match name {
+            "a" => Ok(5),
+            "b" => Ok(3),
+            _ => Err("unknown variable"),
+        };
+        let result = eval_postfix_with_context(postfix.clone(), &mut Calculator, &context);
+        assert_eq!(result, Ok(2));
+
+        let context = |name: &str| match name {
+            "a" => Ok(5),
+            _ => Err("unknown variable"),
+        };
+        let result = eval_postfix_with_context(postfix, &mut Calculator, &context);
+        assert_eq!(
+            result,
+            Err(EvalError::Evaluator {
+                pos: 1,
+                error: "unknown variable"
+            })
+        );
+    }
+
+    #[test]
+    fn stack_machine_reuse() {
+        let mut machine = StackMachine::new();
+        let result = machine.eval(
+            [
+                OutputToken::Value(1),
+                OutputToken::Value(2),
+                OutputToken::Value(3),
+            ],
+            &mut Calculator,
+        );
+        assert_eq!(result, Err(EvalError::UnusedValues { count: 3 }));
+        assert_eq!(machine.peak_depth(), 3);
+
+        machine.reset_peak_depth();
+        let result = machine.eval(
+            [
+                OutputToken::Value(1),
+                OutputToken::Value(2),
+                OutputToken::Operator(Math::Add.into()),
+            ],
+            &mut Calculator,
+        );
+        assert_eq!(result, Ok(3));
+        assert_eq!(machine.peak_depth(), 2);
+    }
+
+    #[test]
+    fn budget_exceeded() {
+        // 1 2 3 + +
+        let postfix = [
+            OutputToken::Value(1),
+            OutputToken::Value(2),
+            OutputToken::Value(3),
+            OutputToken::Operator(Math::Add.into()),
+            OutputToken::Operator(Math::Add.into()),
+        ];
+        let mut machine = StackMachine::with_budget(Budget::default().with_max_instructions(4));
+        assert_eq!(
+            machine.eval(postfix.clone(), &mut Calculator),
+            Err(EvalError::BudgetExceeded {
+                pos: 4,
+                limit: Limit::Instructions
+            })
+        );
+
+        machine.set_budget(Budget::default().with_max_stack_depth(2));
+        assert_eq!(
+            machine.eval(postfix, &mut Calculator),
+            Err(EvalError::BudgetExceeded {
+                pos: 2,
+                limit: Limit::StackDepth
+            })
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn deadline_exceeded() {
+        // 1 2 +
+        let postfix = [
+            OutputToken::Value(1),
+            OutputToken::Value(2),
+            OutputToken::Operator(Math::Add.into()),
+        ];
+        let budget = Budget::default().with_deadline(std::time::Instant::now());
+        let mut machine = StackMachine::with_budget(budget);
+        assert_eq!(
+            machine.eval(postfix, &mut Calculator),
+            Err(EvalError::BudgetExceeded {
+                pos: 0,
+                limit: Limit::Deadline
+            })
+        );
+    }
+}
@@ -0,0 +1,437 @@
+//! Evaluators for exact numbers, for formulas that can not accept `f64` rounding.
+//!
+//! `ExactEvaluator` evaluates `op::Math` over any `ExactNumber` and `op::All` into `Exact` values.
+//! `ExactNumber` is implemented for `rust_decimal::Decimal` with the `decimal` feature, for
+//! `num_bigint::BigInt` with the `bigint` feature and for the fixed-point types of the `fixed`
+//! crate, i.e. `fixed::types::I16F16`, with the `fixed` feature. Fixed-point arithmetic gives the
+//! same results on every platform, for simulations that have to be deterministic and for targets
+//! without floating point. Unlike `num::NumEvaluator` every operation is checked, an overflow is
+//! an error instead of a panic.
+//!
+//! ```rust
+//! use gyard::{
+//!     eval::{eval_postfix, exact::{Exact, ExactEvaluator}},
+//!     op::{All, Compare, Math},
+//!     to_postfix, InputToken,
+//! };
+//! use rust_decimal::Decimal;
+//!
+//! // 0.1 + 0.2 == 0.3
+//! let postfix = to_postfix::<_, (), _>([
+//!     InputToken::Value(Decimal::new(1, 1)),
+//!     InputToken::Operator(All::Math(Math::Add)),
+//!     InputToken::Value(Decimal::new(2, 1)),
+//!     InputToken::Operator(All::Compare(Compare::Eq)),
+//!     InputToken::Value(Decimal::new(3, 1)),
+//! ]).unwrap();
+//! assert_eq!(eval_postfix(postfix, &mut ExactEvaluator::new()), Ok(Exact::Bool(true)));
+//! ```
+
+use alloc::vec::Vec;
+
+use super::Evaluator;
+use crate::op::{All, Compare, Logical, Math};
+
+/// This error is returned by `ExactEvaluator`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ExactError {
+    /// The divisor of a division is zero.
+    DivisionByZero,
+    /// The result does not fit into the number type.
+    Overflow,
+    /// The exponent is negative, fractional or too large.
+    InvalidExponent,
+    /// The operator can not be applied to the types of its operands, i.e. `1 + true`.
+    TypeMismatch,
+    /// The expression contains a function. `ExactEvaluator` does not support functions.
+    UnknownFunction,
+}
+
+impl core::fmt::Display for ExactError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ExactError::DivisionByZero => write!(f, "Division by zero"),
+            ExactError::Overflow => write!(f, "Overflow"),
+            ExactError::InvalidExponent => write!(f, "Invalid exponent"),
+            ExactError::TypeMismatch => write!(f, "Type mismatch"),
+            ExactError::UnknownFunction => write!(f, "Unknown function"),
+        }
+    }
+}
+
+impl core::error::Error for ExactError {}
+
+/// A number type whose arithmetic does not round silently.
+pub trait ExactNumber: PartialOrd + Sized {
+    /// Applies a math operator to two operands.
+    fn apply(operator: Math, a: Self, b: Self) -> Result<Self, ExactError>;
+}
+
+#[cfg(feature = "decimal")]
+impl ExactNumber for rust_decimal::Decimal {
+    /// Only integer exponents are supported. Division rounds to the 28 decimal places of
+    /// `Decimal`.
+    fn apply(operator: Math, a: Self, b: Self) -> Result<Self, ExactError> {
+        match operator {
+            Math::Add => a.checked_add(b).ok_or(ExactError::Overflow),
+            Math::Sub => a.checked_sub(b).ok_or(ExactError::Overflow),
+            Math::Mul => a.checked_mul(b).ok_or(ExactError::Overflow),
+            Math::Div if b.is_zero() => Err(ExactError::DivisionByZero),
+            Math::Div => a.checked_div(b).ok_or(ExactError::Overflow),
+            Math::Exponent => {
+                use rust_decimal::prelude::ToPrimitive;
+
+                if !b.fract().is_zero() {
+                    return Err(ExactError::InvalidExponent);
+                }
+                let mut exponent = b.abs().to_u32().ok_or(ExactError::InvalidExponent)?;
+                let (mut base, mut result) = (a, Self::ONE);
+                while exponent > 0 {
+                    if exponent & 1 == 1 {
+                        result = result.checked_mul(base).ok_or(ExactError::Overflow)?;
+                    }
+                    exponent >>= 1;
+                    if exponent > 0 {
+                        base = base.checked_mul(base).ok_or(ExactError::Overflow)?;
+                    }
+                }
+                if b.is_sign_negative() {
+                    Self::apply(Math::Div, Self::ONE, result)
+                } else {
+                    Ok(result)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl ExactNumber for num_bigint::BigInt {
+    /// Division truncates towards zero. Exponents must be non-negative.
+    fn apply(operator: Math, a: Self, b: Self) -> Result<Self, ExactError> {
+        match operator {
+            Math::Add => Ok(a + b),
+            Math::Sub => Ok(a - b),
+            Math::Mul => Ok(a * b),
+            Math::Div if b == Self::ZERO => Err(ExactError::DivisionByZero),
+            Math::Div => Ok(a / b),
+            Math::Exponent => u32::try_from(&b)
+                .map(|exponent| a.pow(exponent))
+                .map_err(|_| ExactError::InvalidExponent),
+        }
+    }
+}
+
+/// Implements `ExactNumber` for the fixed-point types of the `fixed` crate.
+#[cfg(feature = "fixed")]
+macro_rules! impl_fixed {
+    ($($fixed:ident: $bound:ident),*) => {$(
+        impl<Frac: fixed::types::extra::$bound> ExactNumber for fixed::$fixed<Frac> {
+            /// Only integer exponents are supported. Multiplication and division round towards
+            /// zero. A negative exponent is an overflow if the type can not represent one.
+            fn apply(operator: Math, a: Self, b: Self) -> Result<Self, ExactError> {
+                match operator {
+                    Math::Add => a.checked_add(b).ok_or(ExactError::Overflow),
+                    Math::Sub => a.checked_sub(b).ok_or(ExactError::Overflow),
+                    Math::Mul => a.checked_mul(b).ok_or(ExactError::Overflow),
+                    Math::Div if b == Self::ZERO => Err(ExactError::DivisionByZero),
+                    Math::Div => a.checked_div(b).ok_or(ExactError::Overflow),
+                    Math::Exponent => {
+                        if b.frac() != Self::ZERO {
+                            return Err(ExactError::InvalidExponent);
+                        }
+                        let exponent = b
+                            .checked_to_num::<i32>()
+                            .ok_or(ExactError::InvalidExponent)?;
+                        let one = Self::checked_from_num(1).ok_or(ExactError::Overflow)?;
+                        let (mut base, mut result) = (a, one);
+                        let mut remaining = exponent.unsigned_abs();
+                        while remaining > 0 {
+                            if remaining & 1 == 1 {
+                                result = result.checked_mul(base).ok_or(ExactError::Overflow)?;
+                            }
+                            remaining >>= 1;
+                            if remaining > 0 {
+                                base = base.checked_mul(base).ok_or(ExactError::Overflow)?;
+                            }
+                        }
+                        if exponent < 0 {
+                            Self::apply(Math::Div, one, result)
+                        } else {
+                            Ok(result)
+                        }
+                    }
+                }
+            }
+        }
+    )*};
+}
+
+#[cfg(feature = "fixed")]
+impl_fixed!(
+    FixedI8: LeEqU8,
+    FixedI16: LeEqU16,
+    FixedI32: LeEqU32,
+    FixedI64: LeEqU64,
+    FixedI128: LeEqU128,
+    FixedU8: LeEqU8,
+    FixedU16: LeEqU16,
+    FixedU32: LeEqU32,
+    FixedU64: LeEqU64,
+    FixedU128: LeEqU128
+);
+
+/// The result of evaluating `op::All` with `ExactEvaluator`
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Exact<T> {
+    /// A number
+    Number(T),
+    /// The result of a comparison or logical operator
+    Bool(bool),
+}
+
+/// Evaluates `op::Math` and `op::All` over an `ExactNumber`.
+/// Math operators require numbers, logical operators require booleans and compare operators
+/// compare two numbers or, with `==` and `!=`, two booleans.
+#[derive(Debug, Clone, Copy)]
+pub struct ExactEvaluator<T> {
+    number: core::marker::PhantomData<fn() -> T>,
+}
+
+impl<T> Default for ExactEvaluator<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ExactEvaluator<T> {
+    /// Creates an evaluator.
+    pub fn new() -> Self {
+        Self {
+            number: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: ExactNumber> ExactEvaluator<T> {
+    /// Applies an operator to its operands.
+    /// Use this to reuse the arithmetic from your own `Evaluator`.
+    pub fn apply(&self, operator: All, args: &mut Vec<Exact<T>>) -> Result<Exact<T>, ExactError> {
+        if operator == All::Logical(Logical::Not) {
+            return match args.pop() {
+                Some(Exact::Bool(a)) => Ok(Exact::Bool(!a)),
+                _ => Err(ExactError::TypeMismatch),
+            };
+        }
+        let (Some(b), Some(a)) = (args.pop(), args.pop()) else {
+            unreachable!("Only Logical::Not is unary")
+        };
+        match (operator, a, b) {
+            (All::Math(math), Exact::Number(a), Exact::Number(b)) => {
+                T::apply(math, a, b).map(Exact::Number)
+            }
+            (All::Compare(compare), Exact::Number(a), Exact::Number(b)) => {
+                Ok(Exact::Bool(compare_with(compare, &a, &b)))
+            }
+            (
+                All::Compare(compare @ (Compare::Eq | Compare::Ne)),
+                Exact::Bool(a),
+                Exact::Bool(b),
+            ) => Ok(Exact::Bool(compare_with(compare, &a, &b))),
+            (All::Logical(logical), Exact::Bool(a), Exact::Bool(b)) => {
+                Ok(Exact::Bool(match logical {
+                    Logical::Xor => a ^ b,
+                    Logical::And => a && b,
+                    Logical::Or => a || b,
+                    Logical::Not => unreachable!("Handled above"),
+                }))
+            }
+            _ => Err(ExactError::TypeMismatch),
+        }
+    }
+}
+
+fn compare_with<T: PartialOrd>(compare: Compare, a: &T, b: &T) -> bool {
+    match compare {
+        Compare::Lt => a < b,
+        Compare::Le => a <= b,
+        Compare::Eq => a == b,
+        Compare::Ne => a != b,
+        Compare::Ge => a >= b,
+        Compare::Gt => a > b,
+    }
+}
+
+impl<T: ExactNumber, F> Evaluator<T, F, Math> for ExactEvaluator<T> {
+    type Value = T;
+    type Error = ExactError;
+
+    fn value(&mut self, value: T) -> Result<T, ExactError> {
+        Ok(value)
+    }
+
+    fn operator(&mut self, operator: Math, args: &mut Vec<T>) -> Result<T, ExactError> {
+        let (Some(b), Some(a)) = (args.pop(), args.pop()) else {
+            unreachable!("Math operators are binary")
+        };
+        T::apply(operator, a, b)
+    }
+
+    fn function(&mut self, _: F, _: &mut Vec<T>) -> Result<T, ExactError> {
+        Err(ExactError::UnknownFunction)
+    }
+
+    fn function_arity(&self, _: &F) -> usize {
+        0
+    }
+}
+
+impl<T: ExactNumber, F> Evaluator<T, F, All> for ExactEvaluator<T> {
+    type Value = Exact<T>;
+    type Error = ExactError;
+
+    fn value(&mut self, value: T) -> Result<Exact<T>, ExactError> {
+        Ok(Exact::Number(value))
+    }
+
+    fn operator(
+        &mut self,
+        operator: All,
+        args: &mut Vec<Exact<T>>,
+    ) -> Result<Exact<T>, ExactError> {
+        self.apply(operator, args)
+    }
+
+    fn function(&mut self, _: F, _: &mut Vec<Exact<T>>) -> Result<Exact<T>, ExactError> {
+        Err(ExactError::UnknownFunction)
+    }
+
+    fn function_arity(&self, _: &F) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Exact, ExactError, ExactEvaluator};
+    use crate::{
+        eval::{eval_postfix, EvalError},
+        op::{All, Compare, Math},
+        OutputToken,
+    };
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn decimal_exponent_and_overflow() {
+        use rust_decimal::Decimal;
+
+        let pow = |a: Decimal, b: Decimal| {
+            let postfix = [
+                OutputToken::<_, (), _>::Value(a),
+                OutputToken::Value(b),
+                OutputToken::Operator(Math::Exponent),
+            ];
+            eval_postfix(postfix, &mut ExactEvaluator::new())
+        };
+        assert_eq!(
+            pow(Decimal::new(15, 1), Decimal::TWO),
+            Ok(Decimal::new(225, 2))
+        );
+        assert_eq!(
+            pow(Decimal::TWO, Decimal::NEGATIVE_ONE),
+            Ok(Decimal::new(5, 1))
+        );
+        assert_eq!(
+            pow(Decimal::TWO, Decimal::new(5, 1)),
+            Err(EvalError::Evaluator {
+                pos: 2,
+                error: ExactError::InvalidExponent
+            })
+        );
+        assert_eq!(
+            pow(Decimal::MAX, Decimal::TWO),
+            Err(EvalError::Evaluator {
+                pos: 2,
+                error: ExactError::Overflow
+            })
+        );
+    }
+
+    #[cfg(feature = "fixed")]
+    #[test]
+    fn fixed_point_is_deterministic() {
+        use fixed::types::{I16F16, U8F8};
+
+        // 1 / 3 * 3 < 1
+        let postfix = [
+            OutputToken::<_, (), _>::Value(I16F16::from_num(1)),
+            OutputToken::Value(I16F16::from_num(3)),
+            OutputToken::Operator(All::Math(Math::Div)),
+            OutputToken::Value(I16F16::from_num(3)),
+            OutputToken::Operator(All::Math(Math::Mul)),
+            OutputToken::Value(I16F16::from_num(1)),
+            OutputToken::Operator(All::Compare(Compare::Lt)),
+        ];
+        assert_eq!(
+            eval_postfix(postfix, &mut ExactEvaluator::new()),
+            Ok(Exact::Bool(true))
+        );
+        let pow = |a: U8F8, b: U8F8| {
+            let postfix = [
+                OutputToken::<_, (), _>::Value(a),
+                OutputToken::Value(b),
+                OutputToken::Operator(Math::Exponent),
+            ];
+            eval_postfix(postfix, &mut ExactEvaluator::new())
+        };
+        assert_eq!(
+            pow(U8F8::from_num(1.5), U8F8::from_num(2)),
+            Ok(U8F8::from_num(2.25))
+        );
+        assert_eq!(
+            pow(U8F8::from_num(2), U8F8::from_num(8)),
+            Err(EvalError::Evaluator {
+                pos: 2,
+                error: ExactError::Overflow
+            })
+        );
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn bigint_compare_and_type_mismatch() {
+        use num_bigint::BigInt;
+
+        // 2 ^ 100 > 10 ^ 30
+        let postfix = [
+            OutputToken::<_, (), _>::Value(BigInt::from(2)),
+            OutputToken::Value(BigInt::from(100)),
+            OutputToken::Operator(All::Math(Math::Exponent)),
+            OutputToken::Value(BigInt::from(10)),
+            OutputToken::Value(BigInt::from(30)),
+            OutputToken::Operator(All::Math(Math::Exponent)),
+            OutputToken::Operator(All::Compare(Compare::Gt)),
+        ];
+        assert_eq!(
+            eval_postfix(postfix.clone(), &mut ExactEvaluator::new()),
+            Ok(Exact::Bool(true))
+        );
+        let mut postfix = postfix.to_vec();
+        postfix.extend([
+            OutputToken::Value(BigInt::from(1)),
+            OutputToken::Operator(All::Math(Math::Add)),
+        ]);
+        assert_eq!(
+            eval_postfix(postfix, &mut ExactEvaluator::new()),
+            Err(EvalError::Evaluator {
+                pos: 8,
+                error: ExactError::TypeMismatch
+            })
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! Human readable traces of an evaluation.
+//!
+//! ```rust
+//! use gyard::{eval::{explain::explain, Evaluator}, op::Math, to_postfix, InputToken};
+//!
+//! struct Calculator;
+//! impl Evaluator<i64, &str, Math> for Calculator {
+//!     type Value = i64;
+//!     type Error = String;
+//!     fn value(&mut self, value: i64) -> Result<i64, String> {
+//!         Ok(value)
+//!     }
+//!     fn operator(&mut self, operator: Math, args: &mut Vec<i64>) -> Result<i64, String> {
+//!         match operator {
+//!             Math::Add => Ok(args[0] + args[1]),
+//!             Math::Mul => Ok(args[0] * args[1]),
+//!             _ => Err(format!("unsupported operator {operator}")),
+//!         }
+//!     }
+//!     fn function(&mut self, _: &str, args: &mut Vec<i64>) -> Result<i64, String> {
+//!         Ok(args[0].abs())
+//!     }
+//!     fn function_arity(&self, _: &&str) -> usize {
+//!         1
+//!     }
+//! }
+//!
+//! // 2 * abs ( 3 ) + 1
+//! let postfix = to_postfix([
+//!     InputToken::Value(2),
+//!     InputToken::Operator(Math::Mul),
+//!     InputToken::Function("abs"),
+//!     InputToken::LeftParen,
+//!     InputToken::Value(3),
+//!     InputToken::RightParen,
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Value(1),
+//! ])
+//! .unwrap();
+//! let explanation = explain(postfix, &mut Calculator);
+//! assert_eq!(explanation.to_string(), "\
+//! pushed 2
+//! pushed 3
+//! called abs(3) → 3
+//! applied * to 2, 3 → 6
+//! pushed 1
+//! applied + to 6, 1 → 7
+//! result 7
+//! ");
+//! ```
+
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+use super::{EvalError, Evaluator, Step, Steps};
+use crate::{Operator, OutputToken};
+
+/// A single step of an `Explanation`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExplainStep<F, O, T> {
+    /// A value was pushed onto the stack.
+    Push {
+        /// The position of the value
+        pos: usize,
+        /// The evaluated value
+        value: T,
+    },
+    /// An operator was applied.
+    Operator {
+        /// The position of the operator
+        pos: usize,
+        /// The operator
+        operator: O,
+        /// The operands
+        args: Vec<T>,
+        /// The result
+        result: T,
+    },
+    /// A function was called.
+    Function {
+        /// The position of the function
+        pos: usize,
+        /// The function
+        function: F,
+        /// The arguments
+        args: Vec<T>,
+        /// The result
+        result: T,
+    },
+}
+
+/// The trace of an evaluation returned by `explain`.
+/// `Display` prints one line per step followed by the result or the error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation<F, O, T, E> {
+    /// The steps in evaluation order
+    pub steps: Vec<ExplainStep<F, O, T>>,
+    /// The result of the evaluation
+    pub result: Result<T, EvalError<E>>,
+}
+
+/// Evaluates a postfix expression and records every step. See `eval_postfix`.
+pub fn explain<V, F, O, E>(
+    postfix: impl IntoIterator<Item = OutputToken<V, F, O>>,
+    evaluator: &mut E,
+) -> Explanation<F, O, E::Value, E::Error>
+where
+    V: Clone,
+    F: Clone,
+    O: Operator + Clone,
+    E: Evaluator<V, F, O>,
+    E::Value: Clone,
+{
+    let mut iter = Steps::new(postfix, evaluator);
+    let mut steps = Vec::new();
+    let mut previous: Vec<E::Value> = Vec::new();
+
+    for Step { pos, token, stack } in iter.by_ref() {
+        let arity = previous.len() + 1 - stack.len();
+        let args = previous.split_off(previous.len() - arity);
+        let Some(result) = stack.last().cloned() else {
+            unreachable!("every step pushes a value")
+        };
+        steps.push(match token {
+            OutputToken::Value(_) => ExplainStep::Push { pos, value: result },
+            OutputToken::Operator(operator) => ExplainStep::Operator {
+                pos,
+                operator,
+                args,
+                result,
+            },
+            OutputToken::Function(function) => ExplainStep::Function {
+                pos,
+                function,
+                args,
+                result,
+            },
+        });
+        previous = stack;
+    }
+
+    Explanation {
+        steps,
+        result: iter.finish(),
+    }
+}
+
+/// Writes `args` separated by commas.
+fn write_args<T: Display>(f: &mut core::fmt::Formatter<'_>, args: &[T]) -> core::fmt::Result {
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{arg}")?;
+    }
+    Ok(())
+}
+
+impl<F: Display, O: Display, T: Display> Display for ExplainStep<F, O, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ExplainStep::Push { value, .. } => write!(f, "pushed {value}"),
+            ExplainStep::Operator {
+                operator,
+                args,
+                result,
+                ..
+            } => {
+                write!(f, "applied {operator} to ")?;
+                write_args(f, args)?;
+                write!(f, " → {result}")
+            }
+            ExplainStep::Function {
+                function,
+                args,
+                result,
+                ..
+            } => {
+                write!(f, "called {function}(")?;
+                write_args(f, args)?;
+                write!(f, ") → {result}")
+            }
+        }
+    }
+}
+
+impl<F, O, T, E> Display for Explanation<F, O, T, E>
+where
+    F: Display,
+    O: Display,
+    T: Display,
+    E: Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{step}")?;
+        }
+        match &self.result {
+            Ok(value) => writeln!(f, "result {value}"),
+            Err(error) => writeln!(f, "error: {error}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec::Vec};
+
+    use super::{explain, ExplainStep};
+    use crate::{
+        eval::{EvalError, Evaluator},
+        op::Math,
+        OutputToken,
+    };
+
+    struct Div;
+
+    impl Evaluator<i64, &str, Math> for Div {
+        type Value = i64;
+        type Error = &'static str;
+
+        fn value(&mut self, value: i64) -> Result<i64, &'static str> {
+            Ok(value)
+        }
+
+        fn operator(&mut self, _: Math, args: &mut Vec<i64>) -> Result<i64, &'static str> {
+            args[0].checked_div(args[1]).ok_or("division by zero")
+        }
+
+        fn function(&mut self, _: &str, _: &mut Vec<i64>) -> Result<i64, &'static str> {
+            Err("unknown function")
+        }
+
+        fn function_arity(&self, _: &&str) -> usize {
+            0
+        }
+    }
+
+    #[test]
+    fn explain_error() {
+        // 1 / 0
+        let postfix = [
+            OutputToken::<_, &str, _>::Value(1),
+            OutputToken::Value(0),
+            OutputToken::Operator(Math::Div),
+        ];
+        let explanation = explain(postfix, &mut Div);
+        assert_eq!(
+            explanation.steps,
+            [
+                ExplainStep::Push { pos: 0, value: 1 },
+                ExplainStep::Push { pos: 1, value: 0 }
+            ]
+        );
+        assert_eq!(
+            explanation.result,
+            Err(EvalError::Evaluator {
+                pos: 2,
+                error: "division by zero"
+            })
+        );
+        assert_eq!(
+            explanation.to_string(),
+            "pushed 1\npushed 0\nerror: Failed to evaluate token at position 2: division by zero\n"
+        );
+    }
+}
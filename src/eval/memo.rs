@@ -0,0 +1,390 @@
+//! Memoized evaluation of postfix expressions.
+//! `Memo` caches the results of subexpressions. Subexpressions that appear again, in the same or
+//! in another expression, are not evaluated a second time, even if the operands of an operator
+//! are swapped for which `Evaluator::is_commutative` returns `true`. The evaluation does not
+//! recurse, so deeply nested expressions are fine.
+//!
+//! Operators and values are expected to be pure. Functions are only cached if
+//! `Evaluator::is_pure` returns `true`. Clear the cache if the evaluator starts to produce
+//! different results, e.g. because a variable changed.
+//!
+//! ```rust
+//! use gyard::{eval::{memo::Memo, Evaluator}, op::Math, to_postfix_with_arity, InputToken};
+//!
+//! struct Calculator {
+//!     calls: usize,
+//! }
+//!
+//! impl Evaluator<i64, &str, Math> for Calculator {
+//!     type Value = i64;
+//!     type Error = ();
+//!     fn value(&mut self, value: i64) -> Result<i64, ()> {
+//!         Ok(value)
+//!     }
+//!     fn operator(&mut self, _: Math, args: &mut Vec<i64>) -> Result<i64, ()> {
+//!         Ok(args[0] + args[1])
+//!     }
+//!     fn function(&mut self, _: &str, args: &mut Vec<i64>) -> Result<i64, ()> {
+//!         self.calls += 1;
+//!         Ok(args[0] * 10)
+//!     }
+//!     fn function_arity(&self, _: &&str) -> usize {
+//!         1
+//!     }
+//!     fn is_pure(&self, _: &&str) -> bool {
+//!         true
+//!     }
+//! }
+//!
+//! // f ( 1 ) + f ( 1 )
+//! let postfix = to_postfix_with_arity([
+//!     InputToken::Function("f"),
+//!     InputToken::LeftParen,
+//!     InputToken::Value(1),
+//!     InputToken::RightParen,
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Function("f"),
+//!     InputToken::LeftParen,
+//!     InputToken::Value(1),
+//!     InputToken::RightParen,
+//! ])
+//! .unwrap();
+//! let mut memo = Memo::new();
+//! let mut calculator = Calculator { calls: 0 };
+//! assert_eq!(memo.eval(&postfix, &mut calculator), Ok(20));
+//! assert_eq!(calculator.calls, 1);
+//! ```
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use super::{arity_error, EvalError, Evaluator};
+use crate::{ast::hash::StableHasher, Call, Operator, OutputToken};
+
+/// A cache of subexpression results.
+/// Subexpressions are identified by a 128 bit hash of their canonical form, so the operands of a
+/// commutative operator may appear in any order. The hash is built bottom-up from the hashes of
+/// the operands with `ast::hash::StableHasher`. Only the hash is stored, so two subexpressions
+/// with the same hash share one entry and the second one evaluates to the result of the first.
+/// By accident this is very unlikely, but the hash is not cryptographic, so do not share a cache
+/// between expressions of different untrusted users.
+#[derive(Debug, Clone)]
+pub struct Memo<T> {
+    cache: HashMap<u128, T>,
+    hits: usize,
+}
+
+impl<T> Default for Memo<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Memo<T> {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self {
+            cache: HashMap::new(),
+            hits: 0,
+        }
+    }
+
+    /// Returns the number of cached subexpressions.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Returns `true` if no subexpression is cached.
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Returns the number of subexpressions that were taken from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Removes all cached results.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.hits = 0;
+    }
+}
+
+/// The subexpression ending at a position of the postfix expression
+struct Node {
+    /// The position of the first token
+    start: usize,
+    /// The hash of the canonical form
+    hash: u128,
+    /// True if the subexpression only calls pure functions
+    pure: bool,
+}
+
+/// A step of the evaluation
+enum Task {
+    /// Evaluate the subexpression ending at the position, or take it from the cache
+    Eval(usize),
+    /// Apply the operator or function at the position to the evaluated operands
+    Apply(usize),
+}
+
+impl<T: Clone> Memo<T> {
+    /// Evaluates a postfix expression created by `to_postfix_with_arity`, reusing and caching the
+    /// results of subexpressions. See `eval_postfix_with_arity`.
+    pub fn eval<V, F, O, E>(
+        &mut self,
+        postfix: &[OutputToken<V, Call<F>, O>],
+        evaluator: &mut E,
+    ) -> Result<T, EvalError<E::Error>>
+    where
+        V: Clone + Hash,
+        F: Clone + Hash,
+        O: Operator + Clone + Hash,
+        E: Evaluator<V, F, O, Value = T>,
+    {
+        let nodes = Self::nodes(postfix, evaluator)?;
+        let mut tasks = vec![Task::Eval(postfix.len() - 1)];
+        let mut values = Vec::new();
+        while let Some(task) = tasks.pop() {
+            match task {
+                Task::Eval(end) => {
+                    let node = &nodes[end];
+                    if let OutputToken::Value(value) = &postfix[end] {
+                        let value = evaluator
+                            .value(value.clone())
+                            .map_err(|error| EvalError::Evaluator { pos: end, error })?;
+                        values.push(value);
+                    } else if let Some(value) = self.cache.get(&node.hash) {
+                        self.hits += 1;
+                        values.push(value.clone());
+                    } else {
+                        tasks.push(Task::Apply(end));
+                        // The operands are pushed from last to first, so the first one is
+                        // evaluated first
+                        let mut child = end;
+                        while child > node.start {
+                            child -= 1;
+                            tasks.push(Task::Eval(child));
+                            child = nodes[child].start;
+                        }
+                    }
+                }
+                Task::Apply(end) => {
+                    let value = match &postfix[end] {
+                        OutputToken::Operator(op) => {
+                            let mut args = values.split_off(values.len() - op.arity());
+                            evaluator.operator(op.clone(), &mut args)
+                        }
+                        OutputToken::Function(call) => {
+                            let mut args = values.split_off(values.len() - call.arity);
+                            evaluator.function(call.function.clone(), &mut args)
+                        }
+                        OutputToken::Value(_) => unreachable!("Values are evaluated directly"),
+                    };
+                    let value = value.map_err(|error| EvalError::Evaluator { pos: end, error })?;
+                    if nodes[end].pure {
+                        self.cache.insert(nodes[end].hash, value.clone());
+                    }
+                    values.push(value);
+                }
+            }
+        }
+        Ok(values
+            .pop()
+            .expect("The checked expression has a single value"))
+    }
+
+    /// Checks the shape of `postfix` and returns the subexpression ending at every position.
+    fn nodes<V, F, O, E>(
+        postfix: &[OutputToken<V, Call<F>, O>],
+        evaluator: &E,
+    ) -> Result<Vec<Node>, EvalError<E::Error>>
+    where
+        V: Hash,
+        F: Hash,
+        O: Operator + Hash,
+        E: Evaluator<V, F, O, Value = T>,
+    {
+        let mut nodes: Vec<Node> = Vec::with_capacity(postfix.len());
+        // The last position of every subexpression on the evaluation stack
+        let mut stack: Vec<usize> = Vec::new();
+        let mut operands: Vec<u128> = Vec::new();
+        for (pos, token) in postfix.iter().enumerate() {
+            let (arity, pure, commutative) = match token {
+                OutputToken::Value(_) => (0, true, false),
+                OutputToken::Operator(op) => (op.arity(), true, evaluator.is_commutative(op)),
+                OutputToken::Function(call) => {
+                    let expected = evaluator.accepted_arity(&call.function);
+                    if let Some(error) = arity_error(expected, call.arity, pos) {
+                        return Err(error);
+                    }
+                    (call.arity, evaluator.is_pure(&call.function), false)
+                }
+            };
+            let Some(first) = stack.len().checked_sub(arity) else {
+                return Err(EvalError::StackUnderflow {
+                    pos,
+                    required: arity,
+                    available: stack.len(),
+                });
+            };
+            operands.clear();
+            operands.extend(stack[first..].iter().map(|&end| nodes[end].hash));
+            if commutative {
+                operands.sort_unstable();
+            }
+            let mut hasher = StableHasher::new();
+            token.hash(&mut hasher);
+            operands.iter().for_each(|hash| hasher.write_u128(*hash));
+            nodes.push(Node {
+                start: stack.get(first).map_or(pos, |&end| nodes[end].start),
+                hash: hasher.finish128(),
+                pure: pure && stack[first..].iter().all(|&end| nodes[end].pure),
+            });
+            stack.truncate(first);
+            stack.push(pos);
+        }
+        match stack.len() {
+            0 => Err(EvalError::EmptyExpression),
+            1 => Ok(nodes),
+            count => Err(EvalError::UnusedValues { count }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Memo;
+    use crate::{
+        eval::{EvalError, Evaluator},
+        op::Math,
+        Call, OutputToken,
+    };
+
+    struct Counter {
+        calls: usize,
+    }
+
+    impl Evaluator<i64, &str, Math> for Counter {
+        type Value = i64;
+        type Error = ();
+
+        fn value(&mut self, value: i64) -> Result<i64, ()> {
+            Ok(value)
+        }
+
+        fn operator(&mut self, operator: Math, args: &mut Vec<i64>) -> Result<i64, ()> {
+            self.calls += 1;
+            let [a, b] = args[..] else { return Err(()) };
+            match operator {
+                Math::Add => Ok(a + b),
+                Math::Sub => Ok(a - b),
+                Math::Mul => Ok(a * b),
+                Math::Div | Math::Exponent => Err(()),
+            }
+        }
+
+        fn function(&mut self, _: &str, _: &mut Vec<i64>) -> Result<i64, ()> {
+            self.calls += 1;
+            Ok(self.calls as i64)
+        }
+
+        fn function_arity(&self, _: &&str) -> usize {
+            0
+        }
+    }
+
+    #[test]
+    fn impure_functions_are_not_cached() {
+        // ( 3 - 1 ) - rand ( )
+        let postfix = [
+            OutputToken::Value(3),
+            OutputToken::Value(1),
+            OutputToken::Operator(Math::Sub),
+            OutputToken::Function(Call {
+                function: "rand",
+                arity: 0,
+            }),
+            OutputToken::Operator(Math::Sub),
+        ];
+        let mut memo = Memo::new();
+        let mut counter = Counter { calls: 0 };
+        assert_eq!(memo.eval(&postfix, &mut counter), Ok(0));
+        assert_eq!(memo.len(), 1);
+        assert_eq!(memo.eval(&postfix, &mut counter), Ok(-2));
+        assert_eq!(memo.hits(), 1);
+        assert_eq!(counter.calls, 5);
+    }
+
+    #[test]
+    fn swapped_sum() {
+        let value = OutputToken::<_, Call<&str>, _>::Value;
+        let mut memo = Memo::new();
+        let mut counter = Counter { calls: 0 };
+        // 1 + 2
+        let sum = [value(1), value(2), OutputToken::Operator(Math::Add)];
+        assert_eq!(memo.eval(&sum, &mut counter), Ok(3));
+        // 2 + 1 is taken from the cache
+        let swapped = [value(2), value(1), OutputToken::Operator(Math::Add)];
+        assert_eq!(memo.eval(&swapped, &mut counter), Ok(3));
+        assert_eq!(memo.hits(), 1);
+        assert_eq!(counter.calls, 1);
+    }
+
+    #[test]
+    fn shared_subexpressions_across_expressions() {
+        let value = OutputToken::<_, Call<&str>, _>::Value;
+        let op = OutputToken::Operator;
+        let mut memo = Memo::new();
+        let mut counter = Counter { calls: 0 };
+        // ( 3 - 1 ) * 2
+        let first = [value(3), value(1), op(Math::Sub), value(2), op(Math::Mul)];
+        assert_eq!(memo.eval(&first, &mut counter), Ok(4));
+        // 2 * ( 3 - 1 ) is the same expression, 4 - ( 3 - 1 ) shares the subtraction
+        let commuted = [value(2), value(3), value(1), op(Math::Sub), op(Math::Mul)];
+        assert_eq!(memo.eval(&commuted, &mut counter), Ok(4));
+        let shared = [value(4), value(3), value(1), op(Math::Sub), op(Math::Sub)];
+        assert_eq!(memo.eval(&shared, &mut counter), Ok(2));
+        assert_eq!(memo.hits(), 2);
+        assert_eq!(counter.calls, 3);
+    }
+
+    #[test]
+    fn deep_expressions() {
+        // 1 - 1 - 1 - ... - 1
+        let mut postfix = vec![OutputToken::<_, Call<&str>, _>::Value(1)];
+        for _ in 0..100_000 {
+            postfix.extend([OutputToken::Value(1), OutputToken::Operator(Math::Sub)]);
+        }
+        let mut memo = Memo::new();
+        assert_eq!(memo.eval(&postfix, &mut Counter { calls: 0 }), Ok(-99_999));
+        assert_eq!(memo.len(), 100_000);
+    }
+
+    #[test]
+    fn right_nested_expressions() {
+        // 1 - ( 1 - ( 1 - ... ( 1 - 1 ) ) )
+        let mut postfix = vec![OutputToken::<_, Call<&str>, _>::Value(1); 100_001];
+        postfix.extend((0..100_000).map(|_| OutputToken::Operator(Math::Sub)));
+        let mut memo = Memo::new();
+        assert_eq!(memo.eval(&postfix, &mut Counter { calls: 0 }), Ok(1));
+        assert_eq!(memo.len(), 100_000);
+    }
+
+    #[test]
+    fn stack_underflow() {
+        let postfix = [
+            OutputToken::<_, Call<&str>, _>::Value(1),
+            OutputToken::Operator(Math::Sub),
+        ];
+        assert_eq!(
+            Memo::new().eval(&postfix, &mut Counter { calls: 0 }),
+            Err(EvalError::StackUnderflow {
+                pos: 1,
+                required: 2,
+                available: 1
+            })
+        );
+    }
+}
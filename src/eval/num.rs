@@ -0,0 +1,165 @@
+//! A ready-made evaluator for `op::Math` over any numeric type implementing `num_traits::Num`.
+//!
+//! ```rust
+//! use gyard::{eval::{eval_postfix, num::NumEvaluator}, op::Math, to_postfix, InputToken};
+//!
+//! // 7 - 2 * 3
+//! let postfix = to_postfix::<_, (), _>([
+//!     InputToken::Value(7i128),
+//!     InputToken::Operator(Math::Sub),
+//!     InputToken::Value(2),
+//!     InputToken::Operator(Math::Mul),
+//!     InputToken::Value(3),
+//! ]).unwrap();
+//! assert_eq!(eval_postfix(postfix, &mut NumEvaluator::new()), Ok(1));
+//!
+//! // 2 ^ 0.5
+//! let postfix = to_postfix::<_, (), _>([
+//!     InputToken::Value(2f64),
+//!     InputToken::Operator(Math::Exponent),
+//!     InputToken::Value(0.5),
+//! ]).unwrap();
+//! let mut evaluator = NumEvaluator::with_pow(|a: f64, b| Some(a.powf(b)));
+//! assert_eq!(eval_postfix(postfix, &mut evaluator), Ok(2f64.sqrt()));
+//! ```
+
+use alloc::vec::Vec;
+use num_traits::Num;
+
+use super::Evaluator;
+use crate::op::Math;
+
+/// This error is returned by `NumEvaluator`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum NumError {
+    /// The divisor of a division is zero.
+    DivisionByZero,
+    /// The result of an exponentiation is undefined or no power function was provided.
+    InvalidExponent,
+    /// The expression contains a function. `NumEvaluator` does not support functions.
+    UnknownFunction,
+}
+
+impl core::fmt::Display for NumError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            NumError::DivisionByZero => write!(f, "Division by zero"),
+            NumError::InvalidExponent => write!(f, "Invalid exponentiation"),
+            NumError::UnknownFunction => write!(f, "Unknown function"),
+        }
+    }
+}
+
+impl core::error::Error for NumError {}
+
+/// Evaluates `op::Math` operators over `T`.
+/// Arithmetic follows the operators of `T`, i.e. integer overflow panics in debug builds.
+/// Exponentiation is only supported if a power function is passed to `NumEvaluator::with_pow`.
+#[derive(Debug, Clone, Copy)]
+pub struct NumEvaluator<T> {
+    pow: Option<fn(T, T) -> Option<T>>,
+}
+
+impl<T> Default for NumEvaluator<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> NumEvaluator<T> {
+    /// Creates an evaluator without support for `Math::Exponent`.
+    pub fn new() -> Self {
+        Self { pow: None }
+    }
+
+    /// Creates an evaluator using `pow` for `Math::Exponent`. `pow` returns `None` if the result
+    /// is undefined.
+    pub fn with_pow(pow: fn(T, T) -> Option<T>) -> Self {
+        Self { pow: Some(pow) }
+    }
+}
+
+impl<T: Num> NumEvaluator<T> {
+    /// Applies a math operator to two operands.
+    /// Use this to reuse the arithmetic from your own `Evaluator`.
+    pub fn apply(&self, operator: Math, a: T, b: T) -> Result<T, NumError> {
+        match operator {
+            Math::Add => Ok(a + b),
+            Math::Sub => Ok(a - b),
+            Math::Mul => Ok(a * b),
+            Math::Div if b.is_zero() => Err(NumError::DivisionByZero),
+            Math::Div => Ok(a / b),
+            Math::Exponent => self
+                .pow
+                .and_then(|pow| pow(a, b))
+                .ok_or(NumError::InvalidExponent),
+        }
+    }
+}
+
+impl<T: Num, F> Evaluator<T, F, Math> for NumEvaluator<T> {
+    type Value = T;
+    type Error = NumError;
+
+    fn value(&mut self, value: T) -> Result<T, NumError> {
+        Ok(value)
+    }
+
+    fn operator(&mut self, operator: Math, args: &mut Vec<T>) -> Result<T, NumError> {
+        let (Some(b), Some(a)) = (args.pop(), args.pop()) else {
+            unreachable!("Math operators are binary")
+        };
+        self.apply(operator, a, b)
+    }
+
+    fn function(&mut self, _: F, _: &mut Vec<T>) -> Result<T, NumError> {
+        Err(NumError::UnknownFunction)
+    }
+
+    fn function_arity(&self, _: &F) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NumError, NumEvaluator};
+    use crate::{eval::eval_postfix, eval::EvalError, op::Math, OutputToken};
+
+    #[test]
+    fn integer_division_by_zero() {
+        let postfix = [
+            OutputToken::<_, (), _>::Value(1u8),
+            OutputToken::Value(0),
+            OutputToken::Operator(Math::Div),
+        ];
+        assert_eq!(
+            eval_postfix(postfix, &mut NumEvaluator::new()),
+            Err(EvalError::Evaluator {
+                pos: 2,
+                error: NumError::DivisionByZero
+            })
+        );
+    }
+
+    #[test]
+    fn exponent_without_pow() {
+        let postfix = [
+            OutputToken::<_, (), _>::Value(2),
+            OutputToken::Value(3),
+            OutputToken::Operator(Math::Exponent),
+        ];
+        assert_eq!(
+            eval_postfix(postfix.clone(), &mut NumEvaluator::new()),
+            Err(EvalError::Evaluator {
+                pos: 2,
+                error: NumError::InvalidExponent
+            })
+        );
+        let mut evaluator = NumEvaluator::with_pow(|a: i32, b| a.checked_pow(b.try_into().ok()?));
+        assert_eq!(eval_postfix(postfix, &mut evaluator), Ok(8));
+    }
+}
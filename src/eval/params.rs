@@ -0,0 +1,186 @@
+//! Named parameters bound right before evaluation.
+//!
+//! Convert an expression with variables once, then bind a `Params` map to it for every
+//! evaluation, like the parameters of a prepared statement. `Params::bind` replaces every
+//! variable with its value and reports all variables without a value at once.
+//!
+//! ```rust
+//! use gyard::{
+//!     eval::params::{Binding, Params, UnboundVariable},
+//!     op::Math,
+//!     to_postfix, InputToken, OutputToken,
+//! };
+//!
+//! enum Token {
+//!     Number(f64),
+//!     Variable(&'static str),
+//! }
+//!
+//! let classify = |token| match token {
+//!     Token::Number(number) => Binding::Value(number),
+//!     Token::Variable(name) => Binding::Variable(name),
+//! };
+//! // price * quantity
+//! let infix = || [
+//!     InputToken::<_, (), _>::Value(Token::Variable("price")),
+//!     InputToken::Operator(Math::Mul),
+//!     InputToken::Value(Token::Variable("quantity")),
+//! ];
+//!
+//! let params = Params::new().with("price", 2.5).with("quantity", 4.);
+//! let postfix = params.bind(to_postfix(infix()).unwrap(), classify).unwrap();
+//! assert_eq!(postfix[1], OutputToken::Value(4.));
+//!
+//! let missing = Params::new().with("price", 2.5).bind(to_postfix(infix()).unwrap(), classify);
+//! assert_eq!(missing, Err(vec![UnboundVariable { name: "quantity", pos: 1 }]));
+//! ```
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::OutputToken;
+
+type BindResult<T, F, O, K> = Result<Vec<OutputToken<T, F, O>>, Vec<UnboundVariable<K>>>;
+
+/// A value token classified by the callback of `Params::bind`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Binding<T, K> {
+    /// A value that does not depend on the parameters
+    Value(T),
+    /// A variable that is replaced with the parameter with the same name
+    Variable(K),
+}
+
+/// This error is returned by `Params::bind` for every variable without a value.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct UnboundVariable<K> {
+    /// The name of the variable
+    pub name: K,
+    /// The position of the variable in the postfix expression
+    pub pos: usize,
+}
+
+impl<K: core::fmt::Debug> core::fmt::Display for UnboundVariable<K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Variable {:?} at position {} is not bound",
+            self.name, self.pos
+        )
+    }
+}
+
+impl<K: core::fmt::Debug> core::error::Error for UnboundVariable<K> {}
+
+/// Values of named parameters. See the module documentation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Params<K, T> {
+    values: BTreeMap<K, T>,
+}
+
+impl<K, T> Default for Params<K, T> {
+    fn default() -> Self {
+        Self {
+            values: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K: Ord, T> Params<K, T> {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the value of the parameter `name`.
+    pub fn with(mut self, name: K, value: T) -> Self {
+        self.insert(name, value);
+        self
+    }
+
+    /// Sets the value of the parameter `name` and returns the previous value.
+    pub fn insert(&mut self, name: K, value: T) -> Option<T> {
+        self.values.insert(name, value)
+    }
+
+    /// Returns the value of the parameter `name`.
+    pub fn get(&self, name: &K) -> Option<&T> {
+        self.values.get(name)
+    }
+
+    /// Replaces the values of `postfix` with the result of `classify`. Variables are replaced
+    /// with a clone of their parameter. Returns every variable without a parameter in the order
+    /// they appear in `postfix` if at least one is missing.
+    pub fn bind<V, F, O>(
+        &self,
+        postfix: impl IntoIterator<Item = OutputToken<V, F, O>>,
+        mut classify: impl FnMut(V) -> Binding<T, K>,
+    ) -> BindResult<T, F, O, K>
+    where
+        T: Clone,
+    {
+        let mut unbound = Vec::new();
+        let postfix: Vec<_> = postfix
+            .into_iter()
+            .enumerate()
+            .filter_map(|(pos, token)| {
+                Some(match token {
+                    OutputToken::Value(value) => OutputToken::Value(match classify(value) {
+                        Binding::Value(value) => value,
+                        Binding::Variable(name) => match self.get(&name) {
+                            Some(value) => value.clone(),
+                            None => {
+                                unbound.push(UnboundVariable { name, pos });
+                                return None;
+                            }
+                        },
+                    }),
+                    OutputToken::Operator(operator) => OutputToken::Operator(operator),
+                    OutputToken::Function(function) => OutputToken::Function(function),
+                })
+            })
+            .collect();
+        if unbound.is_empty() {
+            Ok(postfix)
+        } else {
+            Err(unbound)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::{Binding, Params, UnboundVariable};
+    use crate::{op::Math, to_postfix, InputToken, OutputToken};
+
+    #[test]
+    fn lists_all_unbound_variables() {
+        // a + b * a - 1
+        let postfix = to_postfix([
+            InputToken::<_, (), _>::Value(Binding::Variable("a")),
+            InputToken::Operator(Math::Add),
+            InputToken::Value(Binding::Variable("b")),
+            InputToken::Operator(Math::Mul),
+            InputToken::Value(Binding::Variable("a")),
+            InputToken::Operator(Math::Sub),
+            InputToken::Value(Binding::Value(1)),
+        ])
+        .expect("valid expression");
+        let empty = Params::<_, i32>::new();
+        assert_eq!(
+            empty.bind(postfix.clone(), |value| value),
+            Err(vec![
+                UnboundVariable { name: "a", pos: 0 },
+                UnboundVariable { name: "b", pos: 1 },
+                UnboundVariable { name: "a", pos: 2 },
+            ])
+        );
+        let params = Params::new().with("a", 2).with("b", 3);
+        let bound = params.bind(postfix, |value| value).expect("all bound");
+        assert_eq!(bound[..3], [2, 3, 2].map(OutputToken::Value));
+    }
+}
@@ -0,0 +1,396 @@
+//! One table of functions for validation, conversion and evaluation.
+//!
+//! A `FunctionRegistry` stores the accepted number of arguments and the implementation of every
+//! function. `FunctionRegistry::to_postfix` converts an expression and rejects unknown functions
+//! and calls with the wrong number of arguments, `FunctionRegistry::evaluator` adds the functions
+//! to an evaluator for values and operators.
+//!
+//! ```rust
+//! use gyard::{
+//!     eval::{eval_postfix_with_arity, registry::{FunctionRegistry, RegistryError}, Evaluator},
+//!     op::Math,
+//!     InputToken,
+//! };
+//!
+//! struct Arithmetic;
+//! impl Evaluator<f64, &str, Math> for Arithmetic {
+//!     type Value = f64;
+//!     type Error = ();
+//!     fn value(&mut self, value: f64) -> Result<f64, ()> {
+//!         Ok(value)
+//!     }
+//!     fn operator(&mut self, _: Math, args: &mut Vec<f64>) -> Result<f64, ()> {
+//!         Ok(args[0] + args[1])
+//!     }
+//!     fn function(&mut self, _: &str, _: &mut Vec<f64>) -> Result<f64, ()> {
+//!         Err(())
+//!     }
+//!     fn function_arity(&self, _: &&str) -> usize {
+//!         0
+//!     }
+//! }
+//!
+//! let registry = FunctionRegistry::new()
+//!     .with_function("max", 1..=usize::MAX, |args: &mut Vec<f64>| {
+//!         Ok(args.iter().copied().fold(f64::MIN, f64::max))
+//!     });
+//! // max ( 1 , 2 + 3 )
+//! let postfix = registry.to_postfix([
+//!     InputToken::Function("max"),
+//!     InputToken::LeftParen,
+//!     InputToken::Value(1.),
+//!     InputToken::ArgSeparator,
+//!     InputToken::Value(2.),
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Value(3.),
+//!     InputToken::RightParen,
+//! ]).unwrap();
+//! let mut evaluator = registry.evaluator(Arithmetic);
+//! assert_eq!(eval_postfix_with_arity(postfix, &mut evaluator), Ok(5.));
+//!
+//! let unknown = registry.to_postfix([
+//!     InputToken::<_, _, Math>::Function("min"),
+//!     InputToken::LeftParen,
+//!     InputToken::Value(1.),
+//!     InputToken::RightParen,
+//! ]);
+//! assert_eq!(unknown, Err(RegistryError::UnknownFunction { pos: 1, function: "min" }));
+//! ```
+
+use alloc::{boxed::Box, vec::Vec};
+use core::{hash::Hash, ops::RangeInclusive};
+use std::collections::HashMap;
+
+use super::Evaluator;
+use crate::{
+    to_postfix_with_arity, Call, ErrorCode, InputToken, Operator, OutputToken, ParenMissmatchError,
+};
+
+/// The implementation of a function in a `FunctionRegistry`.
+/// `args` contains exactly the arguments the function was called with.
+pub type Callback<'a, T, E> = Box<dyn Fn(&mut Vec<T>) -> Result<T, E> + Send + Sync + 'a>;
+
+type RegistryResult<V, F, O> = Result<Vec<OutputToken<V, Call<F>, O>>, RegistryError<F>>;
+
+/// This error is returned by `FunctionRegistry::to_postfix` and `FunctionRegistry::validate`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RegistryError<F> {
+    /// The parentheses inside the expression do not match.
+    ParenMissmatch(ParenMissmatchError),
+    /// The function is not registered.
+    UnknownFunction {
+        /// The position of the function in the postfix expression
+        pos: usize,
+        /// The function
+        function: F,
+    },
+    /// The function was called with a number of arguments it does not accept.
+    ArityMismatch {
+        /// The position of the function in the postfix expression
+        pos: usize,
+        /// The function
+        function: F,
+        /// The number of arguments the function was called with
+        found: usize,
+    },
+}
+
+impl<F> RegistryError<F> {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            RegistryError::ParenMissmatch(error) => error.code(),
+            RegistryError::UnknownFunction { .. } => ErrorCode::UnknownFunction,
+            RegistryError::ArityMismatch { .. } => ErrorCode::ArityMismatch,
+        }
+    }
+}
+
+impl<F> From<ParenMissmatchError> for RegistryError<F> {
+    fn from(error: ParenMissmatchError) -> Self {
+        RegistryError::ParenMissmatch(error)
+    }
+}
+
+impl<F: core::fmt::Debug> core::fmt::Display for RegistryError<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RegistryError::ParenMissmatch(error) => error.fmt(f),
+            RegistryError::UnknownFunction { pos, function } => {
+                write!(f, "Unknown function {function:?} at position {pos}")
+            }
+            RegistryError::ArityMismatch {
+                pos,
+                function,
+                found,
+            } => write!(
+                f,
+                "Function {function:?} at position {pos} does not accept {found} arguments"
+            ),
+        }
+    }
+}
+
+impl<F: core::fmt::Debug> core::error::Error for RegistryError<F> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            RegistryError::ParenMissmatch(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+struct Entry<'a, T, E> {
+    arity: RangeInclusive<usize>,
+    callback: Callback<'a, T, E>,
+}
+
+/// The functions of an application with the number of arguments they accept and their
+/// implementation.
+pub struct FunctionRegistry<'a, F, T, E> {
+    functions: HashMap<F, Entry<'a, T, E>>,
+}
+
+impl<F, T, E> Default for FunctionRegistry<'_, F, T, E> {
+    fn default() -> Self {
+        Self {
+            functions: HashMap::new(),
+        }
+    }
+}
+
+impl<'a, F: Eq + Hash, T, E> FunctionRegistry<'a, F, T, E> {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `function`. It accepts every number of arguments in `arity`.
+    /// A function that is already registered is replaced.
+    pub fn with_function(
+        mut self,
+        function: F,
+        arity: RangeInclusive<usize>,
+        callback: impl Fn(&mut Vec<T>) -> Result<T, E> + Send + Sync + 'a,
+    ) -> Self {
+        self.functions.insert(
+            function,
+            Entry {
+                arity,
+                callback: Box::new(callback),
+            },
+        );
+        self
+    }
+
+    /// Returns the number of arguments `function` accepts or `None` if it is not registered.
+    pub fn arity(&self, function: &F) -> Option<&RangeInclusive<usize>> {
+        self.functions.get(function).map(|entry| &entry.arity)
+    }
+
+    /// Returns true if `function` is registered and accepts `arity` arguments.
+    pub fn accepts(&self, function: &F, arity: usize) -> bool {
+        self.arity(function)
+            .is_some_and(|range| range.contains(&arity))
+    }
+
+    /// Checks that every function in `postfix` is registered and called with a number of
+    /// arguments it accepts.
+    pub fn validate<V, O>(
+        &self,
+        postfix: &[OutputToken<V, Call<F>, O>],
+    ) -> Result<(), RegistryError<F>>
+    where
+        F: Clone,
+    {
+        for (pos, token) in postfix.iter().enumerate() {
+            let OutputToken::Function(call) = token else {
+                continue;
+            };
+            match self.arity(&call.function) {
+                None => {
+                    return Err(RegistryError::UnknownFunction {
+                        pos,
+                        function: call.function.clone(),
+                    })
+                }
+                Some(range) if !range.contains(&call.arity) => {
+                    return Err(RegistryError::ArityMismatch {
+                        pos,
+                        function: call.function.clone(),
+                        found: call.arity,
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts `infix` with `to_postfix_with_arity` and validates the result.
+    pub fn to_postfix<V, O: Operator>(
+        &self,
+        infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+    ) -> RegistryResult<V, F, O>
+    where
+        F: Clone,
+    {
+        let postfix = to_postfix_with_arity(infix)?;
+        self.validate(&postfix)?;
+        Ok(postfix)
+    }
+
+    /// Wraps `evaluator` to evaluate registered functions with their callbacks. Values,
+    /// operators and functions that are not registered are evaluated by `evaluator`.
+    pub fn evaluator<Ev>(&self, evaluator: Ev) -> Registered<'_, 'a, Ev, F, T, E> {
+        Registered {
+            registry: self,
+            evaluator,
+        }
+    }
+}
+
+/// An evaluator using the functions of a `FunctionRegistry`. Created by
+/// `FunctionRegistry::evaluator`.
+pub struct Registered<'r, 'a, Ev, F, T, E> {
+    registry: &'r FunctionRegistry<'a, F, T, E>,
+    evaluator: Ev,
+}
+
+impl<Ev, F, T, E> Registered<'_, '_, Ev, F, T, E> {
+    /// Returns the wrapped evaluator.
+    pub fn into_inner(self) -> Ev {
+        self.evaluator
+    }
+}
+
+impl<V, F, O, T, E, Ev> Evaluator<V, F, O> for Registered<'_, '_, Ev, F, T, E>
+where
+    F: Eq + Hash,
+    Ev: Evaluator<V, F, O, Value = T, Error = E>,
+{
+    type Value = T;
+    type Error = E;
+
+    fn value(&mut self, value: V) -> Result<T, E> {
+        self.evaluator.value(value)
+    }
+
+    fn operator(&mut self, operator: O, args: &mut Vec<T>) -> Result<T, E> {
+        self.evaluator.operator(operator, args)
+    }
+
+    fn function(&mut self, function: F, args: &mut Vec<T>) -> Result<T, E> {
+        match self.registry.functions.get(&function) {
+            Some(entry) => (entry.callback)(args),
+            None => self.evaluator.function(function, args),
+        }
+    }
+
+    fn function_arity(&self, function: &F) -> usize {
+        match self.registry.arity(function) {
+            Some(range) => *range.start(),
+            None => self.evaluator.function_arity(function),
+        }
+    }
+
+    fn accepted_arity(&self, function: &F) -> RangeInclusive<usize> {
+        match self.registry.arity(function) {
+            Some(range) => range.clone(),
+            None => self.evaluator.accepted_arity(function),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FunctionRegistry, RegistryError};
+    use crate::{
+        eval::{eval_postfix_with_arity, EvalError, Evaluator},
+        op::Math,
+        Call, OutputToken,
+    };
+
+    struct Values;
+
+    impl Evaluator<i64, &str, Math> for Values {
+        type Value = i64;
+        type Error = &'static str;
+
+        fn value(&mut self, value: i64) -> Result<i64, &'static str> {
+            Ok(value)
+        }
+
+        fn operator(&mut self, _: Math, _: &mut Vec<i64>) -> Result<i64, &'static str> {
+            Err("no operators")
+        }
+
+        fn function(&mut self, _: &str, _: &mut Vec<i64>) -> Result<i64, &'static str> {
+            Err("unknown function")
+        }
+
+        fn function_arity(&self, _: &&str) -> usize {
+            0
+        }
+    }
+
+    #[test]
+    fn validation_and_evaluation_agree() {
+        let registry = FunctionRegistry::new()
+            .with_function("neg", 1..=1, |args: &mut Vec<i64>| Ok(-args[0]))
+            .with_function("sum", 0..=3, |args: &mut Vec<i64>| Ok(args.iter().sum()));
+        let call =
+            |function, arity| OutputToken::<i64, _, Math>::Function(Call { function, arity });
+        let postfix = vec![
+            OutputToken::Value(1),
+            OutputToken::Value(2),
+            call("sum", 2),
+            call("neg", 1),
+        ];
+        assert_eq!(registry.validate(&postfix), Ok(()));
+        assert_eq!(
+            eval_postfix_with_arity(postfix, &mut registry.evaluator(Values)),
+            Ok(-3)
+        );
+
+        let postfix = vec![OutputToken::Value(1), call("neg", 2)];
+        assert_eq!(
+            registry.validate(&postfix),
+            Err(RegistryError::ArityMismatch {
+                pos: 1,
+                function: "neg",
+                found: 2
+            })
+        );
+        assert_eq!(
+            eval_postfix_with_arity(postfix, &mut registry.evaluator(Values)),
+            Err(EvalError::ArityMismatch {
+                pos: 1,
+                expected: 1..=1,
+                found: 2
+            })
+        );
+
+        let postfix = vec![OutputToken::Value(1); 4]
+            .into_iter()
+            .chain([call("sum", 4)])
+            .collect::<Vec<_>>();
+        let error = eval_postfix_with_arity(postfix, &mut registry.evaluator(Values))
+            .expect_err("sum takes at most 3 arguments");
+        assert_eq!(
+            error,
+            EvalError::ArityMismatch {
+                pos: 4,
+                expected: 0..=3,
+                found: 4
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Function at position 4 takes 0 to 3 arguments but 4 were given"
+        );
+    }
+}
@@ -0,0 +1,501 @@
+//! A tokenizer for Excel style formulas.
+//!
+//! The lexer reads the operators of `op::Spreadsheet` and produces `Operand` values:
+//!
+//! - Numbers like `12`, `0.5` or `1e-3`, strings like `"say ""hi"""` and `TRUE` or `FALSE`.
+//! - Cell references in A1 style like `B3` or `$B$3`, or in R1C1 style like `R3C2` or
+//!   `R[-1]C`. `Style` selects the notation.
+//! - Sheet qualified references and names like `Sheet1!A1` or `'Other sheet'!Total`.
+//! - Every other name is a defined name, unless it is followed by `(`, then it is a function.
+//!
+//! A leading `=` is skipped. Ranges like `A1:B2` are written with the `Spreadsheet::Range`
+//! operator. A `-` in front of a value is `Spreadsheet::Negate`, a `+` in front of a value is
+//! skipped.
+//!
+//! ```rust
+//! use gyard::{
+//!     excel::{tokenize, CellRef, Coordinate, Operand, Style},
+//!     op::Spreadsheet,
+//!     to_postfix, OutputToken,
+//! };
+//!
+//! let infix = tokenize("=SUM(A1:$B$2) & \"%\"", Style::A1).unwrap();
+//! let postfix = to_postfix(infix.into_iter().map(|token| token.value)).unwrap();
+//! let cell = |column, row| OutputToken::Value(Operand::Cell(CellRef { sheet: None, column, row }));
+//! assert_eq!(postfix, [
+//!     cell(Coordinate::Relative(1), Coordinate::Relative(1)),
+//!     cell(Coordinate::Absolute(2), Coordinate::Absolute(2)),
+//!     OutputToken::Operator(Spreadsheet::Range),
+//!     OutputToken::Function("SUM".to_string()),
+//!     OutputToken::Value(Operand::Text("%".to_string())),
+//!     OutputToken::Operator(Spreadsheet::Concat),
+//! ]);
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::ops::Range;
+
+use crate::{op::Spreadsheet, span::Spanned, InputToken};
+
+/// A token produced by `Lexer`
+pub type Token = InputToken<Operand, String, Spreadsheet>;
+
+/// The operator symbols. A symbol must be listed before every symbol it starts with.
+const OPERATORS: [(&str, Spreadsheet); 13] = [
+    ("<>", Spreadsheet::Ne),
+    ("<=", Spreadsheet::Le),
+    (">=", Spreadsheet::Ge),
+    ("=", Spreadsheet::Eq),
+    ("<", Spreadsheet::Lt),
+    (">", Spreadsheet::Gt),
+    ("&", Spreadsheet::Concat),
+    ("+", Spreadsheet::Add),
+    ("*", Spreadsheet::Mul),
+    ("/", Spreadsheet::Div),
+    ("^", Spreadsheet::Exponent),
+    ("%", Spreadsheet::Percent),
+    (":", Spreadsheet::Range),
+];
+
+/// The notation of cell references
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Style {
+    /// References like `B3` and `$B$3`
+    A1,
+    /// References like `R3C2` and `R[-1]C[1]`
+    R1C1,
+}
+
+/// The row or column of a cell reference. Indices start at 1, column `A` is 1.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Coordinate {
+    /// An index that does not change if the formula is copied, i.e. `$B` or `R3`
+    Absolute(u32),
+    /// An index that moves if the formula is copied, i.e. `B`
+    Relative(u32),
+    /// An offset from the cell containing the formula, i.e. `R[-1]`
+    Offset(i32),
+}
+
+/// A reference to a single cell
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CellRef {
+    /// The sheet if the reference is sheet qualified
+    pub sheet: Option<String>,
+    /// The column
+    pub column: Coordinate,
+    /// The row
+    pub row: Coordinate,
+}
+
+/// A value token of a formula
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Operand {
+    /// A number
+    Number(f64),
+    /// A string
+    Text(String),
+    /// `TRUE` or `FALSE`
+    Bool(bool),
+    /// A cell reference
+    Cell(CellRef),
+    /// A defined name
+    Name {
+        /// The sheet if the name is sheet qualified
+        sheet: Option<String>,
+        /// The name
+        name: String,
+    },
+}
+
+/// This error is returned if the input is not a valid formula.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum FormulaError {
+    /// The character at byte offset `pos` does not start a token.
+    UnexpectedCharacter {
+        /// The unexpected character
+        character: char,
+        /// The byte offset of the character
+        pos: usize,
+    },
+    /// The text at `span` looks like a number but can not be parsed, i.e. `1.2.3`.
+    InvalidNumber {
+        /// The byte range of the number
+        span: Range<usize>,
+    },
+    /// The string or quoted sheet name starting at byte offset `pos` is not closed.
+    Unterminated {
+        /// The byte offset of the opening quote
+        pos: usize,
+    },
+}
+
+impl core::fmt::Display for FormulaError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FormulaError::UnexpectedCharacter { character, pos } => {
+                write!(f, "Unexpected character {character:?} at offset {pos}")
+            }
+            FormulaError::InvalidNumber { span } => {
+                write!(f, "Invalid number at offset {}", span.start)
+            }
+            FormulaError::Unterminated { pos } => {
+                write!(f, "Missing closing quote for quote at offset {pos}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for FormulaError {}
+
+/// Reads the tokens of a formula. Stops after the first error.
+#[derive(Debug, Clone)]
+pub struct Lexer<'a> {
+    input: &'a str,
+    pos: usize,
+    style: Style,
+    expects_value: bool,
+}
+
+impl<'a> Lexer<'a> {
+    /// Creates a lexer reading `input` with cell references in `style`.
+    pub fn new(input: &'a str, style: Style) -> Self {
+        let trimmed = input.trim_start();
+        let pos = match trimmed.strip_prefix('=') {
+            Some(_) => input.len() - trimmed.len() + 1,
+            None => 0,
+        };
+        Self {
+            input,
+            pos,
+            style,
+            expects_value: true,
+        }
+    }
+
+    fn token(&self, rest: &str, c: char) -> Result<(Token, usize), FormulaError> {
+        if c.is_ascii_digit() || (c == '.' && rest[1..].starts_with(|c: char| c.is_ascii_digit())) {
+            return self.number(rest);
+        }
+        if c == '"' {
+            let (text, len) =
+                quoted(rest, '"').ok_or(FormulaError::Unterminated { pos: self.pos })?;
+            return Ok((InputToken::Value(Operand::Text(text)), len));
+        }
+        if c == '\'' {
+            let (sheet, len) =
+                quoted(rest, '\'').ok_or(FormulaError::Unterminated { pos: self.pos })?;
+            if !rest[len..].starts_with('!') {
+                return Err(FormulaError::UnexpectedCharacter {
+                    character: '\'',
+                    pos: self.pos,
+                });
+            }
+            let (operand, operand_len) = self.operand(&rest[len + 1..], Some(sheet), len + 1)?;
+            return Ok((InputToken::Value(operand), len + 1 + operand_len));
+        }
+        if is_name_start(c) {
+            let len = name_len(rest);
+            if rest[len..].starts_with('(') {
+                return Ok((InputToken::Function(rest[..len].to_string()), len));
+            }
+            if rest[len..].starts_with('!') {
+                let sheet = rest[..len].to_string();
+                let (operand, operand_len) =
+                    self.operand(&rest[len + 1..], Some(sheet), len + 1)?;
+                return Ok((InputToken::Value(operand), len + 1 + operand_len));
+            }
+            let (operand, len) = self.operand(rest, None, 0)?;
+            return Ok((InputToken::Value(operand), len));
+        }
+        let token = match c {
+            '(' => InputToken::LeftParen,
+            ')' => InputToken::RightParen,
+            ',' => InputToken::ArgSeparator,
+            '-' if self.expects_value => InputToken::Operator(Spreadsheet::Negate),
+            '-' => InputToken::Operator(Spreadsheet::Sub),
+            _ => {
+                return OPERATORS
+                    .iter()
+                    .find(|(symbol, _)| rest.starts_with(symbol))
+                    .map(|(symbol, operator)| (InputToken::Operator(*operator), symbol.len()))
+                    .ok_or(FormulaError::UnexpectedCharacter {
+                        character: c,
+                        pos: self.pos,
+                    })
+            }
+        };
+        Ok((token, 1))
+    }
+
+    fn number(&self, rest: &str) -> Result<(Token, usize), FormulaError> {
+        let mut len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        // An exponent only belongs to the number if digits follow
+        if let Some(exponent) = rest[len..].strip_prefix(['e', 'E']) {
+            let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+            if digits.starts_with(|c: char| c.is_ascii_digit()) {
+                let end = digits
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(digits.len());
+                len = rest.len() - digits.len() + end;
+            }
+        }
+        match rest[..len].parse() {
+            Ok(number) => Ok((InputToken::Value(Operand::Number(number)), len)),
+            Err(_) => Err(FormulaError::InvalidNumber {
+                span: self.pos..self.pos + len,
+            }),
+        }
+    }
+
+    /// Reads a cell reference, boolean or defined name. `offset` is the position of `rest` relative
+    /// to the current token.
+    fn operand(
+        &self,
+        rest: &str,
+        sheet: Option<String>,
+        offset: usize,
+    ) -> Result<(Operand, usize), FormulaError> {
+        if self.style == Style::R1C1 {
+            if let Some(((column, row), len)) = r1c1(rest) {
+                if !rest[len..].starts_with(is_name_char) {
+                    return Ok((Operand::Cell(CellRef { sheet, column, row }), len));
+                }
+            }
+        }
+        let len = name_len(rest);
+        if len == 0 {
+            return Err(match rest.chars().next() {
+                Some(character) => FormulaError::UnexpectedCharacter {
+                    character,
+                    pos: self.pos + offset,
+                },
+                None => FormulaError::UnexpectedCharacter {
+                    character: '!',
+                    pos: self.pos + offset - 1,
+                },
+            });
+        }
+        let name = &rest[..len];
+        let operand = match a1(name).filter(|_| self.style == Style::A1) {
+            Some((column, row)) => Operand::Cell(CellRef { sheet, column, row }),
+            None if sheet.is_none() && name.eq_ignore_ascii_case("TRUE") => Operand::Bool(true),
+            None if sheet.is_none() && name.eq_ignore_ascii_case("FALSE") => Operand::Bool(false),
+            None => Operand::Name {
+                sheet,
+                name: name.to_string(),
+            },
+        };
+        Ok((operand, len))
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Result<Spanned<Token>, FormulaError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.input[self.pos..].trim_start();
+        self.pos = self.input.len() - rest.len();
+        let c = rest.chars().next()?;
+        // A prefix `+` does not change its operand
+        if c == '+' && self.expects_value {
+            self.pos += 1;
+            return self.next();
+        }
+        match self.token(rest, c) {
+            Ok((value, len)) => {
+                let span = self.pos..self.pos + len;
+                self.pos += len;
+                self.expects_value = matches!(
+                    value,
+                    InputToken::LeftParen
+                        | InputToken::ArgSeparator
+                        | InputToken::Operator(
+                            Spreadsheet::Negate
+                                | Spreadsheet::Range
+                                | Spreadsheet::Exponent
+                                | Spreadsheet::Mul
+                                | Spreadsheet::Div
+                                | Spreadsheet::Add
+                                | Spreadsheet::Sub
+                                | Spreadsheet::Concat
+                                | Spreadsheet::Eq
+                                | Spreadsheet::Ne
+                                | Spreadsheet::Lt
+                                | Spreadsheet::Le
+                                | Spreadsheet::Gt
+                                | Spreadsheet::Ge
+                        )
+                );
+                Some(Ok(Spanned { value, span }))
+            }
+            Err(error) => {
+                self.pos = self.input.len();
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// Reads all tokens of the formula `input`.
+pub fn tokenize(input: &str, style: Style) -> Result<Vec<Spanned<Token>>, FormulaError> {
+    Lexer::new(input, style).collect()
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '$'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '$' | '.')
+}
+
+fn name_len(rest: &str) -> usize {
+    if !rest.starts_with(is_name_start) {
+        return 0;
+    }
+    rest.find(|c| !is_name_char(c)).unwrap_or(rest.len())
+}
+
+/// Reads the text between `quote`s at the start of `rest`. Two quotes are an escaped quote.
+/// Returns the text and the length including the quotes.
+fn quoted(rest: &str, quote: char) -> Option<(String, usize)> {
+    let mut text = String::new();
+    let mut chars = rest.char_indices().skip(1).peekable();
+    while let Some((i, c)) = chars.next() {
+        if c != quote {
+            text.push(c);
+        } else if chars.next_if(|(_, c)| *c == quote).is_some() {
+            text.push(quote);
+        } else {
+            return Some((text, i + 1));
+        }
+    }
+    None
+}
+
+/// Parses a reference like `$B3`.
+fn a1(name: &str) -> Option<(Coordinate, Coordinate)> {
+    let (column_absolute, rest) = strip_dollar(name);
+    let letters = rest
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(rest.len());
+    if !(1..=3).contains(&letters) {
+        return None;
+    }
+    let column = rest[..letters].bytes().fold(0, |index, letter| {
+        index * 26 + u32::from(letter.to_ascii_uppercase() - b'A') + 1
+    });
+    let (row_absolute, digits) = strip_dollar(&rest[letters..]);
+    if !digits.bytes().all(|digit| digit.is_ascii_digit()) || digits.starts_with('0') {
+        return None;
+    }
+    let row = digits.parse().ok()?;
+    let coordinate = |index, absolute| match absolute {
+        true => Coordinate::Absolute(index),
+        false => Coordinate::Relative(index),
+    };
+    Some((
+        coordinate(column, column_absolute),
+        coordinate(row, row_absolute),
+    ))
+}
+
+fn strip_dollar(text: &str) -> (bool, &str) {
+    match text.strip_prefix('$') {
+        Some(text) => (true, text),
+        None => (false, text),
+    }
+}
+
+/// Parses a reference like `R[-1]C2` at the start of `rest` and returns its length.
+fn r1c1(rest: &str) -> Option<((Coordinate, Coordinate), usize)> {
+    let axis = |text: &str, prefix: char| -> Option<(Coordinate, usize)> {
+        let text = text.strip_prefix([prefix, prefix.to_ascii_lowercase()])?;
+        if let Some(offset) = text.strip_prefix('[') {
+            let end = offset.find(']')?;
+            let offset = offset[..end].parse().ok()?;
+            return Some((Coordinate::Offset(offset), end + 3));
+        }
+        let digits = text
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(text.len());
+        match digits {
+            0 => Some((Coordinate::Offset(0), 1)),
+            _ => Some((
+                Coordinate::Absolute(text[..digits].parse().ok()?),
+                digits + 1,
+            )),
+        }
+    };
+    let (row, row_len) = axis(rest, 'R')?;
+    let (column, column_len) = axis(&rest[row_len..], 'C')?;
+    Some(((column, row), row_len + column_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::{tokenize, CellRef, Coordinate, FormulaError, Operand, Style};
+    use crate::{op::Spreadsheet, to_postfix, InputToken, OutputToken};
+
+    #[test]
+    fn sheets_r1c1_and_unary_operators() {
+        let postfix = to_postfix(
+            tokenize("'Q1 ''24'!R[-1]C2 * -2^2 + Rates!Tax%", Style::R1C1)
+                .expect("valid formula")
+                .into_iter()
+                .map(|token| token.value),
+        )
+        .expect("valid expression");
+        assert_eq!(
+            postfix,
+            [
+                OutputToken::Value(Operand::Cell(CellRef {
+                    sheet: Some("Q1 '24".to_string()),
+                    column: Coordinate::Absolute(2),
+                    row: Coordinate::Offset(-1),
+                })),
+                OutputToken::Value(Operand::Number(2.)),
+                OutputToken::Operator(Spreadsheet::Negate),
+                OutputToken::Value(Operand::Number(2.)),
+                OutputToken::Operator(Spreadsheet::Exponent),
+                OutputToken::Operator(Spreadsheet::Mul),
+                OutputToken::Value(Operand::Name {
+                    sheet: Some("Rates".to_string()),
+                    name: "Tax".to_string()
+                }),
+                OutputToken::Operator(Spreadsheet::Percent),
+                OutputToken::Operator(Spreadsheet::Add),
+            ]
+        );
+
+        let tokens = tokenize("RC <> +TRUE", Style::A1).expect("valid formula");
+        assert_eq!(
+            tokens[0].value,
+            InputToken::Value(Operand::Name {
+                sheet: None,
+                name: "RC".to_string()
+            })
+        );
+        assert_eq!(tokens[2].value, InputToken::Value(Operand::Bool(true)));
+        assert_eq!(
+            tokenize("\"open", Style::A1),
+            Err(FormulaError::Unterminated { pos: 0 })
+        );
+    }
+}
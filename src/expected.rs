@@ -0,0 +1,123 @@
+//! Sets of tokens that would have been valid where an error occurred.
+//!
+//! Conversion and validation errors expose an `Expected` set through their `expected` method and
+//! append it to their `Display` output, i.e. "expected a value, '(' or a function".
+//!
+//! ```rust
+//! use gyard::{ast::to_ast, expected::Expected, op::Math, InputToken};
+//!
+//! // 1 +
+//! let error = to_ast([InputToken::<_, (), _>::Value(1), InputToken::Operator(Math::Add)])
+//!     .unwrap_err();
+//! assert_eq!(error.expected(), Some(Expected::OPERAND));
+//! assert_eq!(
+//!     error.to_string(),
+//!     "Token at position 1 requires 2 values but only 1 are available, \
+//!      expected a value, '(' or a function"
+//! );
+//! ```
+
+/// A set of token kinds. Combine sets with `|`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Expected {
+    bits: u8,
+}
+
+/// The token kinds in the order they are listed by `Display`
+const NAMES: [(Expected, &str); 7] = [
+    (Expected::VALUE, "a value"),
+    (Expected::LEFT_PAREN, "'('"),
+    (Expected::FUNCTION, "a function"),
+    (Expected::OPERATOR, "an operator"),
+    (Expected::RIGHT_PAREN, "')'"),
+    (Expected::ARG_SEPARATOR, "','"),
+    (Expected::END, "the end of the expression"),
+];
+
+impl Expected {
+    /// No token
+    pub const NONE: Self = Self { bits: 0 };
+    /// A value
+    pub const VALUE: Self = Self { bits: 1 };
+    /// A left parenthesis
+    pub const LEFT_PAREN: Self = Self { bits: 1 << 1 };
+    /// A function
+    pub const FUNCTION: Self = Self { bits: 1 << 2 };
+    /// An operator
+    pub const OPERATOR: Self = Self { bits: 1 << 3 };
+    /// A right parenthesis
+    pub const RIGHT_PAREN: Self = Self { bits: 1 << 4 };
+    /// An argument separator
+    pub const ARG_SEPARATOR: Self = Self { bits: 1 << 5 };
+    /// The end of the expression
+    pub const END: Self = Self { bits: 1 << 6 };
+    /// Any token that starts an operand
+    pub const OPERAND: Self = Self {
+        bits: Self::VALUE.bits | Self::LEFT_PAREN.bits | Self::FUNCTION.bits,
+    };
+
+    /// Returns true if every token kind of `other` is part of the set.
+    pub fn contains(self, other: Self) -> bool {
+        self.bits & other.bits == other.bits
+    }
+
+    /// Returns true if the set does not contain any token kind.
+    pub fn is_empty(self) -> bool {
+        self.bits == 0
+    }
+}
+
+impl core::ops::BitOr for Expected {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self {
+            bits: self.bits | rhs.bits,
+        }
+    }
+}
+
+impl core::fmt::Display for Expected {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut names = NAMES
+            .iter()
+            .filter(|(kind, _)| self.contains(*kind))
+            .map(|(_, name)| name)
+            .peekable();
+        let mut first = true;
+        while let Some(name) = names.next() {
+            let separator = match (first, names.peek().is_none()) {
+                (true, _) => "",
+                (false, true) => " or ",
+                (false, false) => ", ",
+            };
+            write!(f, "{separator}{name}")?;
+            first = false;
+        }
+        if first {
+            write!(f, "nothing")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::Expected;
+
+    #[test]
+    fn display() {
+        assert_eq!(Expected::NONE.to_string(), "nothing");
+        assert_eq!(Expected::RIGHT_PAREN.to_string(), "')'");
+        assert_eq!(
+            (Expected::OPERATOR | Expected::END).to_string(),
+            "an operator or the end of the expression"
+        );
+        assert!(Expected::OPERAND.contains(Expected::FUNCTION | Expected::VALUE));
+    }
+}
@@ -0,0 +1,449 @@
+//! A ready to use calculator.
+//!
+//! `Expression::parse` tokenizes a string with `lexer`, converts it and checks that every
+//! function exists and is called with a supported number of arguments. `Expression::eval`
+//! evaluates it with `value::Calculator`. Names that are not followed by `(` are variables and are
+//! looked up in the variables passed to `eval`. The positions in a `ParseError` are byte offsets
+//! into the parsed string.
+//!
+//! There is no unary minus. A `-` directly in front of a number is part of the number, like in
+//! `2 * -3`, but `-x` or `-(1 + 2)` are missing an operand. Write `0 - x` or `-1 * x` instead.
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//! use gyard::{expression::Expression, value::Value};
+//!
+//! let expr = Expression::parse("2 * max(x, 3) + 1").unwrap();
+//! let variables = HashMap::from([("x", Value::Number(5.))]);
+//! assert_eq!(expr.eval(&variables), Ok(Value::Number(11.)));
+//! assert!(Expression::parse("2 * max()").is_err());
+//! ```
+
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::{
+    borrow::Borrow,
+    ops::{Range, RangeInclusive},
+};
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash},
+};
+
+use crate::{
+    ast::AstError,
+    eval::{self, Evaluator},
+    lexer::{tokenize, LexError},
+    op::All,
+    postfix::PostfixExpr,
+    span::Spanned,
+    to_postfix_with_arity,
+    value::{Calculator, Value, ValueError},
+    Call, InputToken, OutputToken, ParenMissmatchError,
+};
+
+/// A value token of an `Expression`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    /// A number
+    Number(f64),
+    /// A variable
+    Variable(String),
+}
+
+/// Provides the values of variables to `Expression::eval`.
+///
+/// This trait is implemented for maps from names to values and for closures
+/// `Fn(&str) -> Option<Value>`.
+pub trait Variables {
+    /// Returns the value of the variable `name` or `None` if it is not defined.
+    fn get(&self, name: &str) -> Option<Value>;
+}
+
+impl<K, S> Variables for HashMap<K, Value, S>
+where
+    K: Borrow<str> + Eq + Hash,
+    S: BuildHasher,
+{
+    fn get(&self, name: &str) -> Option<Value> {
+        HashMap::get(self, name).cloned()
+    }
+}
+
+impl<K: Borrow<str> + Ord> Variables for BTreeMap<K, Value> {
+    fn get(&self, name: &str) -> Option<Value> {
+        BTreeMap::get(self, name).cloned()
+    }
+}
+
+impl<F: Fn(&str) -> Option<Value>> Variables for F {
+    fn get(&self, name: &str) -> Option<Value> {
+        self(name)
+    }
+}
+
+/// This error is returned by `Expression::parse`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum ParseError {
+    /// The string can not be tokenized.
+    Lex(LexError),
+    /// The tokens do not form a valid expression.
+    Syntax {
+        /// The error. Its positions are byte offsets into the parsed string.
+        error: AstError,
+        /// The byte range of the token the error was found at, if there is one
+        span: Option<Range<usize>>,
+    },
+    /// The function does not exist or does not accept the number of arguments.
+    InvalidCall {
+        /// The name of the function
+        name: String,
+        /// The number of arguments
+        arity: usize,
+    },
+}
+
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseError::Lex(error) => error.fmt(f),
+            ParseError::Syntax { error, .. } => error.fmt(f),
+            ParseError::InvalidCall { name, arity } => {
+                write!(
+                    f,
+                    "Function {name} can not be called with {arity} arguments"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            ParseError::Lex(error) => Some(error),
+            ParseError::Syntax { error, .. } => Some(error),
+            ParseError::InvalidCall { .. } => None,
+        }
+    }
+}
+
+impl From<LexError> for ParseError {
+    fn from(error: LexError) -> Self {
+        Self::Lex(error)
+    }
+}
+
+/// This error is returned by `Expression::eval`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum EvalError {
+    /// The variable is not defined.
+    UnknownVariable(String),
+    /// An operator or function can not be applied to its operands.
+    Value(ValueError),
+}
+
+impl core::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EvalError::UnknownVariable(name) => write!(f, "Unknown variable {name}"),
+            EvalError::Value(error) => error.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for EvalError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            EvalError::UnknownVariable(_) => None,
+            EvalError::Value(error) => Some(error),
+        }
+    }
+}
+
+impl From<ValueError> for EvalError {
+    fn from(error: ValueError) -> Self {
+        Self::Value(error)
+    }
+}
+
+/// A parsed and validated math expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    postfix: PostfixExpr<Operand, String, All>,
+}
+
+impl Expression {
+    /// Parses and validates `input`.
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let tokens = tokenize(input)?;
+        let spans: Vec<_> = tokens.iter().map(|token| token.span.clone()).collect();
+        let mut infix = Vec::with_capacity(tokens.len());
+        let mut tokens = tokens.into_iter().peekable();
+        while let Some(Spanned { value: token, span }) = tokens.next() {
+            let called = tokens
+                .peek()
+                .is_some_and(|next| next.value == InputToken::LeftParen);
+            let token = match token {
+                InputToken::Function(name) if !called => InputToken::Value(Operand::Variable(name)),
+                token => token.map_value(Operand::Number),
+            };
+            infix.push(token.map(
+                |value| Spanned {
+                    value,
+                    span: span.clone(),
+                },
+                |value| Spanned {
+                    value,
+                    span: span.clone(),
+                },
+                |value| Spanned {
+                    value,
+                    span: span.clone(),
+                },
+            ));
+        }
+        let postfix = to_postfix_with_arity(infix)
+            .map_err(|error| syntax_error(AstError::ParenMissmatch(error), &spans, input))?;
+        let (tokens, spans): (Vec<_>, Vec<_>) = postfix
+            .into_iter()
+            .map(|token| match token {
+                OutputToken::Value(Spanned { value, span }) => (OutputToken::Value(value), span),
+                OutputToken::Function(Call {
+                    function: Spanned { value, span },
+                    arity,
+                }) => (
+                    OutputToken::Function(Call {
+                        function: value,
+                        arity,
+                    }),
+                    span,
+                ),
+                OutputToken::Operator(Spanned { value, span }) => {
+                    (OutputToken::Operator(value), span)
+                }
+            })
+            .unzip();
+        let postfix =
+            PostfixExpr::new(tokens).map_err(|error| syntax_error(error, &spans, input))?;
+        if let Some(call) = postfix.functions().find(|call| {
+            !Evaluator::<Value, _, All>::accepted_arity(&Calculator, &call.function)
+                .contains(&call.arity)
+        }) {
+            return Err(ParseError::InvalidCall {
+                name: call.function.clone(),
+                arity: call.arity,
+            });
+        }
+        Ok(Self { postfix })
+    }
+
+    /// Evaluates the expression. Variables are looked up in `variables`.
+    pub fn eval(&self, variables: &impl Variables) -> Result<Value, EvalError> {
+        match self.postfix.eval(&mut Resolver { variables }) {
+            Ok(value) => Ok(value),
+            Err(eval::EvalError::Evaluator { error, .. }) => Err(error),
+            Err(_) => unreachable!("Expression is always well-formed"),
+        }
+    }
+
+    /// Returns the names of all variables in the order they appear in the expression.
+    pub fn variables(&self) -> impl Iterator<Item = &str> {
+        self.postfix.values().filter_map(|value| match value {
+            Operand::Variable(name) => Some(name.as_str()),
+            Operand::Number(_) => None,
+        })
+    }
+
+    /// Returns the validated postfix expression.
+    pub fn postfix(&self) -> &PostfixExpr<Operand, String, All> {
+        &self.postfix
+    }
+}
+
+/// Resolves variables and evaluates everything else with `Calculator`.
+struct Resolver<'a, C> {
+    variables: &'a C,
+}
+
+impl<C: Variables> Evaluator<Operand, String, All> for Resolver<'_, C> {
+    type Value = Value;
+    type Error = EvalError;
+
+    fn value(&mut self, value: Operand) -> Result<Value, EvalError> {
+        match value {
+            Operand::Number(number) => Ok(Value::Number(number)),
+            Operand::Variable(name) => self
+                .variables
+                .get(&name)
+                .ok_or(EvalError::UnknownVariable(name)),
+        }
+    }
+
+    fn operator(&mut self, operator: All, args: &mut Vec<Value>) -> Result<Value, EvalError> {
+        Ok(Calculator.apply(operator, args)?)
+    }
+
+    fn function(&mut self, function: String, args: &mut Vec<Value>) -> Result<Value, EvalError> {
+        Ok(Calculator.call(&function, args)?)
+    }
+
+    fn function_arity(&self, function: &String) -> usize {
+        Evaluator::<Value, _, All>::function_arity(&Calculator, function)
+    }
+
+    fn accepted_arity(&self, function: &String) -> RangeInclusive<usize> {
+        Evaluator::<Value, _, All>::accepted_arity(&Calculator, function)
+    }
+
+    fn is_commutative(&self, operator: &All) -> bool {
+        Evaluator::<Value, String, _>::is_commutative(&Calculator, operator)
+    }
+}
+
+/// Replaces the token positions in `error` with byte offsets into `input`. `spans` contains the
+/// byte range of every token the positions refer to.
+fn syntax_error(error: AstError, spans: &[Range<usize>], input: &str) -> ParseError {
+    let span = |pos: usize| spans.get(pos).cloned().unwrap_or(input.len()..input.len());
+    let offset = |pos: usize| span(pos).start;
+    let (error, span) = match error {
+        AstError::ParenMissmatch(ParenMissmatchError::UnexpectedClosing { pos, open_pos }) => (
+            ParenMissmatchError::UnexpectedClosing {
+                pos: offset(pos),
+                open_pos: open_pos.map(offset),
+            },
+            span(pos),
+        ),
+        AstError::ParenMissmatch(ParenMissmatchError::UnclosedOpening { open_pos }) => (
+            ParenMissmatchError::UnclosedOpening {
+                open_pos: offset(open_pos),
+            },
+            span(open_pos),
+        ),
+        AstError::ParenMissmatch(ParenMissmatchError::UnexpectedSeparator { pos, open_pos }) => (
+            ParenMissmatchError::UnexpectedSeparator {
+                pos: offset(pos),
+                open_pos: open_pos.map(offset),
+            },
+            span(pos),
+        ),
+        AstError::StackUnderflow {
+            pos,
+            required,
+            available,
+        } => {
+            let error = AstError::StackUnderflow {
+                pos: offset(pos),
+                required,
+                available,
+            };
+            return ParseError::Syntax {
+                error,
+                span: Some(span(pos)),
+            };
+        }
+        error @ (AstError::EmptyExpression | AstError::UnusedValues { .. }) => {
+            return ParseError::Syntax { error, span: None }
+        }
+    };
+    ParseError::Syntax {
+        error: AstError::ParenMissmatch(error),
+        span: Some(span),
+    }
+}
+
+impl core::str::FromStr for Expression {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, ParseError> {
+        Self::parse(input)
+    }
+}
+
+impl core::fmt::Display for Operand {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Operand::Number(number) => number.fmt(f),
+            Operand::Variable(name) => f.write_str(name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{EvalError, Expression, ParseError};
+    use crate::{ast::AstError, value::Value, ParenMissmatchError};
+
+    #[test]
+    fn parse_errors_and_variables() {
+        assert_eq!(
+            Expression::parse("sqrt(1, 2)"),
+            Err(ParseError::InvalidCall {
+                name: "sqrt".to_string(),
+                arity: 2
+            })
+        );
+        assert_eq!(
+            Expression::parse("foo(1)"),
+            Err(ParseError::InvalidCall {
+                name: "foo".to_string(),
+                arity: 1
+            })
+        );
+        assert_eq!(
+            Expression::parse("10 +"),
+            Err(ParseError::Syntax {
+                error: AstError::StackUnderflow {
+                    pos: 3,
+                    required: 2,
+                    available: 1
+                },
+                span: Some(3..4)
+            })
+        );
+        assert_eq!(
+            Expression::parse("1 + max(2, 3"),
+            Err(ParseError::Syntax {
+                error: AstError::ParenMissmatch(ParenMissmatchError::UnclosedOpening {
+                    open_pos: 7
+                }),
+                span: Some(7..8)
+            })
+        );
+        assert_eq!(
+            Expression::parse("1 2"),
+            Err(ParseError::Syntax {
+                error: AstError::UnusedValues { count: 2 },
+                span: None
+            })
+        );
+
+        let expr: Expression = "a < b || !flag".parse().expect("valid expression");
+        assert_eq!(expr.variables().collect::<Vec<_>>(), ["a", "b", "flag"]);
+        let variables = |name: &str| match name {
+            "a" => Some(Value::Number(1.)),
+            "b" => Some(Value::Number(0.)),
+            _ => None,
+        };
+        assert_eq!(
+            expr.eval(&variables),
+            Err(EvalError::UnknownVariable("flag".to_string()))
+        );
+    }
+
+    #[test]
+    fn evaluation() {
+        let variables = HashMap::from([("x", Value::Number(4.)), ("y", Value::Number(-2.))]);
+        let eval = |input: &str| {
+            Expression::parse(input)
+                .expect("valid expression")
+                .eval(&variables)
+        };
+        assert_eq!(eval("1 + 2 * 3 ** 2"), Ok(Value::Number(19.)));
+        assert_eq!(eval("(1 + 2) * x"), Ok(Value::Number(12.)));
+        assert_eq!(eval("max(abs(y), sqrt(x), 1)"), Ok(Value::Number(2.)));
+        assert_eq!(eval("min(x, max(y, -3), 7, 0)"), Ok(Value::Number(-2.)));
+        assert_eq!(eval("x * -1 < y && !(x == 4)"), Ok(Value::Bool(false)));
+    }
+}
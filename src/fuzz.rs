@@ -0,0 +1,141 @@
+//! Structured fuzzing with the `arbitrary` crate.
+//!
+//! With the `arbitrary` feature the token types and the predefined operators implement
+//! `Arbitrary`. Arbitrary `InputToken`s form mostly unbalanced token streams, use them to fuzz the
+//! error handling.
+//!
+//! `Expression` instead generates a random tree and renders it as a well formed infix expression
+//! together with the postfix expression the conversion must produce. Use it to fuzz parsers and
+//! evaluators with valid input or to compare the conversion against the tree.
+//!
+//! ```rust
+//! use arbitrary::{Arbitrary, Unstructured};
+//! use gyard::{fuzz::Expression, op::All, to_postfix_with_arity};
+//!
+//! let data = [7, 1, 42, 3, 2, 0, 9, 8, 1, 5, 1, 4];
+//! let expression = Expression::<u8, u8, All>::arbitrary(&mut Unstructured::new(&data)).unwrap();
+//! assert_eq!(to_postfix_with_arity(expression.infix), Ok(expression.postfix));
+//! ```
+
+use alloc::vec::Vec;
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{ast::Expr, Call, InputToken, Operator, OutputToken};
+
+/// The maximum nesting depth of a generated expression
+const MAX_DEPTH: usize = 8;
+/// The maximum number of arguments of a generated function call
+const MAX_ARGS: usize = 3;
+
+/// A well formed infix expression and its postfix form.
+///
+/// Unary operators are generated as prefix operators. Operators taking no or more than two
+/// operands are never generated, a value is generated instead.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Expression<V, F, O> {
+    /// The infix expression
+    pub infix: Vec<InputToken<V, F, O>>,
+    /// The result of `to_postfix_with_arity` for `infix`
+    pub postfix: Vec<OutputToken<V, Call<F>, O>>,
+}
+
+impl<'a, V, F, O> Arbitrary<'a> for Expression<V, F, O>
+where
+    V: Arbitrary<'a> + Clone,
+    F: Arbitrary<'a> + Clone,
+    O: Arbitrary<'a> + Operator + Clone,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let tree = arbitrary_expr(u, MAX_DEPTH)?;
+        Ok(Self {
+            infix: tree.to_infix(),
+            postfix: tree.into_postfix(),
+        })
+    }
+}
+
+/// Never generates the deprecated `InputToken::ArgSeperator`.
+impl<'a, V, F, O> Arbitrary<'a> for InputToken<V, F, O>
+where
+    V: Arbitrary<'a>,
+    F: Arbitrary<'a>,
+    O: Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=5)? {
+            0 => InputToken::Value(u.arbitrary()?),
+            1 => InputToken::LeftParen,
+            2 => InputToken::RightParen,
+            3 => InputToken::Function(u.arbitrary()?),
+            4 => InputToken::ArgSeparator,
+            _ => InputToken::Operator(u.arbitrary()?),
+        })
+    }
+}
+
+fn arbitrary_expr<'a, V, F, O>(u: &mut Unstructured<'a>, depth: usize) -> Result<Expr<V, F, O>>
+where
+    V: Arbitrary<'a>,
+    F: Arbitrary<'a>,
+    O: Arbitrary<'a> + Operator,
+{
+    if depth == 0 || u.is_empty() {
+        return Ok(Expr::Value(u.arbitrary()?));
+    }
+    let args = |u: &mut Unstructured<'a>, count| {
+        (0..count)
+            .map(|_| arbitrary_expr(u, depth - 1))
+            .collect::<Result<Vec<_>>>()
+    };
+    Ok(match u.int_in_range(0..=2)? {
+        0 => Expr::Value(u.arbitrary()?),
+        1 => {
+            let function = u.arbitrary()?;
+            let count = u.int_in_range(0..=MAX_ARGS)?;
+            Expr::Function {
+                function,
+                args: args(u, count)?,
+            }
+        }
+        _ => {
+            let operator: O = u.arbitrary()?;
+            match operator.arity() {
+                count @ (1 | 2) => Expr::Operator {
+                    args: args(u, count)?,
+                    operator,
+                },
+                _ => Expr::Value(u.arbitrary()?),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    use super::Expression;
+    use crate::{op::All, to_postfix_with_arity};
+
+    #[test]
+    fn conversion_matches_tree() {
+        let mut seed = 1u32;
+        for _ in 0..500 {
+            let data: Vec<u8> = (0..256)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                    (seed >> 16) as u8
+                })
+                .collect();
+            let expression = Expression::<u8, u8, All>::arbitrary(&mut Unstructured::new(&data))
+                .expect("enough data");
+            assert_eq!(
+                to_postfix_with_arity(expression.infix.clone()),
+                Ok(expression.postfix),
+                "{:?}",
+                expression.infix
+            );
+        }
+    }
+}
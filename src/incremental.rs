@@ -0,0 +1,175 @@
+//! Conversion that is updated after local edits.
+//!
+//! `IncrementalConversion` keeps the infix expression, its postfix expression and snapshots of the
+//! conversion state every `CHECKPOINT_INTERVAL` tokens. `IncrementalConversion::edit` replaces a
+//! range of tokens and only converts the tokens from the last snapshot before the edit onwards.
+//! Editors that convert on every keystroke reuse the work for everything in front of the cursor.
+//!
+//! ```rust
+//! use gyard::{incremental::IncrementalConversion, op::Math, InputToken, OutputToken};
+//!
+//! // 1 + 2
+//! let mut conversion = IncrementalConversion::new([
+//!     InputToken::<_, (), _>::Value(1),
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Value(2),
+//! ]);
+//! // 1 + 2 * 3
+//! let postfix = conversion
+//!     .edit(3..3, [InputToken::Operator(Math::Mul), InputToken::Value(3)])
+//!     .unwrap();
+//! assert_eq!(postfix, [
+//!     OutputToken::Value(1),
+//!     OutputToken::Value(2),
+//!     OutputToken::Value(3),
+//!     OutputToken::Operator(Math::Mul),
+//!     OutputToken::Operator(Math::Add),
+//! ]);
+//! ```
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{growable, Converter, InputToken, Operator, OutputToken, ParenMissmatchError, Stack};
+
+/// The number of tokens between two snapshots of the conversion state
+pub const CHECKPOINT_INTERVAL: usize = 64;
+
+/// The state of the conversion in front of a token
+#[derive(Clone)]
+struct Checkpoint<F, O> {
+    converter: Converter<Stack<F, O>>,
+    /// The length of the output at the checkpoint
+    output: usize,
+}
+
+/// An infix expression and its postfix expression. See the module documentation.
+#[derive(Clone)]
+pub struct IncrementalConversion<V, F, O> {
+    infix: Vec<InputToken<V, F, O>>,
+    postfix: Vec<OutputToken<V, F, O>>,
+    /// The state in front of every `CHECKPOINT_INTERVAL`th token up to the first error
+    checkpoints: Vec<Checkpoint<F, O>>,
+    error: Option<ParenMissmatchError>,
+}
+
+impl<V: Clone, F: Clone, O: Operator + Clone> IncrementalConversion<V, F, O> {
+    /// Converts `infix`.
+    pub fn new(infix: impl IntoIterator<Item = InputToken<V, F, O>>) -> Self {
+        let mut conversion = Self {
+            infix: infix.into_iter().collect(),
+            postfix: Vec::new(),
+            checkpoints: Vec::new(),
+            error: None,
+        };
+        conversion.convert_from(Checkpoint {
+            converter: Converter::new(Stack::default()),
+            output: 0,
+        });
+        conversion
+    }
+
+    /// Replaces the tokens in `range` with `replacement` and updates the postfix expression.
+    /// Panics if `range` is out of bounds, like `Vec::splice`.
+    pub fn edit(
+        &mut self,
+        range: Range<usize>,
+        replacement: impl IntoIterator<Item = InputToken<V, F, O>>,
+    ) -> Result<&[OutputToken<V, F, O>], ParenMissmatchError> {
+        let start = range.start;
+        self.infix.splice(range, replacement);
+        let kept = (start / CHECKPOINT_INTERVAL + 1).min(self.checkpoints.len());
+        self.checkpoints.truncate(kept);
+        if let Some(checkpoint) = self.checkpoints.pop() {
+            self.convert_from(checkpoint);
+        }
+        self.postfix()
+    }
+
+    /// Converts the tokens from `checkpoint` onwards and records new checkpoints.
+    fn convert_from(&mut self, checkpoint: Checkpoint<F, O>) {
+        let Checkpoint {
+            mut converter,
+            output,
+        } = checkpoint;
+        let start = self.checkpoints.len() * CHECKPOINT_INTERVAL;
+        self.postfix.truncate(output);
+        self.error = None;
+        let identity = |func, _| func;
+        for (pos, token) in self.infix.iter().enumerate().skip(start) {
+            if pos.is_multiple_of(CHECKPOINT_INTERVAL) {
+                self.checkpoints.push(Checkpoint {
+                    converter: converter.clone(),
+                    output: self.postfix.len(),
+                });
+            }
+            let result = converter.push(pos, token.clone(), &mut self.postfix, &identity);
+            if let Err(error) = growable(result) {
+                self.error = Some(error);
+                return;
+            }
+        }
+        if self.infix.len().is_multiple_of(CHECKPOINT_INTERVAL) {
+            // Appending tokens restarts in front of the end
+            self.checkpoints.push(Checkpoint {
+                converter: converter.clone(),
+                output: self.postfix.len(),
+            });
+        }
+        if let Err(error) = growable(converter.finish(&mut self.postfix, &identity)) {
+            self.error = Some(error);
+        }
+    }
+}
+
+impl<V, F, O> IncrementalConversion<V, F, O> {
+    /// Returns the infix expression.
+    pub fn infix(&self) -> &[InputToken<V, F, O>] {
+        &self.infix
+    }
+
+    /// Returns the postfix expression or the error of the conversion.
+    pub fn postfix(&self) -> Result<&[OutputToken<V, F, O>], ParenMissmatchError> {
+        match &self.error {
+            Some(error) => Err(error.clone()),
+            None => Ok(&self.postfix),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::{IncrementalConversion, CHECKPOINT_INTERVAL};
+    use crate::{op::Math, to_postfix, InputToken};
+
+    #[test]
+    fn matches_full_conversion() {
+        // 0 + 1 + 2 + ...
+        let mut infix = vec![InputToken::<_, (), _>::Value(0)];
+        for value in 1..CHECKPOINT_INTERVAL * 2 {
+            infix.extend([InputToken::Operator(Math::Add), InputToken::Value(value)]);
+        }
+        let mut conversion = IncrementalConversion::new(infix.clone());
+        let edits = [
+            // Wraps `1 + 2` in parentheses
+            (2..2, vec![InputToken::LeftParen]),
+            (6..6, vec![InputToken::RightParen]),
+            // Replaces a value behind the second checkpoint with an unclosed parenthesis, then
+            // with an operator
+            (150..151, vec![InputToken::LeftParen]),
+            (150..151, vec![InputToken::Operator(Math::Mul)]),
+            // Appends at the end
+            (
+                infix.len() + 2..infix.len() + 2,
+                vec![InputToken::Operator(Math::Sub), InputToken::Value(1)],
+            ),
+        ];
+        for (range, replacement) in edits {
+            infix.splice(range.clone(), replacement.clone());
+            let postfix = conversion.edit(range, replacement).map(<[_]>::to_vec);
+            assert_eq!(postfix, to_postfix(infix.clone()));
+        }
+    }
+}
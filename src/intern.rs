@@ -0,0 +1,563 @@
+//! Small handles for functions and operators.
+//!
+//! An `Interner` stores every distinct function and operator once and replaces them with handles.
+//! Operator handles carry the precedence and associativity, so the conversion never touches the
+//! interned operators. Use `Interner::evaluator` to evaluate an expression made of handles with an
+//! evaluator for the original types.
+//!
+//! `FunctionNames` only interns function names. It looks names up by `&str`, so converting an
+//! expression does not allocate for names that are already known, and evaluators can match on
+//! `FunctionId`s instead of comparing strings.
+//!
+//! ```rust
+//! use gyard::{eval::{eval_postfix, Evaluator}, intern::Interner, op::Math, InputToken};
+//!
+//! struct Calculator;
+//! impl<'a> Evaluator<f64, &'a String, &'a Math> for Calculator {
+//!     type Value = f64;
+//!     type Error = ();
+//!     fn value(&mut self, value: f64) -> Result<f64, ()> {
+//!         Ok(value)
+//!     }
+//!     fn operator(&mut self, operator: &Math, args: &mut Vec<f64>) -> Result<f64, ()> {
+//!         match operator {
+//!             Math::Add => Ok(args[0] + args[1]),
+//!             _ => Err(()),
+//!         }
+//!     }
+//!     fn function(&mut self, function: &String, args: &mut Vec<f64>) -> Result<f64, ()> {
+//!         match function.as_str() {
+//!             "sqrt" => Ok(args[0].sqrt()),
+//!             _ => Err(()),
+//!         }
+//!     }
+//!     fn function_arity(&self, _: &&String) -> usize {
+//!         1
+//!     }
+//! }
+//!
+//! let mut interner = Interner::new();
+//! // sqrt ( 7 + 2 )
+//! let postfix = interner.to_postfix([
+//!     InputToken::Function("sqrt".to_string()),
+//!     InputToken::LeftParen,
+//!     InputToken::Value(7.),
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Value(2.),
+//!     InputToken::RightParen,
+//! ])
+//! .unwrap();
+//! assert_eq!(eval_postfix(postfix, &mut interner.evaluator(&mut Calculator)), Ok(3.));
+//! ```
+
+use core::ops::RangeInclusive;
+use std::{collections::HashMap, hash::Hash};
+
+use crate::{
+    eval::Evaluator, to_postfix, to_postfix_with_arity, Call, InputToken, Operator, OutputToken,
+    ParenMissmatchError,
+};
+
+/// A function stored in an `Interner`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FunctionHandle(u32);
+
+impl FunctionHandle {
+    /// The position of the function in `Interner::functions`
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// An operator stored in an `Interner`. Implements `Operator` like the interned operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OperatorHandle {
+    index: u32,
+    precedence: usize,
+    left_associative: bool,
+    arity: usize,
+    commutative: bool,
+}
+
+impl OperatorHandle {
+    /// The position of the operator in `Interner::operators`
+    pub fn index(self) -> usize {
+        self.index as usize
+    }
+}
+
+impl Operator for OperatorHandle {
+    fn precedence(&self) -> usize {
+        self.precedence
+    }
+
+    fn is_left_associative(&self) -> bool {
+        self.left_associative
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn is_commutative(&self) -> bool {
+        self.commutative
+    }
+}
+
+/// Maps functions and operators to handles and back.
+#[derive(Debug, Clone)]
+pub struct Interner<F, O> {
+    functions: Vec<F>,
+    function_handles: HashMap<F, FunctionHandle>,
+    operators: Vec<O>,
+    operator_handles: HashMap<O, OperatorHandle>,
+}
+
+impl<F, O> Default for Interner<F, O> {
+    fn default() -> Self {
+        Self {
+            functions: Vec::new(),
+            function_handles: HashMap::new(),
+            operators: Vec::new(),
+            operator_handles: HashMap::new(),
+        }
+    }
+}
+
+impl<F, O> Interner<F, O> {
+    /// Creates an empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All interned functions. `FunctionHandle::index` is the position in this slice.
+    pub fn functions(&self) -> &[F] {
+        &self.functions
+    }
+
+    /// All interned operators. `OperatorHandle::index` is the position in this slice.
+    pub fn operators(&self) -> &[O] {
+        &self.operators
+    }
+
+    /// Returns the function of `handle` or `None` if it was created by another interner.
+    pub fn function(&self, handle: FunctionHandle) -> Option<&F> {
+        self.functions.get(handle.index())
+    }
+
+    /// Returns the operator of `handle` or `None` if it was created by another interner.
+    pub fn operator(&self, handle: OperatorHandle) -> Option<&O> {
+        self.operators.get(handle.index())
+    }
+
+    /// Replaces the handles in `token` with the interned function or operator.
+    /// Returns `None` if a handle was created by another interner.
+    pub fn resolve<V>(
+        &self,
+        token: OutputToken<V, FunctionHandle, OperatorHandle>,
+    ) -> Option<OutputToken<V, &F, &O>> {
+        Some(match token {
+            OutputToken::Value(value) => OutputToken::Value(value),
+            OutputToken::Function(handle) => OutputToken::Function(self.function(handle)?),
+            OutputToken::Operator(handle) => OutputToken::Operator(self.operator(handle)?),
+        })
+    }
+
+    /// Wraps `evaluator` to evaluate expressions made of handles.
+    ///
+    /// # Panics
+    /// Evaluation panics if a handle was created by another interner.
+    pub fn evaluator<'a, E>(&'a self, evaluator: &'a mut E) -> Resolve<'a, E, F, O> {
+        Resolve {
+            interner: self,
+            evaluator,
+        }
+    }
+}
+
+impl<F: Eq + Hash + Clone, O: Operator + Eq + Hash + Clone> Interner<F, O> {
+    /// Returns the handle of `function`, storing it first if necessary.
+    ///
+    /// # Panics
+    /// Panics if more than `u32::MAX` functions are interned.
+    pub fn intern_function(&mut self, function: F) -> FunctionHandle {
+        if let Some(handle) = self.function_handles.get(&function) {
+            return *handle;
+        }
+        let handle = FunctionHandle(to_u32(self.functions.len()));
+        self.functions.push(function.clone());
+        self.function_handles.insert(function, handle);
+        handle
+    }
+
+    /// Returns the handle of `operator`, storing it first if necessary.
+    ///
+    /// # Panics
+    /// Panics if more than `u32::MAX` operators are interned.
+    pub fn intern_operator(&mut self, operator: O) -> OperatorHandle {
+        if let Some(handle) = self.operator_handles.get(&operator) {
+            return *handle;
+        }
+        let handle = OperatorHandle {
+            index: to_u32(self.operators.len()),
+            precedence: operator.precedence(),
+            left_associative: operator.is_left_associative(),
+            arity: operator.arity(),
+            commutative: operator.is_commutative(),
+        };
+        self.operators.push(operator.clone());
+        self.operator_handles.insert(operator, handle);
+        handle
+    }
+
+    /// Replaces the function or operator in `token` with its handle.
+    pub fn intern<V>(
+        &mut self,
+        token: InputToken<V, F, O>,
+    ) -> InputToken<V, FunctionHandle, OperatorHandle> {
+        #[expect(deprecated, reason = "")]
+        match token {
+            InputToken::Value(value) => InputToken::Value(value),
+            InputToken::LeftParen => InputToken::LeftParen,
+            InputToken::RightParen => InputToken::RightParen,
+            InputToken::Function(function) => InputToken::Function(self.intern_function(function)),
+            InputToken::ArgSeperator => InputToken::ArgSeperator,
+            InputToken::ArgSeparator => InputToken::ArgSeparator,
+            InputToken::Operator(operator) => InputToken::Operator(self.intern_operator(operator)),
+        }
+    }
+
+    /// Interns all functions and operators of `infix` and converts it into a postfix expression.
+    pub fn to_postfix<V>(
+        &mut self,
+        infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+    ) -> Result<Vec<OutputToken<V, FunctionHandle, OperatorHandle>>, ParenMissmatchError> {
+        to_postfix(infix.into_iter().map(|token| self.intern(token)))
+    }
+}
+
+fn to_u32(n: usize) -> u32 {
+    u32::try_from(n).unwrap_or_else(|_| panic!("More than {} entries interned", u32::MAX))
+}
+
+/// Evaluates expressions made of handles with an evaluator for the interned functions and
+/// operators. Created by `Interner::evaluator`.
+pub struct Resolve<'a, E, F, O> {
+    interner: &'a Interner<F, O>,
+    evaluator: &'a mut E,
+}
+
+impl<'a, E, F, O> Resolve<'a, E, F, O> {
+    fn lookup(&self, handle: &FunctionHandle) -> &'a F {
+        let interner = self.interner;
+        &interner.functions[handle.index()]
+    }
+}
+
+impl<'a, V, E, F, O> Evaluator<V, FunctionHandle, OperatorHandle> for Resolve<'a, E, F, O>
+where
+    E: Evaluator<V, &'a F, &'a O>,
+{
+    type Value = E::Value;
+    type Error = E::Error;
+
+    fn value(&mut self, value: V) -> Result<Self::Value, Self::Error> {
+        self.evaluator.value(value)
+    }
+
+    fn operator(
+        &mut self,
+        operator: OperatorHandle,
+        args: &mut Vec<Self::Value>,
+    ) -> Result<Self::Value, Self::Error> {
+        let interner = self.interner;
+        self.evaluator
+            .operator(&interner.operators[operator.index()], args)
+    }
+
+    fn function(
+        &mut self,
+        function: FunctionHandle,
+        args: &mut Vec<Self::Value>,
+    ) -> Result<Self::Value, Self::Error> {
+        let function = self.lookup(&function);
+        self.evaluator.function(function, args)
+    }
+
+    fn function_arity(&self, function: &FunctionHandle) -> usize {
+        self.evaluator.function_arity(&self.lookup(function))
+    }
+
+    fn accepted_arity(&self, function: &FunctionHandle) -> RangeInclusive<usize> {
+        self.evaluator.accepted_arity(&self.lookup(function))
+    }
+
+    fn is_pure(&self, function: &FunctionHandle) -> bool {
+        self.evaluator.is_pure(&self.lookup(function))
+    }
+}
+
+/// A function name stored in `FunctionNames`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FunctionId(u32);
+
+impl FunctionId {
+    /// The position of the name in `FunctionNames::names`
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Maps function names to `FunctionId`s and back.
+///
+/// ```rust
+/// use gyard::{eval::{eval_postfix, Evaluator}, intern::{FunctionId, FunctionNames}, op::Math, InputToken};
+///
+/// struct Calculator {
+///     sqrt: FunctionId,
+/// }
+/// impl Evaluator<f64, FunctionId, Math> for Calculator {
+///     type Value = f64;
+///     type Error = ();
+///     fn value(&mut self, value: f64) -> Result<f64, ()> {
+///         Ok(value)
+///     }
+///     fn operator(&mut self, _: Math, args: &mut Vec<f64>) -> Result<f64, ()> {
+///         Ok(args[0] + args[1])
+///     }
+///     fn function(&mut self, function: FunctionId, args: &mut Vec<f64>) -> Result<f64, ()> {
+///         if function == self.sqrt { Ok(args[0].sqrt()) } else { Err(()) }
+///     }
+///     fn function_arity(&self, _: &FunctionId) -> usize {
+///         1
+///     }
+/// }
+///
+/// let mut names = FunctionNames::new();
+/// let mut calculator = Calculator { sqrt: names.id("sqrt") };
+/// // sqrt ( 7 + 2 )
+/// let postfix = names.to_postfix([
+///     InputToken::Function("sqrt"),
+///     InputToken::LeftParen,
+///     InputToken::Value(7.),
+///     InputToken::Operator(Math::Add),
+///     InputToken::Value(2.),
+///     InputToken::RightParen,
+/// ])
+/// .unwrap();
+/// assert_eq!(names.name(calculator.sqrt), Some("sqrt"));
+/// assert_eq!(eval_postfix(postfix, &mut calculator), Ok(3.));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FunctionNames {
+    names: Vec<Box<str>>,
+    ids: HashMap<Box<str>, FunctionId>,
+}
+
+impl FunctionNames {
+    /// Creates an empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All interned names. `FunctionId::index` is the position in this slice.
+    pub fn names(&self) -> &[Box<str>] {
+        &self.names
+    }
+
+    /// Returns the id of `name` without interning it.
+    pub fn get(&self, name: &str) -> Option<FunctionId> {
+        self.ids.get(name).copied()
+    }
+
+    /// Returns the id of `name`, storing it first if necessary.
+    ///
+    /// # Panics
+    /// Panics if more than `u32::MAX` names are interned.
+    pub fn id(&mut self, name: &str) -> FunctionId {
+        if let Some(id) = self.get(name) {
+            return id;
+        }
+        let id = FunctionId(to_u32(self.names.len()));
+        self.names.push(name.into());
+        self.ids.insert(name.into(), id);
+        id
+    }
+
+    /// Returns the name of `id` or `None` if it was created by another interner.
+    pub fn name(&self, id: FunctionId) -> Option<&str> {
+        self.names.get(id.index()).map(|name| &**name)
+    }
+
+    /// Interns all function names of `infix` and converts it into a postfix expression.
+    pub fn to_postfix<V, S: AsRef<str>, O: Operator>(
+        &mut self,
+        infix: impl IntoIterator<Item = InputToken<V, S, O>>,
+    ) -> Result<Vec<OutputToken<V, FunctionId, O>>, ParenMissmatchError> {
+        to_postfix(
+            infix
+                .into_iter()
+                .map(|token| token.map_function(|name| self.id(name.as_ref()))),
+        )
+    }
+
+    /// Like `to_postfix`, but annotates every function with the number of its arguments.
+    pub fn to_postfix_with_arity<V, S: AsRef<str>, O: Operator>(
+        &mut self,
+        infix: impl IntoIterator<Item = InputToken<V, S, O>>,
+    ) -> Result<Vec<OutputToken<V, Call<FunctionId>, O>>, ParenMissmatchError> {
+        to_postfix_with_arity(
+            infix
+                .into_iter()
+                .map(|token| token.map_function(|name| self.id(name.as_ref()))),
+        )
+    }
+
+    /// Wraps `evaluator` to evaluate expressions made of ids with an evaluator for names.
+    ///
+    /// # Panics
+    /// Evaluation panics if an id was created by another interner.
+    pub fn evaluator<'a, E>(&'a self, evaluator: &'a mut E) -> ResolveNames<'a, E> {
+        ResolveNames {
+            names: self,
+            evaluator,
+        }
+    }
+}
+
+/// Evaluates expressions made of `FunctionId`s with an evaluator for function names. Created by
+/// `FunctionNames::evaluator`.
+pub struct ResolveNames<'a, E> {
+    names: &'a FunctionNames,
+    evaluator: &'a mut E,
+}
+
+impl<'a, E> ResolveNames<'a, E> {
+    fn lookup(&self, id: &FunctionId) -> &'a str {
+        let names = self.names;
+        &names.names[id.index()]
+    }
+}
+
+impl<'a, V, O, E> Evaluator<V, FunctionId, O> for ResolveNames<'a, E>
+where
+    E: Evaluator<V, &'a str, O>,
+{
+    type Value = E::Value;
+    type Error = E::Error;
+
+    fn value(&mut self, value: V) -> Result<Self::Value, Self::Error> {
+        self.evaluator.value(value)
+    }
+
+    fn operator(
+        &mut self,
+        operator: O,
+        args: &mut Vec<Self::Value>,
+    ) -> Result<Self::Value, Self::Error> {
+        self.evaluator.operator(operator, args)
+    }
+
+    fn function(
+        &mut self,
+        function: FunctionId,
+        args: &mut Vec<Self::Value>,
+    ) -> Result<Self::Value, Self::Error> {
+        let function = self.lookup(&function);
+        self.evaluator.function(function, args)
+    }
+
+    fn function_arity(&self, function: &FunctionId) -> usize {
+        self.evaluator.function_arity(&self.lookup(function))
+    }
+
+    fn accepted_arity(&self, function: &FunctionId) -> RangeInclusive<usize> {
+        self.evaluator.accepted_arity(&self.lookup(function))
+    }
+
+    fn is_pure(&self, function: &FunctionId) -> bool {
+        self.evaluator.is_pure(&self.lookup(function))
+    }
+
+    fn is_commutative(&self, operator: &O) -> bool
+    where
+        O: Operator,
+    {
+        self.evaluator.is_commutative(operator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FunctionNames, Interner};
+    use crate::{op::Math, to_postfix, Call, InputToken, Operator, OutputToken};
+
+    #[test]
+    fn handles_keep_operator_properties() {
+        // 2 ^ 3 ^ 2 - 1
+        let infix = vec![
+            InputToken::<_, &str, _>::Value(2),
+            InputToken::Operator(Math::Exponent),
+            InputToken::Value(3),
+            InputToken::Operator(Math::Exponent),
+            InputToken::Value(2),
+            InputToken::Operator(Math::Sub),
+            InputToken::Value(1),
+        ];
+        let mut interner = Interner::new();
+        let postfix = interner
+            .to_postfix(infix.clone())
+            .expect("valid expression");
+        assert_eq!(interner.operators(), [Math::Exponent, Math::Sub]);
+        let resolved: Vec<_> = postfix
+            .into_iter()
+            .map(|token| interner.resolve(token))
+            .collect::<Option<_>>()
+            .expect("handles of this interner");
+        let expected: Vec<_> = to_postfix(infix).expect("valid expression");
+        assert_eq!(
+            resolved,
+            expected
+                .iter()
+                .map(|token| match token {
+                    OutputToken::Value(value) => OutputToken::Value(*value),
+                    OutputToken::Function(function) => OutputToken::Function(function),
+                    OutputToken::Operator(operator) => OutputToken::Operator(operator),
+                })
+                .collect::<Vec<_>>()
+        );
+        let handle = interner.intern_operator(Math::Exponent);
+        assert!(!handle.is_left_associative());
+    }
+
+    #[test]
+    fn function_names_round_trip() {
+        let mut names = FunctionNames::new();
+        // max ( min ( 1 ) , 2 )
+        let postfix = names
+            .to_postfix_with_arity([
+                InputToken::<_, String, Math>::Function("max".to_string()),
+                InputToken::LeftParen,
+                InputToken::Function("min".to_string()),
+                InputToken::LeftParen,
+                InputToken::Value(1),
+                InputToken::RightParen,
+                InputToken::ArgSeparator,
+                InputToken::Value(2),
+                InputToken::RightParen,
+            ])
+            .expect("valid expression");
+        let max = names.get("max").expect("interned");
+        assert_eq!(names.id("max"), max);
+        assert_eq!(names.names().len(), 2);
+        assert_eq!(
+            postfix.last(),
+            Some(&OutputToken::Function(Call {
+                function: max,
+                arity: 2
+            }))
+        );
+        assert_eq!(names.name(max), Some("max"));
+        assert_eq!(names.get("sin"), None);
+    }
+}
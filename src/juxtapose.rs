@@ -0,0 +1,155 @@
+//! Operators between adjacent operands.
+//!
+//! `juxtapose` inserts an operator wherever an operand directly follows another operand, i.e.
+//! `2 x`, `2 (x + 1)` or `(a) (b)`. Use it for implicit multiplication, string concatenation or
+//! applying units to numbers. The inserted operator is converted like any other operator, choose
+//! its precedence accordingly.
+//!
+//! A token ends an operand if it is a value, a right parenthesis or a postfix operator. A token
+//! starts an operand if it is a value, a left parenthesis, a function or a prefix operator. Unary
+//! operators follow the convention of the predefined operators: right associative unary
+//! operators are prefix operators, left associative unary operators are postfix operators.
+//!
+//! ```rust
+//! use gyard::{juxtapose::juxtapose, op::Math, to_postfix, InputToken, OutputToken};
+//!
+//! // 2 x ( x + 1 )
+//! let infix = [
+//!     InputToken::<_, (), _>::Value("2"),
+//!     InputToken::Value("x"),
+//!     InputToken::LeftParen,
+//!     InputToken::Value("x"),
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Value("1"),
+//!     InputToken::RightParen,
+//! ];
+//! let postfix = to_postfix(juxtapose(infix, Math::Mul)).unwrap();
+//! assert_eq!(postfix, [
+//!     OutputToken::Value("2"),
+//!     OutputToken::Value("x"),
+//!     OutputToken::Operator(Math::Mul),
+//!     OutputToken::Value("x"),
+//!     OutputToken::Value("1"),
+//!     OutputToken::Operator(Math::Add),
+//!     OutputToken::Operator(Math::Mul),
+//! ]);
+//! ```
+
+use crate::{InputToken, Operator};
+
+/// Returns true if `token` is the last token of an operand.
+fn ends_operand<V, F, O: Operator>(token: &InputToken<V, F, O>) -> bool {
+    match token {
+        InputToken::Value(_) | InputToken::RightParen => true,
+        InputToken::Operator(operator) => operator.arity() == 1 && operator.is_left_associative(),
+        _ => false,
+    }
+}
+
+/// Returns true if `token` is the first token of an operand.
+fn starts_operand<V, F, O: Operator>(token: &InputToken<V, F, O>) -> bool {
+    match token {
+        InputToken::Value(_) | InputToken::LeftParen | InputToken::Function(_) => true,
+        InputToken::Operator(operator) => operator.arity() == 1 && !operator.is_left_associative(),
+        _ => false,
+    }
+}
+
+/// Inserts `operator` between adjacent operands of `infix`. See the module documentation.
+pub fn juxtapose<I, V, F, O>(infix: I, operator: O) -> Juxtapose<I::IntoIter, V, F, O>
+where
+    I: IntoIterator<Item = InputToken<V, F, O>>,
+    O: Operator + Clone,
+{
+    Juxtapose {
+        infix: infix.into_iter(),
+        operator,
+        after_operand: false,
+        next: None,
+    }
+}
+
+/// The iterator returned by `juxtapose`
+#[derive(Debug, Clone)]
+pub struct Juxtapose<I, V, F, O> {
+    infix: I,
+    operator: O,
+    after_operand: bool,
+    /// The token following an inserted operator
+    next: Option<InputToken<V, F, O>>,
+}
+
+impl<I, V, F, O> Iterator for Juxtapose<I, V, F, O>
+where
+    I: Iterator<Item = InputToken<V, F, O>>,
+    O: Operator + Clone,
+{
+    type Item = InputToken<V, F, O>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = match self.next.take() {
+            Some(token) => token,
+            None => {
+                let token = self.infix.next()?;
+                if self.after_operand && starts_operand(&token) {
+                    self.after_operand = false;
+                    self.next = Some(token);
+                    return Some(InputToken::Operator(self.operator.clone()));
+                }
+                token
+            }
+        };
+        self.after_operand = ends_operand(&token);
+        Some(token)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = usize::from(self.next.is_some());
+        let (lower, upper) = self.infix.size_hint();
+        (
+            lower.saturating_add(pending),
+            // At most every other token is inserted
+            upper.and_then(|upper| upper.checked_mul(2)?.checked_add(pending)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::juxtapose;
+    use crate::{
+        op::{Spreadsheet, Sql},
+        InputToken,
+    };
+
+    #[test]
+    fn unary_operators_and_functions() {
+        // 50 % f ( ) - 1 ( 2 ) -3
+        let infix = [
+            InputToken::Value(50),
+            InputToken::Operator(Spreadsheet::Percent),
+            InputToken::Function("f"),
+            InputToken::LeftParen,
+            InputToken::RightParen,
+            InputToken::Operator(Spreadsheet::Sub),
+            InputToken::Value(1),
+            InputToken::LeftParen,
+            InputToken::Value(2),
+            InputToken::RightParen,
+            InputToken::Operator(Spreadsheet::Negate),
+            InputToken::Value(3),
+        ];
+        let concat = InputToken::Operator(Spreadsheet::Concat);
+        let juxtaposed: Vec<_> = juxtapose(infix.clone(), Spreadsheet::Concat).collect();
+        let mut expected = infix.to_vec();
+        for pos in [10, 7, 2] {
+            expected.insert(pos, concat.clone());
+        }
+        assert_eq!(juxtaposed, expected);
+
+        let strings = [InputToken::<_, (), _>::Value("a"), InputToken::Value("b")];
+        assert_eq!(juxtapose(strings, Sql::Concat).size_hint(), (2, Some(4)));
+    }
+}
@@ -0,0 +1,268 @@
+//! A tokenizer for plain math expressions.
+//!
+//! The lexer reads numbers, functions, parentheses, commas and the operators of `op::All`:
+//!
+//! - Numbers are written like `12`, `0.5`, `.5` or `1e-3`. A `-` directly in front of a number is
+//!   part of the number if a value is expected, i.e. in `-2 * (3 - -1)`.
+//! - Every name like `sin` or `max` is a function. Whether it is called or used as a variable is
+//!   decided after lexing, so a name is treated like a value: a `-` following it is a subtraction.
+//!   `x -1` and `sin -1` both read as a subtraction, write `sin(-1)` to call `sin` on `-1`.
+//! - Operators are the symbols of `op::unicode`, i.e. `+ - * / ** ^ < <= == != >= > && || !` and
+//!   Unicode symbols like `×` or `≤`. `**` is the exponent, `^` the exclusive or. `√` is the
+//!   function `sqrt`.
+//!
+//! ```rust
+//! use gyard::{lexer::tokenize, op::{All, Math}, to_postfix, OutputToken};
+//!
+//! let infix = tokenize("5 + 2*sin(123)").unwrap();
+//! assert_eq!(infix[4].span, 6..9);
+//! let postfix = to_postfix(infix.into_iter().map(|token| token.value));
+//! assert_eq!(postfix, Ok(vec![
+//!     OutputToken::Value(5.),
+//!     OutputToken::Value(2.),
+//!     OutputToken::Value(123.),
+//!     OutputToken::Function("sin".to_string()),
+//!     OutputToken::Operator(All::Math(Math::Mul)),
+//!     OutputToken::Operator(All::Math(Math::Add)),
+//! ]));
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::ops::Range;
+
+use crate::{
+    op::{
+        unicode::{symbol_prefix, Symbol},
+        All,
+    },
+    span::Spanned,
+    InputToken,
+};
+
+/// A token produced by `Lexer`
+pub type Token = InputToken<f64, String, All>;
+
+/// This error is returned if the input is not a valid math expression.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LexError {
+    /// The character at byte offset `pos` does not start a token.
+    UnexpectedCharacter {
+        /// The unexpected character
+        character: char,
+        /// The byte offset of the character
+        pos: usize,
+    },
+    /// The text at `span` looks like a number but can not be parsed, i.e. `1.2.3`.
+    InvalidNumber {
+        /// The byte range of the number
+        span: Range<usize>,
+    },
+}
+
+impl core::fmt::Display for LexError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LexError::UnexpectedCharacter { character, pos } => {
+                write!(f, "Unexpected character {character:?} at offset {pos}")
+            }
+            LexError::InvalidNumber { span } => {
+                write!(f, "Invalid number at offset {}", span.start)
+            }
+        }
+    }
+}
+
+impl core::error::Error for LexError {}
+
+/// Reads the tokens of a math expression. Stops after the first error.
+#[derive(Debug, Clone)]
+pub struct Lexer<'a> {
+    input: &'a str,
+    pos: usize,
+    expects_value: bool,
+}
+
+impl<'a> Lexer<'a> {
+    /// Creates a lexer reading `input`.
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            pos: 0,
+            expects_value: true,
+        }
+    }
+
+    fn token(&self, rest: &str, c: char) -> Result<(Token, usize), LexError> {
+        let number = |c: char| c.is_ascii_digit() || c == '.';
+        let negative = c == '-' && self.expects_value && rest[1..].starts_with(number);
+        if number(c) || negative {
+            let mut len = rest[1..]
+                .find(|c| !number(c))
+                .map_or(rest.len(), |len| len + 1);
+            // An exponent only belongs to the number if digits follow
+            if let Some(exponent) = rest[len..].strip_prefix(['e', 'E']) {
+                let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+                if digits.starts_with(|c: char| c.is_ascii_digit()) {
+                    let end = digits
+                        .find(|c: char| !c.is_ascii_digit())
+                        .unwrap_or(digits.len());
+                    len = rest.len() - digits.len() + end;
+                }
+            }
+            return match rest[..len].parse() {
+                Ok(value) => Ok((InputToken::Value(value), len)),
+                Err(_) => Err(LexError::InvalidNumber {
+                    span: self.pos..self.pos + len,
+                }),
+            };
+        }
+        if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            return Ok((InputToken::Function(rest[..len].to_string()), len));
+        }
+        let token = match c {
+            '(' => InputToken::LeftParen,
+            ')' => InputToken::RightParen,
+            ',' => InputToken::ArgSeparator,
+            _ => {
+                return symbol_prefix(rest)
+                    .map(|(symbol, len)| match symbol {
+                        Symbol::Operator(operator) => (InputToken::Operator(operator), len),
+                        Symbol::Function(function) => (InputToken::Function(function.into()), len),
+                    })
+                    .ok_or(LexError::UnexpectedCharacter {
+                        character: c,
+                        pos: self.pos,
+                    })
+            }
+        };
+        Ok((token, 1))
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Result<Spanned<Token>, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.input[self.pos..].trim_start();
+        self.pos = self.input.len() - rest.len();
+        let c = rest.chars().next()?;
+        match self.token(rest, c) {
+            Ok((value, len)) => {
+                let span = self.pos..self.pos + len;
+                self.pos += len;
+                self.expects_value = matches!(
+                    value,
+                    InputToken::LeftParen | InputToken::ArgSeparator | InputToken::Operator(_)
+                );
+                Some(Ok(Spanned { value, span }))
+            }
+            Err(error) => {
+                self.pos = self.input.len();
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// Reads all tokens of `input`.
+pub fn tokenize(input: &str) -> Result<Vec<Spanned<Token>>, LexError> {
+    Lexer::new(input).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec::Vec};
+
+    use super::{tokenize, LexError, Lexer};
+    use crate::{
+        op::{All, Compare, Logical, Math},
+        InputToken,
+    };
+
+    #[test]
+    fn numbers_operators_and_errors() {
+        let tokens: Vec<_> = tokenize("-1.5e3 - -2 ** x <= .5e")
+            .expect("valid expression")
+            .into_iter()
+            .map(|token| token.value)
+            .collect();
+        assert_eq!(
+            tokens,
+            [
+                InputToken::Value(-1500.),
+                InputToken::Operator(All::Math(Math::Sub)),
+                InputToken::Value(-2.),
+                InputToken::Operator(All::Math(Math::Exponent)),
+                InputToken::Function("x".to_string()),
+                InputToken::Operator(All::Compare(Compare::Le)),
+                InputToken::Value(0.5),
+                InputToken::Function("e".to_string()),
+            ]
+        );
+
+        assert_eq!(
+            tokenize("1 + 1.2.3"),
+            Err(LexError::InvalidNumber { span: 4..9 })
+        );
+        let tokens: Vec<_> = tokenize("a ^ b × √c ≤ 2")
+            .expect("valid expression")
+            .into_iter()
+            .map(|token| (token.value, token.span))
+            .collect();
+        assert_eq!(
+            tokens,
+            [
+                (InputToken::Function("a".to_string()), 0..1),
+                (InputToken::Operator(All::Logical(Logical::Xor)), 2..3),
+                (InputToken::Function("b".to_string()), 4..5),
+                (InputToken::Operator(All::Math(Math::Mul)), 6..8),
+                (InputToken::Function("sqrt".to_string()), 9..12),
+                (InputToken::Function("c".to_string()), 12..13),
+                (InputToken::Operator(All::Compare(Compare::Le)), 14..17),
+                (InputToken::Value(2.), 18..19),
+            ]
+        );
+        let values = |input| -> Vec<_> {
+            tokenize(input)
+                .expect("valid expression")
+                .into_iter()
+                .map(|token| token.value)
+                .collect()
+        };
+        let name = |name: &str| InputToken::Function(name.to_string());
+        let sub = InputToken::Operator(All::Math(Math::Sub));
+        assert_eq!(
+            values("x -1"),
+            [name("x"), sub.clone(), InputToken::Value(1.)]
+        );
+        assert_eq!(values("sin -1"), [name("sin"), sub, InputToken::Value(1.)]);
+        assert_eq!(
+            values("sin(-1)"),
+            [
+                name("sin"),
+                InputToken::LeftParen,
+                InputToken::Value(-1.),
+                InputToken::RightParen
+            ]
+        );
+        let mut lexer = Lexer::new("1 # 2");
+        assert!(lexer.next().is_some_and(|token| token.is_ok()));
+        assert_eq!(
+            lexer.next(),
+            Some(Err(LexError::UnexpectedCharacter {
+                character: '#',
+                pos: 2
+            }))
+        );
+        assert_eq!(lexer.next(), None);
+    }
+}
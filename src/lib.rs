@@ -1,11 +1,23 @@
 //! A generic Shunting yard algorithm implementation
 //! This crate only provides the shunting yard algorithm.
-//! You need to write your own parser and convert its result to `InputToken`s
+//! You need to write your own parser and convert its result to `InputToken`s, or enable the
+//! `lexer` feature to tokenize plain math expressions with `lexer::tokenize`.
 //! The types for values, functions and operators are generic. Operators must implement the
 //! `Operator` trait.
 //!
 //! This crate contains definitions for some operators in the `op` module.
 //!
+//! Disable the default `std` feature to use the crate with `#![no_std]` and enable the `alloc`
+//! feature, which `std` implies, for everything that returns a `Vec`. Without `alloc` the crate
+//! only provides the operators and `to_postfix_fixed` of the `heapless` feature. Hash based
+//! helpers like `analysis::DependencyGraph`, `intern` and `stream` require `std`. The `defmt`
+//! feature implements `defmt::Format` for tokens, operators and errors. The `paranoid` feature
+//! checks internal invariants of the conversion after every token and panics if one is violated.
+//!
+//! With the default `forbid-unsafe` feature the crate is compiled with `#![forbid(unsafe_code)]`.
+//! Without it the conversion skips a few checks whose outcome the operator stack already
+//! guarantees, using `unwrap_unchecked` and `unreachable_unchecked`.
+//!
 //! ```rust
 //! use gyard::{InputToken, OutputToken, op::Math, to_postfix};
 //! // 5 + 2 * sin(123)
@@ -29,6 +41,7 @@
 //!     OutputToken::Operator(Math::Add),
 //! ]));
 //! ```
+#![cfg_attr(feature = "forbid-unsafe", forbid(unsafe_code))]
 #![warn(clippy::allow_attributes_without_reason)]
 #![warn(clippy::missing_safety_doc)]
 #![warn(clippy::undocumented_unsafe_blocks)]
@@ -36,10 +49,177 @@
 #![warn(clippy::unwrap_in_result)]
 #![warn(clippy::unwrap_used)]
 #![warn(missing_docs)]
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(
+    not(any(feature = "alloc", feature = "heapless")),
+    allow(
+        dead_code,
+        reason = "without `alloc` only `to_postfix_fixed` drives the conversion"
+    )
+)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    vec::Vec,
+};
+use core::cmp::Ordering;
+#[cfg(feature = "alloc")]
+use core::{cell::RefCell, convert::Infallible};
 
+#[cfg(feature = "alloc")]
+use stack::Stack;
+use stack::{OperatorStack, Tag};
+
+#[cfg(feature = "alloc")]
+pub mod analysis;
+#[cfg(feature = "alloc")]
+pub mod application;
+#[cfg(feature = "alloc")]
+pub mod ast;
+#[cfg(feature = "alloc")]
+pub mod batch;
+#[cfg(feature = "alloc")]
+pub mod binary;
+#[cfg(feature = "alloc")]
+pub mod builder;
+#[cfg(feature = "alloc")]
+#[cfg_attr(
+    feature = "rkyv",
+    expect(
+        missing_docs,
+        reason = "rkyv does not document the fields of generated resolvers"
+    )
+)]
+pub mod compile;
+#[cfg(feature = "config")]
+pub mod config;
+#[cfg(feature = "std")]
+pub mod document;
+#[cfg(feature = "dynamic")]
+mod dynamic;
+#[cfg(feature = "alloc")]
+pub mod eval;
+#[cfg(feature = "excel")]
+pub mod excel;
+pub mod expected;
+#[cfg(feature = "expression")]
+pub mod expression;
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
+#[cfg(feature = "alloc")]
+pub mod incremental;
+#[cfg(feature = "std")]
+pub mod intern;
+#[cfg(feature = "alloc")]
+pub mod juxtapose;
+#[cfg(feature = "lexer")]
+pub mod lexer;
+#[cfg(feature = "alloc")]
+pub mod list;
+#[cfg(feature = "logos")]
+pub mod logos;
+#[cfg(feature = "alloc")]
+pub mod message;
 pub mod op;
+mod paranoid;
+#[cfg(feature = "pest")]
+pub mod pest;
+#[cfg(feature = "alloc")]
+pub mod policy;
+#[cfg(feature = "alloc")]
+pub mod postfix;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "alloc")]
+pub mod resumable;
+#[cfg(feature = "alloc")]
+pub mod rpn;
+#[cfg(feature = "alloc")]
+pub mod span;
+#[cfg(feature = "sql")]
+pub mod sql;
+mod stack;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+#[cfg(feature = "std")]
+pub mod stream;
+#[cfg(feature = "syn")]
+pub mod syn;
+#[cfg(feature = "alloc")]
+pub mod tape;
+#[cfg(feature = "alloc")]
+pub mod template;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod trace;
+#[cfg(feature = "alloc")]
+pub mod typecheck;
+#[cfg(feature = "value")]
+pub mod value;
+#[cfg(feature = "alloc")]
+pub mod walkthrough;
+#[cfg(feature = "alloc")]
+pub mod warning;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+/// Derives `From<Token> for InputToken<V, F, O>` for the token enum of a lexer.
+///
+/// Every variant needs a `#[token(...)]` attribute naming the `InputToken` it becomes:
+/// `left_paren`, `right_paren` and `arg_separator` ignore the fields of the variant. `value`,
+/// `function` and `operator` convert the only field of the variant with `Into`, or use an
+/// expression if one is given, i.e. `#[token(operator = Math::Add)]`. The types of the
+/// `InputToken` are set on the enum with `#[token(value = V, function = F, operator = O)]`, a
+/// missing type is `()`.
+///
+/// ```rust
+/// use gyard::{op::Math, to_postfix, InputToken, IntoInputToken, OutputToken};
+///
+/// #[derive(IntoInputToken)]
+/// #[token(value = f64, function = String, operator = Math)]
+/// enum Token<'a> {
+///     #[token(value)]
+///     Number(f64),
+///     #[token(function)]
+///     Ident(&'a str),
+///     #[token(operator = Math::Add)]
+///     Plus,
+///     #[token(operator = Math::Mul)]
+///     Star,
+///     #[token(left_paren)]
+///     Open,
+///     #[token(right_paren)]
+///     Close,
+///     #[token(arg_separator)]
+///     Comma,
+/// }
+///
+/// // 2 * max(1, 3)
+/// let tokens = [
+///     Token::Number(2.),
+///     Token::Star,
+///     Token::Ident("max"),
+///     Token::Open,
+///     Token::Number(1.),
+///     Token::Comma,
+///     Token::Number(3.),
+///     Token::Close,
+/// ];
+/// let postfix = to_postfix(tokens.into_iter().map(InputToken::from)).unwrap();
+/// assert_eq!(postfix[3], OutputToken::Function("max".to_string()));
+/// assert_eq!(postfix[4], OutputToken::Operator(Math::Mul));
+/// ```
+#[cfg(feature = "derive")]
+pub use gyard_derive::IntoInputToken;
 /// All valid input tokens
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum InputToken<V, F, O> {
     /// A value inside of a expression. I.e. numbers or variables.
     Value(V),
@@ -61,15 +241,125 @@ pub enum InputToken<V, F, O> {
     Operator(O),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-enum StackToken<F, O> {
-    LeftParen(usize),
-    Function(F),
-    Operator(O),
+/// Shorthands for building token streams by hand.
+///
+/// ```rust
+/// use gyard::{op::{All, Math}, InputToken};
+///
+/// let infix: [InputToken<i32, &str, All>; 3] = [
+///     InputToken::val(1),
+///     Math::Add.into(),
+///     InputToken::val(2),
+/// ];
+/// assert_eq!(infix[1], InputToken::op(All::Math(Math::Add)));
+/// ```
+impl<V, F, O> InputToken<V, F, O> {
+    /// Creates a `InputToken::Value`.
+    pub fn val(value: V) -> Self {
+        Self::Value(value)
+    }
+
+    /// Creates a `InputToken::Function`.
+    pub fn func(function: F) -> Self {
+        Self::Function(function)
+    }
+
+    /// Creates a `InputToken::Operator`.
+    pub fn op(operator: O) -> Self {
+        Self::Operator(operator)
+    }
+
+    /// Converts the value, function or operator of the token with the matching closure.
+    /// Parentheses and separators are kept.
+    ///
+    /// ```rust
+    /// use gyard::{op::Math, InputToken};
+    ///
+    /// let token = InputToken::<&str, &str, Math>::val("12");
+    /// let token = token.map(|value| value.len(), str::to_uppercase, |op| op);
+    /// assert_eq!(token, InputToken::Value(2));
+    /// ```
+    pub fn map<W, G, P>(
+        self,
+        value: impl FnOnce(V) -> W,
+        function: impl FnOnce(F) -> G,
+        operator: impl FnOnce(O) -> P,
+    ) -> InputToken<W, G, P> {
+        #[expect(deprecated, reason = "")]
+        match self {
+            InputToken::Value(v) => InputToken::Value(value(v)),
+            InputToken::LeftParen => InputToken::LeftParen,
+            InputToken::RightParen => InputToken::RightParen,
+            InputToken::Function(f) => InputToken::Function(function(f)),
+            InputToken::ArgSeperator => InputToken::ArgSeperator,
+            InputToken::ArgSeparator => InputToken::ArgSeparator,
+            InputToken::Operator(o) => InputToken::Operator(operator(o)),
+        }
+    }
+
+    /// Converts the value of a `InputToken::Value` and keeps all other tokens.
+    pub fn map_value<W>(self, f: impl FnOnce(V) -> W) -> InputToken<W, F, O> {
+        self.map(f, |function| function, |operator| operator)
+    }
+
+    /// Converts the function of a `InputToken::Function` and keeps all other tokens.
+    pub fn map_function<G>(self, f: impl FnOnce(F) -> G) -> InputToken<V, G, O> {
+        self.map(|value| value, f, |operator| operator)
+    }
+
+    /// Converts the operator of a `InputToken::Operator` and keeps all other tokens.
+    pub fn map_operator<P>(self, f: impl FnOnce(O) -> P) -> InputToken<V, F, P> {
+        self.map(|value| value, |function| function, f)
+    }
+
+    /// Converts from `&InputToken<V, F, O>` to `InputToken<&V, &F, &O>`.
+    pub fn as_ref(&self) -> InputToken<&V, &F, &O> {
+        #[expect(deprecated, reason = "")]
+        match self {
+            InputToken::Value(value) => InputToken::Value(value),
+            InputToken::LeftParen => InputToken::LeftParen,
+            InputToken::RightParen => InputToken::RightParen,
+            InputToken::Function(function) => InputToken::Function(function),
+            InputToken::ArgSeperator => InputToken::ArgSeperator,
+            InputToken::ArgSeparator => InputToken::ArgSeparator,
+            InputToken::Operator(operator) => InputToken::Operator(operator),
+        }
+    }
+}
+
+/// `ArgSeperator` is formatted like `ArgSeparator`.
+#[cfg(feature = "defmt")]
+impl<V, F, O> defmt::Format for InputToken<V, F, O>
+where
+    V: defmt::Format,
+    F: defmt::Format,
+    O: defmt::Format,
+{
+    fn format(&self, f: defmt::Formatter<'_>) {
+        #[expect(deprecated, reason = "")]
+        match self {
+            InputToken::Value(value) => defmt::write!(f, "Value({})", value),
+            InputToken::LeftParen => defmt::write!(f, "LeftParen"),
+            InputToken::RightParen => defmt::write!(f, "RightParen"),
+            InputToken::Function(function) => defmt::write!(f, "Function({})", function),
+            InputToken::ArgSeperator | InputToken::ArgSeparator => {
+                defmt::write!(f, "ArgSeparator")
+            }
+            InputToken::Operator(operator) => defmt::write!(f, "Operator({})", operator),
+        }
+    }
 }
 
 /// All valid output tokens
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum OutputToken<V, F, O> {
     /// A value inside of a expression. I.e. numbers or variables.
     Value(V),
@@ -79,8 +369,188 @@ pub enum OutputToken<V, F, O> {
     Operator(O),
 }
 
+impl<V, F, O> OutputToken<V, F, O> {
+    /// Creates a `OutputToken::Value`.
+    pub fn val(value: V) -> Self {
+        Self::Value(value)
+    }
+
+    /// Creates a `OutputToken::Function`.
+    pub fn func(function: F) -> Self {
+        Self::Function(function)
+    }
+
+    /// Creates a `OutputToken::Operator`.
+    pub fn op(operator: O) -> Self {
+        Self::Operator(operator)
+    }
+
+    /// Converts the value, function or operator of the token with the matching closure.
+    pub fn map<W, G, P>(
+        self,
+        value: impl FnOnce(V) -> W,
+        function: impl FnOnce(F) -> G,
+        operator: impl FnOnce(O) -> P,
+    ) -> OutputToken<W, G, P> {
+        match self {
+            OutputToken::Value(v) => OutputToken::Value(value(v)),
+            OutputToken::Function(f) => OutputToken::Function(function(f)),
+            OutputToken::Operator(o) => OutputToken::Operator(operator(o)),
+        }
+    }
+
+    /// Converts the value of a `OutputToken::Value` and keeps all other tokens.
+    pub fn map_value<W>(self, f: impl FnOnce(V) -> W) -> OutputToken<W, F, O> {
+        self.map(f, |function| function, |operator| operator)
+    }
+
+    /// Converts the function of a `OutputToken::Function` and keeps all other tokens.
+    pub fn map_function<G>(self, f: impl FnOnce(F) -> G) -> OutputToken<V, G, O> {
+        self.map(|value| value, f, |operator| operator)
+    }
+
+    /// Converts the operator of a `OutputToken::Operator` and keeps all other tokens.
+    pub fn map_operator<P>(self, f: impl FnOnce(O) -> P) -> OutputToken<V, F, P> {
+        self.map(|value| value, |function| function, f)
+    }
+
+    /// Converts from `&OutputToken<V, F, O>` to `OutputToken<&V, &F, &O>`.
+    /// This allows evaluating a stored postfix expression without consuming it.
+    pub fn as_ref(&self) -> OutputToken<&V, &F, &O> {
+        match self {
+            OutputToken::Value(value) => OutputToken::Value(value),
+            OutputToken::Function(func) => OutputToken::Function(func),
+            OutputToken::Operator(op) => OutputToken::Operator(op),
+        }
+    }
+}
+
+/// Writes the value, function or operator, `(`, `)` or `,`.
+impl<V, F, O> core::fmt::Display for InputToken<V, F, O>
+where
+    V: core::fmt::Display,
+    F: core::fmt::Display,
+    O: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        #[expect(deprecated, reason = "")]
+        match self {
+            InputToken::Value(value) => value.fmt(f),
+            InputToken::LeftParen => f.write_str("("),
+            InputToken::RightParen => f.write_str(")"),
+            InputToken::Function(function) => function.fmt(f),
+            InputToken::ArgSeperator | InputToken::ArgSeparator => f.write_str(","),
+            InputToken::Operator(operator) => operator.fmt(f),
+        }
+    }
+}
+
+/// Writes the value, function or operator.
+impl<V, F, O> core::fmt::Display for OutputToken<V, F, O>
+where
+    V: core::fmt::Display,
+    F: core::fmt::Display,
+    O: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            OutputToken::Value(value) => value.fmt(f),
+            OutputToken::Function(function) => function.fmt(f),
+            OutputToken::Operator(operator) => operator.fmt(f),
+        }
+    }
+}
+
+/// Tokens whose functions are borrowed or owned, so names taken from the input and names created
+/// at runtime can be mixed in one expression.
+///
+/// ```rust
+/// use std::borrow::Cow;
+/// use gyard::{op::Math, to_postfix, InputToken, OutputToken};
+///
+/// let input = String::from("max");
+/// let infix = [
+///     InputToken::<_, _, Math>::function_name(input.as_str()),
+///     InputToken::LeftParen,
+///     InputToken::Value(1),
+///     InputToken::RightParen,
+///     InputToken::Operator(Math::Add),
+///     InputToken::function_name(format!("{input}_{}", 2)),
+///     InputToken::LeftParen,
+///     InputToken::Value(2),
+///     InputToken::RightParen,
+/// ];
+/// let postfix: Vec<OutputToken<_, Cow<'static, str>, _>> = to_postfix(infix)
+///     .unwrap()
+///     .into_iter()
+///     .map(|token| token.into_owned())
+///     .collect();
+/// drop(input);
+/// assert_eq!(postfix[1], OutputToken::Function(Cow::Borrowed("max")));
+/// assert_eq!(postfix[3], OutputToken::Function(Cow::Borrowed("max_2")));
+/// ```
+#[cfg(feature = "alloc")]
+impl<'a, V, B: ToOwned + ?Sized + 'static, O> InputToken<V, Cow<'a, B>, O> {
+    /// Creates a `InputToken::Function` from a borrowed or an owned function.
+    pub fn function_name(function: impl Into<Cow<'a, B>>) -> Self {
+        Self::Function(function.into())
+    }
+
+    /// Copies a borrowed function, so the token no longer borrows anything.
+    pub fn into_owned(self) -> InputToken<V, Cow<'static, B>, O> {
+        self.map_function(|function| Cow::Owned(function.into_owned()))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V, B: ToOwned + ?Sized + 'static, O> OutputToken<V, Cow<'_, B>, O> {
+    /// Copies a borrowed function, so the token no longer borrows anything.
+    pub fn into_owned(self) -> OutputToken<V, Cow<'static, B>, O> {
+        self.map_function(|function| Cow::Owned(function.into_owned()))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V, B: ToOwned + ?Sized + 'static, O> OutputToken<V, Call<Cow<'_, B>>, O> {
+    /// Copies a borrowed function, so the token no longer borrows anything.
+    pub fn into_owned(self) -> OutputToken<V, Call<Cow<'static, B>>, O> {
+        self.map_function(|call| Call {
+            function: Cow::Owned(call.function.into_owned()),
+            arity: call.arity,
+        })
+    }
+}
+
+impl<V, F, O> From<OutputToken<V, F, O>> for InputToken<V, F, O> {
+    fn from(token: OutputToken<V, F, O>) -> Self {
+        match token {
+            OutputToken::Value(value) => InputToken::Value(value),
+            OutputToken::Function(function) => InputToken::Function(function),
+            OutputToken::Operator(operator) => InputToken::Operator(operator),
+        }
+    }
+}
+
+/// Fails for parentheses and argument separators.
+impl<V, F, O> TryFrom<InputToken<V, F, O>> for OutputToken<V, F, O> {
+    type Error = NoOutputTokenError;
+
+    fn try_from(token: InputToken<V, F, O>) -> Result<Self, NoOutputTokenError> {
+        #[expect(deprecated, reason = "")]
+        match token {
+            InputToken::Value(value) => Ok(OutputToken::Value(value)),
+            InputToken::Function(function) => Ok(OutputToken::Function(function)),
+            InputToken::Operator(operator) => Ok(OutputToken::Operator(operator)),
+            InputToken::LeftParen
+            | InputToken::RightParen
+            | InputToken::ArgSeperator
+            | InputToken::ArgSeparator => Err(NoOutputTokenError),
+        }
+    }
+}
+
 /// Mark any struct or enum as an Operator. Each operator has to define its precedence and if it is
-/// left associative.
+/// left associative. Operators are binary by default, unary operators override `arity`.
 ///
 /// ```rust
 /// pub struct MyOp;
@@ -98,32 +568,473 @@ pub trait Operator {
     fn precedence(&self) -> usize;
     /// Returns true if the operator is left associative.
     fn is_left_associative(&self) -> bool;
+    /// Returns the number of operands the operator takes.
+    fn arity(&self) -> usize {
+        2
+    }
+    /// Returns true if the order of the operands does not change the result.
+    /// Used by `ast::canonical` to sort operands.
+    fn is_commutative(&self) -> bool {
+        false
+    }
+    /// Returns true if the right operand of the operator is a parenthesized list, like the list
+    /// of `x IN (1, 2, 3)`. Only used by `list::to_postfix_with_lists`.
+    fn takes_list(&self) -> bool {
+        false
+    }
 }
 
-/// This error is returned if the parentheses inside a expression do not match.
+/// This error is returned if the parentheses inside a expression do not match or an argument
+/// separator is not part of a function call.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
-pub struct ParenMissmatchError {
-    pos: usize,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ParenMissmatchError {
+    /// A right parenthesis without a matching left parenthesis
+    UnexpectedClosing {
+        /// The position of the right parenthesis
+        pos: usize,
+        /// The position of the most recent left parenthesis, which is already closed. Often
+        /// one of the right parentheses between them is misplaced.
+        open_pos: Option<usize>,
+    },
+    /// A left parenthesis that is never closed
+    UnclosedOpening {
+        /// The position of the left parenthesis
+        open_pos: usize,
+    },
+    /// An argument separator outside of a function call
+    UnexpectedSeparator {
+        /// The position of the argument separator
+        pos: usize,
+        /// The position of the left parenthesis that directly contains the separator, if any
+        open_pos: Option<usize>,
+    },
 }
 
 impl ParenMissmatchError {
-    /// The error position
+    /// The error position. This is the position of the unexpected right parenthesis or of the
+    /// unclosed left parenthesis.
     pub fn pos(&self) -> usize {
-        self.pos
+        match self {
+            Self::UnexpectedClosing { pos, .. } | Self::UnexpectedSeparator { pos, .. } => *pos,
+            Self::UnclosedOpening { open_pos } => *open_pos,
+        }
+    }
+
+    /// The position of the left parenthesis involved in the error. This is the unclosed left
+    /// parenthesis or the most recent left parenthesis before an unexpected right parenthesis.
+    pub fn open_pos(&self) -> Option<usize> {
+        match self {
+            Self::UnexpectedClosing { open_pos, .. }
+            | Self::UnexpectedSeparator { open_pos, .. } => *open_pos,
+            Self::UnclosedOpening { open_pos } => Some(*open_pos),
+        }
+    }
+
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::UnexpectedClosing { .. } => ErrorCode::UnexpectedClosing,
+            Self::UnclosedOpening { .. } => ErrorCode::UnclosedOpening,
+            Self::UnexpectedSeparator { .. } => ErrorCode::UnexpectedSeparator,
+        }
+    }
+
+    /// Returns the tokens that would have been valid. An unclosed parenthesis expects `)`.
+    pub fn expected(&self) -> Option<expected::Expected> {
+        match self {
+            Self::UnexpectedClosing { .. } | Self::UnexpectedSeparator { .. } => None,
+            Self::UnclosedOpening { .. } => Some(expected::Expected::RIGHT_PAREN),
+        }
+    }
+}
+
+impl core::fmt::Display for ParenMissmatchError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnexpectedClosing { pos, open_pos } => {
+                write!(f, "Unexpected ')' at position {pos}")?;
+                if let Some(open_pos) = open_pos {
+                    write!(f, ", the last '(' at position {open_pos} is already closed")?;
+                }
+            }
+            Self::UnclosedOpening { open_pos } => {
+                write!(f, "'(' at position {open_pos} was never closed")?;
+            }
+            Self::UnexpectedSeparator { pos, open_pos } => {
+                write!(
+                    f,
+                    "Unexpected ',' at position {pos} outside of a function call"
+                )?;
+                if let Some(open_pos) = open_pos {
+                    write!(
+                        f,
+                        ", the '(' at position {open_pos} does not follow a function"
+                    )?;
+                }
+            }
+        }
+        match self.expected() {
+            Some(expected) => write!(f, ", expected {expected}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl core::error::Error for ParenMissmatchError {}
+
+/// This error is returned when converting a parenthesis or argument separator into an
+/// `OutputToken`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NoOutputTokenError;
+
+impl core::fmt::Display for NoOutputTokenError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Parentheses and argument separators have no output token"
+        )
+    }
+}
+
+impl core::error::Error for NoOutputTokenError {}
+
+/// Decides how `to_postfix_with_tie_break` handles two operators with the same precedence but
+/// different associativity, i.e. `a ~ b ~ c` where the first `~` is left and the second `~` is
+/// right associative. This is usually a mistake in an operator set defined at runtime.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TieBreak {
+    /// The associativity of the later operator decides. This is what `to_postfix` does.
+    #[default]
+    Incoming,
+    /// Like `Incoming`, but emits a warning with the `log` and `tracing` features.
+    Warn,
+    /// Both operators are treated as left associative: `(a ~ b) ~ c`
+    Left,
+    /// Both operators are treated as right associative: `a ~ (b ~ c)`
+    Right,
+    /// The conversion fails with `TieBreakError::MixedAssociativity`.
+    Error,
+}
+
+/// This error is returned by `to_postfix_with_tie_break`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TieBreakError {
+    /// The parentheses inside the expression do not match.
+    ParenMissmatch(ParenMissmatchError),
+    /// The operator at `pos` has the same precedence but a different associativity than the
+    /// operator before it and the tie break is `TieBreak::Error`.
+    MixedAssociativity {
+        /// The position of the later operator
+        pos: usize,
+    },
+}
+
+impl TieBreakError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            TieBreakError::ParenMissmatch(error) => error.code(),
+            TieBreakError::MixedAssociativity { .. } => ErrorCode::MixedAssociativity,
+        }
+    }
+
+    /// Returns the tokens that would have been valid.
+    pub fn expected(&self) -> Option<expected::Expected> {
+        match self {
+            TieBreakError::ParenMissmatch(error) => error.expected(),
+            TieBreakError::MixedAssociativity { .. } => None,
+        }
+    }
+}
+
+impl From<ParenMissmatchError> for TieBreakError {
+    fn from(error: ParenMissmatchError) -> Self {
+        TieBreakError::ParenMissmatch(error)
+    }
+}
+
+impl core::fmt::Display for TieBreakError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TieBreakError::ParenMissmatch(error) => error.fmt(f),
+            TieBreakError::MixedAssociativity { pos } => write!(
+                f,
+                "Operator at position {pos} has the same precedence but a different associativity \
+                 than the operator before it"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for TieBreakError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            TieBreakError::ParenMissmatch(error) => Some(error),
+            TieBreakError::MixedAssociativity { .. } => None,
+        }
+    }
+}
+
+/// One error type for conversion, validation and evaluation. Every error of these steps converts
+/// into it, so applications can use `?` on all of them. `E` is the error type of the
+/// `eval::Evaluator`.
+///
+/// ```rust
+/// use gyard::{
+///     eval::{eval_postfix_with_arity, Evaluator},
+///     op::Math,
+///     postfix::PostfixExpr,
+///     to_postfix_with_arity, Error, InputToken,
+/// };
+///
+/// struct Sum;
+/// impl Evaluator<i64, (), Math> for Sum {
+///     type Value = i64;
+///     type Error = std::num::TryFromIntError;
+///     fn value(&mut self, value: i64) -> Result<i64, Self::Error> {
+///         Ok(value)
+///     }
+///     fn operator(&mut self, _: Math, args: &mut Vec<i64>) -> Result<i64, Self::Error> {
+///         Ok(args.iter().sum())
+///     }
+///     fn function(&mut self, _: (), _: &mut Vec<i64>) -> Result<i64, Self::Error> {
+///         Ok(0)
+///     }
+///     fn function_arity(&self, _: &()) -> usize {
+///         0
+///     }
+/// }
+///
+/// fn run(infix: Vec<InputToken<i64, (), Math>>) -> Result<i64, Error<std::num::TryFromIntError>> {
+///     let postfix = to_postfix_with_arity(infix)?;
+///     let expr = PostfixExpr::new(postfix)?;
+///     Ok(expr.eval(&mut Sum)?)
+/// }
+///
+/// assert_eq!(run(vec![InputToken::Value(1), InputToken::Operator(Math::Add), InputToken::Value(2)]), Ok(3));
+/// assert!(matches!(run(vec![InputToken::RightParen]), Err(Error::ParenMissmatch(_))));
+/// assert!(matches!(run(vec![InputToken::Operator(Math::Add)]), Err(Error::Malformed(_))));
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error<E = Infallible> {
+    /// The parentheses inside the expression do not match.
+    ParenMissmatch(ParenMissmatchError),
+    /// Two operators with the same precedence have different associativity. See `TieBreak`.
+    MixedAssociativity {
+        /// The position of the later operator
+        pos: usize,
+    },
+    /// The postfix expression does not form exactly one tree. This is never
+    /// `AstError::ParenMissmatch`, which is converted into `Error::ParenMissmatch`.
+    Malformed(ast::AstError),
+    /// The output of a conversion with fixed capacity is full.
+    OutputFull {
+        /// The position of the input token
+        pos: usize,
+    },
+    /// The operator stack of a conversion with fixed capacity is full.
+    StackFull {
+        /// The position of the input token
+        pos: usize,
+    },
+    /// The evaluation failed or exceeded a limit of its `eval::Budget`.
+    Eval(eval::EvalError<E>),
+}
+
+#[cfg(feature = "alloc")]
+impl<E> Error<E> {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Error::ParenMissmatch(error) => error.code(),
+            Error::MixedAssociativity { .. } => ErrorCode::MixedAssociativity,
+            Error::Malformed(error) => error.code(),
+            Error::OutputFull { .. } => ErrorCode::OutputFull,
+            Error::StackFull { .. } => ErrorCode::StackFull,
+            Error::Eval(error) => error.code(),
+        }
+    }
+
+    /// Returns the tokens that would have been valid if the error occurred during the conversion
+    /// or validation.
+    pub fn expected(&self) -> Option<expected::Expected> {
+        match self {
+            Error::ParenMissmatch(error) => error.expected(),
+            Error::Malformed(error) => error.expected(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E> From<ParenMissmatchError> for Error<E> {
+    fn from(error: ParenMissmatchError) -> Self {
+        Error::ParenMissmatch(error)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E> From<TieBreakError> for Error<E> {
+    fn from(error: TieBreakError) -> Self {
+        match error {
+            TieBreakError::ParenMissmatch(error) => Error::ParenMissmatch(error),
+            TieBreakError::MixedAssociativity { pos } => Error::MixedAssociativity { pos },
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E> From<ast::AstError> for Error<E> {
+    fn from(error: ast::AstError) -> Self {
+        match error {
+            ast::AstError::ParenMissmatch(error) => Error::ParenMissmatch(error),
+            error => Error::Malformed(error),
+        }
+    }
+}
+
+#[cfg(all(feature = "alloc", feature = "heapless"))]
+impl<E> From<FixedError> for Error<E> {
+    fn from(error: FixedError) -> Self {
+        match error {
+            FixedError::ParenMissmatch(error) => Error::ParenMissmatch(error),
+            FixedError::OutputFull { pos } => Error::OutputFull { pos },
+            FixedError::StackFull { pos } => Error::StackFull { pos },
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E> From<eval::EvalError<E>> for Error<E> {
+    fn from(error: eval::EvalError<E>) -> Self {
+        Error::Eval(error)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E: core::fmt::Display> core::fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::ParenMissmatch(error) => error.fmt(f),
+            Error::MixedAssociativity { pos } => {
+                TieBreakError::MixedAssociativity { pos: *pos }.fmt(f)
+            }
+            Error::Malformed(error) => error.fmt(f),
+            Error::OutputFull { pos } => write!(f, "Output capacity exceeded at position {pos}"),
+            Error::StackFull { pos } => write!(f, "Stack capacity exceeded at position {pos}"),
+            Error::Eval(error) => error.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E: core::error::Error + 'static> core::error::Error for Error<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Error::ParenMissmatch(error) => Some(error),
+            Error::Malformed(error) => Some(error),
+            Error::Eval(error) => Some(error),
+            _ => None,
+        }
     }
 }
 
-impl std::fmt::Display for ParenMissmatchError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Unexpected parenthese at position {}", self.pos)
+/// A stable, machine-readable code for every error variant of the conversion, validation and
+/// evaluation. Unlike the `Display` output, codes never change between releases, so front-ends can
+/// use them to look up localized messages or documentation.
+///
+/// ```rust
+/// use gyard::{op::Math, to_postfix, ErrorCode, InputToken};
+///
+/// let error = to_postfix::<(), (), Math>([InputToken::LeftParen]).unwrap_err();
+/// assert_eq!(error.code(), ErrorCode::UnclosedOpening);
+/// assert_eq!(error.code().as_str(), "E002");
+/// ```
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ErrorCode {
+    /// `E001`: A right parenthesis without a matching left parenthesis
+    UnexpectedClosing,
+    /// `E002`: A left parenthesis that is never closed
+    UnclosedOpening,
+    /// `E003`: Operators with the same precedence and different associativity
+    MixedAssociativity,
+    /// `E004`: The output of a conversion with fixed capacity is full
+    OutputFull,
+    /// `E005`: The operator stack of a conversion with fixed capacity is full
+    StackFull,
+    /// `E006`: An operator or function with fewer operands than it requires
+    StackUnderflow,
+    /// `E007`: A function called with the wrong number of arguments
+    ArityMismatch,
+    /// `E008`: An expression without tokens
+    EmptyExpression,
+    /// `E009`: An expression that does not reduce to a single value
+    UnusedValues,
+    /// `E010`: The evaluation exceeded a limit of its budget
+    BudgetExceeded,
+    /// `E011`: The evaluator returned an error
+    Evaluator,
+    /// `E012`: A function that is not registered
+    UnknownFunction,
+    /// `E013`: Writing the output failed
+    Io,
+    /// `E014`: An argument separator outside of a function call
+    UnexpectedSeparator,
+    /// `E015`: A function on the denylist of a `policy::FunctionPolicy`
+    ForbiddenFunction,
+}
+
+impl ErrorCode {
+    /// Returns the code, i.e. `"E001"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::UnexpectedClosing => "E001",
+            ErrorCode::UnclosedOpening => "E002",
+            ErrorCode::MixedAssociativity => "E003",
+            ErrorCode::OutputFull => "E004",
+            ErrorCode::StackFull => "E005",
+            ErrorCode::StackUnderflow => "E006",
+            ErrorCode::ArityMismatch => "E007",
+            ErrorCode::EmptyExpression => "E008",
+            ErrorCode::UnusedValues => "E009",
+            ErrorCode::BudgetExceeded => "E010",
+            ErrorCode::Evaluator => "E011",
+            ErrorCode::UnknownFunction => "E012",
+            ErrorCode::Io => "E013",
+            ErrorCode::UnexpectedSeparator => "E014",
+            ErrorCode::ForbiddenFunction => "E015",
+        }
     }
 }
 
-impl std::error::Error for ParenMissmatchError {}
+impl core::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
 /// Convert a infix expression into a postfix expression.
 /// It is highly recomended to wrap function arguments in parentheses as the result may be
-/// unexpected otherwise.
+/// unexpected otherwise. `application::to_postfix_with_application` binds functions without
+/// parentheses with a fixed precedence instead.
+/// Use `to_postfix_cloned` to keep the infix expression, i.e. to display it next to an error.
 /// Example:
 ///
 /// ```rust
@@ -205,84 +1116,1486 @@ impl std::error::Error for ParenMissmatchError {}
 /// ]));
 /// ```
 ///
+#[cfg(feature = "alloc")]
 pub fn to_postfix<V, F, O>(
     infix: impl IntoIterator<Item = InputToken<V, F, O>>,
-) -> Result<Vec<OutputToken<V, F, O>>, ParenMissmatchError>
+) -> Result<Vec<OutputToken<V, F, O>>, ParenMissmatchError>
+where
+    O: Operator,
+{
+    #[cfg(feature = "dynamic")]
+    return dynamic::convert_dynamic(infix, |func, _| func);
+    #[cfg(not(feature = "dynamic"))]
+    convert(infix, |func, _| func, None, Vec::with_capacity)
+}
+
+/// Like `to_postfix`, but converts a borrowed slice and clones the tokens into the output.
+/// The length of the slice is known, so the output is allocated exactly once. The tokens are read
+/// in an index loop and the operators left on the stack at the end are moved into the output in
+/// one go.
+///
+/// ```rust
+/// use gyard::{InputToken, OutputToken, op::Math, to_postfix_slice};
+/// // 1 + 2
+/// let infix = vec![
+///     InputToken::<_, (), _>::Value(1),
+///     InputToken::Operator(Math::Add),
+///     InputToken::Value(2),
+/// ];
+/// assert_eq!(to_postfix_slice(&infix), Ok(vec![
+///     OutputToken::Value(1),
+///     OutputToken::Value(2),
+///     OutputToken::Operator(Math::Add),
+/// ]));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn to_postfix_slice<V, F, O>(
+    infix: &[InputToken<V, F, O>],
+) -> Result<Vec<OutputToken<V, F, O>>, ParenMissmatchError>
+where
+    V: Clone,
+    F: Clone,
+    O: Operator + Clone,
+{
+    #[cfg(feature = "dynamic")]
+    return dynamic::convert_dynamic(infix.iter().cloned(), |func, _| func);
+    #[cfg(not(feature = "dynamic"))]
+    convert_slice(infix, |func, _| func)
+}
+
+/// Like `to_postfix`, but keeps `infix` intact, i.e. to display it next to an error. The tokens
+/// are cloned into the output, see `to_postfix_slice`.
+///
+/// ```rust
+/// use gyard::{InputToken, op::Math, to_postfix_cloned, ParenMissmatchError};
+/// // ( 1 + 2
+/// let infix = [
+///     InputToken::<_, (), _>::LeftParen,
+///     InputToken::Value(1),
+///     InputToken::Operator(Math::Add),
+///     InputToken::Value(2),
+/// ];
+/// let error = to_postfix_cloned(&infix).unwrap_err();
+/// assert_eq!(error, ParenMissmatchError::UnclosedOpening { open_pos: 0 });
+/// assert_eq!(infix[0], InputToken::LeftParen);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn to_postfix_cloned<V, F, O>(
+    infix: &[InputToken<V, F, O>],
+) -> Result<Vec<OutputToken<V, F, O>>, ParenMissmatchError>
+where
+    V: Clone,
+    F: Clone,
+    O: Operator + Clone,
+{
+    to_postfix_slice(infix)
+}
+
+/// The conversion of `to_postfix_slice`, which avoids the iterator machinery of `convert`.
+#[cfg(feature = "alloc")]
+fn convert_slice<V, F, G, O>(
+    infix: &[InputToken<V, F, O>],
+    call: impl Fn(F, usize) -> G,
+) -> Result<Vec<OutputToken<V, G, O>>, ParenMissmatchError>
+where
+    V: Clone,
+    F: Clone,
+    O: Operator + Clone,
+{
+    let span = trace::Span::conversion();
+    let mut output = Vec::with_capacity(infix.len());
+    let mut converter: Converter<Stack<F, O>> =
+        Converter::new(Stack::with_capacity(infix.len().min(STACK_CAPACITY)));
+    let result = (0..infix.len())
+        .try_for_each(|pos| converter.push(pos, infix[pos].clone(), &mut output, &call))
+        .and_then(|()| converter.finish_vec(&mut output, &call));
+    span.finish(converter.end, None, result.is_ok());
+    growable(result).map(|()| output)
+}
+
+/// Like `to_postfix`, but decides with `tie_break` how operators with the same precedence and
+/// different associativity are handled.
+///
+/// ```rust
+/// use gyard::{
+///     op::{Associativity, DynOp},
+///     to_postfix_with_tie_break, InputToken, OutputToken, TieBreak, TieBreakError,
+/// };
+///
+/// let op = |name: &str, associativity| DynOp {
+///     name: name.to_string(),
+///     precedence: 1,
+///     associativity,
+///     arity: 2,
+/// };
+/// let (left, right) = (op("<<", Associativity::Left), op("^^", Associativity::Right));
+/// // 1 << 2 ^^ 3
+/// let infix = || [
+///     InputToken::<_, (), _>::Value(1),
+///     InputToken::Operator(left.clone()),
+///     InputToken::Value(2),
+///     InputToken::Operator(right.clone()),
+///     InputToken::Value(3),
+/// ];
+/// assert_eq!(
+///     to_postfix_with_tie_break(infix(), TieBreak::Error),
+///     Err(TieBreakError::MixedAssociativity { pos: 3 })
+/// );
+/// let postfix = to_postfix_with_tie_break(infix(), TieBreak::Left).unwrap();
+/// assert_eq!(postfix[2], OutputToken::Operator(left));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn to_postfix_with_tie_break<V, F, O>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+    tie_break: TieBreak,
+) -> Result<Vec<OutputToken<V, F, O>>, TieBreakError>
+where
+    O: Operator,
+{
+    let infix = infix.into_iter();
+    let Growable {
+        mut output,
+        mut converter,
+    }: Growable<_, F, O> = Growable::new(&infix, Vec::with_capacity);
+    converter.tie_break = tie_break;
+    let identity = |func, _| func;
+    let result = infix
+        .enumerate()
+        .try_for_each(|(pos, token)| converter.push(pos, token, &mut output, &identity))
+        .and_then(|()| converter.finish(&mut output, &identity));
+    match result {
+        Ok(()) => Ok(output),
+        Err(ConvertError::ParenMissmatch(error)) => Err(error.into()),
+        Err(ConvertError::MixedAssociativity(pos)) => {
+            Err(TieBreakError::MixedAssociativity { pos })
+        }
+        Err(ConvertError::OutputFull(pos) | ConvertError::StackFull(pos)) => {
+            unreachable!("Growable buffer full at position {pos}")
+        }
+    }
+}
+
+/// The result of `to_postfix_partial`
+#[cfg(feature = "alloc")]
+pub type PartialResult<V, F, O> =
+    Result<Vec<OutputToken<V, F, O>>, (Vec<OutputToken<V, F, O>>, ParenMissmatchError)>;
+
+/// Like `to_postfix`, but returns the postfix expression produced before the error together with
+/// the error. Use this to offer completions or hover information on the valid prefix of a broken
+/// expression. Operators and functions that were still waiting for their operands when the error
+/// occurred are not part of the partial output.
+///
+/// ```rust
+/// use gyard::{op::Math, to_postfix_partial, InputToken, OutputToken, ParenMissmatchError};
+/// // 1 * 2 + ( 3
+/// let (partial, error) = to_postfix_partial([
+///     InputToken::<_, (), _>::Value(1),
+///     InputToken::Operator(Math::Mul),
+///     InputToken::Value(2),
+///     InputToken::Operator(Math::Add),
+///     InputToken::LeftParen,
+///     InputToken::Value(3),
+/// ])
+/// .unwrap_err();
+/// assert_eq!(partial, [
+///     OutputToken::Value(1),
+///     OutputToken::Value(2),
+///     OutputToken::Operator(Math::Mul),
+///     OutputToken::Value(3),
+/// ]);
+/// assert_eq!(error, ParenMissmatchError::UnclosedOpening { open_pos: 4 });
+/// ```
+#[cfg(feature = "alloc")]
+pub fn to_postfix_partial<V, F, O>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+) -> PartialResult<V, F, O>
+where
+    O: Operator,
+{
+    let infix = infix.into_iter();
+    let mut conversion: Growable<_, F, O> = Growable::new(&infix, Vec::with_capacity);
+    let identity = |func, _| func;
+    let result = infix
+        .enumerate()
+        .try_for_each(|(pos, token)| conversion.push(pos, token, &identity))
+        .and_then(|()| conversion.finish(&identity));
+    match result {
+        Ok(()) => Ok(conversion.output),
+        Err(error) => Err((conversion.output, error)),
+    }
+}
+
+/// Like `to_postfix`, but returns `AstError::EmptyExpression` instead of an empty postfix
+/// expression. Input that only consists of parentheses, i.e. `( )`, is empty as well.
+/// Use this if an empty expression is a mistake rather than a valid expression without a value.
+///
+/// ```rust
+/// use gyard::{ast::AstError, op::Math, to_postfix, to_postfix_non_empty, InputToken};
+///
+/// assert_eq!(to_postfix::<(), (), Math>([]), Ok(vec![]));
+/// assert_eq!(
+///     to_postfix_non_empty::<(), (), Math>([]),
+///     Err(AstError::EmptyExpression)
+/// );
+/// assert_eq!(
+///     to_postfix_non_empty::<(), (), Math>([InputToken::LeftParen, InputToken::RightParen]),
+///     Err(AstError::EmptyExpression)
+/// );
+/// ```
+#[cfg(feature = "alloc")]
+pub fn to_postfix_non_empty<V, F, O>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+) -> Result<Vec<OutputToken<V, F, O>>, ast::AstError>
+where
+    O: Operator,
+{
+    let postfix = to_postfix(infix)?;
+    if postfix.is_empty() {
+        return Err(ast::AstError::EmptyExpression);
+    }
+    Ok(postfix)
+}
+
+/// Like `to_postfix_slice`, but never copies any value. Values in the output are the positions of
+/// the values in `infix`, functions and operators are borrowed.
+/// Use this if values are large and can be looked up in `infix` during evaluation.
+///
+/// ```rust
+/// use gyard::{InputToken, OutputToken, op::Math, to_postfix_indexed};
+/// // a * b
+/// let infix = [
+///     InputToken::<_, (), _>::Value("a"),
+///     InputToken::Operator(Math::Mul),
+///     InputToken::Value("b"),
+/// ];
+/// assert_eq!(to_postfix_indexed(&infix), Ok(vec![
+///     OutputToken::Value(0),
+///     OutputToken::Value(2),
+///     OutputToken::Operator(&Math::Mul),
+/// ]));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn to_postfix_indexed<V, F, O>(
+    infix: &[InputToken<V, F, O>],
+) -> Result<Vec<OutputToken<usize, &F, &O>>, ParenMissmatchError>
+where
+    O: Operator,
+{
+    let infix = infix.iter().enumerate().map(|(pos, token)| {
+        #[expect(deprecated, reason = "")]
+        match token {
+            InputToken::Value(_) => InputToken::Value(pos),
+            InputToken::LeftParen => InputToken::LeftParen,
+            InputToken::RightParen => InputToken::RightParen,
+            InputToken::Function(function) => InputToken::Function(function),
+            InputToken::ArgSeperator => InputToken::ArgSeperator,
+            InputToken::ArgSeparator => InputToken::ArgSeparator,
+            InputToken::Operator(operator) => InputToken::Operator(operator),
+        }
+    });
+    convert(infix, |func, _| func, None, Vec::with_capacity)
+}
+
+/// Checks the parentheses and argument separators of `infix` like `to_postfix`, without building
+/// the output. Returns the error `to_postfix` would return for the same tokens.
+///
+/// ```rust
+/// use gyard::{validate, InputToken, ParenMissmatchError, op::Math};
+/// // ( 1 + 2
+/// let infix = [
+///     InputToken::<_, (), _>::LeftParen,
+///     InputToken::Value(1),
+///     InputToken::Operator(Math::Add),
+///     InputToken::Value(2),
+/// ];
+/// assert_eq!(validate(&infix), Err(ParenMissmatchError::UnclosedOpening { open_pos: 0 }));
+/// assert_eq!(validate(&infix[1..]), Ok(()));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn validate<'a, V: 'a, F: 'a, O: Operator + 'a>(
+    infix: impl IntoIterator<Item = &'a InputToken<V, F, O>>,
+) -> Result<(), ParenMissmatchError> {
+    convert(
+        infix.into_iter().map(InputToken::as_ref),
+        |func, _| func,
+        None,
+        |_| Discard,
+    )?;
+    Ok(())
+}
+
+/// Like `to_postfix`, but skips all checks of the parentheses and argument separators and never
+/// constructs an error. Use it for input that is converted often but only has to be checked once.
+///
+/// `infix` must have passed `validate`: every right parenthesis closes a preceding left
+/// parenthesis, every left parenthesis is closed and every argument separator is directly inside
+/// the parentheses of a function call. Tokens from a parser that already checked this are fine,
+/// too. If the input was not validated the output is unspecified, but this function never panics
+/// and never causes undefined behavior.
+///
+/// ```rust
+/// use gyard::{validate, InputToken, OutputToken, op::Math, to_postfix_unchecked};
+/// // ( 1 + 2 ) * 3
+/// let infix = [
+///     InputToken::<_, (), _>::LeftParen,
+///     InputToken::Value(1),
+///     InputToken::Operator(Math::Add),
+///     InputToken::Value(2),
+///     InputToken::RightParen,
+///     InputToken::Operator(Math::Mul),
+///     InputToken::Value(3),
+/// ];
+/// assert_eq!(validate(&infix), Ok(()));
+/// assert_eq!(to_postfix_unchecked(infix), [
+///     OutputToken::Value(1),
+///     OutputToken::Value(2),
+///     OutputToken::Operator(Math::Add),
+///     OutputToken::Value(3),
+///     OutputToken::Operator(Math::Mul),
+/// ]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn to_postfix_unchecked<V, F, O>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+) -> Vec<OutputToken<V, F, O>>
+where
+    O: Operator,
+{
+    convert_specialized::<_, _, _, _, false, true>(infix)
+        .expect("Only the checks of the parentheses fail a conversion into growable buffers")
+}
+
+/// Like `to_postfix`, but for grammars without functions. The conversion skips all handling of
+/// functions. Without functions there is no argument list, so every `ArgSeparator` is an
+/// `UnexpectedSeparator` error.
+///
+/// ```rust
+/// use gyard::{InputToken, OutputToken, ParenMissmatchError, op::Math, to_postfix_without_functions};
+/// // ( 1 + 2 ) * 3
+/// let infix = [
+///     InputToken::LeftParen,
+///     InputToken::Value(1),
+///     InputToken::Operator(Math::Add),
+///     InputToken::Value(2),
+///     InputToken::RightParen,
+///     InputToken::Operator(Math::Mul),
+///     InputToken::Value(3),
+/// ];
+/// assert_eq!(to_postfix_without_functions(infix), Ok(vec![
+///     OutputToken::Value(1),
+///     OutputToken::Value(2),
+///     OutputToken::Operator(Math::Add),
+///     OutputToken::Value(3),
+///     OutputToken::Operator(Math::Mul),
+/// ]));
+/// // ( 1 , 2 )
+/// let infix = [
+///     InputToken::LeftParen,
+///     InputToken::Value(1),
+///     InputToken::<_, _, Math>::ArgSeparator,
+///     InputToken::Value(2),
+///     InputToken::RightParen,
+/// ];
+/// assert_eq!(
+///     to_postfix_without_functions(infix),
+///     Err(ParenMissmatchError::UnexpectedSeparator { pos: 2, open_pos: Some(0) })
+/// );
+/// ```
+#[cfg(feature = "alloc")]
+pub fn to_postfix_without_functions<V, O>(
+    infix: impl IntoIterator<Item = InputToken<V, Infallible, O>>,
+) -> Result<Vec<OutputToken<V, Infallible, O>>, ParenMissmatchError>
+where
+    O: Operator,
+{
+    convert_specialized::<_, _, _, _, true, false>(infix)
+}
+
+/// Converts into a `Vec` using a converter that skips the parenthesis checks or the handling of
+/// functions at compile time.
+#[cfg(feature = "alloc")]
+fn convert_specialized<V, F, O, I, const CHECKED: bool, const FUNCTIONS: bool>(
+    infix: I,
+) -> Result<Vec<OutputToken<V, F, O>>, ParenMissmatchError>
+where
+    O: Operator,
+    I: IntoIterator<Item = InputToken<V, F, O>>,
+{
+    let infix = infix.into_iter();
+    let mut conversion = Growable::<_, _, _, CHECKED, FUNCTIONS>::new(&infix, Vec::with_capacity);
+    let identity = |func, _| func;
+    infix
+        .enumerate()
+        .try_for_each(|(pos, token)| conversion.push(pos, token, &identity))
+        .and_then(|()| conversion.finish(&identity))
+        .map(|()| conversion.output)
+}
+
+/// Converts an infix expression into a postfix expression without allocating a second buffer.
+/// The output is written into `tokens` using the `Value`, `Function` and `Operator` variants,
+/// which halves the peak memory for large expressions.
+/// If an error is returned the content of `tokens` is unspecified.
+///
+/// ```rust
+/// use gyard::{InputToken, op::Math, to_postfix_in_place};
+/// // ( 1 + 2 ) * 3
+/// let mut tokens = vec![
+///     InputToken::<_, (), _>::LeftParen,
+///     InputToken::Value(1),
+///     InputToken::Operator(Math::Add),
+///     InputToken::Value(2),
+///     InputToken::RightParen,
+///     InputToken::Operator(Math::Mul),
+///     InputToken::Value(3),
+/// ];
+/// to_postfix_in_place(&mut tokens).unwrap();
+/// assert_eq!(tokens, [
+///     InputToken::Value(1),
+///     InputToken::Value(2),
+///     InputToken::Operator(Math::Add),
+///     InputToken::Value(3),
+///     InputToken::Operator(Math::Mul),
+/// ]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn to_postfix_in_place<V, F, O>(
+    tokens: &mut Vec<InputToken<V, F, O>>,
+) -> Result<(), ParenMissmatchError>
+where
+    O: Operator,
+{
+    let len = tokens.len();
+    let cell = RefCell::new(core::mem::take(tokens));
+    // Every input token produces at most one output token, so the output never overtakes the
+    // input. Consumed tokens are replaced with a placeholder until they are overwritten.
+    let infix =
+        (0..len).map(|i| core::mem::replace(&mut cell.borrow_mut()[i], InputToken::RightParen));
+    let written = growable(convert_into(
+        infix,
+        |func, _| func,
+        None,
+        |_| InPlaceQueue {
+            tokens: &cell,
+            len: 0,
+        },
+        Stack::with_capacity,
+    ))
+    .map(|queue| queue.len);
+    *tokens = cell.into_inner();
+    tokens.truncate(written?);
+    Ok(())
+}
+
+/// Like `to_postfix`, but reserves `output_capacity` tokens for the output and `stack_capacity`
+/// tokens for the operator stack instead of guessing them from the size hint of `infix`.
+/// The output never reallocates if `output_capacity` is at least the number of values,
+/// operators and functions in `infix`. The stack never reallocates if `stack_capacity` is at
+/// least the maximum number of pending operators, functions and left parentheses.
+///
+/// ```rust
+/// use gyard::{InputToken, op::Math, to_postfix_with_capacity};
+/// // 1 + 2
+/// let infix = [
+///     InputToken::<_, (), _>::Value(1),
+///     InputToken::Operator(Math::Add),
+///     InputToken::Value(2),
+/// ];
+/// let postfix = to_postfix_with_capacity(infix, 16, 4).unwrap();
+/// assert_eq!(postfix.capacity(), 16);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn to_postfix_with_capacity<V, F, O>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+    output_capacity: usize,
+    stack_capacity: usize,
+) -> Result<Vec<OutputToken<V, F, O>>, ParenMissmatchError>
+where
+    O: Operator,
+{
+    convert(
+        infix,
+        |func, _| func,
+        Some((output_capacity, stack_capacity)),
+        Vec::with_capacity,
+    )
+}
+
+#[cfg(feature = "alloc")]
+type BoxedPostfix<V, F, O> = Box<[OutputToken<V, F, O>]>;
+
+/// Like `to_postfix`, but returns a boxed slice without unused capacity. Use this for
+/// expressions that are stored for a long time.
+///
+/// ```rust
+/// use gyard::{InputToken, OutputToken, op::Math, to_postfix_boxed};
+/// // ( 1 )
+/// let infix = [
+///     InputToken::<_, (), Math>::LeftParen,
+///     InputToken::Value(1),
+///     InputToken::RightParen,
+/// ];
+/// let postfix: Box<[_]> = to_postfix_boxed(infix).unwrap();
+/// assert_eq!(*postfix, [OutputToken::Value(1)]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn to_postfix_boxed<V, F, O>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+) -> Result<BoxedPostfix<V, F, O>, ParenMissmatchError>
+where
+    O: Operator,
+{
+    to_postfix(infix).map(Vec::into_boxed_slice)
+}
+
+/// Like `to_postfix`, but allocates the output in `bump`.
+/// Use this to free all expressions of a request at once by resetting the arena.
+/// The operator stack is a temporary buffer and is not allocated in the arena.
+///
+/// ```rust
+/// use gyard::{InputToken, OutputToken, op::Math, to_postfix_in};
+/// let mut bump = bumpalo::Bump::new();
+/// // 1 + 2
+/// let infix = [
+///     InputToken::<_, (), _>::Value(1),
+///     InputToken::Operator(Math::Add),
+///     InputToken::Value(2),
+/// ];
+/// let postfix = to_postfix_in(&bump, infix).unwrap();
+/// assert_eq!(postfix, [
+///     OutputToken::Value(1),
+///     OutputToken::Value(2),
+///     OutputToken::Operator(Math::Add),
+/// ]);
+/// drop(postfix);
+/// bump.reset();
+/// ```
+#[cfg(feature = "bumpalo")]
+pub fn to_postfix_in<V, F, O>(
+    bump: &bumpalo::Bump,
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+) -> Result<bumpalo::collections::Vec<'_, OutputToken<V, F, O>>, ParenMissmatchError>
+where
+    O: Operator,
+{
+    convert(
+        infix,
+        |func, _| func,
+        None,
+        |capacity| bumpalo::collections::Vec::with_capacity_in(capacity, bump),
+    )
+}
+
+/// This error is returned by `to_postfix_fixed`.
+#[cfg(feature = "heapless")]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FixedError {
+    /// The parentheses inside the expression do not match.
+    ParenMissmatch(ParenMissmatchError),
+    /// The output is full. `pos` is the position of the input token that could not be converted
+    /// or the length of the input if the tokens left on the stack do not fit.
+    OutputFull {
+        /// The position of the input token
+        pos: usize,
+    },
+    /// The operator stack is full.
+    StackFull {
+        /// The position of the input token
+        pos: usize,
+    },
+}
+
+#[cfg(feature = "heapless")]
+impl FixedError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            FixedError::ParenMissmatch(error) => error.code(),
+            FixedError::OutputFull { .. } => ErrorCode::OutputFull,
+            FixedError::StackFull { .. } => ErrorCode::StackFull,
+        }
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl core::fmt::Display for FixedError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FixedError::ParenMissmatch(error) => error.fmt(f),
+            FixedError::OutputFull { pos } => {
+                write!(f, "Output capacity exceeded at position {pos}")
+            }
+            FixedError::StackFull { pos } => write!(f, "Stack capacity exceeded at position {pos}"),
+        }
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl core::error::Error for FixedError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            FixedError::ParenMissmatch(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Like `to_postfix`, but uses fixed capacity buffers and never allocates. The output holds up to
+/// `N` tokens, the operator stack up to `M` pending operators, functions and left parentheses.
+///
+/// ```rust
+/// use gyard::{FixedError, InputToken, OutputToken, op::Math, to_postfix_fixed};
+/// // 1 + 2
+/// let infix = [
+///     InputToken::<_, (), _>::Value(1),
+///     InputToken::Operator(Math::Add),
+///     InputToken::Value(2),
+/// ];
+/// let postfix = to_postfix_fixed::<8, 4, _, _, _>(infix.clone()).unwrap();
+/// assert_eq!(postfix, [
+///     OutputToken::Value(1),
+///     OutputToken::Value(2),
+///     OutputToken::Operator(Math::Add),
+/// ]);
+/// assert_eq!(
+///     to_postfix_fixed::<2, 4, _, _, _>(infix),
+///     Err(FixedError::OutputFull { pos: 3 })
+/// );
+/// ```
+#[cfg(feature = "heapless")]
+pub fn to_postfix_fixed<const N: usize, const M: usize, V, F, O>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+) -> Result<heapless::Vec<OutputToken<V, F, O>, N>, FixedError>
+where
+    O: Operator,
+{
+    convert_into(
+        infix,
+        |func, _| func,
+        Some((N, M)),
+        |_| heapless::Vec::new(),
+        |_| {
+            stack::SplitStack::new(
+                heapless::Vec::<_, M>::new(),
+                heapless::Vec::<_, M>::new(),
+                heapless::Vec::<_, M>::new(),
+            )
+        },
+    )
+    .map_err(|error| match error {
+        ConvertError::ParenMissmatch(error) => FixedError::ParenMissmatch(error),
+        ConvertError::OutputFull(pos) => FixedError::OutputFull { pos },
+        ConvertError::StackFull(pos) => FixedError::StackFull { pos },
+        ConvertError::MixedAssociativity(pos) => {
+            unreachable!("Mixed associativity at position {pos} with the default tie break")
+        }
+    })
+}
+
+/// A function call inside a postfix expression created by `to_postfix_with_arity`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Call<F> {
+    /// The called function
+    pub function: F,
+    /// The number of arguments passed to the function
+    pub arity: usize,
+}
+
+/// An input token of a math expression with `f64` values, borrowed function names and the
+/// operators of `op::All`.
+///
+/// ```rust
+/// use gyard::{op::{All, Math}, to_postfix, MathInput, MathResult};
+///
+/// let infix: [MathInput; 3] = [
+///     MathInput::Value(1.),
+///     MathInput::Operator(All::Math(Math::Add)),
+///     MathInput::Value(2.),
+/// ];
+/// let postfix: MathResult = to_postfix(infix);
+/// assert_eq!(postfix.map(|postfix| postfix.len()), Ok(3));
+/// ```
+pub type MathInput<'a> = InputToken<f64, &'a str, op::All>;
+/// The output token of a `MathInput` converted with `to_postfix`.
+pub type MathOutput<'a> = OutputToken<f64, &'a str, op::All>;
+/// The output token of a `MathInput` converted with `to_postfix_with_arity`.
+pub type MathCallOutput<'a> = OutputToken<f64, Call<&'a str>, op::All>;
+/// The result of converting `MathInput`s with `to_postfix`.
+#[cfg(feature = "alloc")]
+pub type MathResult<'a> = Result<Vec<MathOutput<'a>>, ParenMissmatchError>;
+
+/// Convert a infix expression into a postfix expression. Unlike `to_postfix` every function in the
+/// output is annotated with the number of arguments it was called with.
+///
+/// ```rust
+/// use gyard::{Call, InputToken, OutputToken, op::Math, to_postfix_with_arity};
+/// // max ( 1 , 2 , 3 ) + pi ( )
+/// let infix = [
+///     InputToken::Function("max"),
+///     InputToken::LeftParen,
+///     InputToken::Value(1),
+///     InputToken::ArgSeparator,
+///     InputToken::Value(2),
+///     InputToken::ArgSeparator,
+///     InputToken::Value(3),
+///     InputToken::RightParen,
+///     InputToken::Operator(Math::Add),
+///     InputToken::Function("pi"),
+///     InputToken::LeftParen,
+///     InputToken::RightParen,
+/// ];
+/// let postfix = to_postfix_with_arity(infix);
+/// assert_eq!(postfix, Ok(vec![
+///     OutputToken::Value(1),
+///     OutputToken::Value(2),
+///     OutputToken::Value(3),
+///     OutputToken::Function(Call { function: "max", arity: 3 }),
+///     OutputToken::Function(Call { function: "pi", arity: 0 }),
+///     OutputToken::Operator(Math::Add),
+/// ]));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn to_postfix_with_arity<V, F, O>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+) -> Result<Vec<OutputToken<V, Call<F>, O>>, ParenMissmatchError>
+where
+    O: Operator,
+{
+    #[cfg(feature = "dynamic")]
+    return dynamic::convert_dynamic(infix, |function, arity| Call { function, arity });
+    #[cfg(not(feature = "dynamic"))]
+    convert(
+        infix,
+        |function, arity| Call { function, arity },
+        None,
+        Vec::with_capacity,
+    )
+}
+
+/// A buffer used for the output and the operator stack of the conversion.
+trait Buffer<T>: core::ops::DerefMut<Target = [T]> {
+    /// Returns false if the buffer is full.
+    fn push(&mut self, item: T) -> bool;
+    fn pop(&mut self) -> Option<T>;
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Buffer<T> for Vec<T> {
+    fn push(&mut self, item: T) -> bool {
+        Vec::push(self, item);
+        true
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        Vec::pop(self)
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> Buffer<A::Item> for smallvec::SmallVec<A> {
+    fn push(&mut self, item: A::Item) -> bool {
+        smallvec::SmallVec::push(self, item);
+        true
+    }
+
+    fn pop(&mut self) -> Option<A::Item> {
+        smallvec::SmallVec::pop(self)
+    }
+}
+
+#[cfg(feature = "bumpalo")]
+impl<T> Buffer<T> for bumpalo::collections::Vec<'_, T> {
+    fn push(&mut self, item: T) -> bool {
+        bumpalo::collections::Vec::push(self, item);
+        true
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        bumpalo::collections::Vec::pop(self)
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<T, const N: usize> Buffer<T> for heapless::Vec<T, N> {
+    fn push(&mut self, item: T) -> bool {
+        heapless::Vec::push(self, item).is_ok()
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        heapless::Vec::pop(self)
+    }
+}
+
+/// The output of the conversion. Only needs to support pushing.
+trait Queue<T> {
+    /// Returns false if the queue is full.
+    fn push(&mut self, item: T) -> bool;
+}
+
+impl<T, B: Buffer<T>> Queue<T> for B {
+    fn push(&mut self, item: T) -> bool {
+        Buffer::push(self, item)
+    }
+}
+
+/// Drops the output of `validate`.
+#[cfg(feature = "alloc")]
+struct Discard;
+
+#[cfg(feature = "alloc")]
+impl<T> Queue<T> for Discard {
+    fn push(&mut self, _: T) -> bool {
+        true
+    }
+}
+
+/// Writes the output of `to_postfix_in_place` into the already consumed part of the input.
+#[cfg(feature = "alloc")]
+struct InPlaceQueue<'a, V, F, O> {
+    tokens: &'a RefCell<Vec<InputToken<V, F, O>>>,
+    len: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<V, F, O> Queue<OutputToken<V, F, O>> for InPlaceQueue<'_, V, F, O> {
+    fn push(&mut self, item: OutputToken<V, F, O>) -> bool {
+        self.tokens.borrow_mut()[self.len] = match item {
+            OutputToken::Value(value) => InputToken::Value(value),
+            OutputToken::Function(function) => InputToken::Function(function),
+            OutputToken::Operator(operator) => InputToken::Operator(operator),
+        };
+        self.len += 1;
+        true
+    }
+}
+
+/// Pushes `item` or returns `error` if `queue` is full.
+fn push<T>(
+    buffer: &mut impl Queue<T>,
+    item: T,
+    error: impl FnOnce() -> ConvertError,
+) -> Result<(), ConvertError> {
+    if buffer.push(item) {
+        Ok(())
+    } else {
+        Err(error())
+    }
+}
+
+/// Returns `error` if a push onto the operator stack failed.
+fn push_stack(pushed: bool, error: impl FnOnce() -> ConvertError) -> Result<(), ConvertError> {
+    if pushed {
+        Ok(())
+    } else {
+        Err(error())
+    }
+}
+
+/// All errors the conversion can run into. Buffers only get full with fixed capacity buffers.
+enum ConvertError {
+    ParenMissmatch(ParenMissmatchError),
+    OutputFull(usize),
+    StackFull(usize),
+    /// Only returned with `TieBreak::Error`
+    MixedAssociativity(usize),
+}
+
+/// Unwraps the result of a conversion into growable buffers.
+#[cfg(feature = "alloc")]
+fn growable<T>(result: Result<T, ConvertError>) -> Result<T, ParenMissmatchError> {
+    result.map_err(|error| match error {
+        ConvertError::ParenMissmatch(error) => error,
+        ConvertError::OutputFull(pos) | ConvertError::StackFull(pos) => {
+            unreachable!("Growable buffer full at position {pos}")
+        }
+        ConvertError::MixedAssociativity(pos) => {
+            unreachable!("Mixed associativity at position {pos} with the default tie break")
+        }
+    })
+}
+
+/// Converts into a growable output container. `new_queue` creates the container from the
+/// output capacity.
+#[cfg(feature = "alloc")]
+fn convert<V, F, G, O, Q>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+    call: impl Fn(F, usize) -> G,
+    capacity: Option<(usize, usize)>,
+    new_queue: impl FnOnce(usize) -> Q,
+) -> Result<Q, ParenMissmatchError>
+where
+    O: Operator,
+    Q: Queue<OutputToken<V, G, O>>,
+{
+    growable(convert_into(
+        infix,
+        call,
+        capacity,
+        new_queue,
+        Stack::with_capacity,
+    ))
+}
+
+/// `new_queue` and `new_stack` create the buffers from the output and stack capacity.
+fn convert_into<V, F, G, O, Q, S>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+    call: impl Fn(F, usize) -> G,
+    capacity: Option<(usize, usize)>,
+    new_queue: impl FnOnce(usize) -> Q,
+    new_stack: impl FnOnce(usize) -> S,
+) -> Result<Q, ConvertError>
 where
     O: Operator,
+    Q: Queue<OutputToken<V, G, O>>,
+    S: OperatorStack<F, O>,
+{
+    let span = trace::Span::conversion();
+    let mut tokens = 0;
+    let result = convert_tokens(
+        infix.into_iter().inspect(|_| tokens += 1),
+        call,
+        capacity,
+        new_queue,
+        new_stack,
+    );
+    span.finish(tokens, None, result.is_ok());
+    result
+}
+
+/// The maximum capacity reserved for the operator stack up front and the inline capacity of the
+/// stack with the `smallvec` feature.
+/// Nesting rarely gets deep, so reserving the full input length would mostly waste memory.
+const STACK_CAPACITY: usize = 32;
+
+fn convert_tokens<V, F, G, O, Q, S>(
+    infix: impl Iterator<Item = InputToken<V, F, O>>,
+    call: impl Fn(F, usize) -> G,
+    capacity: Option<(usize, usize)>,
+    new_queue: impl FnOnce(usize) -> Q,
+    new_stack: impl FnOnce(usize) -> S,
+) -> Result<Q, ConvertError>
+where
+    O: Operator,
+    Q: Queue<OutputToken<V, G, O>>,
+    S: OperatorStack<F, O>,
+{
+    let (output_capacity, stack_capacity) = capacity.unwrap_or_else(|| reserved_capacity(&infix));
+    let mut out_queue = new_queue(output_capacity);
+    let mut converter: Converter<S> = Converter::new(new_stack(stack_capacity));
+    for (pos, token) in infix.enumerate() {
+        converter.push(pos, token, &mut out_queue, &call)?;
+    }
+    converter.finish(&mut out_queue, &call)?;
+    Ok(out_queue)
+}
+
+/// Returns the output and stack capacity to reserve for `infix`.
+/// Every input token produces at most one output token. Like `Vec::from_iter` only the lower
+/// bound of the size hint is reserved, the upper bound may be far larger than the input.
+fn reserved_capacity(infix: &impl Iterator) -> (usize, usize) {
+    let (len, _) = infix.size_hint();
+    (len, len.min(STACK_CAPACITY))
+}
+
+/// A conversion into a growable output with a growable stack, for conversions that handle some
+/// input tokens themselves.
+#[cfg(feature = "alloc")]
+struct Growable<Q, F, O, const CHECKED: bool = true, const FUNCTIONS: bool = true> {
+    output: Q,
+    converter: Converter<Stack<F, O>, CHECKED, FUNCTIONS>,
+}
+
+#[cfg(feature = "alloc")]
+impl<Q, F, O, const CHECKED: bool, const FUNCTIONS: bool> Growable<Q, F, O, CHECKED, FUNCTIONS> {
+    /// Prepares the conversion of `infix`. `new_queue` creates the output from its capacity.
+    fn new(infix: &impl Iterator, new_queue: impl FnOnce(usize) -> Q) -> Self {
+        let (output_capacity, stack_capacity) = reserved_capacity(infix);
+        Self {
+            output: new_queue(output_capacity),
+            converter: Converter::new(Stack::with_capacity(stack_capacity)),
+        }
+    }
+
+    /// Converts the input token at `pos`.
+    fn push<V, G>(
+        &mut self,
+        pos: usize,
+        token: InputToken<V, F, O>,
+        call: &impl Fn(F, usize) -> G,
+    ) -> Result<(), ParenMissmatchError>
+    where
+        O: Operator,
+        Q: Queue<OutputToken<V, G, O>>,
+    {
+        growable(self.converter.push(pos, token, &mut self.output, call))
+    }
+
+    /// Moves the remaining tokens from the stack into the output.
+    fn finish<V, G>(&mut self, call: &impl Fn(F, usize) -> G) -> Result<(), ParenMissmatchError>
+    where
+        O: Operator,
+        Q: Queue<OutputToken<V, G, O>>,
+    {
+        growable(self.converter.finish(&mut self.output, call))
+    }
+}
+
+/// The state of a conversion between two input tokens.
+/// Parentheses are only checked if `CHECKED` is true, functions and argument separators are only
+/// handled if `FUNCTIONS` is true.
+#[derive(Clone)]
+struct Converter<S, const CHECKED: bool = true, const FUNCTIONS: bool = true> {
+    stack: S,
+    paren_count: isize,
+    after_left_paren: bool,
+    after_function: bool,
+    /// The number of converted tokens
+    end: usize,
+    tie_break: TieBreak,
+    /// The position of the most recent left parenthesis
+    last_paren: Option<usize>,
+    invariants: paranoid::Invariants,
+}
+
+impl<S: core::fmt::Debug, const CHECKED: bool, const FUNCTIONS: bool> core::fmt::Debug
+    for Converter<S, CHECKED, FUNCTIONS>
 {
-    let mut out_queue: Vec<OutputToken<V, F, O>> = Vec::new();
-    let mut stack: Vec<StackToken<F, O>> = Vec::new();
-    let mut paren_count: isize = 0;
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Converter")
+            .field("converted", &self.end)
+            .field("paren_depth", &self.paren_count)
+            .field("stack", &self.stack)
+            .field("after_left_paren", &self.after_left_paren)
+            .field("after_function", &self.after_function)
+            .finish()
+    }
+}
+
+impl<S, const CHECKED: bool, const FUNCTIONS: bool> Converter<S, CHECKED, FUNCTIONS> {
+    fn new(stack: S) -> Self {
+        Self {
+            stack,
+            paren_count: 0,
+            after_left_paren: false,
+            after_function: false,
+            end: 0,
+            tie_break: TieBreak::Incoming,
+            last_paren: None,
+            invariants: paranoid::Invariants::new(),
+        }
+    }
 
-    for (pos, token) in infix.into_iter().enumerate() {
+    /// Converts the input token at `pos`.
+    fn push<V, F, G, O>(
+        &mut self,
+        pos: usize,
+        token: InputToken<V, F, O>,
+        out_queue: &mut impl Queue<OutputToken<V, G, O>>,
+        call: &impl Fn(F, usize) -> G,
+    ) -> Result<(), ConvertError>
+    where
+        O: Operator,
+        S: OperatorStack<F, O>,
+    {
+        self.invariants.input(&token, FUNCTIONS);
+        let stack = &mut self.stack;
+        self.end = pos + 1;
+        let full = || ConvertError::OutputFull(pos);
+        let stack_full = || ConvertError::StackFull(pos);
+        let (is_left_paren, is_function) = (
+            matches!(token, InputToken::LeftParen),
+            matches!(token, InputToken::Function(_)),
+        );
         match token {
-            InputToken::Value(value) => out_queue.push(OutputToken::Value(value)),
+            InputToken::Value(value) => {
+                self.invariants.output(0);
+                push(out_queue, OutputToken::Value(value), full)?
+            }
             InputToken::LeftParen => {
-                paren_count += 1;
-                stack.push(StackToken::LeftParen(pos))
+                if CHECKED {
+                    self.paren_count += 1;
+                    self.last_paren = Some(pos);
+                }
+                push_stack(stack.push_paren(pos), stack_full)?
+            }
+            InputToken::RightParen if CHECKED && self.paren_count == 0 => {
+                return Err(ConvertError::ParenMissmatch(
+                    ParenMissmatchError::UnexpectedClosing {
+                        pos,
+                        open_pos: self.last_paren,
+                    },
+                ))
             }
-            InputToken::RightParen if paren_count == 0 => return Err(ParenMissmatchError { pos }),
             InputToken::RightParen => {
-                paren_count -= 1;
-                while let Some(StackToken::Operator(op)) =
-                    stack.pop_if(|token| matches!(token, StackToken::Operator(_)))
-                {
-                    out_queue.push(OutputToken::Operator(op))
+                if CHECKED {
+                    self.paren_count -= 1;
+                }
+                while let Some(op) = stack.pop_operator() {
+                    self.invariants.output(op.arity());
+                    push(out_queue, OutputToken::Operator(op), full)?
+                }
+                if let Some(Tag::LeftParen(open)) = stack.top() {
+                    trace::paren_matched(open, pos);
                 }
-                stack.pop();
-                if let Some(StackToken::Function(func)) =
-                    stack.pop_if(|token| matches!(token, StackToken::Function(_)))
-                {
-                    out_queue.push(OutputToken::Function(func));
+                stack.discard();
+                if let Some((func, arity)) = stack.pop_function().filter(|_| FUNCTIONS) {
+                    let arity = if self.after_left_paren { 0 } else { arity };
+                    trace::function_closed(pos, arity);
+                    self.invariants.output(arity);
+                    push(out_queue, OutputToken::Function(call(func, arity)), full)?;
                 }
             }
-            InputToken::Function(func) => stack.push(StackToken::Function(func)),
+            InputToken::Function(func) if FUNCTIONS => {
+                push_stack(stack.push_function(func), stack_full)?
+            }
             #[expect(deprecated, reason = "")]
-            InputToken::ArgSeperator | InputToken::ArgSeparator => {
-                while let Some(StackToken::Operator(o)) =
-                    stack.pop_if(|token| matches!(token, StackToken::Operator(_)))
-                {
-                    out_queue.push(OutputToken::Operator(o))
+            InputToken::ArgSeperator | InputToken::ArgSeparator if FUNCTIONS => {
+                while let Some(o) = stack.pop_operator() {
+                    self.invariants.output(o.arity());
+                    push(out_queue, OutputToken::Operator(o), full)?
+                }
+                if !stack.add_argument() && CHECKED {
+                    return Err(ConvertError::ParenMissmatch(
+                        ParenMissmatchError::UnexpectedSeparator {
+                            pos,
+                            open_pos: stack.last_paren(),
+                        },
+                    ));
                 }
             }
             InputToken::Operator(o1) => {
-                while let Some(StackToken::Operator(o2)) = stack.last() {
-                    if o2.precedence() > o1.precedence()
-                        || (o1.precedence() == o2.precedence() && o1.is_left_associative())
-                    {
-                        let Some(StackToken::Operator(o2)) = stack.pop() else {
+                while let Some(o2) = stack.last_operator() {
+                    let pop = match o2.precedence().cmp(&o1.precedence()) {
+                        Ordering::Greater => true,
+                        Ordering::Less => false,
+                        Ordering::Equal if o1.is_left_associative() == o2.is_left_associative() => {
+                            o1.is_left_associative()
+                        }
+                        Ordering::Equal => match self.tie_break {
+                            TieBreak::Incoming => o1.is_left_associative(),
+                            TieBreak::Warn => {
+                                trace::mixed_associativity(pos);
+                                o1.is_left_associative()
+                            }
+                            TieBreak::Left => true,
+                            TieBreak::Right => false,
+                            TieBreak::Error => return Err(ConvertError::MixedAssociativity(pos)),
+                        },
+                    };
+                    if pop {
+                        trace::precedence_pop(pos, o2.precedence(), o1.precedence());
+                        let o2 = stack.pop_operator();
+                        #[cfg(not(feature = "forbid-unsafe"))]
+                        let Some(o2) = o2
+                        else {
                             // SAFETY:
-                            // This has been checked in the while condition
-                            unsafe { std::hint::unreachable_unchecked() }
+                            // `last_operator` returned an operator in the loop condition, so the
+                            // topmost entry is an operator
+                            unsafe { core::hint::unreachable_unchecked() }
+                        };
+                        #[cfg(feature = "forbid-unsafe")]
+                        let Some(o2) = o2
+                        else {
+                            unreachable!("Checked in the loop condition")
                         };
-                        out_queue.push(OutputToken::Operator(o2))
+                        self.invariants.output(o2.arity());
+                        push(out_queue, OutputToken::Operator(o2), full)?
                     } else {
                         break;
                     }
                 }
-                stack.push(StackToken::Operator(o1));
+                push_stack(stack.push_operator(o1), stack_full)?;
+            }
+            #[expect(deprecated, reason = "")]
+            InputToken::ArgSeperator | InputToken::ArgSeparator if CHECKED => {
+                return Err(ConvertError::ParenMissmatch(
+                    ParenMissmatchError::UnexpectedSeparator {
+                        pos,
+                        open_pos: stack.last_paren(),
+                    },
+                ));
+            }
+            #[expect(deprecated, reason = "")]
+            InputToken::Function(_) | InputToken::ArgSeperator | InputToken::ArgSeparator => {}
+        }
+        self.after_left_paren = is_left_paren;
+        self.after_function = is_function;
+        self.invariants
+            .stack(&self.stack, CHECKED.then_some(self.paren_count));
+        Ok(())
+    }
+
+    /// Converts the prefix operator at `pos`. Unlike `push` no operator is popped: the operand of
+    /// a prefix operator follows it, so the operators on the stack are still waiting for theirs.
+    #[cfg(feature = "alloc")]
+    fn push_prefix<F, O>(&mut self, pos: usize, operator: O) -> Result<(), ConvertError>
+    where
+        O: Operator,
+        S: OperatorStack<F, O>,
+    {
+        let token = InputToken::<(), F, O>::Operator(operator);
+        self.invariants.input(&token, FUNCTIONS);
+        self.end = pos + 1;
+        if let InputToken::Operator(operator) = token {
+            push_stack(self.stack.push_operator(operator), || {
+                ConvertError::StackFull(pos)
+            })?;
+        }
+        self.after_left_paren = false;
+        self.after_function = false;
+        self.invariants
+            .stack(&self.stack, CHECKED.then_some(self.paren_count));
+        Ok(())
+    }
+
+    /// Moves the remaining tokens from the stack into the output.
+    fn finish<V, F, G, O>(
+        &mut self,
+        out_queue: &mut impl Queue<OutputToken<V, G, O>>,
+        call: &impl Fn(F, usize) -> G,
+    ) -> Result<(), ConvertError>
+    where
+        O: Operator,
+        S: OperatorStack<F, O>,
+    {
+        let stack = &mut self.stack;
+        if FUNCTIONS && self.after_function {
+            stack.clear_last_function_arity();
+        }
+        // Check for unclosed parentheses before moving the remaining tokens into the output
+        if let Some(open_pos) = stack.last_paren().filter(|_| CHECKED) {
+            return Err(ConvertError::ParenMissmatch(
+                ParenMissmatchError::UnclosedOpening { open_pos },
+            ));
+        }
+        while let Some(tag) = stack.top() {
+            let out = match tag {
+                Tag::LeftParen(_) if CHECKED => unreachable!("Checked above"),
+                Tag::LeftParen(_) => {
+                    stack.discard();
+                    None
+                }
+                Tag::Function => stack.pop_function().map(|(func, arity)| {
+                    self.invariants.output(arity);
+                    OutputToken::Function(call(func, arity))
+                }),
+                Tag::Operator => stack.pop_operator().map(|operator| {
+                    self.invariants.output(operator.arity());
+                    OutputToken::Operator(operator)
+                }),
+            };
+            if let Some(out) = out {
+                push(out_queue, out, || ConvertError::OutputFull(self.end))?;
             }
         }
+        self.invariants.finish();
+        Ok(())
     }
-    for token in stack.into_iter().rev() {
-        let out = match token {
-            StackToken::LeftParen(pos) => return Err(ParenMissmatchError { pos }),
-            StackToken::Function(func) => OutputToken::Function(func),
-            StackToken::Operator(o) => OutputToken::Operator(o),
+}
+
+#[cfg(feature = "alloc")]
+impl<F, O, const CHECKED: bool, const FUNCTIONS: bool> Converter<Stack<F, O>, CHECKED, FUNCTIONS> {
+    /// Like `finish`, but moves the remaining operators into `out_queue` in one go if the stack
+    /// holds nothing else.
+    fn finish_vec<V, G>(
+        &mut self,
+        out_queue: &mut Vec<OutputToken<V, G, O>>,
+        call: &impl Fn(F, usize) -> G,
+    ) -> Result<(), ConvertError>
+    where
+        O: Operator,
+    {
+        let Some(operators) = self.stack.drain_operators() else {
+            return self.finish(out_queue, call);
         };
-        out_queue.push(out);
+        let invariants = &mut self.invariants;
+        out_queue.extend(operators.map(|operator| {
+            invariants.output(operator.arity());
+            OutputToken::Operator(operator)
+        }));
+        self.invariants.finish();
+        Ok(())
     }
-    Ok(out_queue)
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
+    use alloc::{
+        format,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
     use crate::{
         op::{Logical, Math},
-        to_postfix, InputToken, OutputToken,
+        to_postfix, to_postfix_in_place, to_postfix_slice, to_postfix_unchecked,
+        to_postfix_with_arity, to_postfix_with_tie_break, Call, InputToken, OutputToken, TieBreak,
+        TieBreakError,
     };
 
+    #[test]
+    fn map_output_tokens() {
+        let postfix = [
+            OutputToken::val("1"),
+            OutputToken::func("neg"),
+            OutputToken::op(Math::Add),
+        ];
+        let mapped: Vec<OutputToken<usize, String, Logical>> = postfix
+            .into_iter()
+            .map(|token| {
+                token
+                    .map_value(str::len)
+                    .map_function(str::to_uppercase)
+                    .map_operator(|_| Logical::Or)
+            })
+            .collect();
+        assert_eq!(
+            mapped,
+            [
+                OutputToken::Value(1),
+                OutputToken::Function("NEG".to_string()),
+                OutputToken::Operator(Logical::Or),
+            ]
+        );
+    }
+
+    #[test]
+    fn convert_between_token_types() {
+        let token = InputToken::<i32, (), Math>::from(OutputToken::Value(1));
+        assert_eq!(OutputToken::try_from(token), Ok(OutputToken::Value(1)));
+        assert_eq!(
+            OutputToken::<i32, (), Math>::try_from(InputToken::ArgSeparator),
+            Err(crate::NoOutputTokenError)
+        );
+    }
+
+    #[test]
+    fn display_tokens() {
+        let infix = [
+            InputToken::Function("max"),
+            InputToken::LeftParen,
+            InputToken::Value(1),
+            InputToken::ArgSeparator,
+            InputToken::Value(2),
+            InputToken::RightParen,
+            InputToken::Operator(Logical::Or),
+        ];
+        let text: Vec<_> = infix.iter().map(ToString::to_string).collect();
+        assert_eq!(text.concat(), "max(1,2)||");
+        assert_eq!(
+            format!("{:>3}", OutputToken::<i32, &str, Math>::Value(7)),
+            "  7"
+        );
+    }
+
+    #[test]
+    fn preallocated_output() {
+        let infix = [
+            InputToken::<_, (), _>::LeftParen,
+            InputToken::Value(1),
+            InputToken::Operator(Math::Add),
+            InputToken::Value(2),
+            InputToken::RightParen,
+        ];
+        let postfix = to_postfix(infix.clone()).expect("valid expression");
+        assert_eq!(postfix.capacity(), 5);
+        // Only the lower bound of the size hint is reserved
+        let unbounded = || (0..usize::MAX).map_while(|i| infix.get(i).cloned());
+        assert_eq!(to_postfix(unbounded()).map(|postfix| postfix.len()), Ok(3));
+        assert_eq!(
+            to_postfix_with_tie_break(unbounded(), TieBreak::Error).map(|postfix| postfix.len()),
+            Ok(3)
+        );
+    }
+
+    #[test]
+    fn in_place_matches_to_postfix() {
+        // max ( 1 , 2 + 3 ) * 4
+        let infix = vec![
+            InputToken::Function("max"),
+            InputToken::LeftParen,
+            InputToken::Value(1),
+            InputToken::ArgSeparator,
+            InputToken::Value(2),
+            InputToken::Operator(Math::Add),
+            InputToken::Value(3),
+            InputToken::RightParen,
+            InputToken::Operator(Math::Mul),
+            InputToken::Value(4),
+        ];
+        let expected: Vec<_> = to_postfix(infix.clone())
+            .expect("valid expression")
+            .into_iter()
+            .map(|token| match token {
+                OutputToken::Value(v) => InputToken::Value(v),
+                OutputToken::Function(f) => InputToken::Function(f),
+                OutputToken::Operator(o) => InputToken::Operator(o),
+            })
+            .collect();
+        let mut tokens = infix;
+        assert_eq!(to_postfix_in_place(&mut tokens), Ok(()));
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn slice_matches_to_postfix() {
+        // 1 + 2 * 3 ^ max ( 4 , 5 ^ 6 ^ 7 )
+        let infix = vec![
+            InputToken::Value(1),
+            InputToken::Operator(Math::Add),
+            InputToken::Value(2),
+            InputToken::Operator(Math::Mul),
+            InputToken::Value(3),
+            InputToken::Operator(Math::Exponent),
+            InputToken::Function("max"),
+            InputToken::LeftParen,
+            InputToken::Value(4),
+            InputToken::ArgSeparator,
+            InputToken::Value(5),
+            InputToken::Operator(Math::Exponent),
+            InputToken::Value(6),
+            InputToken::Operator(Math::Exponent),
+            InputToken::Value(7),
+            InputToken::RightParen,
+        ];
+        // Every prefix, so the stack holds only operators at the end of some of them
+        for len in 0..=infix.len() {
+            let infix = &infix[..len];
+            assert_eq!(to_postfix_slice(infix), to_postfix(infix.to_vec()));
+        }
+    }
+
+    #[test]
+    fn unchecked_unbalanced_parens() {
+        // ) 1 + ( 2
+        let postfix = to_postfix_unchecked([
+            InputToken::<_, (), _>::RightParen,
+            InputToken::Value(1),
+            InputToken::Operator(Math::Add),
+            InputToken::LeftParen,
+            InputToken::Value(2),
+        ]);
+        assert_eq!(
+            postfix,
+            [
+                OutputToken::Value(1),
+                OutputToken::Value(2),
+                OutputToken::Operator(Math::Add)
+            ]
+        );
+    }
+
     #[test]
     fn value_only() {
         let post_fix = to_postfix([InputToken::<_, (), Math>::Value(1)]);
@@ -348,6 +2661,38 @@ mod tests {
         )
     }
 
+    #[test]
+    fn tie_break_rules() {
+        use crate::op::{Associativity, DynOp};
+        let op = |associativity| DynOp {
+            name: String::new(),
+            precedence: 1,
+            associativity,
+            arity: 2,
+        };
+        // 1 ~ 2 ~ 3 with a right associative first and a left associative second operator
+        let infix = [
+            InputToken::<_, (), _>::Value(1),
+            InputToken::Operator(op(Associativity::Right)),
+            InputToken::Value(2),
+            InputToken::Operator(op(Associativity::Left)),
+            InputToken::Value(3),
+        ];
+        let popped = |tie_break| {
+            let postfix =
+                to_postfix_with_tie_break(infix.clone(), tie_break).expect("valid expression");
+            matches!(postfix[2], OutputToken::Operator(_))
+        };
+        assert!(popped(TieBreak::Incoming));
+        assert!(popped(TieBreak::Warn));
+        assert!(popped(TieBreak::Left));
+        assert!(!popped(TieBreak::Right));
+        assert_eq!(
+            to_postfix_with_tie_break(infix.clone(), TieBreak::default()),
+            to_postfix(infix).map_err(TieBreakError::from)
+        );
+    }
+
     #[test]
     fn wikipedia_example() {
         let post_fix = to_postfix([
@@ -405,7 +2750,80 @@ mod tests {
             InputToken::<_, (), _>::Operator(Logical::Not),
             InputToken::Value(true),
         ]);
-        assert_eq!(postfix, Err(crate::ParenMissmatchError { pos: 1 }))
+        assert_eq!(
+            postfix,
+            Err(crate::ParenMissmatchError::UnexpectedClosing {
+                pos: 1,
+                open_pos: None
+            })
+        )
+    }
+
+    #[test]
+    fn partial_output_before_unexpected_closing_paren() {
+        // 1 - 2 ) * 3
+        let (partial, error) = crate::to_postfix_partial([
+            InputToken::<_, (), _>::Value(1),
+            InputToken::Operator(Math::Sub),
+            InputToken::Value(2),
+            InputToken::RightParen,
+            InputToken::Operator(Math::Mul),
+            InputToken::Value(3),
+        ])
+        .expect_err("extra )");
+        assert_eq!(partial, [OutputToken::Value(1), OutputToken::Value(2)]);
+        assert_eq!(error.pos(), 3);
+    }
+
+    #[test]
+    fn separator_outside_of_function_call() {
+        // max ( 1 , ( 2 , 3 ) )
+        let error = to_postfix([
+            InputToken::<_, _, Math>::Function("max"),
+            InputToken::LeftParen,
+            InputToken::Value(1),
+            InputToken::ArgSeparator,
+            InputToken::LeftParen,
+            InputToken::Value(2),
+            InputToken::ArgSeparator,
+            InputToken::Value(3),
+            InputToken::RightParen,
+            InputToken::RightParen,
+        ])
+        .expect_err("separator in plain parentheses");
+        assert_eq!(
+            error,
+            crate::ParenMissmatchError::UnexpectedSeparator {
+                pos: 6,
+                open_pos: Some(4)
+            }
+        );
+        let error = to_postfix([
+            InputToken::<_, (), Math>::Value(1),
+            InputToken::ArgSeparator,
+            InputToken::Value(2),
+        ])
+        .expect_err("separator outside of parentheses");
+        assert_eq!(error.code(), crate::ErrorCode::UnexpectedSeparator);
+    }
+
+    #[test]
+    fn unexpected_closing_paren_after_group() {
+        // ( 1 ) ) + 2
+        let error = to_postfix([
+            InputToken::<_, (), _>::LeftParen,
+            InputToken::Value(1),
+            InputToken::RightParen,
+            InputToken::RightParen,
+            InputToken::Operator(Math::Add),
+            InputToken::Value(2),
+        ])
+        .expect_err("extra )");
+        assert_eq!((error.pos(), error.open_pos()), (3, Some(0)));
+        assert_eq!(
+            error.to_string(),
+            "Unexpected ')' at position 3, the last '(' at position 0 is already closed"
+        );
     }
 
     #[test]
@@ -417,7 +2835,10 @@ mod tests {
             InputToken::<_, (), _>::Operator(Logical::Not),
             InputToken::Value(true),
         ]);
-        assert_eq!(postfix, Err(crate::ParenMissmatchError { pos: 1 }))
+        assert_eq!(
+            postfix,
+            Err(crate::ParenMissmatchError::UnclosedOpening { open_pos: 1 })
+        )
     }
 
     #[test]
@@ -502,4 +2923,116 @@ mod tests {
         ]);
         assert_ne!(postfix1, postfix2);
     }
+
+    #[test]
+    fn arity_nested_calls() {
+        // f ( g ( 1 , 2 ) , ( 3 ) , h ( ) )
+        let postfix = to_postfix_with_arity([
+            InputToken::<_, _, Math>::Function("f"),
+            InputToken::LeftParen,
+            InputToken::Function("g"),
+            InputToken::LeftParen,
+            InputToken::Value(1),
+            InputToken::ArgSeparator,
+            InputToken::Value(2),
+            InputToken::RightParen,
+            InputToken::ArgSeparator,
+            InputToken::LeftParen,
+            InputToken::Value(3),
+            InputToken::RightParen,
+            InputToken::ArgSeparator,
+            InputToken::Function("h"),
+            InputToken::LeftParen,
+            InputToken::RightParen,
+            InputToken::RightParen,
+        ]);
+        assert_eq!(
+            postfix,
+            Ok(vec![
+                OutputToken::Value(1),
+                OutputToken::Value(2),
+                OutputToken::Function(Call {
+                    function: "g",
+                    arity: 2
+                }),
+                OutputToken::Value(3),
+                OutputToken::Function(Call {
+                    function: "h",
+                    arity: 0
+                }),
+                OutputToken::Function(Call {
+                    function: "f",
+                    arity: 3
+                }),
+            ])
+        );
+    }
+
+    #[test]
+    fn arity_call_without_paren() {
+        // f 1 , 2 + 2
+        let postfix = to_postfix_with_arity([
+            InputToken::Function("f"),
+            InputToken::Value(1),
+            InputToken::ArgSeparator,
+            InputToken::Value(2),
+            InputToken::Operator(Math::Add),
+            InputToken::Value(2),
+        ]);
+        assert_eq!(
+            postfix,
+            Ok(vec![
+                OutputToken::Value(1),
+                OutputToken::Value(2),
+                OutputToken::Value(2),
+                OutputToken::Operator(Math::Add),
+                OutputToken::Function(Call {
+                    function: "f",
+                    arity: 2
+                }),
+            ])
+        );
+
+        // 1 + pi
+        let postfix = to_postfix_with_arity([
+            InputToken::Value(1),
+            InputToken::Operator(Math::Add),
+            InputToken::Function("pi"),
+        ]);
+        assert_eq!(
+            postfix,
+            Ok(vec![
+                OutputToken::Value(1),
+                OutputToken::Function(Call {
+                    function: "pi",
+                    arity: 0
+                }),
+                OutputToken::Operator(Math::Add),
+            ])
+        );
+    }
+
+    #[test]
+    fn error_codes() {
+        let malformed: crate::Error = crate::ast::to_ast([
+            InputToken::<_, (), _>::Value(1),
+            InputToken::Operator(Math::Add),
+        ])
+        .expect_err("missing operand")
+        .into();
+        assert_eq!(malformed.code(), crate::ErrorCode::StackUnderflow);
+        assert_eq!(malformed.code().to_string(), "E006");
+        let unexpected = to_postfix::<(), (), Math>([InputToken::RightParen]).expect_err("extra )");
+        assert_eq!(crate::Error::<()>::from(unexpected).code().as_str(), "E001");
+    }
+
+    #[test]
+    fn error_trait_object() {
+        fn convert() -> Result<(), alloc::boxed::Box<dyn core::error::Error>> {
+            to_postfix::<(), (), Math>([InputToken::RightParen])?;
+            Ok(())
+        }
+        let error = convert().expect_err("unbalanced parentheses");
+        assert_eq!(error.to_string(), "Unexpected ')' at position 0");
+    }
 }
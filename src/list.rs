@@ -0,0 +1,253 @@
+//! Operators whose right operand is a list, like SQL's `x IN (1, 2, 3)`.
+//!
+//! An operator returning true from `Operator::takes_list` and followed by a left parenthesis
+//! takes the parenthesized, separated elements as its right operand. `to_postfix_with_lists`
+//! counts the elements like the arguments of a function call and emits every operator as a
+//! `ListOp` with the number of operands it takes. The postfix expression can be evaluated with
+//! `eval::eval_postfix_with_arity`, which passes the left operand and all elements to the
+//! operator.
+//!
+//! ```rust
+//! use gyard::{
+//!     list::{to_postfix_with_lists, ListOp},
+//!     op::Sql,
+//!     InputToken, OutputToken,
+//! };
+//!
+//! // x IN ( 1 , 2 , 3 ) AND y
+//! let postfix = to_postfix_with_lists::<_, (), _>([
+//!     InputToken::Value("x"),
+//!     InputToken::Operator(Sql::In),
+//!     InputToken::LeftParen,
+//!     InputToken::Value("1"),
+//!     InputToken::ArgSeparator,
+//!     InputToken::Value("2"),
+//!     InputToken::ArgSeparator,
+//!     InputToken::Value("3"),
+//!     InputToken::RightParen,
+//!     InputToken::Operator(Sql::And),
+//!     InputToken::Value("y"),
+//! ])
+//! .unwrap();
+//! assert_eq!(postfix, [
+//!     OutputToken::Value("x"),
+//!     OutputToken::Value("1"),
+//!     OutputToken::Value("2"),
+//!     OutputToken::Value("3"),
+//!     OutputToken::Operator(ListOp { operator: Sql::In, arity: 4 }),
+//!     OutputToken::Value("y"),
+//!     OutputToken::Operator(ListOp { operator: Sql::And, arity: 2 }),
+//! ]);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{Call, Growable, InputToken, Operator, OutputToken, ParenMissmatchError, Queue};
+
+type ListResult<V, F, O> = Result<Vec<OutputToken<V, Call<F>, ListOp<O>>>, ParenMissmatchError>;
+
+/// An operator in the output of `to_postfix_with_lists`
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ListOp<O> {
+    /// The operator
+    pub operator: O,
+    /// The number of operands. If the operator is followed by a list, the list counts as one
+    /// operand per element.
+    pub arity: usize,
+}
+
+impl<O: Operator> Operator for ListOp<O> {
+    fn precedence(&self) -> usize {
+        self.operator.precedence()
+    }
+
+    fn is_left_associative(&self) -> bool {
+        self.operator.is_left_associative()
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn is_commutative(&self) -> bool {
+        self.operator.is_commutative()
+    }
+
+    fn takes_list(&self) -> bool {
+        self.operator.takes_list()
+    }
+}
+
+/// A function of the input or the list after a list operator
+enum Callee<F> {
+    Function(F),
+    List,
+}
+
+/// Receives the output of the conversion and attaches the length of every list to the operator
+/// taking it.
+struct ListQueue<V, F, O> {
+    output: Vec<OutputToken<V, Call<F>, ListOp<O>>>,
+    /// For every value the output leaves on the stack: the length if the value is a list
+    lists: Vec<Option<usize>>,
+}
+
+impl<V, F, O> ListQueue<V, F, O> {
+    /// Removes the `count` topmost values and returns the length of the topmost one if it is a
+    /// list.
+    fn take(&mut self, count: usize) -> Option<usize> {
+        if count == 0 {
+            return None;
+        }
+        let top = self.lists.last().copied().flatten();
+        self.lists.truncate(self.lists.len().saturating_sub(count));
+        top
+    }
+}
+
+impl<V, F, O: Operator> Queue<OutputToken<V, (Callee<F>, usize), O>> for ListQueue<V, F, O> {
+    fn push(&mut self, item: OutputToken<V, (Callee<F>, usize), O>) -> bool {
+        let token = match item {
+            OutputToken::Value(value) => OutputToken::Value(value),
+            OutputToken::Function((Callee::List, len)) => {
+                // The elements stay in the output, the operator takes them all
+                self.take(len);
+                self.lists.push(Some(len));
+                return true;
+            }
+            OutputToken::Function((Callee::Function(function), arity)) => {
+                self.take(arity);
+                OutputToken::Function(Call { function, arity })
+            }
+            OutputToken::Operator(operator) => {
+                let arity = operator.arity();
+                let arity = match self.take(arity) {
+                    Some(len) if operator.takes_list() => arity - 1 + len,
+                    _ => arity,
+                };
+                OutputToken::Operator(ListOp { operator, arity })
+            }
+        };
+        self.lists.push(None);
+        self.output.push(token);
+        true
+    }
+}
+
+/// Like `to_postfix_with_arity`, but reads the parenthesized list after an operator that
+/// `Operator::takes_list` as its right operand. A list must directly follow its operator and
+/// may be empty. Outside of a list and a function call an argument separator is an error.
+pub fn to_postfix_with_lists<V, F, O>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+) -> ListResult<V, F, O>
+where
+    O: Operator,
+{
+    let infix = infix.into_iter();
+    let mut conversion: Growable<_, Callee<F>, O> = Growable::new(&infix, |len| ListQueue {
+        output: Vec::with_capacity(len),
+        lists: Vec::new(),
+    });
+    let call = |callee, arity| (callee, arity);
+    let mut after_list_operator = false;
+    for (pos, token) in infix.enumerate() {
+        if after_list_operator && matches!(token, InputToken::LeftParen) {
+            // The list is converted like the arguments of a call
+            conversion.push(pos, InputToken::Function(Callee::List), &call)?;
+        }
+        after_list_operator =
+            matches!(&token, InputToken::Operator(operator) if operator.takes_list());
+        conversion.push(pos, token.map_function(Callee::Function), &call)?;
+    }
+    conversion.finish(&call)?;
+    Ok(conversion.output.output)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use core::ops::RangeInclusive;
+
+    use super::{to_postfix_with_lists, ListOp};
+    use crate::{
+        eval::{eval_postfix_with_arity, Evaluator},
+        op::Sql,
+        InputToken, ParenMissmatchError,
+    };
+
+    struct Membership;
+
+    impl Evaluator<i64, &str, ListOp<Sql>> for Membership {
+        type Value = i64;
+        type Error = ();
+
+        fn value(&mut self, value: i64) -> Result<i64, ()> {
+            Ok(value)
+        }
+
+        fn operator(&mut self, operator: ListOp<Sql>, args: &mut Vec<i64>) -> Result<i64, ()> {
+            match (operator.operator, args.split_first()) {
+                (Sql::In, Some((x, list))) => Ok(list.contains(x) as i64),
+                (Sql::Add, Some((a, [b]))) => Ok(a + b),
+                _ => Err(()),
+            }
+        }
+
+        fn function(&mut self, _: &str, args: &mut Vec<i64>) -> Result<i64, ()> {
+            args.iter().copied().max().ok_or(())
+        }
+
+        fn function_arity(&self, _: &&str) -> usize {
+            0
+        }
+
+        fn accepted_arity(&self, _: &&str) -> RangeInclusive<usize> {
+            0..=usize::MAX
+        }
+    }
+
+    #[test]
+    fn lists_are_evaluable() {
+        // max ( 1 , 4 ) IN ( 2 + 2 , 5 )
+        let infix = [
+            InputToken::Function("max"),
+            InputToken::LeftParen,
+            InputToken::Value(1),
+            InputToken::ArgSeparator,
+            InputToken::Value(4),
+            InputToken::RightParen,
+            InputToken::Operator(Sql::In),
+            InputToken::LeftParen,
+            InputToken::Value(2),
+            InputToken::Operator(Sql::Add),
+            InputToken::Value(2),
+            InputToken::ArgSeparator,
+            InputToken::Value(5),
+            InputToken::RightParen,
+        ];
+        let postfix = to_postfix_with_lists(infix.clone()).expect("balanced");
+        assert_eq!(eval_postfix_with_arity(postfix, &mut Membership), Ok(1));
+        // 1 IN ( )
+        let empty = to_postfix_with_lists::<_, (), _>([
+            InputToken::Value(1),
+            InputToken::Operator(Sql::In),
+            InputToken::LeftParen,
+            InputToken::RightParen,
+        ])
+        .expect("empty list");
+        assert!(matches!(
+            empty.last(),
+            Some(crate::OutputToken::Operator(ListOp { arity: 1, .. }))
+        ));
+        // Separators outside of lists are still rejected
+        let mut grouped = infix.to_vec();
+        grouped.remove(6);
+        assert!(matches!(
+            to_postfix_with_lists(grouped),
+            Err(ParenMissmatchError::UnexpectedSeparator { pos: 10, .. })
+        ));
+    }
+}
@@ -0,0 +1,159 @@
+//! An adapter for lexers generated with `logos`.
+//!
+//! `tokens` turns a `logos::Lexer` into `InputToken`s. A classification callback receives every
+//! lexed token together with its text and returns the matching `InputToken`, or `None` to skip
+//! it. Every token keeps the byte range it was read from, `span::to_postfix_spanned` converts the
+//! tokens and reports errors with that range.
+//!
+//! ```rust
+//! use gyard::{logos::tokens, op::Math, span::to_postfix_spanned, InputToken, OutputToken};
+//! use logos::Logos;
+//!
+//! #[derive(Logos, Debug, Clone, Copy, PartialEq)]
+//! #[logos(skip r"[ \t]+")]
+//! enum Token {
+//!     #[regex("[0-9]+")]
+//!     Number,
+//!     #[token("+")]
+//!     Plus,
+//!     #[token("*")]
+//!     Times,
+//!     #[token("(")]
+//!     LeftParen,
+//!     #[token(")")]
+//!     RightParen,
+//! }
+//!
+//! let classify = |token, text: &str| {
+//!     Some(match token {
+//!         Token::Number => InputToken::<u32, (), _>::Value(text.parse().ok()?),
+//!         Token::Plus => InputToken::Operator(Math::Add),
+//!         Token::Times => InputToken::Operator(Math::Mul),
+//!         Token::LeftParen => InputToken::LeftParen,
+//!         Token::RightParen => InputToken::RightParen,
+//!     })
+//! };
+//!
+//! let infix = tokens(Token::lexer("2 * (3 + 4)"), classify).collect::<Result<Vec<_>, _>>().unwrap();
+//! let postfix = to_postfix_spanned(infix).unwrap();
+//! assert_eq!(postfix[3].value, OutputToken::Operator(Math::Add));
+//! assert_eq!(postfix[3].span, 7..8);
+//!
+//! let infix = tokens(Token::lexer("2 * 3)"), classify).collect::<Result<Vec<_>, _>>().unwrap();
+//! assert_eq!(to_postfix_spanned(infix).unwrap_err().span, 5..6);
+//! ```
+
+use logos::{Lexer, Logos};
+
+use crate::{span::Spanned, InputToken};
+
+/// Reads `InputToken`s from a `logos` lexer. Created by `tokens`.
+pub struct Tokens<'s, T: Logos<'s>, C> {
+    lexer: Lexer<'s, T>,
+    classify: C,
+}
+
+/// Reads the tokens of `lexer` and converts them with `classify`. `classify` receives a token and
+/// its text and returns `None` for tokens that are not part of the expression, i.e. comments.
+/// Lexer errors are returned with their span.
+pub fn tokens<'s, T, C, V, F, O>(lexer: Lexer<'s, T>, classify: C) -> Tokens<'s, T, C>
+where
+    T: Logos<'s, Source = str>,
+    C: FnMut(T, &'s str) -> Option<InputToken<V, F, O>>,
+{
+    Tokens { lexer, classify }
+}
+
+impl<'s, T, C, V, F, O> Iterator for Tokens<'s, T, C>
+where
+    T: Logos<'s, Source = str>,
+    C: FnMut(T, &'s str) -> Option<InputToken<V, F, O>>,
+{
+    type Item = Result<Spanned<InputToken<V, F, O>>, Spanned<T::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let token = self.lexer.next()?;
+            let span = self.lexer.span();
+            match token {
+                Ok(token) => {
+                    if let Some(value) = (self.classify)(token, self.lexer.slice()) {
+                        return Some(Ok(Spanned { value, span }));
+                    }
+                }
+                Err(value) => return Some(Err(Spanned { value, span })),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use logos::Logos;
+
+    use super::tokens;
+    use crate::{
+        op::Math,
+        span::{to_postfix_spanned, Spanned},
+        InputToken, OutputToken,
+    };
+
+    #[derive(Logos, Debug, Clone, Copy, PartialEq)]
+    #[logos(skip r" +")]
+    enum Token {
+        #[regex("[a-z]+")]
+        Ident,
+        #[token("-")]
+        Minus,
+        #[token("(")]
+        LeftParen,
+        #[regex("#[^\n]*", allow_greedy = true)]
+        Comment,
+    }
+
+    fn classify(token: Token, text: &str) -> Option<InputToken<&str, &str, Math>> {
+        match token {
+            Token::Ident => Some(InputToken::Value(text)),
+            Token::Minus => Some(InputToken::Operator(Math::Sub)),
+            Token::LeftParen => Some(InputToken::LeftParen),
+            Token::Comment => None,
+        }
+    }
+
+    #[test]
+    fn comments_errors_and_unclosed_parens() {
+        let infix = tokens(Token::lexer("a - b # note"), classify)
+            .collect::<Result<Vec<_>, _>>()
+            .expect("valid tokens");
+        assert_eq!(
+            to_postfix_spanned(infix),
+            Ok(vec![
+                Spanned {
+                    value: OutputToken::Value("a"),
+                    span: 0..1
+                },
+                Spanned {
+                    value: OutputToken::Value("b"),
+                    span: 4..5
+                },
+                Spanned {
+                    value: OutputToken::Operator(Math::Sub),
+                    span: 2..3
+                },
+            ])
+        );
+
+        let error = tokens(Token::lexer("a ? b"), classify)
+            .find_map(Result::err)
+            .expect("invalid token");
+        assert_eq!(error.span, 2..3);
+
+        let infix = tokens(Token::lexer("(a - b"), classify)
+            .collect::<Result<Vec<_>, _>>()
+            .expect("valid tokens");
+        let error = to_postfix_spanned(infix).expect_err("unclosed parenthesis");
+        assert_eq!(error.span, 0..1);
+    }
+}
@@ -0,0 +1,235 @@
+//! Custom messages for errors.
+//!
+//! The errors of this crate implement `Display` with English messages. Applications that need
+//! localized or branded messages implement `MessageFormatter` and render errors with `render`.
+//! The formatter receives a `Diagnostic` with the stable `ErrorCode` and the positions of the
+//! error, and can fall back to the default message for codes it does not know.
+//!
+//! ```rust
+//! use core::fmt;
+//! use gyard::{
+//!     message::{render, Diagnostic},
+//!     op::Math,
+//!     to_postfix, ErrorCode, InputToken,
+//! };
+//!
+//! let german = |diagnostic: &Diagnostic<'_>, f: &mut fmt::Formatter<'_>| {
+//!     match (diagnostic.code, diagnostic.open_pos) {
+//!         (ErrorCode::UnclosedOpening, Some(pos)) => {
+//!             write!(f, "Die Klammer an Position {pos} wird nicht geschlossen")
+//!         }
+//!         _ => write!(f, "{}", diagnostic.default_message()),
+//!     }
+//! };
+//!
+//! let error = to_postfix::<(), (), Math>([InputToken::LeftParen]).unwrap_err();
+//! assert_eq!(
+//!     render(&german, &error).to_string(),
+//!     "Die Klammer an Position 0 wird nicht geschlossen"
+//! );
+//! let error = to_postfix::<(), (), Math>([InputToken::RightParen]).unwrap_err();
+//! assert_eq!(render(&german, &error).to_string(), error.to_string());
+//! ```
+
+use core::fmt::{Display, Formatter, Result};
+
+use crate::{
+    ast::AstError, eval::EvalError, expected::Expected, policy::PolicyError, Error, ErrorCode,
+    ParenMissmatchError, TieBreakError,
+};
+
+/// The structured data of an error passed to a `MessageFormatter`
+#[derive(Clone, Copy)]
+pub struct Diagnostic<'a> {
+    /// The stable code of the error
+    pub code: ErrorCode,
+    /// The position of the token that caused the error
+    pub pos: Option<usize>,
+    /// The position of the left parenthesis involved in the error
+    pub open_pos: Option<usize>,
+    /// The tokens that would have been valid
+    pub expected: Option<Expected>,
+    error: &'a dyn Display,
+}
+
+impl<'a> Diagnostic<'a> {
+    /// Creates a diagnostic for an error. `error` is used as the default message.
+    pub fn new(code: ErrorCode, error: &'a dyn Display) -> Self {
+        Self {
+            code,
+            pos: None,
+            open_pos: None,
+            expected: None,
+            error,
+        }
+    }
+
+    /// Returns the `Display` output of the error.
+    pub fn default_message(&self) -> &'a dyn Display {
+        self.error
+    }
+}
+
+impl core::fmt::Debug for Diagnostic<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_struct("Diagnostic")
+            .field("code", &self.code)
+            .field("pos", &self.pos)
+            .field("open_pos", &self.open_pos)
+            .field("expected", &self.expected)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Errors that can be rendered by a `MessageFormatter`
+pub trait Diagnose {
+    /// Returns the structured data of the error.
+    fn diagnostic(&self) -> Diagnostic<'_>;
+}
+
+/// Writes the message of a diagnostic. Closures with the same signature as `format` implement
+/// this trait.
+pub trait MessageFormatter {
+    /// Writes the message of `diagnostic` into `f`.
+    fn format(&self, diagnostic: &Diagnostic<'_>, f: &mut Formatter<'_>) -> Result;
+}
+
+impl<T: Fn(&Diagnostic<'_>, &mut Formatter<'_>) -> Result> MessageFormatter for T {
+    fn format(&self, diagnostic: &Diagnostic<'_>, f: &mut Formatter<'_>) -> Result {
+        self(diagnostic, f)
+    }
+}
+
+/// The message of an error, returned by `render`
+pub struct Rendered<'a, M: ?Sized, E: ?Sized> {
+    formatter: &'a M,
+    error: &'a E,
+}
+
+impl<M: MessageFormatter + ?Sized, E: Diagnose + ?Sized> Display for Rendered<'_, M, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        self.formatter.format(&self.error.diagnostic(), f)
+    }
+}
+
+/// Returns the message `formatter` writes for `error`.
+pub fn render<'a, M, E>(formatter: &'a M, error: &'a E) -> Rendered<'a, M, E>
+where
+    M: MessageFormatter + ?Sized,
+    E: Diagnose + ?Sized,
+{
+    Rendered { formatter, error }
+}
+
+impl Diagnose for ParenMissmatchError {
+    fn diagnostic(&self) -> Diagnostic<'_> {
+        Diagnostic {
+            pos: Some(self.pos()),
+            open_pos: self.open_pos(),
+            expected: self.expected(),
+            ..Diagnostic::new(self.code(), self)
+        }
+    }
+}
+
+impl Diagnose for TieBreakError {
+    fn diagnostic(&self) -> Diagnostic<'_> {
+        match self {
+            TieBreakError::ParenMissmatch(error) => error.diagnostic(),
+            TieBreakError::MixedAssociativity { pos } => Diagnostic {
+                pos: Some(*pos),
+                ..Diagnostic::new(self.code(), self)
+            },
+        }
+    }
+}
+
+impl Diagnose for AstError {
+    fn diagnostic(&self) -> Diagnostic<'_> {
+        let pos = match self {
+            AstError::ParenMissmatch(error) => return error.diagnostic(),
+            AstError::StackUnderflow { pos, .. } => Some(*pos),
+            AstError::EmptyExpression | AstError::UnusedValues { .. } => None,
+        };
+        Diagnostic {
+            pos,
+            expected: self.expected(),
+            ..Diagnostic::new(self.code(), self)
+        }
+    }
+}
+
+impl<E: Display> Diagnose for EvalError<E> {
+    fn diagnostic(&self) -> Diagnostic<'_> {
+        let pos = match self {
+            EvalError::StackUnderflow { pos, .. }
+            | EvalError::ArityMismatch { pos, .. }
+            | EvalError::UnknownFunction { pos }
+            | EvalError::BudgetExceeded { pos, .. }
+            | EvalError::Evaluator { pos, .. } => Some(*pos),
+            EvalError::EmptyExpression | EvalError::UnusedValues { .. } => None,
+        };
+        Diagnostic {
+            pos,
+            ..Diagnostic::new(self.code(), self)
+        }
+    }
+}
+
+impl<F: core::fmt::Debug> Diagnose for PolicyError<F> {
+    fn diagnostic(&self) -> Diagnostic<'_> {
+        match self {
+            PolicyError::ParenMissmatch(error) => error.diagnostic(),
+            PolicyError::UnknownFunction { pos, .. }
+            | PolicyError::ForbiddenFunction { pos, .. } => Diagnostic {
+                pos: Some(*pos),
+                ..Diagnostic::new(self.code(), self)
+            },
+        }
+    }
+}
+
+impl<E: Display> Diagnose for Error<E> {
+    fn diagnostic(&self) -> Diagnostic<'_> {
+        match self {
+            Error::ParenMissmatch(error) => error.diagnostic(),
+            Error::Malformed(error) => error.diagnostic(),
+            Error::Eval(error) => error.diagnostic(),
+            Error::MixedAssociativity { pos }
+            | Error::OutputFull { pos }
+            | Error::StackFull { pos } => Diagnostic {
+                pos: Some(*pos),
+                ..Diagnostic::new(self.code(), self)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use core::fmt;
+
+    use super::{render, Diagnose, Diagnostic};
+    use crate::{ast::AstError, Error, ErrorCode};
+
+    #[test]
+    fn wrapped_errors_keep_their_data() {
+        let error: Error = AstError::StackUnderflow {
+            pos: 3,
+            required: 2,
+            available: 1,
+        }
+        .into();
+        let diagnostic = error.diagnostic();
+        assert_eq!(
+            (diagnostic.code, diagnostic.pos),
+            (ErrorCode::StackUnderflow, Some(3))
+        );
+        let codes = |diagnostic: &Diagnostic<'_>, f: &mut fmt::Formatter<'_>| {
+            write!(f, "{} at {:?}", diagnostic.code, diagnostic.pos)
+        };
+        assert_eq!(render(&codes, &error).to_string(), "E006 at Some(3)");
+    }
+}
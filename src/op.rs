@@ -2,8 +2,44 @@
 //! The precedence is based on the JavaScript definition.
 //! https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Operators/Operator_precedence
 
+#[cfg(feature = "alloc")]
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+
 use crate::Operator;
 
+#[cfg(feature = "alloc")]
+pub mod precedence;
+#[cfg(feature = "alloc")]
+pub mod set;
+pub mod unicode;
+
+impl<O: Operator + ?Sized> Operator for &O {
+    fn precedence(&self) -> usize {
+        (**self).precedence()
+    }
+
+    fn is_left_associative(&self) -> bool {
+        (**self).is_left_associative()
+    }
+
+    fn arity(&self) -> usize {
+        (**self).arity()
+    }
+
+    fn is_commutative(&self) -> bool {
+        (**self).is_commutative()
+    }
+
+    fn takes_list(&self) -> bool {
+        (**self).takes_list()
+    }
+}
+
+#[cfg(feature = "alloc")]
 impl Operator for Box<dyn Operator> {
     fn precedence(&self) -> usize {
         self.as_ref().precedence()
@@ -12,10 +48,28 @@ impl Operator for Box<dyn Operator> {
     fn is_left_associative(&self) -> bool {
         self.as_ref().is_left_associative()
     }
+
+    fn arity(&self) -> usize {
+        self.as_ref().arity()
+    }
+
+    fn is_commutative(&self) -> bool {
+        self.as_ref().is_commutative()
+    }
+
+    fn takes_list(&self) -> bool {
+        self.as_ref().takes_list()
+    }
 }
 
 macro_rules! new_op {
-    ($ty: ty {$($pat: pat => ($prec: literal, $left: literal),)*} $(into $conv_ty: ident :: $conv_var:ident)?) => {
+    (@arity) => {
+        2
+    };
+    (@arity $arity: literal) => {
+        $arity
+    };
+    ($ty: ty {$($pat: pat => ($prec: literal, $left: literal $(, $arity: literal)?),)*} $(commutative [$comm: pat])? $(list [$list: pat])? $(into $conv_ty: ident :: $conv_var:ident)?) => {
         impl Operator for $ty {
             fn precedence(&self) -> usize {
                 #[allow(unused, reason = "This import might not be used in the macro")]
@@ -32,6 +86,28 @@ macro_rules! new_op {
                     $($pat => $left,)*
                 }
             }
+
+            fn arity(&self) -> usize {
+                #[allow(unused, reason = "This import might not be used in the macro")]
+                use $ty::*;
+                match self {
+                    $($pat => new_op!(@arity $($arity)?),)*
+                }
+            }
+
+            $(
+            fn is_commutative(&self) -> bool {
+                use $ty::*;
+                matches!(self, $comm)
+            }
+            )?
+
+            $(
+            fn takes_list(&self) -> bool {
+                use $ty::*;
+                matches!(self, $list)
+            }
+            )?
         }
 
         $(
@@ -40,12 +116,32 @@ macro_rules! new_op {
                 Self::$conv_var(value)
             }
         }
+
+        impl<V, F> From<$ty> for crate::InputToken<V, F, $conv_ty> {
+            fn from(value: $ty) -> Self {
+                Self::Operator($conv_ty::$conv_var(value))
+            }
+        }
+
+        impl<V, F> From<$ty> for crate::OutputToken<V, F, $conv_ty> {
+            fn from(value: $ty) -> Self {
+                Self::Operator($conv_ty::$conv_var(value))
+            }
+        }
         )?
     };
 }
 
 /// Common math operators
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Math {
     /// The addition operator
     Add,
@@ -60,7 +156,15 @@ pub enum Math {
 }
 
 /// Common compare operators
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Compare {
     /// The less than operator
     Lt,
@@ -77,7 +181,15 @@ pub enum Compare {
 }
 
 /// Common logical operators
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Logical {
     /// The xor than operator
     Xor,
@@ -89,8 +201,112 @@ pub enum Logical {
     Not,
 }
 
+/// The operators of spreadsheet formulas, with the precedence used by Excel
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Spreadsheet {
+    /// The range operator `:`
+    Range,
+    /// The prefix negation `-`
+    Negate,
+    /// The postfix percent operator `%`
+    Percent,
+    /// The exponent operator `^`
+    Exponent,
+    /// The multiplication operator
+    Mul,
+    /// The divison operator
+    Div,
+    /// The addition operator
+    Add,
+    /// The subtraction operator
+    Sub,
+    /// The text concatenation operator `&`
+    Concat,
+    /// The equal operator `=`
+    Eq,
+    /// The not equal operator `<>`
+    Ne,
+    /// The less than operator
+    Lt,
+    /// The less or equals operator
+    Le,
+    /// The greater than operator
+    Gt,
+    /// The greater or equals operator
+    Ge,
+}
+
+/// The operators of SQL scalar expressions, with the precedence used by PostgreSQL
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Sql {
+    /// The prefix negation `-`
+    Negate,
+    /// The multiplication operator
+    Mul,
+    /// The divison operator
+    Div,
+    /// The remainder operator `%`
+    Mod,
+    /// The addition operator
+    Add,
+    /// The subtraction operator
+    Sub,
+    /// The string concatenation operator `||`
+    Concat,
+    /// The pattern matching operator `LIKE`
+    Like,
+    /// The negated pattern matching operator `NOT LIKE`
+    NotLike,
+    /// The membership operator `IN`, its right operand is a list
+    In,
+    /// The negated membership operator `NOT IN`, its right operand is a list
+    NotIn,
+    /// The equal operator `=`
+    Eq,
+    /// The not equal operator `<>` or `!=`
+    Ne,
+    /// The less than operator
+    Lt,
+    /// The less or equals operator
+    Le,
+    /// The greater than operator
+    Gt,
+    /// The greater or equals operator
+    Ge,
+    /// The prefix operator `NOT`
+    Not,
+    /// The logical operator `AND`
+    And,
+    /// The logical operator `OR`
+    Or,
+}
+
 /// All predefined operators
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum All {
     /// Math operators
     Math(Math),
@@ -116,32 +332,413 @@ impl Operator for All {
             All::Logical(logical) => logical.is_left_associative(),
         }
     }
+
+    fn arity(&self) -> usize {
+        match self {
+            All::Math(math) => math.arity(),
+            All::Compare(compare) => compare.arity(),
+            All::Logical(logical) => logical.arity(),
+        }
+    }
+
+    fn is_commutative(&self) -> bool {
+        match self {
+            All::Math(math) => math.is_commutative(),
+            All::Compare(compare) => compare.is_commutative(),
+            All::Logical(logical) => logical.is_commutative(),
+        }
+    }
+}
+
+/// The associativity of a [`DynOp`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub enum Associativity {
+    /// `a ~ b ~ c` is evaluated as `(a ~ b) ~ c`
+    Left,
+    /// `a ~ b ~ c` is evaluated as `a ~ (b ~ c)`
+    Right,
+}
+
+/// An operator defined at runtime.
+/// Use this if the operators of your grammar are not known at compile time, i.e. they are loaded
+/// from a plugin or configured by a user.
+///
+/// ```rust
+/// use gyard::{InputToken, OutputToken, Operator, op::{Associativity, DynOp}, to_postfix};
+/// let pow = DynOp {
+///     name: "**".to_string(),
+///     precedence: 13,
+///     associativity: Associativity::Right,
+///     arity: 2,
+/// };
+/// assert!(!pow.is_left_associative());
+/// // 2 ** 3 ** 2
+/// let infix = [
+///     InputToken::<_, (), _>::Value(2),
+///     InputToken::Operator(pow.clone()),
+///     InputToken::Value(3),
+///     InputToken::Operator(pow.clone()),
+///     InputToken::Value(2),
+/// ];
+/// assert_eq!(to_postfix(infix), Ok(vec![
+///     OutputToken::Value(2),
+///     OutputToken::Value(3),
+///     OutputToken::Value(2),
+///     OutputToken::Operator(pow.clone()),
+///     OutputToken::Operator(pow),
+/// ]));
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub struct DynOp {
+    /// The name or symbol of the operator
+    pub name: String,
+    /// The precedence of the operator
+    pub precedence: usize,
+    /// The associativity of the operator
+    pub associativity: Associativity,
+    /// The number of operands the operator takes
+    pub arity: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl Operator for DynOp {
+    fn precedence(&self) -> usize {
+        self.precedence
+    }
+
+    fn is_left_associative(&self) -> bool {
+        self.associativity == Associativity::Left
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
 }
 
 new_op!(Math {
     Add | Sub => (11, true),
     Mul | Div => (12, true),
     Exponent => (13, false),
-} into All::Math);
+} commutative [Add | Mul] into All::Math);
 
 new_op!(Compare {
     Lt | Le | Ge | Gt => (9, true),
     Eq | Ne => (8, true),
-} into All::Compare);
+} commutative [Eq | Ne] into All::Compare);
 
 new_op!(Logical {
     Xor => (6,true),
     And => (4, true),
     Or => (3, true),
-    Not => (14, false),
-} into All::Logical);
+    Not => (14, false, 1),
+} commutative [Xor | And | Or] into All::Logical);
+
+// Unlike in JavaScript the exponent is left associative and binds weaker than the negation, so
+// `-2^2` is 4. A postfix operator binds stronger than everything but the prefix negation.
+new_op!(Spreadsheet {
+    Range => (17, true),
+    Negate => (16, false, 1),
+    Percent => (15, true, 1),
+    Exponent => (14, true),
+    Mul | Div => (12, true),
+    Add | Sub => (11, true),
+    Concat => (10, true),
+    Eq | Ne | Lt | Le | Gt | Ge => (9, true),
+} commutative [Mul | Add | Eq | Ne]);
+
+new_op!(Sql {
+    Negate => (15, false, 1),
+    Mul | Div | Mod => (12, true),
+    Add | Sub => (11, true),
+    Concat => (10, true),
+    Like | NotLike | In | NotIn => (9, true),
+    Eq | Ne | Lt | Le | Gt | Ge => (8, true),
+    Not => (5, false, 1),
+    And => (4, true),
+    Or => (3, true),
+} commutative [Mul | Add | Eq | Ne | And | Or] list [In | NotIn]);
+
+/// An operator type with a fixed set of values. Used by `operator_table`.
+pub trait Variants: Sized + 'static {
+    /// All values of the type
+    const VARIANTS: &'static [Self];
+}
+
+macro_rules! variants {
+    ($ty: ident [$($var: ident),* $(,)?]) => {
+        impl Variants for $ty {
+            const VARIANTS: &'static [Self] = &[$($ty::$var),*];
+        }
+    };
+}
+
+variants!(Math [Add, Sub, Mul, Div, Exponent]);
+variants!(Compare [Lt, Le, Eq, Ne, Ge, Gt]);
+variants!(Logical [Xor, And, Or, Not]);
+variants!(Spreadsheet [
+    Range, Negate, Percent, Exponent, Mul, Div, Add, Sub, Concat, Eq, Ne, Lt, Le, Gt, Ge,
+]);
+variants!(Sql [
+    Negate, Mul, Div, Mod, Add, Sub, Concat, Like, NotLike, In, NotIn, Eq, Ne, Lt, Le, Gt, Ge, Not,
+    And, Or,
+]);
+
+impl Variants for All {
+    const VARIANTS: &'static [Self] = &[
+        All::Math(Math::Add),
+        All::Math(Math::Sub),
+        All::Math(Math::Mul),
+        All::Math(Math::Div),
+        All::Math(Math::Exponent),
+        All::Compare(Compare::Lt),
+        All::Compare(Compare::Le),
+        All::Compare(Compare::Eq),
+        All::Compare(Compare::Ne),
+        All::Compare(Compare::Ge),
+        All::Compare(Compare::Gt),
+        All::Logical(Logical::Xor),
+        All::Logical(Logical::And),
+        All::Logical(Logical::Or),
+        All::Logical(Logical::Not),
+    ];
+}
 
-#[cfg(test)]
+/// A row of `operator_table`
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct OperatorInfo {
+    /// The symbol of the operator as written by `Display`
+    pub symbol: String,
+    /// The precedence of the operator
+    pub precedence: usize,
+    /// The associativity of the operator
+    pub associativity: Associativity,
+    /// The number of operands the operator takes
+    pub arity: usize,
+}
+
+/// Describes every operator of `O`, ordered from the strongest to the weakest binding operator.
+/// Operators with the same precedence keep the order of `Variants::VARIANTS`.
+/// Render the table in help screens to keep them in sync with the grammar.
+///
+/// ```rust
+/// use gyard::op::{operator_table, Associativity, Math, OperatorInfo};
+///
+/// let table = operator_table::<Math>();
+/// assert_eq!(table[0], OperatorInfo {
+///     symbol: "**".to_string(),
+///     precedence: 13,
+///     associativity: Associativity::Right,
+///     arity: 2,
+/// });
+/// let symbols: Vec<_> = table.iter().map(|row| row.symbol.as_str()).collect();
+/// assert_eq!(symbols, ["**", "*", "/", "+", "-"]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn operator_table<O: Variants + Operator + core::fmt::Display>() -> Vec<OperatorInfo> {
+    let mut table: Vec<_> = O::VARIANTS
+        .iter()
+        .map(|operator| OperatorInfo {
+            symbol: operator.to_string(),
+            precedence: operator.precedence(),
+            associativity: if operator.is_left_associative() {
+                Associativity::Left
+            } else {
+                Associativity::Right
+            },
+            arity: operator.arity(),
+        })
+        .collect();
+    table.sort_by_key(|row| core::cmp::Reverse(row.precedence));
+    table
+}
+
+impl core::fmt::Display for Math {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let symbol = match self {
+            Math::Add => "+",
+            Math::Sub => "-",
+            Math::Mul => "*",
+            Math::Div => "/",
+            Math::Exponent => "**",
+        };
+        f.write_str(symbol)
+    }
+}
+
+impl core::fmt::Display for Compare {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let symbol = match self {
+            Compare::Lt => "<",
+            Compare::Le => "<=",
+            Compare::Eq => "==",
+            Compare::Ne => "!=",
+            Compare::Ge => ">=",
+            Compare::Gt => ">",
+        };
+        f.write_str(symbol)
+    }
+}
+
+impl core::fmt::Display for Logical {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let symbol = match self {
+            Logical::Xor => "^",
+            Logical::And => "&&",
+            Logical::Or => "||",
+            Logical::Not => "!",
+        };
+        f.write_str(symbol)
+    }
+}
+
+impl core::fmt::Display for Spreadsheet {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let symbol = match self {
+            Spreadsheet::Range => ":",
+            Spreadsheet::Negate | Spreadsheet::Sub => "-",
+            Spreadsheet::Percent => "%",
+            Spreadsheet::Exponent => "^",
+            Spreadsheet::Mul => "*",
+            Spreadsheet::Div => "/",
+            Spreadsheet::Add => "+",
+            Spreadsheet::Concat => "&",
+            Spreadsheet::Eq => "=",
+            Spreadsheet::Ne => "<>",
+            Spreadsheet::Lt => "<",
+            Spreadsheet::Le => "<=",
+            Spreadsheet::Gt => ">",
+            Spreadsheet::Ge => ">=",
+        };
+        f.write_str(symbol)
+    }
+}
+
+impl core::fmt::Display for Sql {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let symbol = match self {
+            Sql::Negate | Sql::Sub => "-",
+            Sql::Mul => "*",
+            Sql::Div => "/",
+            Sql::Mod => "%",
+            Sql::Add => "+",
+            Sql::Concat => "||",
+            Sql::Like => "LIKE",
+            Sql::NotLike => "NOT LIKE",
+            Sql::In => "IN",
+            Sql::NotIn => "NOT IN",
+            Sql::Eq => "=",
+            Sql::Ne => "<>",
+            Sql::Lt => "<",
+            Sql::Le => "<=",
+            Sql::Gt => ">",
+            Sql::Ge => ">=",
+            Sql::Not => "NOT",
+            Sql::And => "AND",
+            Sql::Or => "OR",
+        };
+        f.write_str(symbol)
+    }
+}
+
+impl core::fmt::Display for All {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            All::Math(math) => math.fmt(f),
+            All::Compare(compare) => compare.fmt(f),
+            All::Logical(logical) => logical.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl core::fmt::Display for DynOp {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
+    use super::{Compare, Logical, Math, Variants};
+
+    #[test]
+    fn all_variants_cover_parts() {
+        let parts: alloc::vec::Vec<super::All> = Math::VARIANTS
+            .iter()
+            .map(|&op| op.into())
+            .chain(Compare::VARIANTS.iter().map(|&op| op.into()))
+            .chain(Logical::VARIANTS.iter().map(|&op| op.into()))
+            .collect();
+        assert_eq!(parts, super::All::VARIANTS);
+        let table = super::operator_table::<super::Sql>();
+        assert_eq!(table.len(), super::Sql::VARIANTS.len());
+        assert_eq!(table.last().map(|row| row.symbol.as_str()), Some("OR"));
+    }
+
     #[test]
     fn convert_op_type() {
         let mut _op: super::All = super::Math::Div.into();
         _op = super::Logical::Or.into();
         _ = _op;
+        let token: crate::OutputToken<i32, (), super::All> = super::Compare::Lt.into();
+        assert_eq!(
+            token,
+            crate::OutputToken::op(super::All::Compare(super::Compare::Lt))
+        );
+    }
+
+    #[test]
+    fn arity() {
+        use crate::Operator;
+        assert_eq!(super::Math::Add.arity(), 2);
+        assert_eq!(super::Logical::Not.arity(), 1);
+        assert_eq!(super::All::Logical(super::Logical::Not).arity(), 1);
+    }
+
+    #[test]
+    fn commutative() {
+        use crate::Operator;
+        assert!(super::Math::Mul.is_commutative());
+        assert!(super::Math::Add.is_commutative());
+        assert!(!super::Math::Sub.is_commutative());
+        assert!(super::All::Compare(super::Compare::Ne).is_commutative());
+        assert!(!super::Logical::Not.is_commutative());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use alloc::{vec, vec::Vec};
+
+        let ops = vec![
+            super::All::Math(super::Math::Exponent),
+            super::All::Logical(super::Logical::Not),
+        ];
+        let json = serde_json::to_string(&ops).expect("serializable");
+        assert_eq!(json, r#"[{"Math":"Exponent"},{"Logical":"Not"}]"#);
+        let back: Vec<super::All> = serde_json::from_str(&json).expect("valid json");
+        assert_eq!(back, ops);
+
+        let error: crate::ParenMissmatchError =
+            serde_json::from_str(r#"{"UnexpectedClosing":{"pos":3}}"#).expect("valid json");
+        assert_eq!(error.pos(), 3);
     }
 }
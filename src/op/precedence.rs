@@ -0,0 +1,254 @@
+//! Precedences declared as relations between operators.
+//!
+//! Instead of picking numbers that have to fit between the numbers of every other plugin, declare
+//! which operator binds tighter than which with a `PrecedenceGraph`. `PrecedenceGraph::resolve`
+//! computes the smallest precedences that satisfy all relations or reports a cycle. The loosest
+//! operators get the precedence 0.
+//!
+//! ```rust
+//! use gyard::op::precedence::PrecedenceGraph;
+//!
+//! let precedences = PrecedenceGraph::new()
+//!     .tighter("*", "+")
+//!     .tighter("**", "*")
+//!     .same("-", "+")
+//!     .same("/", "*")
+//!     .resolve()
+//!     .unwrap();
+//! assert_eq!(precedences.get(&"+"), Some(0));
+//! assert_eq!(precedences.get(&"/"), Some(1));
+//! assert_eq!(precedences.get(&"**"), Some(2));
+//!
+//! let cycle = PrecedenceGraph::new()
+//!     .tighter("*", "+")
+//!     .tighter("+", "-")
+//!     .tighter("-", "*")
+//!     .resolve()
+//!     .unwrap_err();
+//! assert_eq!(cycle.path(), ["*", "+", "-", "*"]);
+//! ```
+
+use alloc::{vec, vec::Vec};
+
+/// This error is returned by `PrecedenceGraph::resolve` if the relations contradict each other.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PrecedenceCycle<K> {
+    path: Vec<K>,
+}
+
+impl<K> PrecedenceCycle<K> {
+    /// The operators of the cycle. Every operator binds tighter than the next one or has the
+    /// same precedence as an operator that does. The first and the last operator are the same or
+    /// have the same precedence.
+    pub fn path(&self) -> &[K] {
+        &self.path
+    }
+}
+
+impl<K: core::fmt::Debug> core::fmt::Display for PrecedenceCycle<K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Cyclic precedence")?;
+        for (index, key) in self.path.iter().enumerate() {
+            let separator = if index == 0 { ": " } else { " > " };
+            write!(f, "{separator}{key:?}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<K: core::fmt::Debug> core::error::Error for PrecedenceCycle<K> {}
+
+/// The precedences computed by `PrecedenceGraph::resolve`
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Precedences<K> {
+    precedences: Vec<(K, usize)>,
+}
+
+impl<K: PartialEq> Precedences<K> {
+    /// Returns the precedence of `operator` or `None` if it is not part of the graph.
+    pub fn get(&self, operator: &K) -> Option<usize> {
+        self.iter()
+            .find_map(|(key, precedence)| (key == operator).then_some(precedence))
+    }
+
+    /// Returns all operators with their precedence in the order they were first mentioned.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, usize)> {
+        self.precedences
+            .iter()
+            .map(|(key, precedence)| (key, *precedence))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+    New,
+    Active,
+    Done,
+}
+
+/// Relations between the precedences of operators. See the module documentation.
+#[derive(Debug, Clone)]
+pub struct PrecedenceGraph<K> {
+    keys: Vec<K>,
+    /// The parent of every key in a union find of keys with the same precedence
+    parents: Vec<usize>,
+    /// `(a, b)` if `a` binds tighter than `b`
+    tighter: Vec<(usize, usize)>,
+}
+
+impl<K> Default for PrecedenceGraph<K> {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            parents: Vec::new(),
+            tighter: Vec::new(),
+        }
+    }
+}
+
+impl<K: PartialEq + Clone> PrecedenceGraph<K> {
+    /// Creates an empty graph.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn index(&mut self, key: K) -> usize {
+        match self.keys.iter().position(|other| *other == key) {
+            Some(index) => index,
+            None => {
+                self.keys.push(key);
+                self.parents.push(self.parents.len());
+                self.parents.len() - 1
+            }
+        }
+    }
+
+    fn root(&self, mut index: usize) -> usize {
+        while self.parents[index] != index {
+            index = self.parents[index];
+        }
+        index
+    }
+
+    /// Adds an operator without relations. It gets the precedence 0 unless other relations
+    /// mention it.
+    pub fn operator(mut self, operator: K) -> Self {
+        self.index(operator);
+        self
+    }
+
+    /// Declares that `a` binds tighter than `b`.
+    pub fn tighter(mut self, a: K, b: K) -> Self {
+        let edge = (self.index(a), self.index(b));
+        self.tighter.push(edge);
+        self
+    }
+
+    /// Declares that `a` and `b` have the same precedence.
+    pub fn same(mut self, a: K, b: K) -> Self {
+        let (a, b) = (self.index(a), self.index(b));
+        let (a, b) = (self.root(a), self.root(b));
+        self.parents[a] = b;
+        self
+    }
+
+    /// Computes the precedence of every operator.
+    pub fn resolve(&self) -> Result<Precedences<K>, PrecedenceCycle<K>> {
+        let roots: Vec<_> = (0..self.keys.len()).map(|key| self.root(key)).collect();
+        let mut visits = vec![Visit::New; self.keys.len()];
+        let mut levels = vec![0; self.keys.len()];
+        let mut path = Vec::new();
+        for &root in &roots {
+            self.visit(root, &roots, &mut visits, &mut levels, &mut path)?;
+        }
+        Ok(Precedences {
+            precedences: self
+                .keys
+                .iter()
+                .zip(&roots)
+                .map(|(key, &root)| (key.clone(), levels[root]))
+                .collect(),
+        })
+    }
+
+    /// Computes the level of the group `group` with a depth first search. `path` contains the
+    /// edges leading to `group`.
+    fn visit(
+        &self,
+        group: usize,
+        roots: &[usize],
+        visits: &mut [Visit],
+        levels: &mut [usize],
+        path: &mut Vec<usize>,
+    ) -> Result<usize, PrecedenceCycle<K>> {
+        if visits[group] == Visit::Done {
+            return Ok(levels[group]);
+        }
+        visits[group] = Visit::Active;
+        for (edge, &(a, b)) in self.tighter.iter().enumerate() {
+            if roots[a] != group {
+                continue;
+            }
+            path.push(edge);
+            let looser = roots[b];
+            if visits[looser] == Visit::Active {
+                let start = path
+                    .iter()
+                    .position(|&edge| roots[self.tighter[edge].0] == looser)
+                    .unwrap_or(path.len() - 1);
+                let mut cycle: Vec<_> = path[start..]
+                    .iter()
+                    .map(|&edge| self.keys[self.tighter[edge].0].clone())
+                    .collect();
+                cycle.push(self.keys[b].clone());
+                return Err(PrecedenceCycle { path: cycle });
+            }
+            let level = self.visit(looser, roots, visits, levels, path)? + 1;
+            levels[group] = levels[group].max(level);
+            path.pop();
+        }
+        visits[group] = Visit::Done;
+        Ok(levels[group])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec::Vec};
+
+    use super::PrecedenceGraph;
+
+    #[test]
+    fn groups_and_cycles() {
+        let precedences = PrecedenceGraph::new()
+            .operator("||")
+            .tighter("&&", "||")
+            .tighter("==", "&&")
+            .same("!=", "==")
+            .tighter("+", "!=")
+            .resolve()
+            .expect("acyclic");
+        let levels: Vec<_> = precedences.iter().collect();
+        assert_eq!(
+            levels,
+            [(&"||", 0), (&"&&", 1), (&"==", 2), (&"!=", 2), (&"+", 3)]
+        );
+
+        let cycle = PrecedenceGraph::new()
+            .tighter("*", "+")
+            .same("+", "-")
+            .tighter("-", "*")
+            .resolve()
+            .expect_err("cyclic");
+        assert_eq!(cycle.path(), ["*", "-", "*"]);
+        assert_eq!(cycle.to_string(), r#"Cyclic precedence: "*" > "-" > "*""#);
+        let same = PrecedenceGraph::new()
+            .same("a", "b")
+            .tighter("a", "b")
+            .resolve()
+            .expect_err("cyclic");
+        assert_eq!(same.path(), ["a", "b"]);
+    }
+}
@@ -0,0 +1,273 @@
+//! Operator sets assembled at runtime.
+//!
+//! `OperatorSetBuilder` collects `DynOp`s and checks them before they reach a tokenizer. A symbol
+//! may be used once as prefix operator and once as infix or postfix operator, because a tokenizer
+//! can tell them apart by whether it expects an operand. A symbol that is both an infix and a
+//! postfix operator can not be resolved without arity information and is rejected.
+//!
+//! Unary operators follow the convention of the predefined operators: right associative unary
+//! operators are prefix operators, left associative unary operators are postfix operators.
+//!
+//! ```rust
+//! use gyard::{
+//!     op::{set::{OperatorSetBuilder, OperatorSetError}, Associativity},
+//!     to_postfix, InputToken, OutputToken,
+//! };
+//!
+//! let set = OperatorSetBuilder::new()
+//!     .infix("-", 11, Associativity::Left)
+//!     .infix("*", 12, Associativity::Left)
+//!     .prefix("-", 15)
+//!     .postfix("!", 16)
+//!     .build()
+//!     .unwrap();
+//! let op = |symbol, expects_operand| {
+//!     InputToken::<_, (), _>::Operator(set.get(symbol, expects_operand).unwrap().clone())
+//! };
+//! // - 3 ! * 2
+//! let postfix = to_postfix([
+//!     op("-", true),
+//!     InputToken::Value(3),
+//!     op("!", false),
+//!     op("*", false),
+//!     InputToken::Value(2),
+//! ])
+//! .unwrap();
+//! let symbols: Vec<_> = postfix
+//!     .iter()
+//!     .map(|token| match token {
+//!         OutputToken::Value(value) => value.to_string(),
+//!         OutputToken::Operator(operator) => operator.name.clone(),
+//!         OutputToken::Function(()) => unreachable!(),
+//!     })
+//!     .collect();
+//! assert_eq!(symbols, ["3", "!", "-", "2", "*"]);
+//!
+//! let ambiguous = OperatorSetBuilder::new()
+//!     .infix("!", 3, Associativity::Left)
+//!     .postfix("!", 16)
+//!     .build();
+//! assert_eq!(ambiguous, Err(OperatorSetError::Ambiguous { symbol: "!".to_string() }));
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use super::{Associativity, DynOp};
+
+/// The position of an operator relative to its operands
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Fixity {
+    /// In front of its only operand, i.e. `-a`
+    Prefix,
+    /// Between its operands, i.e. `a - b`
+    Infix,
+    /// Behind its only operand, i.e. `a!`
+    Postfix,
+}
+
+impl Fixity {
+    /// Returns the fixity of `operator`.
+    pub fn of(operator: &DynOp) -> Self {
+        match (operator.arity, operator.associativity) {
+            (1, Associativity::Right) => Fixity::Prefix,
+            (1, Associativity::Left) => Fixity::Postfix,
+            _ => Fixity::Infix,
+        }
+    }
+}
+
+/// This error is returned by `OperatorSetBuilder::build`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum OperatorSetError {
+    /// Two operators with the same symbol and fixity are defined.
+    Duplicate {
+        /// The symbol of the operators
+        symbol: String,
+    },
+    /// The symbol is defined as infix and as postfix operator.
+    Ambiguous {
+        /// The symbol of the operators
+        symbol: String,
+    },
+    /// The operator takes no operands.
+    InvalidArity {
+        /// The symbol of the operator
+        symbol: String,
+    },
+}
+
+impl core::fmt::Display for OperatorSetError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            OperatorSetError::Duplicate { symbol } => {
+                write!(f, "Operator {symbol} is defined twice")
+            }
+            OperatorSetError::Ambiguous { symbol } => {
+                write!(
+                    f,
+                    "Operator {symbol} is both an infix and a postfix operator"
+                )
+            }
+            OperatorSetError::InvalidArity { symbol } => {
+                write!(f, "Operator {symbol} takes no operands")
+            }
+        }
+    }
+}
+
+impl core::error::Error for OperatorSetError {}
+
+/// Collects operators for an `OperatorSet`. See the module documentation.
+#[derive(Debug, Clone, Default)]
+pub struct OperatorSetBuilder {
+    operators: Vec<DynOp>,
+}
+
+impl OperatorSetBuilder {
+    /// Creates an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an operator. Its fixity is derived with `Fixity::of`.
+    pub fn operator(mut self, operator: DynOp) -> Self {
+        self.operators.push(operator);
+        self
+    }
+
+    /// Adds a binary operator.
+    pub fn infix(
+        self,
+        symbol: impl Into<String>,
+        precedence: usize,
+        associativity: Associativity,
+    ) -> Self {
+        self.operator(DynOp {
+            name: symbol.into(),
+            precedence,
+            associativity,
+            arity: 2,
+        })
+    }
+
+    /// Adds a unary operator in front of its operand.
+    pub fn prefix(self, symbol: impl Into<String>, precedence: usize) -> Self {
+        self.operator(DynOp {
+            name: symbol.into(),
+            precedence,
+            associativity: Associativity::Right,
+            arity: 1,
+        })
+    }
+
+    /// Adds a unary operator behind its operand.
+    pub fn postfix(self, symbol: impl Into<String>, precedence: usize) -> Self {
+        self.operator(DynOp {
+            name: symbol.into(),
+            precedence,
+            associativity: Associativity::Left,
+            arity: 1,
+        })
+    }
+
+    /// Checks the operators and returns the first conflict.
+    pub fn build(self) -> Result<OperatorSet, OperatorSetError> {
+        for (index, operator) in self.operators.iter().enumerate() {
+            let symbol = || operator.name.clone();
+            if operator.arity == 0 {
+                return Err(OperatorSetError::InvalidArity { symbol: symbol() });
+            }
+            let fixity = Fixity::of(operator);
+            for other in self.operators[..index]
+                .iter()
+                .filter(|other| other.name == operator.name)
+            {
+                match (Fixity::of(other), fixity) {
+                    (a, b) if a == b => {
+                        return Err(OperatorSetError::Duplicate { symbol: symbol() })
+                    }
+                    (Fixity::Infix, Fixity::Postfix) | (Fixity::Postfix, Fixity::Infix) => {
+                        return Err(OperatorSetError::Ambiguous { symbol: symbol() })
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(OperatorSet {
+            operators: self.operators,
+        })
+    }
+}
+
+/// A checked set of operators created by `OperatorSetBuilder`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OperatorSet {
+    operators: Vec<DynOp>,
+}
+
+impl OperatorSet {
+    /// Returns all operators in the order they were added.
+    pub fn operators(&self) -> &[DynOp] {
+        &self.operators
+    }
+
+    /// Returns the operator with `symbol` and `fixity`.
+    pub fn find(&self, symbol: &str, fixity: Fixity) -> Option<&DynOp> {
+        self.operators
+            .iter()
+            .find(|operator| operator.name == symbol && Fixity::of(operator) == fixity)
+    }
+
+    /// Returns the operator a tokenizer should emit for `symbol`. `expects_operand` is true at
+    /// the start of an expression and after `(`, `,`, prefix and infix operators. In this case
+    /// only prefix operators match, otherwise only infix and postfix operators.
+    pub fn get(&self, symbol: &str, expects_operand: bool) -> Option<&DynOp> {
+        self.operators.iter().find(|operator| {
+            operator.name == symbol && (Fixity::of(operator) == Fixity::Prefix) == expects_operand
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Fixity, OperatorSetBuilder, OperatorSetError};
+    use crate::op::Associativity;
+
+    #[test]
+    fn conflicts() {
+        let duplicate = OperatorSetBuilder::new()
+            .infix("+", 1, Associativity::Left)
+            .infix("+", 2, Associativity::Right)
+            .build();
+        assert_eq!(
+            duplicate,
+            Err(OperatorSetError::Duplicate { symbol: "+".into() })
+        );
+        let nullary = OperatorSetBuilder::new().operator(crate::op::DynOp {
+            name: "?".into(),
+            precedence: 1,
+            associativity: Associativity::Left,
+            arity: 0,
+        });
+        assert_eq!(
+            nullary.build(),
+            Err(OperatorSetError::InvalidArity { symbol: "?".into() })
+        );
+        let set = OperatorSetBuilder::new()
+            .prefix("!", 14)
+            .postfix("!", 16)
+            .build()
+            .expect("prefix and postfix do not conflict");
+        assert_eq!(set.get("!", true).map(|op| op.precedence), Some(14));
+        assert_eq!(
+            set.find("!", Fixity::Postfix).map(|op| op.precedence),
+            Some(16)
+        );
+        assert_eq!(set.get("!", false), set.find("!", Fixity::Postfix));
+    }
+}
@@ -0,0 +1,203 @@
+//! The symbols of the predefined operators.
+//!
+//! `ASCII_SYMBOLS` lists the symbols the `Display` implementations write. `^` is the exclusive or,
+//! the exponent is written `**`. Math pasted from documents often uses `×`, `÷` or `≤` instead of
+//! their ASCII spellings. `UNICODE_SYMBOLS` maps these characters to the predefined operators, and
+//! the `TryFrom` implementations in this module accept both spellings. `√` is not an operator and
+//! maps to the function `sqrt`. The lexers of this crate read the symbols with `symbol_prefix`.
+//!
+//! ```rust
+//! use gyard::op::{unicode::Symbol, All, Compare, Logical, Math};
+//!
+//! assert_eq!(Math::try_from('×'), Ok(Math::Mul));
+//! assert_eq!(Math::try_from("*"), Ok(Math::Mul));
+//! assert_eq!(Logical::try_from("^"), Ok(Logical::Xor));
+//! assert_eq!(All::try_from("≠"), Ok(All::Compare(Compare::Ne)));
+//! assert_eq!(Symbol::try_from('√'), Ok(Symbol::Function("sqrt")));
+//! assert!(Math::try_from('≤').is_err());
+//! ```
+
+use super::{All, Compare, Logical, Math};
+
+/// The meaning of a symbol
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Symbol {
+    /// A predefined operator
+    Operator(All),
+    /// A function, i.e. `sqrt` for `√`
+    Function(&'static str),
+}
+
+/// The ASCII symbols of the predefined operators. A symbol is listed before every symbol it
+/// starts with, so the first symbol a text starts with is the longest one.
+pub const ASCII_SYMBOLS: [(&str, All); 15] = [
+    ("**", All::Math(Math::Exponent)),
+    ("<=", All::Compare(Compare::Le)),
+    (">=", All::Compare(Compare::Ge)),
+    ("==", All::Compare(Compare::Eq)),
+    ("!=", All::Compare(Compare::Ne)),
+    ("&&", All::Logical(Logical::And)),
+    ("||", All::Logical(Logical::Or)),
+    ("+", All::Math(Math::Add)),
+    ("-", All::Math(Math::Sub)),
+    ("*", All::Math(Math::Mul)),
+    ("/", All::Math(Math::Div)),
+    ("^", All::Logical(Logical::Xor)),
+    ("<", All::Compare(Compare::Lt)),
+    (">", All::Compare(Compare::Gt)),
+    ("!", All::Logical(Logical::Not)),
+];
+
+/// The Unicode symbols and their meaning
+pub const UNICODE_SYMBOLS: &[(char, Symbol)] = &[
+    ('×', Symbol::Operator(All::Math(Math::Mul))),
+    ('÷', Symbol::Operator(All::Math(Math::Div))),
+    ('−', Symbol::Operator(All::Math(Math::Sub))),
+    ('≤', Symbol::Operator(All::Compare(Compare::Le))),
+    ('≥', Symbol::Operator(All::Compare(Compare::Ge))),
+    ('≠', Symbol::Operator(All::Compare(Compare::Ne))),
+    ('¬', Symbol::Operator(All::Logical(Logical::Not))),
+    ('∧', Symbol::Operator(All::Logical(Logical::And))),
+    ('∨', Symbol::Operator(All::Logical(Logical::Or))),
+    ('√', Symbol::Function("sqrt")),
+];
+
+/// Returns the meaning of the symbol `text` starts with and the length of the symbol in bytes.
+/// Looks up the longest symbol of `ASCII_SYMBOLS` and then the first character in
+/// `UNICODE_SYMBOLS`.
+pub fn symbol_prefix(text: &str) -> Option<(Symbol, usize)> {
+    if let Some((symbol, operator)) = ASCII_SYMBOLS
+        .iter()
+        .find(|(symbol, _)| text.starts_with(symbol))
+    {
+        return Some((Symbol::Operator(*operator), symbol.len()));
+    }
+    let c = text.chars().next()?;
+    let meaning = Symbol::try_from(c).ok()?;
+    Some((meaning, c.len_utf8()))
+}
+
+/// This error is returned if a symbol does not stand for an operator of the requested type.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct UnknownSymbol;
+
+impl core::fmt::Display for UnknownSymbol {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Unknown operator symbol")
+    }
+}
+
+impl core::error::Error for UnknownSymbol {}
+
+impl TryFrom<char> for Symbol {
+    type Error = UnknownSymbol;
+
+    /// Looks up `symbol` in `UNICODE_SYMBOLS`.
+    fn try_from(symbol: char) -> Result<Self, UnknownSymbol> {
+        UNICODE_SYMBOLS
+            .iter()
+            .find(|(c, _)| *c == symbol)
+            .map(|(_, meaning)| *meaning)
+            .ok_or(UnknownSymbol)
+    }
+}
+
+impl TryFrom<&str> for Symbol {
+    type Error = UnknownSymbol;
+
+    /// Accepts a symbol of `ASCII_SYMBOLS` or a single character of `UNICODE_SYMBOLS`.
+    fn try_from(symbol: &str) -> Result<Self, UnknownSymbol> {
+        match symbol_prefix(symbol) {
+            Some((meaning, len)) if len == symbol.len() => Ok(meaning),
+            _ => Err(UnknownSymbol),
+        }
+    }
+}
+
+impl TryFrom<char> for All {
+    type Error = UnknownSymbol;
+
+    fn try_from(symbol: char) -> Result<Self, UnknownSymbol> {
+        Self::try_from(symbol.encode_utf8(&mut [0; 4]) as &str)
+    }
+}
+
+impl TryFrom<&str> for All {
+    type Error = UnknownSymbol;
+
+    fn try_from(symbol: &str) -> Result<Self, UnknownSymbol> {
+        match Symbol::try_from(symbol)? {
+            Symbol::Operator(operator) => Ok(operator),
+            Symbol::Function(_) => Err(UnknownSymbol),
+        }
+    }
+}
+
+/// Implements `TryFrom<char>` and `TryFrom<&str>` for a part of `All`.
+macro_rules! try_from_part {
+    ($($ty: ident),*) => {$(
+        impl TryFrom<char> for $ty {
+            type Error = UnknownSymbol;
+
+            fn try_from(symbol: char) -> Result<Self, UnknownSymbol> {
+                match All::try_from(symbol)? {
+                    All::$ty(operator) => Ok(operator),
+                    _ => Err(UnknownSymbol),
+                }
+            }
+        }
+
+        impl TryFrom<&str> for $ty {
+            type Error = UnknownSymbol;
+
+            fn try_from(symbol: &str) -> Result<Self, UnknownSymbol> {
+                match All::try_from(symbol)? {
+                    All::$ty(operator) => Ok(operator),
+                    _ => Err(UnknownSymbol),
+                }
+            }
+        }
+    )*};
+}
+
+try_from_part!(Math, Compare, Logical);
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::{symbol_prefix, Symbol, UnknownSymbol, ASCII_SYMBOLS, UNICODE_SYMBOLS};
+    use crate::op::{All, Compare, Logical, Variants};
+
+    #[test]
+    fn display_round_trip() {
+        for operator in All::VARIANTS {
+            let symbol = alloc::string::ToString::to_string(operator);
+            assert_eq!(All::try_from(symbol.as_str()), Ok(*operator));
+        }
+        for (c, meaning) in UNICODE_SYMBOLS {
+            assert_eq!(Symbol::try_from(*c), Ok(*meaning));
+        }
+        assert_eq!(Logical::try_from('¬'), Ok(Logical::Not));
+        assert_eq!(All::try_from('√'), Err(UnknownSymbol));
+        assert_eq!(All::try_from("××"), Err(UnknownSymbol));
+        assert_eq!(All::try_from("<>"), Err(UnknownSymbol));
+    }
+
+    #[test]
+    fn longest_symbol_first() {
+        for (i, (symbol, _)) in ASCII_SYMBOLS.iter().enumerate() {
+            assert!(ASCII_SYMBOLS[..i]
+                .iter()
+                .all(|(before, _)| !symbol.starts_with(before)));
+            assert_eq!(
+                symbol_prefix(symbol).map(|(_, len)| len),
+                Some(symbol.len())
+            );
+        }
+        assert_eq!(
+            symbol_prefix("≤ 2"),
+            Some((Symbol::Operator(All::Compare(Compare::Le)), '≤'.len_utf8()))
+        );
+        assert_eq!(symbol_prefix("x"), None);
+    }
+}
@@ -0,0 +1,160 @@
+//! Internal invariant checks enabled by the `paranoid` feature.
+//!
+//! The conversion verifies after every token that the stack is consistent and that the
+//! parentheses are counted correctly. It also simulates the evaluation of the output: if the infix
+//! expression is well formed, every operator and function finds its operands and exactly one value
+//! is left. A violation is a bug in the conversion and panics. Without the feature all checks
+//! compile to nothing.
+
+#[cfg(feature = "paranoid")]
+use alloc::vec::Vec;
+
+use crate::{stack::OperatorStack, InputToken, Operator};
+
+/// Tracks the infix and the postfix expression of a conversion.
+#[cfg(feature = "paranoid")]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Invariants {
+    /// The number of values the output leaves on the stack
+    depth: usize,
+    /// True if an output token took more operands than available
+    underflow: bool,
+    /// False once the infix expression is known to be malformed
+    well_formed: bool,
+    expect_operand: bool,
+    /// For every open left parenthesis: true if it starts a function call
+    parens: Vec<bool>,
+    after_function: bool,
+    after_left_paren: bool,
+}
+
+#[cfg(feature = "paranoid")]
+impl Invariants {
+    pub(crate) fn new() -> Self {
+        Self {
+            well_formed: true,
+            expect_operand: true,
+            ..Self::default()
+        }
+    }
+
+    /// Records an input token. `functions` is false if the conversion ignores functions and
+    /// argument separators.
+    pub(crate) fn input<V, F, O: Operator>(
+        &mut self,
+        token: &InputToken<V, F, O>,
+        functions: bool,
+    ) {
+        let expect_operand = self.expect_operand;
+        let (valid, next) = match token {
+            InputToken::Value(_) => (expect_operand, false),
+            InputToken::Operator(operator) => match operator.arity() {
+                1 if operator.is_left_associative() => (!expect_operand, false),
+                1 => (expect_operand, true),
+                2 => (!expect_operand, true),
+                _ => (false, expect_operand),
+            },
+            InputToken::Function(_) => (functions && expect_operand, true),
+            InputToken::LeftParen => {
+                self.parens.push(self.after_function);
+                (expect_operand, true)
+            }
+            InputToken::RightParen => match self.parens.pop() {
+                Some(call) => (!expect_operand || (call && self.after_left_paren), false),
+                None => (false, false),
+            },
+            #[expect(deprecated, reason = "")]
+            InputToken::ArgSeperator | InputToken::ArgSeparator => (
+                functions && !expect_operand && self.parens.last() == Some(&true),
+                true,
+            ),
+        };
+        self.well_formed &= valid;
+        self.expect_operand = next;
+        self.after_function = matches!(token, InputToken::Function(_));
+        self.after_left_paren = matches!(token, InputToken::LeftParen);
+    }
+
+    /// Records an output token that takes `arity` operands.
+    pub(crate) fn output(&mut self, arity: usize) {
+        match self.depth.checked_sub(arity) {
+            Some(depth) => self.depth = depth + 1,
+            None => {
+                self.underflow = true;
+                self.depth = 1;
+            }
+        }
+    }
+
+    /// Checks the shape of the stack. `paren_count` is the number of left parentheses counted by
+    /// a checked conversion.
+    pub(crate) fn stack<F, O>(&self, stack: &impl OperatorStack<F, O>, paren_count: Option<isize>) {
+        let parens = stack.check_shape();
+        if let Some(paren_count) = paren_count {
+            assert_eq!(
+                usize::try_from(paren_count).ok(),
+                Some(parens),
+                "Paren count does not match the stack"
+            );
+        }
+        if self.well_formed {
+            assert_eq!(parens, self.parens.len(), "Left parentheses got lost");
+        }
+    }
+
+    /// Checks the output of a conversion that finished without an error.
+    pub(crate) fn finish(&self) {
+        if self.well_formed && !self.expect_operand && self.parens.is_empty() {
+            assert!(!self.underflow, "Output token without enough operands");
+            assert_eq!(self.depth, 1, "Output does not reduce to a single value");
+        }
+    }
+}
+
+/// The checks without the `paranoid` feature
+#[cfg(not(feature = "paranoid"))]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Invariants;
+
+#[cfg(not(feature = "paranoid"))]
+impl Invariants {
+    pub(crate) fn new() -> Self {
+        Self
+    }
+
+    #[inline(always)]
+    pub(crate) fn input<V, F, O: Operator>(&mut self, _: &InputToken<V, F, O>, _: bool) {}
+
+    #[inline(always)]
+    pub(crate) fn output(&mut self, _: usize) {}
+
+    #[inline(always)]
+    pub(crate) fn stack<F, O>(&self, _: &impl OperatorStack<F, O>, _: Option<isize>) {}
+
+    #[inline(always)]
+    pub(crate) fn finish(&self) {}
+}
+
+#[cfg(all(test, feature = "paranoid"))]
+mod tests {
+    use super::Invariants;
+    use crate::{op::Math, InputToken};
+
+    #[test]
+    #[should_panic(expected = "Output token without enough operands")]
+    fn detects_operators_before_their_operands() {
+        // 1 + 2 converted into 1 + 2
+        let mut invariants = Invariants::new();
+        for token in [
+            InputToken::<_, (), _>::Value(1),
+            InputToken::Operator(Math::Add),
+            InputToken::Value(2),
+        ] {
+            invariants.input(&token, true);
+        }
+        for arity in [0, 2, 0] {
+            invariants.output(arity);
+        }
+        invariants.finish();
+    }
+}
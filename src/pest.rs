@@ -0,0 +1,203 @@
+//! An adapter for parse trees produced by `pest`.
+//!
+//! The grammar only has to recognise the shape of an expression, precedence is handled by the
+//! conversion. `tokens` walks the parse tree and asks a callback how to treat each pair:
+//!
+//! - `Node::Value` and `Node::Operator` emit a single token.
+//! - `Node::Expr` walks the inner pairs in order, use it for rules containing operands and
+//!   operators.
+//! - `Node::Group` walks the inner pairs inside parentheses.
+//! - `Node::Call` emits a function call. Every inner pair that is not skipped is one argument.
+//! - `Node::Skip` ignores the pair and its inner pairs, i.e. the name of a function or `EOI`.
+//!
+//! ```rust
+//! use gyard::{op::Math, pest::{tokens, Node}, to_postfix, OutputToken};
+//! use pest::Parser;
+//! use pest_derive::Parser;
+//!
+//! #[derive(Parser)]
+//! #[grammar_inline = r#"
+//! WHITESPACE = _{ " " }
+//! expr    = { operand ~ (op ~ operand)* }
+//! operand = _{ call | number | "(" ~ group ~ ")" }
+//! group   = { expr }
+//! call    = { name ~ "(" ~ (expr ~ ("," ~ expr)*)? ~ ")" }
+//! name    = @{ ASCII_ALPHA+ }
+//! number  = @{ ASCII_DIGIT+ }
+//! op      = { "+" | "*" }
+//! main    = _{ SOI ~ expr ~ EOI }
+//! "#]
+//! struct Calc;
+//!
+//! # fn main() {
+//! let pairs = Calc::parse(Rule::main, "2 * max(1, 3 + 4)").unwrap();
+//! let infix = tokens(pairs, |pair| {
+//!     Ok::<_, std::num::ParseIntError>(match pair.as_rule() {
+//!         Rule::expr => Node::Expr,
+//!         Rule::group => Node::Group,
+//!         Rule::call => Node::Call(pair.clone().into_inner().next().map_or("", |name| name.as_str())),
+//!         Rule::number => Node::Value(pair.as_str().parse::<u32>()?),
+//!         Rule::op if pair.as_str() == "+" => Node::Operator(Math::Add),
+//!         Rule::op => Node::Operator(Math::Mul),
+//!         _ => Node::Skip,
+//!     })
+//! })
+//! .unwrap();
+//! assert_eq!(to_postfix(infix), Ok(vec![
+//!     OutputToken::Value(2),
+//!     OutputToken::Value(1),
+//!     OutputToken::Value(3),
+//!     OutputToken::Value(4),
+//!     OutputToken::Operator(Math::Add),
+//!     OutputToken::Function("max"),
+//!     OutputToken::Operator(Math::Mul),
+//! ]));
+//! # }
+//! ```
+
+use alloc::vec::Vec;
+
+use pest::{
+    iterators::{Pair, Pairs},
+    RuleType,
+};
+
+use crate::InputToken;
+
+/// How `tokens` treats a pair of the parse tree.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Node<V, F, O> {
+    /// The pair is a value.
+    Value(V),
+    /// The pair is an operator.
+    Operator(O),
+    /// The inner pairs are operands and operators.
+    Expr,
+    /// The inner pairs are wrapped in parentheses.
+    Group,
+    /// The pair calls a function. Every inner pair that is not skipped is one argument.
+    Call(F),
+    /// The pair is not part of the expression.
+    Skip,
+}
+
+/// Walks `pairs` and emits the infix tokens of the expression. `classify` decides how each pair
+/// is treated. The first error returned by `classify` stops the walk.
+pub fn tokens<'i, R, V, F, O, E>(
+    pairs: Pairs<'i, R>,
+    mut classify: impl FnMut(&Pair<'i, R>) -> Result<Node<V, F, O>, E>,
+) -> Result<Vec<InputToken<V, F, O>>, E>
+where
+    R: RuleType,
+{
+    let mut infix = Vec::new();
+    for pair in pairs {
+        walk(pair, &mut classify, &mut infix)?;
+    }
+    Ok(infix)
+}
+
+/// Emits the tokens of `pair`. Returns false if the pair was skipped.
+fn walk<'i, R, V, F, O, E>(
+    pair: Pair<'i, R>,
+    classify: &mut impl FnMut(&Pair<'i, R>) -> Result<Node<V, F, O>, E>,
+    infix: &mut Vec<InputToken<V, F, O>>,
+) -> Result<bool, E>
+where
+    R: RuleType,
+{
+    match classify(&pair)? {
+        Node::Value(value) => infix.push(InputToken::Value(value)),
+        Node::Operator(operator) => infix.push(InputToken::Operator(operator)),
+        Node::Expr => {
+            for inner in pair.into_inner() {
+                walk(inner, classify, infix)?;
+            }
+        }
+        Node::Group => {
+            infix.push(InputToken::LeftParen);
+            for inner in pair.into_inner() {
+                walk(inner, classify, infix)?;
+            }
+            infix.push(InputToken::RightParen);
+        }
+        Node::Call(function) => {
+            infix.push(InputToken::Function(function));
+            infix.push(InputToken::LeftParen);
+            let mut first = true;
+            for inner in pair.into_inner() {
+                let separator = infix.len();
+                if !first {
+                    infix.push(InputToken::ArgSeparator);
+                }
+                if walk(inner, classify, infix)? {
+                    first = false;
+                } else {
+                    infix.truncate(separator);
+                }
+            }
+            infix.push(InputToken::RightParen);
+        }
+        Node::Skip => return Ok(false),
+    }
+    Ok(true)
+}
+
+// The derived parser needs `std`
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use pest::Parser;
+    use pest_derive::Parser;
+
+    use super::{tokens, Node};
+    use crate::{op::Math, InputToken};
+
+    #[derive(Parser)]
+    #[grammar_inline = r#"
+WHITESPACE = _{ " " }
+expr    = { neg* ~ operand ~ (op ~ neg* ~ operand)* }
+operand = _{ call | ident | "(" ~ group ~ ")" }
+group   = { expr }
+call    = { name ~ "(" ~ (expr ~ ("," ~ expr)*)? ~ ")" }
+name    = @{ ASCII_ALPHA+ }
+ident   = @{ ASCII_ALPHA+ }
+neg     = { "-" }
+op      = { "-" }
+main    = _{ SOI ~ expr ~ EOI }
+"#]
+    struct Grammar;
+
+    #[test]
+    fn calls_groups_and_skipped_pairs() {
+        let pairs = Grammar::parse(Rule::main, "f() - (g(a, -b))").expect("valid expression");
+        let infix = tokens(pairs, |pair| {
+            Ok::<_, ()>(match pair.as_rule() {
+                Rule::expr => Node::Expr,
+                Rule::group => Node::Group,
+                Rule::call => Node::Call(pair.as_str().chars().next().ok_or(())?),
+                Rule::ident => Node::Value(pair.as_str()),
+                Rule::neg | Rule::op => Node::Operator(Math::Sub),
+                _ => Node::Skip,
+            })
+        })
+        .expect("valid tokens");
+        assert_eq!(
+            infix,
+            [
+                InputToken::Function('f'),
+                InputToken::LeftParen,
+                InputToken::RightParen,
+                InputToken::Operator(Math::Sub),
+                InputToken::LeftParen,
+                InputToken::Function('g'),
+                InputToken::LeftParen,
+                InputToken::Value("a"),
+                InputToken::ArgSeparator,
+                InputToken::Operator(Math::Sub),
+                InputToken::Value("b"),
+                InputToken::RightParen,
+                InputToken::RightParen,
+            ]
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! Restrict the functions an expression may call.
+//!
+//! `to_postfix_with_policy` converts like `to_postfix`, but rejects every function that the
+//! `FunctionPolicy` does not permit. The conversion stops at the first rejected function, before
+//! any later token is read. Use it as a first line of defense when compiling formulas written by
+//! untrusted users.
+//!
+//! ```rust
+//! use gyard::{
+//!     op::Math,
+//!     policy::{to_postfix_with_policy, FunctionPolicy, PolicyError},
+//!     InputToken,
+//! };
+//!
+//! let policy = FunctionPolicy::Allow(vec!["min", "max"]);
+//! // max ( 1 ) + exec ( 2 )
+//! let infix = [
+//!     InputToken::Function("max"),
+//!     InputToken::LeftParen,
+//!     InputToken::Value(1),
+//!     InputToken::RightParen,
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Function("exec"),
+//!     InputToken::LeftParen,
+//!     InputToken::Value(2),
+//!     InputToken::RightParen,
+//! ];
+//! assert_eq!(
+//!     to_postfix_with_policy(infix.clone(), &policy),
+//!     Err(PolicyError::UnknownFunction { pos: 5, function: "exec" })
+//! );
+//! assert!(to_postfix_with_policy(infix[..4].to_vec(), &policy).is_ok());
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{ErrorCode, Growable, InputToken, Operator, OutputToken, ParenMissmatchError};
+
+/// The functions an expression may call
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum FunctionPolicy<F> {
+    /// Only the listed functions are permitted.
+    Allow(Vec<F>),
+    /// All functions except the listed ones are permitted.
+    Deny(Vec<F>),
+}
+
+impl<F: PartialEq> FunctionPolicy<F> {
+    /// Returns true if `function` may be called.
+    pub fn permits(&self, function: &F) -> bool {
+        match self {
+            FunctionPolicy::Allow(functions) => functions.contains(function),
+            FunctionPolicy::Deny(functions) => !functions.contains(function),
+        }
+    }
+}
+
+/// This error is returned by `to_postfix_with_policy`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PolicyError<F> {
+    /// The parentheses are unbalanced.
+    ParenMissmatch(ParenMissmatchError),
+    /// The function is not on the allowlist.
+    UnknownFunction {
+        /// The position of the function
+        pos: usize,
+        /// The rejected function
+        function: F,
+    },
+    /// The function is on the denylist.
+    ForbiddenFunction {
+        /// The position of the function
+        pos: usize,
+        /// The rejected function
+        function: F,
+    },
+}
+
+impl<F> PolicyError<F> {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            PolicyError::ParenMissmatch(error) => error.code(),
+            PolicyError::UnknownFunction { .. } => ErrorCode::UnknownFunction,
+            PolicyError::ForbiddenFunction { .. } => ErrorCode::ForbiddenFunction,
+        }
+    }
+}
+
+impl<F> From<ParenMissmatchError> for PolicyError<F> {
+    fn from(value: ParenMissmatchError) -> Self {
+        Self::ParenMissmatch(value)
+    }
+}
+
+impl<F: core::fmt::Debug> core::fmt::Display for PolicyError<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PolicyError::ParenMissmatch(error) => write!(f, "{error}"),
+            PolicyError::UnknownFunction { pos, function } => {
+                write!(f, "Unknown function {function:?} at position {pos}")
+            }
+            PolicyError::ForbiddenFunction { pos, function } => {
+                write!(f, "Function {function:?} at position {pos} is forbidden")
+            }
+        }
+    }
+}
+
+impl<F: core::fmt::Debug> core::error::Error for PolicyError<F> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            PolicyError::ParenMissmatch(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Like `to_postfix`, but returns an error for the first function `policy` does not permit.
+pub fn to_postfix_with_policy<V, F, O>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+    policy: &FunctionPolicy<F>,
+) -> Result<Vec<OutputToken<V, F, O>>, PolicyError<F>>
+where
+    F: PartialEq,
+    O: Operator,
+{
+    let infix = infix.into_iter();
+    let mut conversion: Growable<_, F, O> = Growable::new(&infix, Vec::with_capacity);
+    let identity = |func, _| func;
+    for (pos, token) in infix.enumerate() {
+        match token {
+            InputToken::Function(function) if !policy.permits(&function) => {
+                return Err(match policy {
+                    FunctionPolicy::Allow(_) => PolicyError::UnknownFunction { pos, function },
+                    FunctionPolicy::Deny(_) => PolicyError::ForbiddenFunction { pos, function },
+                });
+            }
+            token => conversion.push(pos, token, &identity)?,
+        }
+    }
+    conversion.finish(&identity)?;
+    Ok(conversion.output)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec};
+
+    use super::{to_postfix_with_policy, FunctionPolicy, PolicyError};
+    use crate::{op::Math, ErrorCode, InputToken, OutputToken};
+
+    #[test]
+    fn denylist() {
+        let policy = FunctionPolicy::Deny(vec!["exec"]);
+        // sum ( 1 )
+        let call = |function| {
+            [
+                InputToken::<_, _, Math>::Function(function),
+                InputToken::LeftParen,
+                InputToken::Value(1),
+                InputToken::RightParen,
+            ]
+        };
+        assert_eq!(
+            to_postfix_with_policy(call("sum"), &policy),
+            Ok(vec![OutputToken::Value(1), OutputToken::Function("sum")])
+        );
+        let error = to_postfix_with_policy(call("exec"), &policy).expect_err("forbidden");
+        assert_eq!(
+            error,
+            PolicyError::ForbiddenFunction {
+                pos: 0,
+                function: "exec"
+            }
+        );
+        assert_eq!(error.code(), ErrorCode::ForbiddenFunction);
+        assert_eq!(
+            error.to_string(),
+            r#"Function "exec" at position 0 is forbidden"#
+        );
+    }
+}
@@ -0,0 +1,395 @@
+//! A validated postfix expression.
+//!
+//! ```rust
+//! use gyard::{op::Math, postfix::PostfixExpr, InputToken};
+//!
+//! // ( 1 + 2 ) * 3
+//! let expr = PostfixExpr::from_infix([
+//!     InputToken::<_, &str, _>::LeftParen,
+//!     InputToken::Value(1),
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Value(2),
+//!     InputToken::RightParen,
+//!     InputToken::Operator(Math::Mul),
+//!     InputToken::Value(3),
+//! ]).unwrap();
+//! assert_eq!(expr.metrics().depth, 3);
+//! assert_eq!(expr.values().copied().collect::<Vec<_>>(), [1, 2, 3]);
+//! assert_eq!(expr.to_sexpr(), "(* (+ 1 2) 3)");
+//! ```
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::{cell::OnceCell, fmt::Display};
+
+use crate::{
+    analysis::{metrics, Metrics, PostfixSlice},
+    ast::{borrowed_ast, postfix_to_ast, sexpr::to_sexpr, AstError, Expr},
+    eval::{eval_postfix_with_arity, EvalError, Evaluator},
+    to_postfix_with_arity, Call, InputToken, Operator, OutputToken,
+};
+
+/// A postfix expression created by `to_postfix_with_arity` that is known to form exactly one
+/// tree. Every operator and function has enough operands and exactly one value is left after
+/// evaluating all tokens.
+///
+/// With the `serde` feature the expression is serialized as its tokens and validated again when
+/// it is deserialized.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(
+        try_from = "Vec<OutputToken<V, Call<F>, O>>",
+        bound(
+            deserialize = "V: serde::Deserialize<'de>, F: serde::Deserialize<'de>, \
+            O: serde::Deserialize<'de> + Operator"
+        )
+    )
+)]
+pub struct PostfixExpr<V, F, O> {
+    tokens: Vec<OutputToken<V, Call<F>, O>>,
+    metrics: Metrics,
+}
+
+impl<V, F, O: Operator> PostfixExpr<V, F, O> {
+    /// Validates a postfix expression created by `to_postfix_with_arity`.
+    pub fn new(tokens: Vec<OutputToken<V, Call<F>, O>>) -> Result<Self, AstError> {
+        let metrics = metrics(&tokens)?;
+        Ok(Self { tokens, metrics })
+    }
+
+    /// Converts an infix expression and validates the result.
+    pub fn from_infix(
+        infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+    ) -> Result<Self, AstError> {
+        Self::new(to_postfix_with_arity(infix)?)
+    }
+
+    /// Converts the expression into a tree.
+    pub fn into_ast(self) -> Expr<V, F, O> {
+        postfix_to_ast(self.tokens).expect("PostfixExpr is always well-formed")
+    }
+
+    /// Converts the expression into a tree borrowing the tokens.
+    pub fn to_ast(&self) -> Expr<&V, &F, &O> {
+        borrowed_ast(&self.tokens).expect("PostfixExpr is always well-formed")
+    }
+
+    /// Evaluates the expression. See `eval::eval_postfix_with_arity`.
+    pub fn eval<E>(&self, evaluator: &mut E) -> Result<E::Value, EvalError<E::Error>>
+    where
+        V: Clone,
+        F: Clone,
+        O: Clone,
+        E: Evaluator<V, F, O>,
+    {
+        eval_postfix_with_arity(self.tokens.iter().cloned(), evaluator)
+    }
+
+    /// Renders the expression as infix tokens with minimal parentheses.
+    pub fn to_infix(&self) -> Vec<InputToken<V, F, O>>
+    where
+        V: Clone,
+        F: Clone,
+        O: Clone,
+    {
+        self.clone().into_ast().to_infix()
+    }
+
+    /// Renders the expression as an s-expression. See `ast::sexpr`.
+    pub fn to_sexpr(&self) -> String
+    where
+        V: Display,
+        F: Display,
+        O: Display,
+    {
+        to_sexpr(&self.to_ast())
+    }
+}
+
+impl<V, F, O> PostfixExpr<V, F, O> {
+    /// Returns the tokens in postfix order.
+    pub fn tokens(&self) -> &[OutputToken<V, Call<F>, O>] {
+        &self.tokens
+    }
+
+    /// Returns the tokens in postfix order.
+    pub fn into_tokens(self) -> Vec<OutputToken<V, Call<F>, O>> {
+        self.tokens
+    }
+
+    /// Returns the tokens as a boxed slice without unused capacity.
+    pub fn into_boxed_tokens(self) -> Box<[OutputToken<V, Call<F>, O>]> {
+        self.tokens.into_boxed_slice()
+    }
+
+    /// Frees unused capacity. Use this before storing the expression for a long time.
+    pub fn shrink_to_fit(&mut self) {
+        self.tokens.shrink_to_fit();
+    }
+
+    /// Returns the size and complexity of the expression.
+    pub fn metrics(&self) -> Metrics {
+        self.metrics
+    }
+
+    /// Returns all values in postfix order.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.tokens.values()
+    }
+
+    /// Returns all function calls in postfix order.
+    pub fn functions(&self) -> impl Iterator<Item = &Call<F>> {
+        self.tokens.functions()
+    }
+
+    /// Returns all operators in postfix order.
+    pub fn operators(&self) -> impl Iterator<Item = &O> {
+        self.tokens.operators()
+    }
+}
+
+impl<V, F, O> AsRef<[OutputToken<V, Call<F>, O>]> for PostfixExpr<V, F, O> {
+    fn as_ref(&self) -> &[OutputToken<V, Call<F>, O>] {
+        &self.tokens
+    }
+}
+
+impl<V, F, O> IntoIterator for PostfixExpr<V, F, O> {
+    type Item = OutputToken<V, Call<F>, O>;
+    type IntoIter = alloc::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tokens.into_iter()
+    }
+}
+
+impl<V, F, O: Operator> TryFrom<Vec<OutputToken<V, Call<F>, O>>> for PostfixExpr<V, F, O> {
+    type Error = AstError;
+
+    fn try_from(tokens: Vec<OutputToken<V, Call<F>, O>>) -> Result<Self, AstError> {
+        Self::new(tokens)
+    }
+}
+
+/// Infix tokens together with their lazily compiled `PostfixExpr`.
+///
+/// The expression is converted the first time it is needed and kept until the tokens are
+/// changed. All mutations go through `tokens_mut` or `set_tokens`, which drop the compiled
+/// expression, so the cache can never be out of date.
+///
+/// ```rust
+/// use gyard::{op::Math, postfix::CachedExpr, InputToken};
+///
+/// // 1 + 2
+/// let mut expr = CachedExpr::new(vec![
+///     InputToken::<_, &str, _>::Value(1),
+///     InputToken::Operator(Math::Add),
+///     InputToken::Value(2),
+/// ]);
+/// assert!(!expr.is_compiled());
+/// assert_eq!(expr.postfix().unwrap().metrics().depth, 2);
+/// assert!(expr.is_compiled());
+///
+/// // 1 + 2 * 3
+/// expr.tokens_mut().extend([InputToken::Operator(Math::Mul), InputToken::Value(3)]);
+/// assert!(!expr.is_compiled());
+/// assert_eq!(expr.postfix().unwrap().metrics().depth, 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CachedExpr<V, F, O> {
+    infix: Vec<InputToken<V, F, O>>,
+    compiled: OnceCell<Result<PostfixExpr<V, F, O>, AstError>>,
+}
+
+impl<V, F, O> CachedExpr<V, F, O> {
+    /// Creates a cache for `infix`. Nothing is converted yet.
+    pub fn new(infix: Vec<InputToken<V, F, O>>) -> Self {
+        Self {
+            infix,
+            compiled: OnceCell::new(),
+        }
+    }
+
+    /// Returns the infix tokens.
+    pub fn tokens(&self) -> &[InputToken<V, F, O>] {
+        &self.infix
+    }
+
+    /// Returns the infix tokens for modification and drops the compiled expression.
+    pub fn tokens_mut(&mut self) -> &mut Vec<InputToken<V, F, O>> {
+        self.compiled.take();
+        &mut self.infix
+    }
+
+    /// Replaces the infix tokens and drops the compiled expression.
+    pub fn set_tokens(&mut self, infix: Vec<InputToken<V, F, O>>) {
+        *self.tokens_mut() = infix;
+    }
+
+    /// Returns the infix tokens.
+    pub fn into_tokens(self) -> Vec<InputToken<V, F, O>> {
+        self.infix
+    }
+
+    /// Returns true if the compiled expression of the current tokens is cached.
+    pub fn is_compiled(&self) -> bool {
+        self.compiled.get().is_some()
+    }
+
+    /// Returns the compiled expression and converts the tokens if necessary. A failed
+    /// conversion is cached as well.
+    pub fn postfix(&self) -> Result<&PostfixExpr<V, F, O>, AstError>
+    where
+        V: Clone,
+        F: Clone,
+        O: Operator + Clone,
+    {
+        self.compiled
+            .get_or_init(|| PostfixExpr::from_infix(self.infix.iter().cloned()))
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    /// Returns the metrics of the compiled expression. See `PostfixExpr::metrics`.
+    pub fn metrics(&self) -> Result<Metrics, AstError>
+    where
+        V: Clone,
+        F: Clone,
+        O: Operator + Clone,
+    {
+        self.postfix().map(PostfixExpr::metrics)
+    }
+}
+
+impl<V, F, O> From<Vec<InputToken<V, F, O>>> for CachedExpr<V, F, O> {
+    fn from(infix: Vec<InputToken<V, F, O>>) -> Self {
+        Self::new(infix)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<V, F, O> serde::Serialize for PostfixExpr<V, F, O>
+where
+    V: serde::Serialize,
+    F: serde::Serialize,
+    O: serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.tokens.serialize(serializer)
+    }
+}
+
+/// The schema of the tokens, the expression is serialized as its tokens.
+#[cfg(feature = "schemars")]
+impl<V, F, O> schemars::JsonSchema for PostfixExpr<V, F, O>
+where
+    V: schemars::JsonSchema,
+    F: schemars::JsonSchema,
+    O: schemars::JsonSchema,
+{
+    fn schema_name() -> alloc::borrow::Cow<'static, str> {
+        <Vec<OutputToken<V, Call<F>, O>>>::schema_name()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        <Vec<OutputToken<V, Call<F>, O>>>::json_schema(generator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::{CachedExpr, PostfixExpr};
+    use crate::{ast::AstError, op::Math, InputToken, OutputToken};
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        let tokens = vec![
+            OutputToken::<_, crate::Call<()>, _>::Value(1),
+            OutputToken::Operator(Math::Add),
+        ];
+        assert_eq!(
+            PostfixExpr::new(tokens),
+            Err(AstError::StackUnderflow {
+                pos: 1,
+                required: 2,
+                available: 1
+            })
+        );
+        assert_eq!(
+            PostfixExpr::<i32, (), Math>::new(vec![]),
+            Err(AstError::EmptyExpression)
+        );
+    }
+
+    #[test]
+    fn cached_errors_are_invalidated() {
+        let mut expr = CachedExpr::new(vec![
+            InputToken::<_, (), _>::Value(1),
+            InputToken::Operator(Math::Add),
+        ]);
+        let error = AstError::StackUnderflow {
+            pos: 1,
+            required: 2,
+            available: 1,
+        };
+        assert_eq!(expr.metrics(), Err(error.clone()));
+        assert!(expr.is_compiled());
+        assert_eq!(expr.postfix().map(|_| ()), Err(error));
+        expr.tokens_mut().push(InputToken::Value(2));
+        assert_eq!(expr.metrics().map(|metrics| metrics.depth), Ok(2));
+        expr.set_tokens(Vec::new());
+        assert_eq!(expr.postfix().map(|_| ()), Err(AstError::EmptyExpression));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_validates() {
+        use alloc::string::String;
+
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Neg;
+        impl crate::Operator for Neg {
+            fn precedence(&self) -> usize {
+                1
+            }
+            fn is_left_associative(&self) -> bool {
+                false
+            }
+            fn arity(&self) -> usize {
+                1
+            }
+        }
+
+        let expr = PostfixExpr::<i32, String, _>::new(vec![
+            OutputToken::Value(1),
+            OutputToken::Operator(Neg),
+        ])
+        .expect("valid expression");
+        let json = serde_json::to_string(&expr).expect("serializable");
+        assert_eq!(json, r#"[{"Value":1},{"Operator":null}]"#);
+        assert_eq!(serde_json::from_str(&json).ok(), Some(expr));
+        assert!(
+            serde_json::from_str::<PostfixExpr<i32, String, Neg>>(r#"[{"Operator":null}]"#)
+                .is_err()
+        );
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn schema_matches_tokens() {
+        use alloc::string::String;
+
+        use crate::{op::All, Call};
+
+        let schema = schemars::schema_for!(PostfixExpr<f64, String, All>);
+        assert_eq!(
+            schema,
+            schemars::schema_for!(Vec<OutputToken<f64, Call<String>, All>>)
+        );
+        let json = serde_json::to_string(&schema).expect("serializable");
+        assert!(json.contains(r#""const":"Exponent""#));
+    }
+}
@@ -0,0 +1,228 @@
+//! Python bindings based on `pyo3`.
+//!
+//! `register` adds the functions `to_postfix` and `evaluate` to a Python module. The functions do
+//! not tokenize text, Python code passes tokens as tuples:
+//!
+//! - `("value", v)` where `v` is `None`, a `bool`, a number, a `str` or a `list` of values,
+//! - `("function", name)` in the input and `("function", name, arity)` in the output,
+//! - `("operator", symbol)` where `symbol` is the `Display` form of an operator of `op::All`,
+//! - `"("`, `")"` and `","`.
+//!
+//! `to_postfix` returns the postfix expression as a list of tuples and `evaluate` evaluates it
+//! with `value::Calculator`, so Python and Rust services share one implementation.
+//!
+//! Extension modules are built as a `cdylib`, add a small crate that registers the functions:
+//!
+//! ```rust,ignore
+//! use pyo3::prelude::*;
+//!
+//! #[pymodule]
+//! fn gyard(module: &Bound<'_, PyModule>) -> PyResult<()> {
+//!     gyard::python::register(module)
+//! }
+//! ```
+//!
+//! ```python
+//! import gyard
+//!
+//! postfix = gyard.to_postfix([("value", 1), ("operator", "+"), ("value", 2)])
+//! assert postfix == [("value", 1.0), ("value", 2.0), ("operator", "+")]
+//! assert gyard.evaluate(postfix) == 3.0
+//! ```
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use pyo3::{
+    exceptions::{PyTypeError, PyValueError},
+    prelude::*,
+    types::{PyBool, PyList, PyString, PyTuple},
+    IntoPyObjectExt,
+};
+
+use crate::{
+    eval::eval_postfix_with_arity,
+    op::{All, Compare, Logical, Math},
+    to_postfix_with_arity,
+    value::{Calculator, Value},
+    Call, InputToken, OutputToken,
+};
+
+/// All operators of `op::All`
+const OPERATORS: [All; 15] = [
+    All::Math(Math::Add),
+    All::Math(Math::Sub),
+    All::Math(Math::Mul),
+    All::Math(Math::Div),
+    All::Math(Math::Exponent),
+    All::Compare(Compare::Lt),
+    All::Compare(Compare::Le),
+    All::Compare(Compare::Eq),
+    All::Compare(Compare::Ne),
+    All::Compare(Compare::Ge),
+    All::Compare(Compare::Gt),
+    All::Logical(Logical::Xor),
+    All::Logical(Logical::And),
+    All::Logical(Logical::Or),
+    All::Logical(Logical::Not),
+];
+
+/// Adds `to_postfix` and `evaluate` to `module`.
+pub fn register(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(to_postfix, module)?)?;
+    module.add_function(wrap_pyfunction!(evaluate, module)?)?;
+    Ok(())
+}
+
+/// Converts a list of infix tokens into a list of postfix tokens.
+#[pyfunction]
+fn to_postfix<'py>(tokens: &Bound<'py, PyList>) -> PyResult<Bound<'py, PyList>> {
+    let py = tokens.py();
+    let infix = tokens
+        .iter()
+        .map(|token| input_token(&token))
+        .collect::<PyResult<Vec<_>>>()?;
+    let postfix =
+        to_postfix_with_arity(infix).map_err(|error| PyValueError::new_err(error.to_string()))?;
+    let tokens = postfix
+        .into_iter()
+        .map(|token| match token {
+            OutputToken::Value(value) => ("value", python_value(py, value)?).into_bound_py_any(py),
+            OutputToken::Function(Call { function, arity }) => {
+                ("function", function, arity).into_bound_py_any(py)
+            }
+            OutputToken::Operator(operator) => {
+                ("operator", operator.to_string()).into_bound_py_any(py)
+            }
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    PyList::new(py, tokens)
+}
+
+/// Evaluates a list of postfix tokens returned by `to_postfix`.
+#[pyfunction]
+fn evaluate<'py>(postfix: &Bound<'py, PyList>) -> PyResult<Bound<'py, PyAny>> {
+    let tokens = postfix
+        .iter()
+        .map(|token| output_token(&token))
+        .collect::<PyResult<Vec<_>>>()?;
+    let value = eval_postfix_with_arity(tokens, &mut Calculator)
+        .map_err(|error| PyValueError::new_err(error.to_string()))?;
+    python_value(postfix.py(), value)
+}
+
+fn input_token(token: &Bound<'_, PyAny>) -> PyResult<InputToken<Value, String, All>> {
+    if let Ok(symbol) = token.cast::<PyString>() {
+        return match symbol.to_str()? {
+            "(" => Ok(InputToken::LeftParen),
+            ")" => Ok(InputToken::RightParen),
+            "," => Ok(InputToken::ArgSeparator),
+            symbol => Err(PyValueError::new_err(format!("Unknown token {symbol:?}"))),
+        };
+    }
+    let (kind, payload) = token.extract::<(String, Bound<'_, PyAny>)>()?;
+    match kind.as_str() {
+        "value" => Ok(InputToken::Value(rust_value(&payload)?)),
+        "function" => Ok(InputToken::Function(payload.extract()?)),
+        "operator" => Ok(InputToken::Operator(operator(&payload)?)),
+        kind => Err(PyValueError::new_err(format!(
+            "Unknown token kind {kind:?}"
+        ))),
+    }
+}
+
+fn output_token(token: &Bound<'_, PyAny>) -> PyResult<OutputToken<Value, Call<String>, All>> {
+    let token = token.cast::<PyTuple>()?;
+    let kind = token.get_item(0)?.extract::<String>()?;
+    match (kind.as_str(), token.len()) {
+        ("value", 2) => Ok(OutputToken::Value(rust_value(&token.get_item(1)?)?)),
+        ("function", 3) => Ok(OutputToken::Function(Call {
+            function: token.get_item(1)?.extract()?,
+            arity: token.get_item(2)?.extract()?,
+        })),
+        ("operator", 2) => Ok(OutputToken::Operator(operator(&token.get_item(1)?)?)),
+        _ => Err(PyValueError::new_err(format!("Invalid token {token}"))),
+    }
+}
+
+fn operator(symbol: &Bound<'_, PyAny>) -> PyResult<All> {
+    let symbol = symbol.extract::<String>()?;
+    OPERATORS
+        .into_iter()
+        .find(|operator| operator.to_string() == symbol)
+        .ok_or_else(|| PyValueError::new_err(format!("Unknown operator {symbol:?}")))
+}
+
+fn rust_value(value: &Bound<'_, PyAny>) -> PyResult<Value> {
+    if value.is_none() {
+        Ok(Value::Null)
+    } else if let Ok(value) = value.cast::<PyBool>() {
+        Ok(Value::Bool(value.is_true()))
+    } else if let Ok(value) = value.cast::<PyString>() {
+        Ok(Value::Str(value.to_str()?.to_string()))
+    } else if let Ok(list) = value.cast::<PyList>() {
+        list.iter()
+            .map(|item| rust_value(&item))
+            .collect::<PyResult<_>>()
+            .map(Value::List)
+    } else if let Ok(number) = value.extract::<f64>() {
+        Ok(Value::Number(number))
+    } else {
+        Err(PyTypeError::new_err(format!("Unsupported value {value}")))
+    }
+}
+
+fn python_value(py: Python<'_>, value: Value) -> PyResult<Bound<'_, PyAny>> {
+    match value {
+        Value::Number(number) => number.into_bound_py_any(py),
+        Value::Bool(boolean) => boolean.into_bound_py_any(py),
+        Value::Str(string) => string.into_bound_py_any(py),
+        Value::Null => Ok(py.None().into_bound(py)),
+        Value::List(list) => {
+            let items = list
+                .into_iter()
+                .map(|item| python_value(py, item))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new(py, items)?.into_bound_py_any(py)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::{
+        prelude::*,
+        types::{IntoPyDict, PyModule},
+    };
+
+    #[test]
+    fn convert_and_evaluate() {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "gyard").expect("valid module name");
+            super::register(&module).expect("functions can be added");
+            let locals = [("gyard", module)].into_py_dict(py).expect("valid dict");
+            py.run(
+                cr#"
+postfix = gyard.to_postfix(["(", ("value", 1), ("operator", "+"), ("value", 2), ")", ("operator", "<"),
+                            ("function", "max"), "(", ("value", 2), ",", ("value", 4), ",", ("value", 3), ")"])
+assert postfix == [("value", 1.0), ("value", 2.0), ("operator", "+"), ("value", 2.0), ("value", 4.0),
+                   ("value", 3.0), ("function", "max", 3), ("operator", "<")], postfix
+assert gyard.evaluate(postfix) is True
+assert gyard.evaluate([("value", "a"), ("value", [None]), ("operator", "==")]) is False
+try:
+    gyard.to_postfix([("value", 1), ")"])
+    assert False
+except ValueError:
+    pass
+"#,
+                None,
+                Some(&locals),
+            )
+            .expect("assertions hold");
+        });
+    }
+}
@@ -0,0 +1,196 @@
+//! Conversion that can be paused and resumed.
+//! Use this to bound the work done per call, e.g. when loading many expressions in a game loop.
+//! See `eval::Steps::resume` to evaluate with the same kind of budget.
+//!
+//! ```rust
+//! use std::task::Poll;
+//! use gyard::{op::Math, resumable::Conversion, InputToken, OutputToken};
+//!
+//! // 1 + 2
+//! let mut conversion = Conversion::new([
+//!     InputToken::<_, (), _>::Value(1),
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Value(2),
+//! ]);
+//! assert_eq!(conversion.resume(2), Poll::Pending);
+//! assert_eq!(conversion.resume(2), Poll::Ready(Ok(vec![
+//!     OutputToken::Value(1),
+//!     OutputToken::Value(2),
+//!     OutputToken::Operator(Math::Add),
+//! ])));
+//! ```
+
+use alloc::{string::String, vec::Vec};
+use core::{
+    fmt::{Debug, Write},
+    iter::{Enumerate, Peekable},
+    task::Poll,
+};
+
+use crate::{
+    growable, walkthrough::StackEntry, Converter, Growable, InputToken, Operator, OutputToken,
+    ParenMissmatchError, Stack,
+};
+
+type ConversionResult<V, F, O> = Result<Vec<OutputToken<V, F, O>>, ParenMissmatchError>;
+
+/// A conversion from infix to postfix that runs in slices of a limited number of tokens.
+pub struct Conversion<I: Iterator, V, F, O> {
+    infix: Peekable<Enumerate<I>>,
+    converter: Converter<Stack<F, O>>,
+    output: Vec<OutputToken<V, F, O>>,
+}
+
+impl<I, V, F, O> Conversion<I, V, F, O>
+where
+    I: Iterator<Item = InputToken<V, F, O>>,
+    O: Operator,
+{
+    /// Prepares the conversion of `infix` without converting any token.
+    pub fn new(infix: impl IntoIterator<IntoIter = I>) -> Self {
+        let infix = infix.into_iter();
+        let Growable { output, converter }: Growable<_, F, O> =
+            Growable::new(&infix, Vec::with_capacity);
+        Self {
+            infix: infix.enumerate().peekable(),
+            converter,
+            output,
+        }
+    }
+
+    /// Converts up to `fuel` input tokens. Returns `Poll::Pending` if there are tokens left.
+    /// After the result has been returned the conversion must not be resumed again.
+    pub fn resume(&mut self, fuel: usize) -> Poll<ConversionResult<V, F, O>> {
+        let identity = |func, _| func;
+        for _ in 0..fuel {
+            let Some((pos, token)) = self.infix.next() else {
+                break;
+            };
+            if let Err(error) =
+                growable(self.converter.push(pos, token, &mut self.output, &identity))
+            {
+                return Poll::Ready(Err(error));
+            }
+        }
+        if self.infix.peek().is_some() {
+            return Poll::Pending;
+        }
+        Poll::Ready(
+            growable(self.converter.finish(&mut self.output, &identity))
+                .map(|()| core::mem::take(&mut self.output)),
+        )
+    }
+}
+
+impl<I: Iterator, V, F, O> Conversion<I, V, F, O> {
+    /// Returns the operator stack from bottom to top.
+    ///
+    /// ```rust
+    /// use gyard::{op::Math, resumable::Conversion, walkthrough::StackEntry, InputToken};
+    ///
+    /// // max ( 1 , 2 +
+    /// let mut conversion = Conversion::new([
+    ///     InputToken::<_, _, Math>::Function("max"),
+    ///     InputToken::LeftParen,
+    ///     InputToken::Value(1),
+    ///     InputToken::ArgSeparator,
+    ///     InputToken::Value(2),
+    ///     InputToken::Operator(Math::Add),
+    ///     InputToken::Value(3),
+    /// ]);
+    /// let _ = conversion.resume(6);
+    /// assert_eq!(conversion.stack().collect::<Vec<_>>(), [
+    ///     StackEntry::Function { function: &"max", args: 2 },
+    ///     StackEntry::LeftParen { pos: 1 },
+    ///     StackEntry::Operator(&Math::Add),
+    /// ]);
+    /// assert_eq!(conversion.pending_function(), Some((&"max", 2)));
+    /// assert_eq!(conversion.output_len(), 2);
+    /// ```
+    pub fn stack(&self) -> impl Iterator<Item = StackEntry<&F, &O>> {
+        self.converter.stack.entries()
+    }
+
+    /// Returns the innermost function whose call is not closed yet and the number of arguments
+    /// seen so far.
+    pub fn pending_function(&self) -> Option<(&F, usize)> {
+        self.converter.stack.last_function()
+    }
+
+    /// The number of tokens in the output so far
+    pub fn output_len(&self) -> usize {
+        self.output.len()
+    }
+}
+
+/// Shows the converted tokens, the parenthesis depth, the operator stack and the output so far.
+impl<I: Iterator, V: Debug, F: Debug, O: Debug> Debug for Conversion<I, V, F, O> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Conversion")
+            .field("converter", &self.converter)
+            .field("output", &self.output)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<I: Iterator, V: Debug, F: Debug, O: Debug> Conversion<I, V, F, O> {
+    /// Renders the state of the conversion with one line per field, for logging while
+    /// diagnosing a parser.
+    ///
+    /// ```rust
+    /// use gyard::{op::Math, resumable::Conversion, InputToken};
+    ///
+    /// // max ( 1 , 2 +
+    /// let mut conversion = Conversion::new([
+    ///     InputToken::<_, _, Math>::Function("max"),
+    ///     InputToken::LeftParen,
+    ///     InputToken::Value(1),
+    ///     InputToken::ArgSeparator,
+    ///     InputToken::Value(2),
+    ///     InputToken::Operator(Math::Add),
+    ///     InputToken::Value(3),
+    /// ]);
+    /// let _ = conversion.resume(6);
+    /// assert_eq!(conversion.dump(), "\
+    /// converted: 6
+    /// paren depth: 1
+    /// stack: [Function(\"max\", 2), LeftParen(1), Operator(Add)]
+    /// output: [Value(1), Value(2)]
+    /// ");
+    /// ```
+    pub fn dump(&self) -> String {
+        let converter = &self.converter;
+        let mut dump = String::new();
+        // Writing into a `String` never fails
+        _ = writeln!(dump, "converted: {}", converter.end);
+        _ = writeln!(dump, "paren depth: {}", converter.paren_count);
+        _ = writeln!(dump, "stack: {:?}", converter.stack);
+        _ = writeln!(dump, "output: {:?}", self.output);
+        dump
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::task::Poll;
+
+    use super::Conversion;
+    use crate::{op::Math, InputToken, ParenMissmatchError};
+
+    #[test]
+    fn error_in_later_slice() {
+        // 1 + ( 2
+        let mut conversion = Conversion::new([
+            InputToken::<_, (), _>::Value(1),
+            InputToken::Operator(Math::Add),
+            InputToken::LeftParen,
+            InputToken::Value(2),
+        ]);
+        assert_eq!(conversion.resume(0), Poll::Pending);
+        assert_eq!(conversion.resume(3), Poll::Pending);
+        assert_eq!(
+            conversion.resume(3),
+            Poll::Ready(Err(ParenMissmatchError::UnclosedOpening { open_pos: 2 }))
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! A stable text format for postfix expressions.
+//! Tokens are separated by spaces. Tokens that are empty or contain whitespace, `"` or `\` are
+//! wrapped in double quotes and `"` and `\` inside of them are escaped with a backslash.
+//! Quoting is transparent, a parser receives the same text for `a` and `"a"`.
+//!
+//! ```rust
+//! use gyard::{op::Math, rpn, to_postfix, InputToken, OutputToken};
+//!
+//! // 5 + 2 * sin ( 123 )
+//! let postfix = to_postfix([
+//!     InputToken::Value(5.0),
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Value(2.0),
+//!     InputToken::Operator(Math::Mul),
+//!     InputToken::Function("sin"),
+//!     InputToken::LeftParen,
+//!     InputToken::Value(123.0),
+//!     InputToken::RightParen,
+//! ]).unwrap();
+//! let text = rpn::Rpn(&postfix).to_string();
+//! assert_eq!(text, "5 2 123 sin * +");
+//!
+//! let parsed = rpn::from_rpn(&text, |token| match token {
+//!     "+" => Ok(OutputToken::Operator(Math::Add)),
+//!     "*" => Ok(OutputToken::Operator(Math::Mul)),
+//!     "sin" => Ok(OutputToken::Function("sin")),
+//!     value => value.parse().map(OutputToken::Value),
+//! });
+//! assert_eq!(parsed, Ok(postfix));
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{Display, Write};
+
+use crate::OutputToken;
+
+/// Writes a postfix expression in the RPN text format using `Display` for all tokens.
+#[derive(Debug, Clone, Copy)]
+pub struct Rpn<'a, V, F, O>(pub &'a [OutputToken<V, F, O>]);
+
+impl<V: Display, F: Display, O: Display> Display for Rpn<'_, V, F, O> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (i, token) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_char(' ')?;
+            }
+            write_token(f, &token.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes a postfix expression in the RPN text format using `token` to serialize each token.
+pub fn to_rpn<V, F, O>(
+    postfix: &[OutputToken<V, F, O>],
+    mut token: impl FnMut(OutputToken<&V, &F, &O>) -> String,
+) -> String {
+    let mut rpn = String::new();
+    for (i, t) in postfix.iter().enumerate() {
+        if i > 0 {
+            rpn.push(' ');
+        }
+        // Writing to a String never fails
+        let _ = write_token(&mut rpn, &token(t.as_ref()));
+    }
+    rpn
+}
+
+fn write_token(out: &mut impl Write, text: &str) -> core::fmt::Result {
+    let quote = text.is_empty()
+        || text
+            .chars()
+            .any(|c| c.is_whitespace() || c == '"' || c == '\\');
+    if !quote {
+        return out.write_str(text);
+    }
+    out.write_char('"')?;
+    for c in text.chars() {
+        if c == '"' || c == '\\' {
+            out.write_char('\\')?;
+        }
+        out.write_char(c)?;
+    }
+    out.write_char('"')
+}
+
+/// This error is returned if a text can not be parsed by `from_rpn`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RpnError<E> {
+    /// The quoted token starting at byte offset `pos` is not closed.
+    UnterminatedQuote {
+        /// The byte offset of the opening quote
+        pos: usize,
+    },
+    /// The backslash at byte offset `pos` does not escape `"` or `\`.
+    InvalidEscape {
+        /// The byte offset of the backslash
+        pos: usize,
+    },
+    /// The closing quote at byte offset `pos` is not followed by whitespace.
+    MissingSeparator {
+        /// The byte offset of the closing quote
+        pos: usize,
+    },
+    /// The token callback returned an error for the token at `index`.
+    Token {
+        /// The index of the token
+        index: usize,
+        /// The error returned by the callback
+        error: E,
+    },
+}
+
+impl<E: Display> Display for RpnError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RpnError::UnterminatedQuote { pos } => write!(f, "Unterminated quote at offset {pos}"),
+            RpnError::InvalidEscape { pos } => write!(f, "Invalid escape sequence at offset {pos}"),
+            RpnError::MissingSeparator { pos } => {
+                write!(f, "Missing whitespace after quote at offset {pos}")
+            }
+            RpnError::Token { index, error } => write!(f, "Invalid token {index}: {error}"),
+        }
+    }
+}
+
+impl<E: core::fmt::Debug + Display> core::error::Error for RpnError<E> {}
+
+/// Parses the RPN text format. `token` converts the unquoted text of each token.
+pub fn from_rpn<V, F, O, E>(
+    rpn: &str,
+    mut token: impl FnMut(&str) -> Result<OutputToken<V, F, O>, E>,
+) -> Result<Vec<OutputToken<V, F, O>>, RpnError<E>> {
+    let mut postfix = Vec::new();
+    let mut chars = rpn.char_indices().peekable();
+    let mut text = String::new();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        text.clear();
+        if c == '"' {
+            chars.next();
+            loop {
+                match chars.next() {
+                    None => return Err(RpnError::UnterminatedQuote { pos: start }),
+                    Some((pos, '"')) => {
+                        if chars.peek().is_some_and(|(_, c)| !c.is_whitespace()) {
+                            return Err(RpnError::MissingSeparator { pos });
+                        }
+                        break;
+                    }
+                    Some((pos, '\\')) => match chars.next() {
+                        Some((_, c @ ('"' | '\\'))) => text.push(c),
+                        _ => return Err(RpnError::InvalidEscape { pos }),
+                    },
+                    Some((_, c)) => text.push(c),
+                }
+            }
+        } else {
+            while let Some(&(_, c)) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                text.push(c);
+                chars.next();
+            }
+        }
+        let index = postfix.len();
+        postfix.push(token(&text).map_err(|error| RpnError::Token { index, error })?);
+    }
+    Ok(postfix)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+
+    use super::{from_rpn, to_rpn, RpnError};
+    use crate::OutputToken;
+
+    #[test]
+    fn quoting_roundtrip() {
+        let postfix = [
+            OutputToken::<_, (), ()>::Value(String::from("a b")),
+            OutputToken::Value(String::from(r#"say "hi" \o/"#)),
+            OutputToken::Value(String::new()),
+        ];
+        let text = to_rpn(&postfix, |token| match token {
+            OutputToken::Value(value) => value.clone(),
+            _ => unreachable!(),
+        });
+        assert_eq!(text, r#""a b" "say \"hi\" \\o/" """#);
+        let parsed = from_rpn(&text, |t| Ok::<_, ()>(OutputToken::Value(t.to_string())));
+        assert_eq!(parsed.as_deref(), Ok(&postfix[..]));
+    }
+
+    #[test]
+    fn invalid_quotes() {
+        let parse = |text| {
+            from_rpn(text, |_| {
+                Ok::<OutputToken<(), (), ()>, ()>(OutputToken::Value(()))
+            })
+        };
+        assert_eq!(
+            parse(r#"1 "2"#),
+            Err(RpnError::UnterminatedQuote { pos: 2 })
+        );
+        assert_eq!(parse(r#""\n""#), Err(RpnError::InvalidEscape { pos: 1 }));
+        assert_eq!(parse(r#""a"b"#), Err(RpnError::MissingSeparator { pos: 2 }));
+    }
+}
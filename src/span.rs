@@ -0,0 +1,131 @@
+//! Tokens annotated with the byte range of the source they were read from.
+//!
+//! Lexers like `lexer` and `logos` produce `Spanned` input tokens. `to_postfix_spanned` keeps the
+//! span of every token during the conversion, so errors found while evaluating the postfix
+//! expression can be reported at the right place in the source.
+//!
+//! ```rust
+//! use gyard::{op::Math, span::{to_postfix_spanned, Spanned}, InputToken, OutputToken};
+//!
+//! // a * b
+//! let infix = [
+//!     Spanned { value: InputToken::<_, (), _>::Value("a"), span: 0..1 },
+//!     Spanned { value: InputToken::Operator(Math::Mul), span: 2..3 },
+//!     Spanned { value: InputToken::Value("b"), span: 4..5 },
+//! ];
+//! let postfix = to_postfix_spanned(infix).unwrap();
+//! assert_eq!(postfix[2], Spanned { value: OutputToken::Operator(Math::Mul), span: 2..3 });
+//! ```
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{to_postfix, InputToken, Operator, OutputToken, ParenMissmatchError};
+
+type SpannedResult<V, F, O> =
+    Result<Vec<Spanned<OutputToken<V, F, O>>>, Spanned<ParenMissmatchError>>;
+
+/// A value and the byte range of the source it was read from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Spanned<T> {
+    /// The value
+    pub value: T,
+    /// The byte range of the value in the source
+    pub span: Range<usize>,
+}
+
+impl<O: Operator> Operator for Spanned<O> {
+    fn precedence(&self) -> usize {
+        self.value.precedence()
+    }
+
+    fn is_left_associative(&self) -> bool {
+        self.value.is_left_associative()
+    }
+
+    fn arity(&self) -> usize {
+        self.value.arity()
+    }
+
+    fn is_commutative(&self) -> bool {
+        self.value.is_commutative()
+    }
+
+    fn takes_list(&self) -> bool {
+        self.value.takes_list()
+    }
+}
+
+/// Converts spanned infix tokens into spanned postfix tokens. Every output token keeps the span of
+/// the input token it was created from. If the parentheses do not match the error is returned
+/// with the span of the parenthesis that is not matched.
+pub fn to_postfix_spanned<V, F, O>(
+    infix: impl IntoIterator<Item = Spanned<InputToken<V, F, O>>>,
+) -> SpannedResult<V, F, O>
+where
+    O: Operator,
+{
+    let mut spans = Vec::new();
+    let infix = infix.into_iter().map(|Spanned { value, span }| {
+        spans.push(span.clone());
+        #[expect(deprecated, reason = "")]
+        match value {
+            InputToken::Value(value) => InputToken::Value(Spanned { value, span }),
+            InputToken::Function(value) => InputToken::Function(Spanned { value, span }),
+            InputToken::Operator(value) => InputToken::Operator(Spanned { value, span }),
+            InputToken::LeftParen => InputToken::LeftParen,
+            InputToken::RightParen => InputToken::RightParen,
+            InputToken::ArgSeperator => InputToken::ArgSeperator,
+            InputToken::ArgSeparator => InputToken::ArgSeparator,
+        }
+    });
+    match to_postfix(infix) {
+        Ok(postfix) => Ok(postfix
+            .into_iter()
+            .map(|token| match token {
+                OutputToken::Value(Spanned { value, span }) => Spanned {
+                    value: OutputToken::Value(value),
+                    span,
+                },
+                OutputToken::Function(Spanned { value, span }) => Spanned {
+                    value: OutputToken::Function(value),
+                    span,
+                },
+                OutputToken::Operator(Spanned { value, span }) => Spanned {
+                    value: OutputToken::Operator(value),
+                    span,
+                },
+            })
+            .collect()),
+        Err(error) => {
+            let end = spans.last().map_or(0, |span| span.end);
+            let span = spans.get(error.pos()).cloned().unwrap_or(end..end);
+            Err(Spanned { value: error, span })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_postfix_spanned, Spanned};
+    use crate::{op::Math, InputToken};
+
+    #[test]
+    fn unmatched_paren_span() {
+        // ( a
+        let infix = [
+            Spanned {
+                value: InputToken::<_, (), Math>::LeftParen,
+                span: 3..4,
+            },
+            Spanned {
+                value: InputToken::Value("a"),
+                span: 4..5,
+            },
+        ];
+        let error = to_postfix_spanned(infix).expect_err("unclosed parenthesis");
+        assert_eq!((error.value.pos(), error.span), (0, 3..4));
+    }
+}
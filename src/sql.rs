@@ -0,0 +1,357 @@
+//! A tokenizer for SQL scalar expressions, i.e. the condition of a `WHERE` clause.
+//!
+//! The lexer reads the operators of `op::Sql` and produces `Operand` values:
+//!
+//! - Numbers like `12` or `0.5`, strings like `'it''s'`, `TRUE`, `FALSE` and `NULL`.
+//! - Columns like `price`, `orders.price` or `"Unit Price"`.
+//! - Every name followed by `(` is a function.
+//!
+//! Keywords are case insensitive. `NOT LIKE` and `NOT IN` are single operators. The list after
+//! `IN` is read like the arguments of a call to `Function::List`, so convert the tokens with
+//! `to_postfix_with_arity` to know the length of the list.
+//!
+//! ```rust
+//! use gyard::{
+//!     op::Sql,
+//!     sql::{tokenize, Function, Operand},
+//!     to_postfix_with_arity, Call, OutputToken,
+//! };
+//!
+//! let infix = tokenize("status NOT IN ('open', 'new') AND lower(name) LIKE 'a%'").unwrap();
+//! let postfix = to_postfix_with_arity(infix.into_iter().map(|token| token.value)).unwrap();
+//! let text = |text: &str| OutputToken::Value(Operand::Text(text.to_string()));
+//! assert_eq!(postfix, [
+//!     OutputToken::Value(Operand::Column("status".to_string())),
+//!     text("open"),
+//!     text("new"),
+//!     OutputToken::Function(Call { function: Function::List, arity: 2 }),
+//!     OutputToken::Operator(Sql::NotIn),
+//!     OutputToken::Value(Operand::Column("name".to_string())),
+//!     OutputToken::Function(Call { function: Function::Call("lower".to_string()), arity: 1 }),
+//!     text("a%"),
+//!     OutputToken::Operator(Sql::Like),
+//!     OutputToken::Operator(Sql::And),
+//! ]);
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::ops::Range;
+
+use crate::{op::Sql, span::Spanned, InputToken};
+
+/// A token produced by `Lexer`
+pub type Token = InputToken<Operand, Function, Sql>;
+
+/// The operator symbols. A symbol must be listed before every symbol it starts with.
+const OPERATORS: [(&str, Sql); 12] = [
+    ("||", Sql::Concat),
+    ("<>", Sql::Ne),
+    ("!=", Sql::Ne),
+    ("<=", Sql::Le),
+    (">=", Sql::Ge),
+    ("=", Sql::Eq),
+    ("<", Sql::Lt),
+    (">", Sql::Gt),
+    ("+", Sql::Add),
+    ("*", Sql::Mul),
+    ("/", Sql::Div),
+    ("%", Sql::Mod),
+];
+
+/// A value token of an expression
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Operand {
+    /// A number
+    Number(f64),
+    /// A string
+    Text(String),
+    /// `TRUE` or `FALSE`
+    Bool(bool),
+    /// `NULL`
+    Null,
+    /// A column, possibly qualified with a table like `orders.price`
+    Column(String),
+}
+
+/// A function token of an expression
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Function {
+    /// A call of the named function
+    Call(String),
+    /// The list after `IN` or `NOT IN`
+    List,
+}
+
+/// This error is returned if the input is not a valid SQL expression.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum SqlError {
+    /// The character at byte offset `pos` does not start a token.
+    UnexpectedCharacter {
+        /// The unexpected character
+        character: char,
+        /// The byte offset of the character
+        pos: usize,
+    },
+    /// The text at `span` looks like a number but can not be parsed, i.e. `1.2.3`.
+    InvalidNumber {
+        /// The byte range of the number
+        span: Range<usize>,
+    },
+    /// The string or quoted column starting at byte offset `pos` is not closed.
+    Unterminated {
+        /// The byte offset of the opening quote
+        pos: usize,
+    },
+    /// `IN` is not followed by a parenthesized list at byte offset `pos`.
+    MissingList {
+        /// The byte offset after `IN`
+        pos: usize,
+    },
+}
+
+impl core::fmt::Display for SqlError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SqlError::UnexpectedCharacter { character, pos } => {
+                write!(f, "Unexpected character {character:?} at offset {pos}")
+            }
+            SqlError::InvalidNumber { span } => {
+                write!(f, "Invalid number at offset {}", span.start)
+            }
+            SqlError::Unterminated { pos } => {
+                write!(f, "Missing closing quote for quote at offset {pos}")
+            }
+            SqlError::MissingList { pos } => write!(f, "Expected a list at offset {pos}"),
+        }
+    }
+}
+
+impl core::error::Error for SqlError {}
+
+/// Reads the tokens of a SQL expression. Stops after the first error.
+#[derive(Debug, Clone)]
+pub struct Lexer<'a> {
+    input: &'a str,
+    pos: usize,
+    expects_value: bool,
+    expects_list: bool,
+}
+
+impl<'a> Lexer<'a> {
+    /// Creates a lexer reading `input`.
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            pos: 0,
+            expects_value: true,
+            expects_list: false,
+        }
+    }
+
+    fn token(&self, rest: &str, c: char) -> Result<(Token, usize), SqlError> {
+        if self.expects_list {
+            // The list is read as the arguments of a call, the left parenthesis follows
+            return match c {
+                '(' => Ok((InputToken::Function(Function::List), 0)),
+                _ => Err(SqlError::MissingList { pos: self.pos }),
+            };
+        }
+        if c.is_ascii_digit() || (c == '.' && rest[1..].starts_with(|c: char| c.is_ascii_digit())) {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            return match rest[..len].parse() {
+                Ok(number) => Ok((InputToken::Value(Operand::Number(number)), len)),
+                Err(_) => Err(SqlError::InvalidNumber {
+                    span: self.pos..self.pos + len,
+                }),
+            };
+        }
+        if c == '\'' || c == '"' {
+            let (text, len) = quoted(rest, c).ok_or(SqlError::Unterminated { pos: self.pos })?;
+            let operand = match c {
+                '\'' => Operand::Text(text),
+                _ => Operand::Column(text),
+            };
+            return Ok((InputToken::Value(operand), len));
+        }
+        if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len());
+            let word = &rest[..len];
+            let keyword = |keyword: &str| word.eq_ignore_ascii_case(keyword);
+            let token = if keyword("AND") {
+                InputToken::Operator(Sql::And)
+            } else if keyword("OR") {
+                InputToken::Operator(Sql::Or)
+            } else if keyword("LIKE") {
+                InputToken::Operator(Sql::Like)
+            } else if keyword("IN") {
+                InputToken::Operator(Sql::In)
+            } else if keyword("NOT") && !self.expects_value {
+                let after = rest[len..].trim_start();
+                let next_len = after
+                    .find(|c: char| !c.is_alphabetic())
+                    .unwrap_or(after.len());
+                let operator = match &after[..next_len] {
+                    next if next.eq_ignore_ascii_case("LIKE") => Sql::NotLike,
+                    next if next.eq_ignore_ascii_case("IN") => Sql::NotIn,
+                    _ => {
+                        return Err(SqlError::UnexpectedCharacter {
+                            character: c,
+                            pos: self.pos,
+                        })
+                    }
+                };
+                let len = rest.len() - after.len() + next_len;
+                return Ok((InputToken::Operator(operator), len));
+            } else if keyword("NOT") {
+                InputToken::Operator(Sql::Not)
+            } else if keyword("TRUE") {
+                InputToken::Value(Operand::Bool(true))
+            } else if keyword("FALSE") {
+                InputToken::Value(Operand::Bool(false))
+            } else if keyword("NULL") {
+                InputToken::Value(Operand::Null)
+            } else if rest[len..].trim_start().starts_with('(') {
+                InputToken::Function(Function::Call(word.to_string()))
+            } else {
+                InputToken::Value(Operand::Column(word.to_string()))
+            };
+            return Ok((token, len));
+        }
+        let token = match c {
+            '(' => InputToken::LeftParen,
+            ')' => InputToken::RightParen,
+            ',' => InputToken::ArgSeparator,
+            '-' if self.expects_value => InputToken::Operator(Sql::Negate),
+            '-' => InputToken::Operator(Sql::Sub),
+            _ => {
+                return OPERATORS
+                    .iter()
+                    .find(|(symbol, _)| rest.starts_with(symbol))
+                    .map(|(symbol, operator)| (InputToken::Operator(*operator), symbol.len()))
+                    .ok_or(SqlError::UnexpectedCharacter {
+                        character: c,
+                        pos: self.pos,
+                    })
+            }
+        };
+        Ok((token, 1))
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Result<Spanned<Token>, SqlError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.input[self.pos..].trim_start();
+        self.pos = self.input.len() - rest.len();
+        let Some(c) = rest.chars().next() else {
+            // `IN` at the end of the input
+            return match core::mem::take(&mut self.expects_list) {
+                true => Some(Err(SqlError::MissingList { pos: self.pos })),
+                false => None,
+            };
+        };
+        match self.token(rest, c) {
+            Ok((value, len)) => {
+                let span = self.pos..self.pos + len;
+                self.pos += len;
+                self.expects_list = matches!(value, InputToken::Operator(Sql::In | Sql::NotIn));
+                self.expects_value = matches!(
+                    value,
+                    InputToken::LeftParen
+                        | InputToken::ArgSeparator
+                        | InputToken::Function(_)
+                        | InputToken::Operator(_)
+                );
+                Some(Ok(Spanned { value, span }))
+            }
+            Err(error) => {
+                self.pos = self.input.len();
+                self.expects_list = false;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// Reads all tokens of `input`.
+pub fn tokenize(input: &str) -> Result<Vec<Spanned<Token>>, SqlError> {
+    Lexer::new(input).collect()
+}
+
+/// Reads the text between `quote`s at the start of `rest`. Two quotes are an escaped quote.
+/// Returns the text and the length including the quotes.
+fn quoted(rest: &str, quote: char) -> Option<(String, usize)> {
+    let mut text = String::new();
+    let mut chars = rest.char_indices().skip(1).peekable();
+    while let Some((i, c)) = chars.next() {
+        if c != quote {
+            text.push(c);
+        } else if chars.next_if(|(_, c)| *c == quote).is_some() {
+            text.push(quote);
+        } else {
+            return Some((text, i + 1));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::{tokenize, Function, Operand, SqlError};
+    use crate::{op::Sql, to_postfix_with_arity, Call, OutputToken};
+
+    #[test]
+    fn precedence_keywords_and_errors() {
+        let postfix = to_postfix_with_arity(
+            tokenize("NOT \"Unit Price\" * -2 >= 10 or x in (1) AnD y = NULL")
+                .expect("valid expression")
+                .into_iter()
+                .map(|token| token.value),
+        )
+        .expect("valid expression");
+        assert_eq!(
+            postfix,
+            [
+                OutputToken::Value(Operand::Column("Unit Price".to_string())),
+                OutputToken::Value(Operand::Number(2.)),
+                OutputToken::Operator(Sql::Negate),
+                OutputToken::Operator(Sql::Mul),
+                OutputToken::Value(Operand::Number(10.)),
+                OutputToken::Operator(Sql::Ge),
+                OutputToken::Operator(Sql::Not),
+                OutputToken::Value(Operand::Column("x".to_string())),
+                OutputToken::Value(Operand::Number(1.)),
+                OutputToken::Function(Call {
+                    function: Function::List,
+                    arity: 1
+                }),
+                OutputToken::Operator(Sql::In),
+                OutputToken::Value(Operand::Column("y".to_string())),
+                OutputToken::Value(Operand::Null),
+                OutputToken::Operator(Sql::Eq),
+                OutputToken::Operator(Sql::And),
+                OutputToken::Operator(Sql::Or),
+            ]
+        );
+
+        assert_eq!(tokenize("a IN b"), Err(SqlError::MissingList { pos: 5 }));
+        assert_eq!(tokenize("a NOT IN"), Err(SqlError::MissingList { pos: 8 }));
+        assert_eq!(tokenize("'open"), Err(SqlError::Unterminated { pos: 0 }));
+    }
+}
@@ -0,0 +1,343 @@
+//! The operator stack used by the conversion.
+//!
+//! Functions and operators are kept in separate buffers, so an entry is only as large as its own
+//! type. The conversion loop decides what to do next by inspecting small tags, which also hold the
+//! positions of the left parentheses.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::walkthrough::StackEntry;
+use crate::Buffer;
+#[cfg(feature = "smallvec")]
+use crate::STACK_CAPACITY;
+
+/// The kind of an entry on the stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Tag {
+    /// A left parenthesis and its position
+    LeftParen(usize),
+    Function,
+    Operator,
+}
+
+/// A stack of left parentheses, functions and operators. `tags` records the order of all entries
+/// and holds the left parentheses, the other buffers hold the functions and operators.
+#[derive(Clone)]
+pub(crate) struct SplitStack<T, Fs, Os> {
+    tags: T,
+    /// The functions and their number of arguments
+    functions: Fs,
+    operators: Os,
+}
+
+impl<T, Fs, Os> SplitStack<T, Fs, Os> {
+    /// Creates a stack from empty buffers. Every `Function` and `Operator` tag has an entry in
+    /// `functions` or `operators`, the unchecked pops rely on that.
+    pub(crate) fn new(tags: T, functions: Fs, operators: Os) -> Self {
+        Self {
+            tags,
+            functions,
+            operators,
+        }
+    }
+}
+
+/// The buffer type of the growable stack. With the `smallvec` feature short expressions are
+/// converted without allocating a stack on the heap.
+#[cfg(all(feature = "alloc", not(feature = "smallvec")))]
+type Storage<T> = alloc::vec::Vec<T>;
+#[cfg(feature = "smallvec")]
+type Storage<T> = smallvec::SmallVec<[T; STACK_CAPACITY]>;
+
+/// The growable stack used by all conversions without fixed capacity.
+#[cfg(feature = "alloc")]
+pub(crate) type Stack<F, O> = SplitStack<Storage<Tag>, Storage<(F, usize)>, Storage<O>>;
+
+/// Lists the entries from bottom to top, i.e. `[LeftParen(0), Function("max", 2), Operator(Add)]`.
+/// The number after a function is the number of arguments seen so far.
+#[cfg(feature = "alloc")]
+impl<F: core::fmt::Debug, O: core::fmt::Debug> core::fmt::Debug for Stack<F, O> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let (mut functions, mut operators) = (self.functions.iter(), self.operators.iter());
+        let mut list = f.debug_list();
+        for tag in self.tags.iter() {
+            match tag {
+                Tag::LeftParen(pos) => list.entry(&format_args!("LeftParen({pos})")),
+                Tag::Function => match functions.next() {
+                    Some((function, arity)) => {
+                        list.entry(&format_args!("Function({function:?}, {arity})"))
+                    }
+                    None => list.entry(&format_args!("Function(?)")),
+                },
+                Tag::Operator => match operators.next() {
+                    Some(operator) => list.entry(&format_args!("Operator({operator:?})")),
+                    None => list.entry(&format_args!("Operator(?)")),
+                },
+            };
+        }
+        list.finish()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<F, O> Stack<F, O> {
+    /// Reserves space for `capacity` entries of every kind.
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self::new(
+            Storage::with_capacity(capacity),
+            Storage::with_capacity(capacity),
+            Storage::with_capacity(capacity),
+        )
+    }
+
+    /// Removes all entries but keeps the allocated memory.
+    pub(crate) fn clear(&mut self) {
+        self.tags.clear();
+        self.functions.clear();
+        self.operators.clear();
+    }
+
+    /// Returns a copy of all entries from bottom to top.
+    pub(crate) fn snapshot(&self) -> Vec<StackEntry<F, O>>
+    where
+        F: Clone,
+        O: Clone,
+    {
+        self.entries()
+            .map(|entry| match entry {
+                StackEntry::LeftParen { pos } => StackEntry::LeftParen { pos },
+                StackEntry::Function { function, args } => StackEntry::Function {
+                    function: function.clone(),
+                    args,
+                },
+                StackEntry::Operator(operator) => StackEntry::Operator(operator.clone()),
+            })
+            .collect()
+    }
+
+    /// Borrows all entries from bottom to top.
+    pub(crate) fn entries(&self) -> impl Iterator<Item = StackEntry<&F, &O>> {
+        let (mut functions, mut operators) = (self.functions.iter(), self.operators.iter());
+        self.tags.iter().filter_map(move |tag| match tag {
+            Tag::LeftParen(pos) => Some(StackEntry::LeftParen { pos: *pos }),
+            Tag::Function => functions
+                .next()
+                .map(|(function, args)| StackEntry::Function {
+                    function,
+                    args: *args,
+                }),
+            Tag::Operator => operators.next().map(StackEntry::Operator),
+        })
+    }
+
+    /// Removes all operators from top to bottom if the stack holds nothing else.
+    pub(crate) fn drain_operators(&mut self) -> Option<impl Iterator<Item = O> + '_> {
+        if self.tags.len() != self.operators.len() {
+            return None;
+        }
+        self.tags.clear();
+        Some(self.operators.drain(..).rev())
+    }
+
+    /// Returns the topmost function and the number of arguments seen so far.
+    pub(crate) fn last_function(&self) -> Option<(&F, usize)> {
+        self.functions
+            .last()
+            .map(|(function, args)| (function, *args))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<F, O> Default for Stack<F, O> {
+    fn default() -> Self {
+        Self::new(Storage::new(), Storage::new(), Storage::new())
+    }
+}
+
+/// The operations the conversion needs from its stack. All `push_*` methods return false if the
+/// stack is full.
+pub(crate) trait OperatorStack<F, O> {
+    /// Returns the kind of the topmost entry.
+    fn top(&self) -> Option<Tag>;
+    fn push_paren(&mut self, pos: usize) -> bool;
+    /// Pushes a function with one argument.
+    fn push_function(&mut self, function: F) -> bool;
+    fn push_operator(&mut self, operator: O) -> bool;
+    /// Removes the topmost entry of any kind.
+    fn discard(&mut self);
+    fn pop_function(&mut self) -> Option<(F, usize)>;
+    fn pop_operator(&mut self) -> Option<O>;
+    /// Returns the topmost operator if it is the topmost entry.
+    fn last_operator(&self) -> Option<&O>;
+    /// Returns the position of the topmost left parenthesis.
+    fn last_paren(&self) -> Option<usize>;
+    /// Sets the number of arguments of the topmost function to zero if it is the topmost entry.
+    fn clear_last_function_arity(&mut self);
+    /// Adds an argument to the function whose arguments are currently converted. That is the
+    /// topmost entry or the entry below a topmost left parenthesis. Returns false if there is no
+    /// such function.
+    fn add_argument(&mut self) -> bool;
+    /// Checks that every function and operator has a tag and returns the number of left
+    /// parentheses.
+    #[cfg(feature = "paranoid")]
+    fn check_shape(&self) -> usize;
+}
+
+impl<F, O, T, Fs, Os> OperatorStack<F, O> for SplitStack<T, Fs, Os>
+where
+    T: Buffer<Tag>,
+    Fs: Buffer<(F, usize)>,
+    Os: Buffer<O>,
+{
+    fn top(&self) -> Option<Tag> {
+        self.tags.last().copied()
+    }
+
+    fn push_paren(&mut self, pos: usize) -> bool {
+        self.tags.push(Tag::LeftParen(pos))
+    }
+
+    fn push_function(&mut self, function: F) -> bool {
+        self.functions.push((function, 1)) && self.tags.push(Tag::Function)
+    }
+
+    fn push_operator(&mut self, operator: O) -> bool {
+        self.operators.push(operator) && self.tags.push(Tag::Operator)
+    }
+
+    fn discard(&mut self) {
+        match self.tags.pop() {
+            Some(Tag::Function) => drop(self.functions.pop()),
+            Some(Tag::Operator) => drop(self.operators.pop()),
+            Some(Tag::LeftParen(_)) | None => {}
+        }
+    }
+
+    fn pop_function(&mut self) -> Option<(F, usize)> {
+        if self.top() != Some(Tag::Function) {
+            return None;
+        }
+        self.tags.pop();
+        let function = self.functions.pop();
+        #[cfg(not(feature = "forbid-unsafe"))]
+        // SAFETY:
+        // Every `Function` tag has an entry in `functions`, they are pushed and popped together
+        let function = Some(unsafe { function.unwrap_unchecked() });
+        function
+    }
+
+    fn pop_operator(&mut self) -> Option<O> {
+        if self.top() != Some(Tag::Operator) {
+            return None;
+        }
+        self.tags.pop();
+        let operator = self.operators.pop();
+        #[cfg(not(feature = "forbid-unsafe"))]
+        // SAFETY:
+        // Every `Operator` tag has an entry in `operators`, they are pushed and popped together
+        let operator = Some(unsafe { operator.unwrap_unchecked() });
+        operator
+    }
+
+    fn last_operator(&self) -> Option<&O> {
+        if self.top() != Some(Tag::Operator) {
+            return None;
+        }
+        self.operators.last()
+    }
+
+    fn last_paren(&self) -> Option<usize> {
+        self.tags.iter().rev().find_map(|tag| match tag {
+            Tag::LeftParen(pos) => Some(*pos),
+            _ => None,
+        })
+    }
+
+    fn clear_last_function_arity(&mut self) {
+        if self.top() == Some(Tag::Function) {
+            if let Some((_, arity)) = self.functions.last_mut() {
+                *arity = 0;
+            }
+        }
+    }
+
+    fn add_argument(&mut self) -> bool {
+        if let [.., Tag::Function, Tag::LeftParen(_)] | [.., Tag::Function] = &self.tags[..] {
+            if let Some((_, arity)) = self.functions.last_mut() {
+                *arity += 1;
+                return true;
+            }
+        }
+        false
+    }
+
+    #[cfg(feature = "paranoid")]
+    fn check_shape(&self) -> usize {
+        let count = |kind: Tag| self.tags.iter().filter(|tag| **tag == kind).count();
+        assert_eq!(
+            count(Tag::Function),
+            self.functions.len(),
+            "Functions without a tag"
+        );
+        assert_eq!(
+            count(Tag::Operator),
+            self.operators.len(),
+            "Operators without a tag"
+        );
+        self.tags.len() - self.functions.len() - self.operators.len()
+    }
+}
+
+/// Lets a conversion borrow a stack that is reused for several expressions.
+impl<F, O, S: OperatorStack<F, O>> OperatorStack<F, O> for &mut S {
+    fn top(&self) -> Option<Tag> {
+        (**self).top()
+    }
+
+    fn push_paren(&mut self, pos: usize) -> bool {
+        (**self).push_paren(pos)
+    }
+
+    fn push_function(&mut self, function: F) -> bool {
+        (**self).push_function(function)
+    }
+
+    fn push_operator(&mut self, operator: O) -> bool {
+        (**self).push_operator(operator)
+    }
+
+    fn discard(&mut self) {
+        (**self).discard()
+    }
+
+    fn pop_function(&mut self) -> Option<(F, usize)> {
+        (**self).pop_function()
+    }
+
+    fn pop_operator(&mut self) -> Option<O> {
+        (**self).pop_operator()
+    }
+
+    fn last_operator(&self) -> Option<&O> {
+        (**self).last_operator()
+    }
+
+    fn last_paren(&self) -> Option<usize> {
+        (**self).last_paren()
+    }
+
+    fn clear_last_function_arity(&mut self) {
+        (**self).clear_last_function_arity()
+    }
+
+    fn add_argument(&mut self) -> bool {
+        (**self).add_argument()
+    }
+
+    #[cfg(feature = "paranoid")]
+    fn check_shape(&self) -> usize {
+        (**self).check_shape()
+    }
+}
@@ -0,0 +1,155 @@
+//! `proptest` strategies producing valid expressions.
+//!
+//! A `Grammar` describes the values, operators and functions an expression may contain. It
+//! generates expression trees and renders them as infix token streams, so every generated
+//! expression can be converted.
+//!
+//! ```rust
+//! use gyard::{op::Math, strategy::Grammar, to_postfix_with_arity};
+//! use proptest::{prelude::*, test_runner::TestRunner};
+//!
+//! let grammar = Grammar::new(0..100i32)
+//!     .with_operators([Math::Add, Math::Sub, Math::Mul, Math::Exponent])
+//!     .with_function("max", 1..=3)
+//!     .with_depth(4);
+//! TestRunner::default()
+//!     .run(&grammar.expr(), |expr| {
+//!         let postfix = to_postfix_with_arity(expr.to_infix());
+//!         prop_assert_eq!(postfix, Ok(expr.into_postfix()));
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! ```
+
+use alloc::vec::Vec;
+use core::{fmt::Debug, ops::RangeInclusive};
+
+use proptest::{
+    collection::vec,
+    prelude::{BoxedStrategy, Just, Strategy},
+    sample::select,
+    strategy::Union,
+};
+
+use crate::{ast::Expr, InputToken, Operator};
+
+/// The building blocks of generated expressions.
+#[derive(Debug, Clone)]
+pub struct Grammar<V, F, O> {
+    values: BoxedStrategy<V>,
+    operators: Vec<O>,
+    functions: Vec<(F, RangeInclusive<usize>)>,
+    depth: u32,
+}
+
+impl<V, F, O> Grammar<V, F, O>
+where
+    V: Debug + Clone + 'static,
+    F: Debug + Clone + 'static,
+    O: Operator + Debug + Clone + 'static,
+{
+    /// Creates a grammar generating the values of `values` without any operators or functions.
+    pub fn new(values: impl Strategy<Value = V> + 'static) -> Self {
+        Self {
+            values: values.boxed(),
+            operators: Vec::new(),
+            functions: Vec::new(),
+            depth: 6,
+        }
+    }
+
+    /// Adds operators. Only operators taking one or two operands are used, unary operators are
+    /// generated as prefix operators.
+    pub fn with_operators(mut self, operators: impl IntoIterator<Item = O>) -> Self {
+        self.operators.extend(
+            operators
+                .into_iter()
+                .filter(|operator| matches!(operator.arity(), 1 | 2)),
+        );
+        self
+    }
+
+    /// Adds a function called with a number of arguments in `arity`.
+    pub fn with_function(mut self, function: F, arity: RangeInclusive<usize>) -> Self {
+        self.functions.push((function, arity));
+        self
+    }
+
+    /// Sets the maximum nesting depth of operators and functions. The default is 6.
+    pub fn with_depth(mut self, depth: u32) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Generates expression trees.
+    pub fn expr(&self) -> BoxedStrategy<Expr<V, F, O>> {
+        let leaf = self.values.clone().prop_map(Expr::Value);
+        let (operators, functions) = (self.operators.clone(), self.functions.clone());
+        if operators.is_empty() && functions.is_empty() {
+            return leaf.boxed();
+        }
+        leaf.prop_recursive(self.depth, 64, 3, move |inner| {
+            let mut nodes = Vec::new();
+            if !operators.is_empty() {
+                let inner = inner.clone();
+                nodes.push(
+                    select(operators.clone())
+                        .prop_flat_map(move |operator| {
+                            let arity = operator.arity();
+                            (Just(operator), vec(inner.clone(), arity))
+                        })
+                        .prop_map(|(operator, args)| Expr::Operator { operator, args })
+                        .boxed(),
+                );
+            }
+            if !functions.is_empty() {
+                nodes.push(
+                    select(functions.clone())
+                        .prop_flat_map(move |(function, arity)| {
+                            (Just(function), vec(inner.clone(), arity))
+                        })
+                        .prop_map(|(function, args)| Expr::Function { function, args })
+                        .boxed(),
+                );
+            }
+            Union::new(nodes)
+        })
+        .boxed()
+    }
+
+    /// Generates infix token streams. See `Expr::to_infix`.
+    pub fn infix(&self) -> BoxedStrategy<Vec<InputToken<V, F, O>>> {
+        self.expr().prop_map(|expr| expr.to_infix()).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::Grammar;
+    use crate::{
+        ast::postfix_to_infix,
+        op::{All, Compare, Logical},
+        to_postfix_with_arity,
+    };
+
+    proptest! {
+        #[test]
+        fn postfix_infix_postfix_is_identity(
+            expr in Grammar::new(any::<i8>())
+                .with_operators([
+                    All::Logical(Logical::Not),
+                    All::Logical(Logical::Or),
+                    All::Compare(Compare::Lt),
+                    All::Compare(Compare::Eq),
+                ])
+                .with_function('f', 0..=2)
+                .expr()
+        ) {
+            let postfix = expr.into_postfix();
+            let infix = postfix_to_infix(postfix.clone()).expect("valid postfix");
+            prop_assert_eq!(to_postfix_with_arity(infix), Ok(postfix));
+        }
+    }
+}
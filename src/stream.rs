@@ -0,0 +1,265 @@
+//! Conversion into an `io::Write` sink.
+//!
+//! `to_postfix_write` writes every output token as soon as it is produced, so the postfix
+//! expression is never held in memory. Each token is written as a frame:
+//!
+//! - one byte for the kind of the token (`0` value, `1` function, `2` operator),
+//! - the length of the payload as an unsigned LEB128 number,
+//! - the payload produced by the caller's encoder.
+//!
+//! `Frames` reads the frames back.
+//!
+//! ```rust
+//! use gyard::{op::Math, stream::{to_postfix_write, Frames}, tape::Opcode, InputToken, OutputToken};
+//!
+//! // 1 + 2
+//! let infix = [
+//!     InputToken::<u8, (), _>::Value(1),
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Value(2),
+//! ];
+//! let mut buffer = Vec::new();
+//! to_postfix_write(infix, &mut buffer, |token, out| match token {
+//!     OutputToken::Value(value) => out.push(*value),
+//!     OutputToken::Function(()) => {}
+//!     OutputToken::Operator(op) => out.extend(op.to_string().bytes()),
+//! })
+//! .unwrap();
+//! assert_eq!(buffer, [0, 1, 1, 0, 1, 2, 2, 1, b'+']);
+//!
+//! let frames: Vec<_> = Frames::new(&buffer[..]).collect::<Result<_, _>>().unwrap();
+//! assert_eq!(frames[2], (Opcode::Operator, b"+".to_vec()));
+//! ```
+
+use std::io::{self, Read, Write};
+
+use crate::{
+    binary::write_len, convert_into, tape::Opcode, ConvertError, ErrorCode, InputToken, Operator,
+    OutputToken, ParenMissmatchError, Queue, Stack,
+};
+
+/// This error is returned by `to_postfix_write`.
+#[derive(Debug)]
+pub enum StreamError {
+    /// The parentheses inside the expression do not match. Some tokens may have been written.
+    ParenMissmatch(ParenMissmatchError),
+    /// Writing to the sink failed.
+    Io(io::Error),
+}
+
+impl std::fmt::Display for StreamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamError::ParenMissmatch(error) => error.fmt(f),
+            StreamError::Io(error) => write!(f, "Failed to write output: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StreamError::ParenMissmatch(error) => Some(error),
+            StreamError::Io(error) => Some(error),
+        }
+    }
+}
+
+impl StreamError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            StreamError::ParenMissmatch(error) => error.code(),
+            StreamError::Io(_) => ErrorCode::Io,
+        }
+    }
+}
+
+impl From<ParenMissmatchError> for StreamError {
+    fn from(error: ParenMissmatchError) -> Self {
+        StreamError::ParenMissmatch(error)
+    }
+}
+
+impl From<io::Error> for StreamError {
+    fn from(error: io::Error) -> Self {
+        StreamError::Io(error)
+    }
+}
+
+/// Converts `infix` and writes every output token to `writer` as a frame. `encode` appends the
+/// payload of a token to the given buffer.
+/// `writer` receives one small write per token, wrap it in a `BufWriter` if that is expensive.
+pub fn to_postfix_write<V, F, O>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+    writer: impl Write,
+    encode: impl FnMut(&OutputToken<V, F, O>, &mut Vec<u8>),
+) -> Result<(), StreamError>
+where
+    O: Operator,
+{
+    let mut error = None;
+    let result = convert_into(
+        infix,
+        |func, _| func,
+        None,
+        |_| Writer {
+            writer,
+            encode,
+            header: Vec::new(),
+            payload: Vec::new(),
+            error: &mut error,
+        },
+        Stack::with_capacity,
+    );
+    match result {
+        Ok(mut queue) => Ok(queue.writer.flush()?),
+        Err(ConvertError::ParenMissmatch(paren_error)) => Err(paren_error.into()),
+        Err(
+            ConvertError::OutputFull(_)
+            | ConvertError::StackFull(_)
+            | ConvertError::MixedAssociativity(_),
+        ) => Err(error
+            .unwrap_or_else(|| io::Error::other("output rejected"))
+            .into()),
+    }
+}
+
+/// The output queue of `to_postfix_write`. Stores the first error and rejects all further tokens.
+struct Writer<'a, W, E> {
+    writer: W,
+    encode: E,
+    /// The opcode and length of the current frame
+    header: Vec<u8>,
+    payload: Vec<u8>,
+    error: &'a mut Option<io::Error>,
+}
+
+impl<V, F, O, W, E> Queue<OutputToken<V, F, O>> for Writer<'_, W, E>
+where
+    W: Write,
+    E: FnMut(&OutputToken<V, F, O>, &mut Vec<u8>),
+{
+    fn push(&mut self, item: OutputToken<V, F, O>) -> bool {
+        let opcode = match item {
+            OutputToken::Value(_) => Opcode::Value,
+            OutputToken::Function(_) => Opcode::Function,
+            OutputToken::Operator(_) => Opcode::Operator,
+        };
+        self.payload.clear();
+        (self.encode)(&item, &mut self.payload);
+        self.header.clear();
+        self.header.push(opcode as u8);
+        write_len(&mut self.header, self.payload.len());
+        let result = self
+            .writer
+            .write_all(&self.header)
+            .and_then(|()| self.writer.write_all(&self.payload));
+        match result {
+            Ok(()) => true,
+            Err(error) => {
+                *self.error = Some(error);
+                false
+            }
+        }
+    }
+}
+
+/// Reads the frames written by `to_postfix_write`. Every frame is returned as its opcode and
+/// payload.
+pub struct Frames<R> {
+    reader: R,
+}
+
+impl<R: Read> Frames<R> {
+    /// Reads frames from `reader` until it is exhausted.
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0];
+        match self.reader.read_exact(&mut byte) {
+            Ok(()) => Ok(Some(byte[0])),
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn read_frame(&mut self) -> io::Result<Option<(Opcode, Vec<u8>)>> {
+        let Some(opcode) = self.read_byte()? else {
+            return Ok(None);
+        };
+        let opcode = match opcode {
+            0 => Opcode::Value,
+            1 => Opcode::Function,
+            2 => Opcode::Operator,
+            opcode => return Err(invalid_data(format!("unknown opcode {opcode}"))),
+        };
+        let mut len = 0usize;
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self
+                .read_byte()?
+                .ok_or_else(|| invalid_data("truncated frame".to_string()))?;
+            len |= usize::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                // Do not trust the length with a large allocation up front
+                let mut payload = Vec::new();
+                (&mut self.reader)
+                    .take(len as u64)
+                    .read_to_end(&mut payload)?;
+                if payload.len() != len {
+                    return Err(invalid_data("truncated frame".to_string()));
+                }
+                return Ok(Some((opcode, payload)));
+            }
+        }
+        Err(invalid_data("frame length too large".to_string()))
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl<R: Read> Iterator for Frames<R> {
+    type Item = io::Result<(Opcode, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_frame().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{to_postfix_write, Frames, StreamError};
+    use crate::{op::Math, tape::Opcode, InputToken};
+
+    #[test]
+    fn long_payload_and_write_error() {
+        // f ( 1 )
+        let infix = || {
+            [
+                InputToken::<_, _, Math>::Function("f"),
+                InputToken::LeftParen,
+                InputToken::Value(1),
+                InputToken::RightParen,
+            ]
+        };
+        let mut buffer = Vec::new();
+        to_postfix_write(infix(), &mut buffer, |_, out| out.extend([7; 200]))
+            .expect("valid expression");
+        let frames: Vec<_> = Frames::new(&buffer[..])
+            .collect::<Result<_, _>>()
+            .expect("valid frames");
+        assert_eq!(frames[1], (Opcode::Function, vec![7; 200]));
+
+        let mut full = [0; 4];
+        let result = to_postfix_write(infix(), &mut full[..], |_, out| out.extend([7; 200]));
+        assert!(
+            matches!(result, Err(StreamError::Io(error)) if error.kind() == io::ErrorKind::WriteZero)
+        );
+    }
+}
@@ -0,0 +1,339 @@
+//! Conversion between postfix expressions and `syn::Expr`, for procedural macros with their own
+//! operator precedence.
+//!
+//! `to_expr` builds a Rust expression from a postfix expression whose values and functions are
+//! already `syn::Expr`s. Every operand that is not a value, call or unary operation is wrapped in
+//! parentheses, so the result keeps the precedence of the postfix expression. `from_expr` turns a
+//! Rust expression back into postfix tokens. Operators are mapped with `SynOperator`.
+//!
+//! ```rust
+//! use gyard::{
+//!     op::Math,
+//!     syn::{from_expr, to_expr},
+//!     to_postfix_with_arity, InputToken,
+//! };
+//! use quote::ToTokens;
+//! use syn::parse_quote;
+//!
+//! // a - ( b - c )
+//! let postfix = to_postfix_with_arity([
+//!     InputToken::Value(parse_quote!(a)),
+//!     InputToken::Operator(Math::Sub),
+//!     InputToken::LeftParen,
+//!     InputToken::Value(parse_quote!(b)),
+//!     InputToken::Operator(Math::Sub),
+//!     InputToken::Value(parse_quote!(c)),
+//!     InputToken::RightParen,
+//! ]).unwrap();
+//! let expr = to_expr(postfix).unwrap();
+//! assert_eq!(expr.to_token_stream().to_string(), "a - (b - c)");
+//! assert_eq!(from_expr::<Math>(&expr).unwrap().len(), 5);
+//! ```
+
+use alloc::{boxed::Box, vec::Vec};
+
+use syn::{spanned::Spanned, BinOp, Expr, ExprBinary, ExprCall, ExprParen, ExprUnary, UnOp};
+
+use crate::{
+    ast::{fold_postfix, AstError},
+    op::{All, Compare, Logical, Math},
+    Call, Operator, OutputToken,
+};
+
+/// A Rust operator
+#[derive(Clone, Copy)]
+pub enum SynOp {
+    /// A binary operator like `+`
+    Binary(BinOp),
+    /// A unary operator like `!`
+    Unary(UnOp),
+}
+
+/// Maps operators to Rust operators and back.
+pub trait SynOperator: Operator + Sized {
+    /// Returns the Rust operator or `None` if there is no equivalent.
+    fn to_syn(&self) -> Option<SynOp>;
+    /// Returns the operator for a Rust operator or `None` if it is not supported.
+    fn from_syn(op: &SynOp) -> Option<Self>;
+}
+
+impl SynOperator for Math {
+    /// `Math::Exponent` has no equivalent.
+    fn to_syn(&self) -> Option<SynOp> {
+        let op = match self {
+            Math::Add => BinOp::Add(Default::default()),
+            Math::Sub => BinOp::Sub(Default::default()),
+            Math::Mul => BinOp::Mul(Default::default()),
+            Math::Div => BinOp::Div(Default::default()),
+            Math::Exponent => return None,
+        };
+        Some(SynOp::Binary(op))
+    }
+
+    fn from_syn(op: &SynOp) -> Option<Self> {
+        match op {
+            SynOp::Binary(BinOp::Add(_)) => Some(Math::Add),
+            SynOp::Binary(BinOp::Sub(_)) => Some(Math::Sub),
+            SynOp::Binary(BinOp::Mul(_)) => Some(Math::Mul),
+            SynOp::Binary(BinOp::Div(_)) => Some(Math::Div),
+            _ => None,
+        }
+    }
+}
+
+impl SynOperator for Compare {
+    fn to_syn(&self) -> Option<SynOp> {
+        let op = match self {
+            Compare::Lt => BinOp::Lt(Default::default()),
+            Compare::Le => BinOp::Le(Default::default()),
+            Compare::Eq => BinOp::Eq(Default::default()),
+            Compare::Ne => BinOp::Ne(Default::default()),
+            Compare::Ge => BinOp::Ge(Default::default()),
+            Compare::Gt => BinOp::Gt(Default::default()),
+        };
+        Some(SynOp::Binary(op))
+    }
+
+    fn from_syn(op: &SynOp) -> Option<Self> {
+        match op {
+            SynOp::Binary(BinOp::Lt(_)) => Some(Compare::Lt),
+            SynOp::Binary(BinOp::Le(_)) => Some(Compare::Le),
+            SynOp::Binary(BinOp::Eq(_)) => Some(Compare::Eq),
+            SynOp::Binary(BinOp::Ne(_)) => Some(Compare::Ne),
+            SynOp::Binary(BinOp::Ge(_)) => Some(Compare::Ge),
+            SynOp::Binary(BinOp::Gt(_)) => Some(Compare::Gt),
+            _ => None,
+        }
+    }
+}
+
+impl SynOperator for Logical {
+    /// `Logical::Xor` is mapped to `^`.
+    fn to_syn(&self) -> Option<SynOp> {
+        Some(match self {
+            Logical::Xor => SynOp::Binary(BinOp::BitXor(Default::default())),
+            Logical::And => SynOp::Binary(BinOp::And(Default::default())),
+            Logical::Or => SynOp::Binary(BinOp::Or(Default::default())),
+            Logical::Not => SynOp::Unary(UnOp::Not(Default::default())),
+        })
+    }
+
+    fn from_syn(op: &SynOp) -> Option<Self> {
+        match op {
+            SynOp::Binary(BinOp::BitXor(_)) => Some(Logical::Xor),
+            SynOp::Binary(BinOp::And(_)) => Some(Logical::And),
+            SynOp::Binary(BinOp::Or(_)) => Some(Logical::Or),
+            SynOp::Unary(UnOp::Not(_)) => Some(Logical::Not),
+            _ => None,
+        }
+    }
+}
+
+impl SynOperator for All {
+    fn to_syn(&self) -> Option<SynOp> {
+        match self {
+            All::Math(math) => math.to_syn(),
+            All::Compare(compare) => compare.to_syn(),
+            All::Logical(logical) => logical.to_syn(),
+        }
+    }
+
+    fn from_syn(op: &SynOp) -> Option<Self> {
+        Math::from_syn(op)
+            .map(All::Math)
+            .or_else(|| Compare::from_syn(op).map(All::Compare))
+            .or_else(|| Logical::from_syn(op).map(All::Logical))
+    }
+}
+
+/// This error is returned by `to_expr`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum SynError {
+    /// The postfix expression is not well-formed.
+    Ast(AstError),
+    /// The operator at `pos` has no Rust equivalent or its arity does not match.
+    UnsupportedOperator {
+        /// The position of the operator
+        pos: usize,
+    },
+}
+
+impl core::fmt::Display for SynError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SynError::Ast(error) => error.fmt(f),
+            SynError::UnsupportedOperator { pos } => {
+                write!(f, "Operator at {pos} has no Rust equivalent")
+            }
+        }
+    }
+}
+
+impl core::error::Error for SynError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            SynError::Ast(error) => Some(error),
+            SynError::UnsupportedOperator { .. } => None,
+        }
+    }
+}
+
+impl From<AstError> for SynError {
+    fn from(error: AstError) -> Self {
+        Self::Ast(error)
+    }
+}
+
+/// A postfix expression with Rust expressions as values and functions
+pub type SynPostfix<O> = Vec<OutputToken<Expr, Call<Expr>, O>>;
+
+/// Builds a Rust expression from a postfix expression created by `to_postfix_with_arity`.
+/// Functions are the callee of the call expression.
+pub fn to_expr<O: SynOperator>(
+    postfix: impl IntoIterator<Item = OutputToken<Expr, Call<Expr>, O>>,
+) -> Result<Expr, SynError> {
+    let postfix: Vec<_> = postfix.into_iter().collect();
+    for (pos, token) in postfix.iter().enumerate() {
+        if let OutputToken::Operator(operator) = token {
+            match (operator.to_syn(), operator.arity()) {
+                (Some(SynOp::Binary(_)), 2) | (Some(SynOp::Unary(_)), 1) => {}
+                _ => return Err(SynError::UnsupportedOperator { pos }),
+            }
+        }
+    }
+    Ok(fold_postfix(
+        postfix,
+        |value| value,
+        |operator, args| {
+            let mut args = args.drain(..).map(parenthesize);
+            match (operator.to_syn(), args.next(), args.next()) {
+                (Some(SynOp::Binary(op)), Some(left), Some(right)) => Expr::Binary(ExprBinary {
+                    attrs: Vec::new(),
+                    left: Box::new(left),
+                    op,
+                    right: Box::new(right),
+                }),
+                (Some(SynOp::Unary(op)), Some(expr), None) => Expr::Unary(ExprUnary {
+                    attrs: Vec::new(),
+                    op,
+                    expr: Box::new(expr),
+                }),
+                _ => unreachable!("Checked above"),
+            }
+        },
+        |function, args| {
+            Expr::Call(ExprCall {
+                attrs: Vec::new(),
+                func: Box::new(function),
+                paren_token: Default::default(),
+                args: args.drain(..).collect(),
+            })
+        },
+    )?)
+}
+
+/// Wraps `expr` in parentheses unless it binds at least as strong as a unary operator.
+fn parenthesize(expr: Expr) -> Expr {
+    match expr {
+        Expr::Call(_)
+        | Expr::Field(_)
+        | Expr::Group(_)
+        | Expr::Index(_)
+        | Expr::Lit(_)
+        | Expr::Macro(_)
+        | Expr::MethodCall(_)
+        | Expr::Paren(_)
+        | Expr::Path(_)
+        | Expr::Unary(_) => expr,
+        expr => Expr::Paren(ExprParen {
+            attrs: Vec::new(),
+            paren_token: Default::default(),
+            expr: Box::new(expr),
+        }),
+    }
+}
+
+/// Converts a Rust expression into postfix tokens. Binary and unary operations are mapped with
+/// `SynOperator::from_syn`, calls become functions and every other expression is a value.
+/// Parentheses are removed. Unsupported operators are reported with their span.
+pub fn from_expr<O: SynOperator>(expr: &Expr) -> syn::Result<SynPostfix<O>> {
+    let mut postfix = Vec::new();
+    push_postfix(expr, &mut postfix)?;
+    Ok(postfix)
+}
+
+fn push_postfix<O: SynOperator>(expr: &Expr, postfix: &mut SynPostfix<O>) -> syn::Result<()> {
+    let operator = |op: SynOp, arity: usize, span: proc_macro2::Span| {
+        O::from_syn(&op)
+            .filter(|operator| operator.arity() == arity)
+            .map(OutputToken::Operator)
+            .ok_or_else(|| syn::Error::new(span, "unsupported operator"))
+    };
+    match expr {
+        Expr::Binary(binary) => {
+            push_postfix(&binary.left, postfix)?;
+            push_postfix(&binary.right, postfix)?;
+            postfix.push(operator(SynOp::Binary(binary.op), 2, binary.op.span())?);
+        }
+        Expr::Unary(unary) => {
+            push_postfix(&unary.expr, postfix)?;
+            postfix.push(operator(SynOp::Unary(unary.op), 1, unary.op.span())?);
+        }
+        Expr::Paren(paren) => push_postfix(&paren.expr, postfix)?,
+        Expr::Group(group) => push_postfix(&group.expr, postfix)?,
+        Expr::Call(call) => {
+            for arg in &call.args {
+                push_postfix(arg, postfix)?;
+            }
+            postfix.push(OutputToken::Function(Call {
+                function: (*call.func).clone(),
+                arity: call.args.len(),
+            }));
+        }
+        expr => postfix.push(OutputToken::Value(expr.clone())),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use syn::{parse_quote, Expr};
+
+    use super::{from_expr, to_expr, SynError};
+    use crate::{
+        op::{All, Logical, Math},
+        OutputToken,
+    };
+
+    #[test]
+    fn calls_unary_and_unsupported_operators() {
+        let expr: Expr = parse_quote!(!max(a, b.len()) || (c ^ d));
+        let postfix = from_expr::<All>(&expr).expect("supported operators");
+        assert_eq!(postfix.len(), 8);
+        assert!(matches!(
+            postfix[7],
+            OutputToken::Operator(All::Logical(Logical::Or))
+        ));
+        let expr = to_expr(postfix).expect("valid expression");
+        assert_eq!(
+            expr.to_token_stream().to_string(),
+            "! max (a , b . len ()) || (c ^ d)"
+        );
+
+        let error = from_expr::<Math>(&parse_quote!(a + b % c)).err();
+        assert_eq!(
+            error.map(|error| error.to_string()).as_deref(),
+            Some("unsupported operator")
+        );
+        let postfix = [
+            OutputToken::Value(parse_quote!(a)),
+            OutputToken::Value(parse_quote!(b)),
+            OutputToken::Operator(Math::Exponent),
+        ];
+        assert_eq!(
+            to_expr(postfix).err(),
+            Some(SynError::UnsupportedOperator { pos: 2 })
+        );
+    }
+}
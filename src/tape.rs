@@ -0,0 +1,208 @@
+//! A struct-of-arrays layout for postfix expressions.
+//!
+//! A `Tape` stores one byte per token describing its kind and keeps values, functions and operators
+//! in separate vectors. Walking the tape touches far less memory than a `Vec` of `OutputToken`s
+//! if any of the token types is large.
+//!
+//! ```rust
+//! use gyard::{op::Math, tape::{Opcode, Tape}, InputToken, OutputToken};
+//!
+//! // 1 + 2 * 3
+//! let tape = Tape::from_infix([
+//!     InputToken::<_, (), _>::Value(1),
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Value(2),
+//!     InputToken::Operator(Math::Mul),
+//!     InputToken::Value(3),
+//! ])
+//! .unwrap();
+//! assert_eq!(tape.values(), [1, 2, 3]);
+//! assert_eq!(tape.operators(), [Math::Mul, Math::Add]);
+//! assert_eq!(tape.opcodes()[3], Opcode::Operator);
+//! assert_eq!(tape.iter().last(), Some(OutputToken::Operator(&Math::Add)));
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{convert, InputToken, Operator, OutputToken, ParenMissmatchError, Queue};
+
+/// The kind of a token on a `Tape`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Opcode {
+    /// The next entry of `Tape::values`
+    Value,
+    /// The next entry of `Tape::functions`
+    Function,
+    /// The next entry of `Tape::operators`
+    Operator,
+}
+
+/// A postfix expression stored as an opcode tape and one vector per token type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Tape<V, F, O> {
+    opcodes: Vec<Opcode>,
+    values: Vec<V>,
+    functions: Vec<F>,
+    operators: Vec<O>,
+}
+
+impl<V, F, O> Default for Tape<V, F, O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V, F, O> Tape<V, F, O> {
+    /// Creates an empty tape.
+    pub fn new() -> Self {
+        Self {
+            opcodes: Vec::new(),
+            values: Vec::new(),
+            functions: Vec::new(),
+            operators: Vec::new(),
+        }
+    }
+
+    /// Reserves space for `len` opcodes.
+    fn with_len(len: usize) -> Self {
+        Self {
+            opcodes: Vec::with_capacity(len),
+            ..Self::new()
+        }
+    }
+
+    /// Like `to_postfix`, but writes the output into a tape.
+    pub fn from_infix(
+        infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+    ) -> Result<Self, ParenMissmatchError>
+    where
+        O: Operator,
+    {
+        convert(infix, |func, _| func, None, Self::with_len)
+    }
+
+    /// Appends a token.
+    pub fn push(&mut self, token: OutputToken<V, F, O>) {
+        match token {
+            OutputToken::Value(value) => {
+                self.opcodes.push(Opcode::Value);
+                self.values.push(value);
+            }
+            OutputToken::Function(function) => {
+                self.opcodes.push(Opcode::Function);
+                self.functions.push(function);
+            }
+            OutputToken::Operator(operator) => {
+                self.opcodes.push(Opcode::Operator);
+                self.operators.push(operator);
+            }
+        }
+    }
+
+    /// The kinds of all tokens in postfix order
+    pub fn opcodes(&self) -> &[Opcode] {
+        &self.opcodes
+    }
+
+    /// All values in postfix order
+    pub fn values(&self) -> &[V] {
+        &self.values
+    }
+
+    /// All functions in postfix order
+    pub fn functions(&self) -> &[F] {
+        &self.functions
+    }
+
+    /// All operators in postfix order
+    pub fn operators(&self) -> &[O] {
+        &self.operators
+    }
+
+    /// The number of tokens
+    pub fn len(&self) -> usize {
+        self.opcodes.len()
+    }
+
+    /// Returns true if the tape holds no tokens.
+    pub fn is_empty(&self) -> bool {
+        self.opcodes.is_empty()
+    }
+
+    /// Iterates over the borrowed tokens in postfix order. Use this to evaluate the tape, e.g.
+    /// with `eval::eval_postfix`.
+    pub fn iter(&self) -> impl Iterator<Item = OutputToken<&V, &F, &O>> {
+        let (mut values, mut functions, mut operators) = (
+            self.values.iter(),
+            self.functions.iter(),
+            self.operators.iter(),
+        );
+        self.opcodes.iter().filter_map(move |opcode| match opcode {
+            Opcode::Value => values.next().map(OutputToken::Value),
+            Opcode::Function => functions.next().map(OutputToken::Function),
+            Opcode::Operator => operators.next().map(OutputToken::Operator),
+        })
+    }
+
+    /// Converts the tape into a `Vec` of tokens.
+    pub fn into_postfix(self) -> Vec<OutputToken<V, F, O>> {
+        let (mut values, mut functions, mut operators) = (
+            self.values.into_iter(),
+            self.functions.into_iter(),
+            self.operators.into_iter(),
+        );
+        self.opcodes
+            .into_iter()
+            .filter_map(|opcode| match opcode {
+                Opcode::Value => values.next().map(OutputToken::Value),
+                Opcode::Function => functions.next().map(OutputToken::Function),
+                Opcode::Operator => operators.next().map(OutputToken::Operator),
+            })
+            .collect()
+    }
+}
+
+impl<V, F, O> FromIterator<OutputToken<V, F, O>> for Tape<V, F, O> {
+    fn from_iter<I: IntoIterator<Item = OutputToken<V, F, O>>>(iter: I) -> Self {
+        let mut tape = Self::new();
+        iter.into_iter().for_each(|token| tape.push(token));
+        tape
+    }
+}
+
+impl<V, F, O> Queue<OutputToken<V, F, O>> for Tape<V, F, O> {
+    fn push(&mut self, item: OutputToken<V, F, O>) -> bool {
+        Tape::push(self, item);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::Tape;
+    use crate::{op::Math, to_postfix, InputToken};
+
+    #[test]
+    fn round_trip() {
+        // f ( 1 , 2 ) - 3
+        let infix = vec![
+            InputToken::Function("f"),
+            InputToken::LeftParen,
+            InputToken::Value(1),
+            InputToken::ArgSeparator,
+            InputToken::Value(2),
+            InputToken::RightParen,
+            InputToken::Operator(Math::Sub),
+            InputToken::Value(3),
+        ];
+        let postfix = to_postfix(infix.clone()).expect("valid expression");
+        let tape = Tape::from_infix(infix).expect("valid expression");
+        assert_eq!(tape.len(), postfix.len());
+        assert_eq!(tape.functions(), ["f"]);
+        assert_eq!(tape.clone().into_postfix(), postfix);
+        assert_eq!(postfix.into_iter().collect::<Tape<_, _, _>>(), tape);
+    }
+}
@@ -0,0 +1,201 @@
+//! Postfix expressions with placeholders.
+//!
+//! A `Template` is converted once and instantiated many times. `Template::bind` replaces every
+//! occurrence of a placeholder with a sub expression. The postfix tokens of the sub expression are
+//! spliced into the template, so neither the template nor the result is converted again.
+//!
+//! ```rust
+//! use gyard::{op::Compare, template::Template, to_postfix, InputToken, OutputToken};
+//!
+//! #[derive(Debug, Clone, PartialEq)]
+//! enum Value {
+//!     Number(f64),
+//!     Metric(&'static str),
+//!     Placeholder(&'static str),
+//! }
+//!
+//! // cpu > threshold
+//! let postfix = to_postfix([
+//!     InputToken::<_, (), _>::Value(Value::Metric("cpu")),
+//!     InputToken::Operator(Compare::Gt),
+//!     InputToken::Value(Value::Placeholder("threshold")),
+//! ])
+//! .unwrap();
+//! let template = Template::new(postfix, |value| match value {
+//!     Value::Placeholder(name) => Some(*name),
+//!     _ => None,
+//! });
+//!
+//! let alert = template.bind_value(&"threshold", Value::Number(0.9));
+//! assert_eq!(alert.into_postfix().unwrap(), [
+//!     OutputToken::Value(Value::Metric("cpu")),
+//!     OutputToken::Value(Value::Number(0.9)),
+//!     OutputToken::Operator(Compare::Gt),
+//! ]);
+//! assert_eq!(template.placeholders().collect::<Vec<_>>(), [&"threshold"]);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::OutputToken;
+
+/// This error is returned by `Template::into_postfix` if a placeholder is not bound.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct UnboundPlaceholder<K> {
+    /// The name of the placeholder
+    pub name: K,
+    /// The position of the placeholder in the postfix expression
+    pub pos: usize,
+}
+
+impl<K: core::fmt::Debug> core::fmt::Display for UnboundPlaceholder<K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Placeholder {:?} at position {} is not bound",
+            self.name, self.pos
+        )
+    }
+}
+
+impl<K: core::fmt::Debug> core::error::Error for UnboundPlaceholder<K> {}
+
+/// A postfix expression with named placeholders. See the module documentation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Template<V, F, O, K> {
+    postfix: Vec<OutputToken<V, F, O>>,
+    /// The positions and names of the unbound placeholders in ascending order
+    placeholders: Vec<(usize, K)>,
+}
+
+impl<V, F, O, K> Template<V, F, O, K> {
+    /// Creates a template from a postfix expression. `placeholder` returns the name of a value
+    /// that is a placeholder or `None` for all other values.
+    pub fn new(
+        postfix: impl IntoIterator<Item = OutputToken<V, F, O>>,
+        mut placeholder: impl FnMut(&V) -> Option<K>,
+    ) -> Self {
+        let postfix: Vec<_> = postfix.into_iter().collect();
+        let placeholders = postfix
+            .iter()
+            .enumerate()
+            .filter_map(|(pos, token)| match token {
+                OutputToken::Value(value) => placeholder(value).map(|name| (pos, name)),
+                _ => None,
+            })
+            .collect();
+        Self {
+            postfix,
+            placeholders,
+        }
+    }
+
+    /// Returns the names of all unbound placeholders in the order they appear in the expression.
+    /// A placeholder used more than once is returned more than once.
+    pub fn placeholders(&self) -> impl Iterator<Item = &K> {
+        self.placeholders.iter().map(|(_, name)| name)
+    }
+
+    /// Returns the postfix expression. Unbound placeholders are still part of it.
+    pub fn postfix(&self) -> &[OutputToken<V, F, O>] {
+        &self.postfix
+    }
+
+    /// Returns the postfix expression or the first unbound placeholder.
+    pub fn into_postfix(self) -> Result<Vec<OutputToken<V, F, O>>, UnboundPlaceholder<K>> {
+        match self.placeholders.into_iter().next() {
+            Some((pos, name)) => Err(UnboundPlaceholder { name, pos }),
+            None => Ok(self.postfix),
+        }
+    }
+}
+
+impl<V: Clone, F: Clone, O: Clone, K: PartialEq + Clone> Template<V, F, O, K> {
+    /// Returns a new template in which every occurrence of the placeholder `name` is replaced
+    /// with `postfix`. `postfix` has to be a complete expression in postfix notation, i.e. the
+    /// result of `to_postfix`. Placeholders inside of `postfix` are not recognized.
+    pub fn bind(&self, name: &K, postfix: impl IntoIterator<Item = OutputToken<V, F, O>>) -> Self {
+        let replacement: Vec<_> = postfix.into_iter().collect();
+        let mut result = Self {
+            postfix: Vec::with_capacity(self.postfix.len() + replacement.len()),
+            placeholders: Vec::with_capacity(self.placeholders.len()),
+        };
+        let mut copied = 0;
+        for (pos, placeholder) in &self.placeholders {
+            result
+                .postfix
+                .extend_from_slice(&self.postfix[copied..*pos]);
+            if placeholder == name {
+                result.postfix.extend_from_slice(&replacement);
+            } else {
+                result
+                    .placeholders
+                    .push((result.postfix.len(), placeholder.clone()));
+                result.postfix.push(self.postfix[*pos].clone());
+            }
+            copied = pos + 1;
+        }
+        result.postfix.extend_from_slice(&self.postfix[copied..]);
+        result
+    }
+
+    /// Returns a new template in which every occurrence of the placeholder `name` is replaced
+    /// with `value`.
+    pub fn bind_value(&self, name: &K, value: V) -> Self {
+        self.bind(name, [OutputToken::Value(value)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::{Template, UnboundPlaceholder};
+    use crate::{op::Math, to_postfix, InputToken, OutputToken};
+
+    #[test]
+    fn bind_sub_expressions() {
+        // x * ( x - y )
+        let postfix = to_postfix([
+            InputToken::<_, (), _>::Value("x"),
+            InputToken::Operator(Math::Mul),
+            InputToken::LeftParen,
+            InputToken::Value("x"),
+            InputToken::Operator(Math::Sub),
+            InputToken::Value("y"),
+            InputToken::RightParen,
+        ])
+        .expect("valid expression");
+        let template = Template::new(postfix, |value| (*value != "1").then_some(*value));
+        let sum = to_postfix([
+            InputToken::Value("1"),
+            InputToken::Operator(Math::Add),
+            InputToken::Value("1"),
+        ])
+        .expect("valid expression");
+        let bound = template.bind(&"x", sum);
+        assert_eq!(bound.placeholders().collect::<Vec<_>>(), [&"y"]);
+        assert_eq!(
+            bound.clone().into_postfix(),
+            Err(UnboundPlaceholder { name: "y", pos: 6 })
+        );
+        let value = |value| OutputToken::Value(value);
+        assert_eq!(
+            bound.bind_value(&"y", "2").into_postfix(),
+            Ok(vec![
+                value("1"),
+                value("1"),
+                OutputToken::Operator(Math::Add),
+                value("1"),
+                value("1"),
+                OutputToken::Operator(Math::Add),
+                value("2"),
+                OutputToken::Operator(Math::Sub),
+                OutputToken::Operator(Math::Mul),
+            ])
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! Helpers for testing grammars built on this crate.
+//!
+//! `assert_postfix!` converts an infix expression and compares the result with the expected
+//! postfix tokens. If they differ, the panic message lists both expressions side by side and marks
+//! the tokens that differ.
+//!
+//! ```rust
+//! use gyard::{assert_postfix, op::Math, InputToken, OutputToken};
+//!
+//! // 1 + 2 * 3
+//! assert_postfix!(
+//!     [
+//!         InputToken::<_, (), _>::Value(1),
+//!         InputToken::Operator(Math::Add),
+//!         InputToken::Value(2),
+//!         InputToken::Operator(Math::Mul),
+//!         InputToken::Value(3),
+//!     ],
+//!     [
+//!         OutputToken::Value(1),
+//!         OutputToken::Value(2),
+//!         OutputToken::Value(3),
+//!         OutputToken::Operator(Math::Mul),
+//!         OutputToken::Operator(Math::Add),
+//!     ]
+//! );
+//! ```
+
+use alloc::{format, string::String};
+use core::fmt::{Debug, Write};
+
+/// Converts an infix expression with `to_postfix` and asserts that the result equals the expected
+/// postfix tokens. Panics with `postfix_diff` if they differ or if the conversion fails.
+#[macro_export]
+macro_rules! assert_postfix {
+    ($infix:expr, $expected:expr $(,)?) => {
+        match $crate::to_postfix($infix) {
+            Ok(actual) => $crate::testing::assert_tokens_eq(&actual, &$expected),
+            Err(error) => panic!("Conversion failed: {error}"),
+        }
+    };
+}
+
+/// Asserts that two token sequences are equal and panics with `postfix_diff` if they are not.
+#[track_caller]
+pub fn assert_tokens_eq<T: Debug + PartialEq>(actual: &[T], expected: &[T]) {
+    if let Some(diff) = postfix_diff(actual, expected) {
+        panic!("Postfix expressions differ\n{diff}");
+    }
+}
+
+/// Returns `None` if both sequences are equal. Otherwise returns one line per position with the
+/// actual and the expected token. Lines of differing tokens start with `!`.
+///
+/// ```rust
+/// use gyard::{op::Math, testing::postfix_diff, OutputToken};
+///
+/// let actual = [OutputToken::<_, (), _>::Value(1), OutputToken::Operator(Math::Add)];
+/// let expected = [OutputToken::Value(1), OutputToken::Operator(Math::Sub)];
+/// assert_eq!(postfix_diff(&actual, &actual), None);
+/// assert_eq!(postfix_diff(&actual, &expected).unwrap(), "  pos | actual        | expected
+///     0 | Value(1)      | Value(1)
+/// !   1 | Operator(Add) | Operator(Sub)
+/// ");
+/// ```
+pub fn postfix_diff<T: Debug + PartialEq>(actual: &[T], expected: &[T]) -> Option<String> {
+    if actual == expected {
+        return None;
+    }
+    let render = |token: Option<&T>| token.map_or(String::from("-"), |token| format!("{token:?}"));
+    let width = actual
+        .iter()
+        .map(|token| render(Some(token)).len())
+        .max()
+        .unwrap_or(0)
+        .max("actual".len());
+    let mut diff = format!("  pos | {:width$} | expected\n", "actual");
+    for pos in 0..actual.len().max(expected.len()) {
+        let (a, e) = (actual.get(pos), expected.get(pos));
+        let marker = if a == e { ' ' } else { '!' };
+        // Writing into a `String` never fails
+        _ = writeln!(
+            diff,
+            "{marker} {pos:>3} | {:width$} | {}",
+            render(a),
+            render(e)
+        );
+    }
+    Some(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::postfix_diff;
+
+    #[test]
+    fn diff_of_different_lengths() {
+        let diff = postfix_diff(&[1, 2], &[1, 2, 3]).expect("different");
+        assert_eq!(
+            diff,
+            "  pos | actual | expected\n    0 | 1      | 1\n    1 | 2      | 2\n!   2 | -      | 3\n"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Conversion failed")]
+    fn conversion_error_panics() {
+        assert_postfix!(
+            [crate::InputToken::<i32, (), crate::op::Math>::RightParen],
+            [crate::OutputToken::Value(1)]
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! Instrumentation with `tracing` and `log`. Without the `tracing` and `log` features all of this
+//! compiles to nothing.
+//!
+//! Spans and their summaries are only emitted with `tracing`. The decisions of a conversion, like
+//! an operator popped because of its precedence or a matched parenthesis, are emitted at trace
+//! level with the target `gyard::convert` by both.
+
+/// Emits a trace level event with the target `gyard::convert`.
+macro_rules! conversion_event {
+    ($($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(target: "gyard::convert", $($arg)+);
+        #[cfg(feature = "log")]
+        log::trace!(target: "gyard::convert", $($arg)+);
+    };
+}
+
+/// A span around a conversion, compilation or evaluation
+pub(crate) struct Span {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing")]
+    start: std::time::Instant,
+}
+
+impl Span {
+    #[cfg(feature = "tracing")]
+    fn new(span: tracing::Span) -> Self {
+        Self {
+            span,
+            start: std::time::Instant::now(),
+        }
+    }
+
+    /// Starts the span of an infix to postfix conversion.
+    pub(crate) fn conversion() -> Self {
+        #[cfg(feature = "tracing")]
+        return Self::new(tracing::debug_span!("gyard::convert"));
+        #[cfg(not(feature = "tracing"))]
+        Self {}
+    }
+
+    /// Starts the span of a compilation.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn compilation() -> Self {
+        #[cfg(feature = "tracing")]
+        return Self::new(tracing::debug_span!("gyard::compile"));
+        #[cfg(not(feature = "tracing"))]
+        Self {}
+    }
+
+    /// Starts the span of an evaluation.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn evaluation() -> Self {
+        #[cfg(feature = "tracing")]
+        return Self::new(tracing::debug_span!("gyard::eval"));
+        #[cfg(not(feature = "tracing"))]
+        Self {}
+    }
+
+    /// Ends the span with an event reporting the number of processed tokens, the stack depth and
+    /// the duration.
+    pub(crate) fn finish(self, tokens: usize, stack_depth: Option<usize>, ok: bool) {
+        #[cfg(feature = "tracing")]
+        self.span.in_scope(|| {
+            tracing::debug!(
+                tokens,
+                stack_depth,
+                duration_us = self.start.elapsed().as_micros() as u64,
+                ok,
+                "finished"
+            )
+        });
+        #[cfg(not(feature = "tracing"))]
+        let _ = (tokens, stack_depth, ok);
+    }
+}
+
+/// Reports that the operator at `pos` pops an operator with precedence `popped` off the stack.
+pub(crate) fn precedence_pop(pos: usize, popped: usize, incoming: usize) {
+    conversion_event!(
+        "operator at {pos} with precedence {incoming} pops operator with precedence {popped}"
+    );
+    #[cfg(not(any(feature = "tracing", feature = "log")))]
+    let _ = (pos, popped, incoming);
+}
+
+/// Reports that the right parenthesis at `close` matches the left parenthesis at `open`.
+pub(crate) fn paren_matched(open: usize, close: usize) {
+    conversion_event!("right parenthesis at {close} matches left parenthesis at {open}");
+    #[cfg(not(any(feature = "tracing", feature = "log")))]
+    let _ = (open, close);
+}
+
+/// Reports that a function is called with `arity` arguments by the right parenthesis at `pos`.
+pub(crate) fn function_closed(pos: usize, arity: usize) {
+    conversion_event!("right parenthesis at {pos} closes function with {arity} arguments");
+    #[cfg(not(any(feature = "tracing", feature = "log")))]
+    let _ = (pos, arity);
+}
+
+/// Warns that the operator at `pos` has the same precedence but a different associativity than
+/// the operator on the stack.
+pub(crate) fn mixed_associativity(pos: usize) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        target: "gyard::convert",
+        "operator at {pos} has the same precedence but a different associativity than the operator before it"
+    );
+    #[cfg(feature = "log")]
+    log::warn!(
+        target: "gyard::convert",
+        "operator at {pos} has the same precedence but a different associativity than the operator before it"
+    );
+    #[cfg(not(any(feature = "tracing", feature = "log")))]
+    let _ = pos;
+}
+
+#[cfg(all(test, feature = "log", feature = "std"))]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::{op::Math, to_postfix, InputToken};
+
+    static MESSAGES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    struct Logger;
+
+    impl log::Log for Logger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.target() == "gyard::convert"
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                MESSAGES
+                    .lock()
+                    .expect("unpoisoned lock")
+                    .push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn logs_conversion_decisions() {
+        log::set_logger(&Logger).expect("no other logger");
+        log::set_max_level(log::LevelFilter::Trace);
+        // Other tests may log concurrently, so only the messages of this expression are checked
+        to_postfix([
+            InputToken::<_, (), _>::LeftParen,
+            InputToken::Value(-1),
+            InputToken::Operator(Math::Mul),
+            InputToken::Value(-2),
+            InputToken::Operator(Math::Add),
+            InputToken::Value(-3),
+            InputToken::RightParen,
+        ])
+        .expect("valid expression");
+        let messages = MESSAGES.lock().expect("unpoisoned lock");
+        assert!(messages
+            .iter()
+            .any(|m| m == "operator at 4 with precedence 11 pops operator with precedence 12"));
+        assert!(messages
+            .iter()
+            .any(|m| m == "right parenthesis at 6 matches left parenthesis at 0"));
+    }
+}
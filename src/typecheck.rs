@@ -0,0 +1,262 @@
+//! Type checking of postfix expressions.
+//! Operators and functions declare which operand types they accept and which type they produce.
+//! `type_check` verifies a postfix expression before it is evaluated.
+//!
+//! ```rust
+//! use gyard::{op::{All, Compare, Logical, Math}, to_postfix_with_arity, InputToken};
+//! use gyard::typecheck::{type_check, TypeError, TypedOperator};
+//!
+//! #[derive(Debug, Clone, Copy, PartialEq)]
+//! enum Type {
+//!     Number,
+//!     Bool,
+//! }
+//!
+//! impl TypedOperator<Type> for All {
+//!     fn result_type(&self, args: &[Type]) -> Option<Type> {
+//!         use Type::*;
+//!         match (self, args) {
+//!             (All::Math(_), [Number, Number]) => Some(Number),
+//!             (All::Compare(_), [Number, Number]) => Some(Bool),
+//!             (All::Logical(Logical::Not), [Bool]) => Some(Bool),
+//!             (All::Logical(_), [Bool, Bool]) => Some(Bool),
+//!             _ => None,
+//!         }
+//!     }
+//! }
+//!
+//! struct Abs;
+//! impl TypedOperator<Type> for Abs {
+//!     fn result_type(&self, args: &[Type]) -> Option<Type> {
+//!         matches!(args, [Type::Number]).then_some(Type::Number)
+//!     }
+//! }
+//!
+//! // 1 < 2 && 3
+//! let postfix = to_postfix_with_arity::<_, Abs, _>([
+//!     InputToken::Value(1),
+//!     InputToken::Operator(All::Compare(Compare::Lt)),
+//!     InputToken::Value(2),
+//!     InputToken::Operator(All::Logical(Logical::And)),
+//!     InputToken::Value(3),
+//! ]).unwrap();
+//! let result = type_check(&postfix, |_| Some(Type::Number));
+//! assert_eq!(result, Err(TypeError::Mismatch { pos: 4, found: vec![Type::Bool, Type::Number] }));
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{Call, Operator, OutputToken};
+
+/// Declares the types an operator or function can be applied to.
+pub trait TypedOperator<Ty> {
+    /// Returns the type of the result if the operator accepts operands of the types in `args`.
+    /// Returns `None` if the operator can not be applied to these types.
+    fn result_type(&self, args: &[Ty]) -> Option<Ty>;
+}
+
+impl<Ty, T: TypedOperator<Ty> + ?Sized> TypedOperator<Ty> for &T {
+    fn result_type(&self, args: &[Ty]) -> Option<Ty> {
+        (**self).result_type(args)
+    }
+}
+
+impl<Ty, F: TypedOperator<Ty>> TypedOperator<Ty> for Call<F> {
+    fn result_type(&self, args: &[Ty]) -> Option<Ty> {
+        self.function.result_type(args)
+    }
+}
+
+/// This error is returned if a postfix expression is not type correct.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TypeError<Ty> {
+    /// The operator or function at `pos` can not be applied to operands of the `found` types.
+    Mismatch {
+        /// The position of the operator or function
+        pos: usize,
+        /// The types of the operands
+        found: Vec<Ty>,
+    },
+    /// The type of the value at `pos` is unknown.
+    UnknownValue {
+        /// The position of the value
+        pos: usize,
+    },
+    /// The operator or function at `pos` requires more values than there are on the stack.
+    StackUnderflow {
+        /// The position of the operator or function
+        pos: usize,
+        /// The number of values the operator or function requires
+        required: usize,
+        /// The number of values on the stack
+        available: usize,
+    },
+    /// The expression does not contain any tokens.
+    EmptyExpression,
+    /// The expression would leave more than one value on the stack.
+    UnusedValues {
+        /// The number of values left on the stack
+        count: usize,
+    },
+}
+
+impl<Ty: core::fmt::Debug> core::fmt::Display for TypeError<Ty> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TypeError::Mismatch { pos, found } => write!(
+                f,
+                "Token at position {pos} can not be applied to operands of type {found:?}"
+            ),
+            TypeError::UnknownValue { pos } => write!(f, "Unknown type of value at position {pos}"),
+            TypeError::StackUnderflow {
+                pos,
+                required,
+                available,
+            } => write!(
+                f,
+                "Token at position {pos} requires {required} values but only {available} are available"
+            ),
+            TypeError::EmptyExpression => write!(f, "Can not type check an empty expression"),
+            TypeError::UnusedValues { count } => {
+                write!(f, "Expression evaluates to {count} values instead of one")
+            }
+        }
+    }
+}
+
+impl<Ty: core::fmt::Debug> core::error::Error for TypeError<Ty> {}
+
+/// Verifies that a postfix expression created by `to_postfix_with_arity` is type correct and
+/// returns the type of its result. `value_type` returns the type of a value token.
+pub fn type_check<V, F, O, Ty>(
+    postfix: &[OutputToken<V, Call<F>, O>],
+    mut value_type: impl FnMut(&V) -> Option<Ty>,
+) -> Result<Ty, TypeError<Ty>>
+where
+    O: Operator + TypedOperator<Ty>,
+    F: TypedOperator<Ty>,
+{
+    let mut stack: Vec<Ty> = Vec::new();
+
+    for (pos, token) in postfix.iter().enumerate() {
+        let (arity, operator): (usize, &dyn TypedOperator<Ty>) = match token {
+            OutputToken::Value(value) => {
+                stack.push(value_type(value).ok_or(TypeError::UnknownValue { pos })?);
+                continue;
+            }
+            OutputToken::Operator(op) => (op.arity(), op),
+            OutputToken::Function(call) => (call.arity, call),
+        };
+        let start = stack
+            .len()
+            .checked_sub(arity)
+            .ok_or(TypeError::StackUnderflow {
+                pos,
+                required: arity,
+                available: stack.len(),
+            })?;
+        let Some(ty) = operator.result_type(&stack[start..]) else {
+            return Err(TypeError::Mismatch {
+                pos,
+                found: stack.split_off(start),
+            });
+        };
+        stack.truncate(start);
+        stack.push(ty);
+    }
+
+    let Some(ty) = stack.pop() else {
+        return Err(TypeError::EmptyExpression);
+    };
+    if !stack.is_empty() {
+        return Err(TypeError::UnusedValues {
+            count: stack.len() + 1,
+        });
+    }
+    Ok(ty)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::{type_check, TypeError, TypedOperator};
+    use crate::{op::Math, Call, OutputToken};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Type {
+        Int,
+        Str,
+    }
+
+    impl TypedOperator<Type> for Math {
+        fn result_type(&self, args: &[Type]) -> Option<Type> {
+            match (self, args) {
+                (_, [Type::Int, Type::Int]) => Some(Type::Int),
+                (Math::Add, [Type::Str, Type::Str]) => Some(Type::Str),
+                _ => None,
+            }
+        }
+    }
+
+    struct Len;
+
+    impl TypedOperator<Type> for Len {
+        fn result_type(&self, args: &[Type]) -> Option<Type> {
+            matches!(args, [Type::Str]).then_some(Type::Int)
+        }
+    }
+
+    fn value_type(value: &&str) -> Option<Type> {
+        match value.parse::<i64>() {
+            Ok(_) => Some(Type::Int),
+            Err(_) => Some(Type::Str),
+        }
+    }
+
+    #[test]
+    fn overloaded_operator() {
+        // len ( "a" + "b" ) * 2
+        let postfix = [
+            OutputToken::Value("a"),
+            OutputToken::Value("b"),
+            OutputToken::Operator(Math::Add),
+            OutputToken::Function(Call {
+                function: Len,
+                arity: 1,
+            }),
+            OutputToken::Value("2"),
+            OutputToken::Operator(Math::Mul),
+        ];
+        assert_eq!(type_check(&postfix, value_type), Ok(Type::Int));
+    }
+
+    #[test]
+    fn mismatch() {
+        // "a" * 2
+        let postfix = [
+            OutputToken::<_, Call<Len>, _>::Value("a"),
+            OutputToken::Value("2"),
+            OutputToken::Operator(Math::Mul),
+        ];
+        assert_eq!(
+            type_check(&postfix, value_type),
+            Err(TypeError::Mismatch {
+                pos: 2,
+                found: vec![Type::Str, Type::Int]
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_value() {
+        let postfix = [OutputToken::<_, Call<Len>, Math>::Value("a")];
+        assert_eq!(
+            type_check(&postfix, |_| None::<Type>),
+            Err(TypeError::UnknownValue { pos: 0 })
+        );
+    }
+}
@@ -0,0 +1,543 @@
+//! A built-in value type and an evaluator covering all predefined operators.
+//!
+//! - Math operators work on numbers and follow IEEE 754. `+` also concatenates strings and lists,
+//!   so wrap the operators in `ValueOp` before canonicalizing or memoizing an expression.
+//! - `==` and `!=` compare values of any type. Values of different types are never equal.
+//! - `<`, `<=`, `>=` and `>` compare numbers or strings and produce booleans.
+//! - Logical operators work on booleans.
+//! - `Null` propagates through math operators and ordering comparisons. Logical operators follow
+//!   three-valued logic, so `false && null` is `false` and `true || null` is `true`.
+//!
+//! ```rust
+//! use gyard::{eval::eval_postfix, op::{All, Compare, Math}, to_postfix, InputToken};
+//! use gyard::value::{Calculator, Value};
+//!
+//! // sqrt ( 16 ) + 1 > 4
+//! let postfix = to_postfix([
+//!     InputToken::Function("sqrt"),
+//!     InputToken::LeftParen,
+//!     InputToken::Value(16.),
+//!     InputToken::RightParen,
+//!     InputToken::Operator(All::Math(Math::Add)),
+//!     InputToken::Value(1.),
+//!     InputToken::Operator(All::Compare(Compare::Gt)),
+//!     InputToken::Value(4.),
+//! ]).unwrap();
+//! assert_eq!(eval_postfix(postfix, &mut Calculator), Ok(Value::Bool(true)));
+//! ```
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::ops::RangeInclusive;
+
+use crate::{
+    eval::Evaluator,
+    op::{All, Compare, Logical, Math},
+    Operator,
+};
+
+/// A value produced by `Calculator`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// A number
+    Number(f64),
+    /// A boolean
+    Bool(bool),
+    /// A string
+    Str(String),
+    /// The absence of a value
+    Null,
+    /// A list of values
+    List(Vec<Value>),
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::Str(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(value: Vec<Value>) -> Self {
+        Self::List(value)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+impl core::fmt::Display for Value {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{n}"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Str(s) => write!(f, "{s:?}"),
+            Value::Null => write!(f, "null"),
+            Value::List(list) => {
+                write!(f, "[")?;
+                for (i, value) in list.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+/// This error is returned by `Calculator`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ValueError {
+    /// The operator can not be applied to operands of these types.
+    TypeMismatch {
+        /// The operator
+        operator: All,
+        /// The operands
+        args: Vec<Value>,
+    },
+    /// The function does not exist or can not be applied to these arguments.
+    InvalidFunctionCall {
+        /// The name of the function
+        name: String,
+        /// The arguments
+        args: Vec<Value>,
+    },
+}
+
+impl core::fmt::Display for ValueError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ValueError::TypeMismatch { operator, args } => {
+                write!(f, "Can not apply {operator:?} to {args:?}")
+            }
+            ValueError::InvalidFunctionCall { name, args } => {
+                write!(f, "Can not call {name} with {args:?}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ValueError {}
+
+/// A predefined operator as `Calculator` applies it.
+/// `+` concatenates strings and lists, so unlike `All` the operands of `+` can not be swapped.
+///
+/// ```rust
+/// use gyard::{op::{All, Math}, value::ValueOp, Operator};
+/// assert!(All::Math(Math::Add).is_commutative());
+/// assert!(!ValueOp(All::Math(Math::Add)).is_commutative());
+/// assert!(ValueOp(All::Math(Math::Mul)).is_commutative());
+/// ```
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct ValueOp(pub All);
+
+impl Operator for ValueOp {
+    fn precedence(&self) -> usize {
+        self.0.precedence()
+    }
+
+    fn is_left_associative(&self) -> bool {
+        self.0.is_left_associative()
+    }
+
+    fn arity(&self) -> usize {
+        self.0.arity()
+    }
+
+    fn is_commutative(&self) -> bool {
+        self.0 != All::Math(Math::Add) && self.0.is_commutative()
+    }
+}
+
+impl From<All> for ValueOp {
+    fn from(value: All) -> Self {
+        Self(value)
+    }
+}
+
+impl From<ValueOp> for All {
+    fn from(value: ValueOp) -> Self {
+        value.0
+    }
+}
+
+/// Evaluates all predefined operators on `Value`s.
+///
+/// Supported functions are `abs`, `sqrt`, `exp`, `ln`, `log10`, `sin`, `cos`, `tan`, `floor`,
+/// `ceil` and `round` with one number, `len` with one string or list and `min` and `max` with two
+/// or, when evaluated with `eval_postfix_with_arity`, any positive number of numbers. Other
+/// functions are rejected with `eval::EvalError::UnknownFunction` before they are called.
+///
+/// `+` concatenates strings and lists, so `Calculator` does not treat it as commutative when
+/// evaluating with `eval::memo::Memo`. Functions working on the operators alone, like
+/// `ast::canonical::canonicalize`, need `ValueOp` instead of `All` for this.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Calculator;
+
+impl Calculator {
+    /// Applies a predefined operator to its operands.
+    pub fn apply(&self, operator: All, args: &[Value]) -> Result<Value, ValueError> {
+        use Value::*;
+        let value = match (operator, args) {
+            (All::Math(op), [Number(a), Number(b)]) => Number(match op {
+                Math::Add => a + b,
+                Math::Sub => a - b,
+                Math::Mul => a * b,
+                Math::Div => a / b,
+                Math::Exponent => a.powf(*b),
+            }),
+            (All::Math(Math::Add), [Str(a), Str(b)]) => Str(format!("{a}{b}")),
+            (All::Math(Math::Add), [List(a), List(b)]) => List([a.as_slice(), b].concat()),
+            (All::Math(_), [Null, _] | [_, Null]) => Null,
+            (All::Compare(Compare::Eq), [a, b]) => Bool(a == b),
+            (All::Compare(Compare::Ne), [a, b]) => Bool(a != b),
+            (All::Compare(op), [Number(a), Number(b)]) => Bool(compare(op, a.partial_cmp(b))),
+            (All::Compare(op), [Str(a), Str(b)]) => Bool(compare(op, Some(a.cmp(b)))),
+            (All::Compare(_), [Null, _] | [_, Null]) => Null,
+            (All::Logical(Logical::Not), [Bool(a)]) => Bool(!a),
+            (All::Logical(Logical::Not), [Null]) => Null,
+            (All::Logical(Logical::And), [Bool(false), Bool(_) | Null])
+            | (All::Logical(Logical::And), [Null, Bool(false)]) => Bool(false),
+            (All::Logical(Logical::Or), [Bool(true), Bool(_) | Null])
+            | (All::Logical(Logical::Or), [Null, Bool(true)]) => Bool(true),
+            (All::Logical(Logical::And), [Bool(a), Bool(b)]) => Bool(*a && *b),
+            (All::Logical(Logical::Or), [Bool(a), Bool(b)]) => Bool(*a || *b),
+            (All::Logical(Logical::Xor), [Bool(a), Bool(b)]) => Bool(a ^ b),
+            (All::Logical(_), [Bool(_) | Null, Bool(_) | Null]) => Null,
+            _ => {
+                return Err(ValueError::TypeMismatch {
+                    operator,
+                    args: args.to_vec(),
+                })
+            }
+        };
+        Ok(value)
+    }
+
+    /// Calls a built-in function.
+    pub fn call(&self, name: &str, args: &[Value]) -> Result<Value, ValueError> {
+        let numbers: Option<Vec<f64>> = args
+            .iter()
+            .map(|arg| match arg {
+                Value::Number(n) => Some(*n),
+                _ => None,
+            })
+            .collect();
+        let result = match (name, numbers.as_deref(), args) {
+            ("len", _, [Value::Str(s)]) => Some(s.chars().count() as f64),
+            ("len", _, [Value::List(list)]) => Some(list.len() as f64),
+            (_, numbers, _) => call_numeric(name, numbers),
+        };
+        result
+            .map(Value::Number)
+            .ok_or_else(|| ValueError::InvalidFunctionCall {
+                name: name.to_string(),
+                args: args.to_vec(),
+            })
+    }
+}
+
+/// Applies a comparison to the ordering of its operands.
+fn compare(operator: Compare, ordering: Option<core::cmp::Ordering>) -> bool {
+    use core::cmp::Ordering::*;
+    matches!(
+        (operator, ordering),
+        (Compare::Lt, Some(Less))
+            | (Compare::Gt, Some(Greater))
+            | (Compare::Le, Some(Less | Equal))
+            | (Compare::Ge, Some(Greater | Equal))
+            | (Compare::Eq, Some(Equal))
+            | (Compare::Ne, Some(Less | Greater) | None)
+    )
+}
+
+/// Calls a built-in function taking numbers.
+fn call_numeric(name: &str, numbers: Option<&[f64]>) -> Option<f64> {
+    match (name, numbers) {
+        ("abs", Some(&[n])) => Some(n.abs()),
+        ("sqrt", Some(&[n])) => Some(n.sqrt()),
+        ("exp", Some(&[n])) => Some(n.exp()),
+        ("ln", Some(&[n])) => Some(n.ln()),
+        ("log10", Some(&[n])) => Some(n.log10()),
+        ("sin", Some(&[n])) => Some(n.sin()),
+        ("cos", Some(&[n])) => Some(n.cos()),
+        ("tan", Some(&[n])) => Some(n.tan()),
+        ("floor", Some(&[n])) => Some(n.floor()),
+        ("ceil", Some(&[n])) => Some(n.ceil()),
+        ("round", Some(&[n])) => Some(n.round()),
+        ("min", Some(numbers)) => numbers.iter().copied().reduce(f64::min),
+        ("max", Some(numbers)) => numbers.iter().copied().reduce(f64::max),
+        _ => None,
+    }
+}
+
+impl<V, F, O> Evaluator<V, F, O> for Calculator
+where
+    V: Into<Value>,
+    F: AsRef<str>,
+    O: Into<All> + Clone,
+{
+    type Value = Value;
+    type Error = ValueError;
+
+    fn value(&mut self, value: V) -> Result<Value, ValueError> {
+        Ok(value.into())
+    }
+
+    fn operator(&mut self, operator: O, args: &mut Vec<Value>) -> Result<Value, ValueError> {
+        self.apply(operator.into(), args)
+    }
+
+    fn function(&mut self, function: F, args: &mut Vec<Value>) -> Result<Value, ValueError> {
+        self.call(function.as_ref(), args)
+    }
+
+    fn function_arity(&self, function: &F) -> usize {
+        match function.as_ref() {
+            "min" | "max" => 2,
+            _ => 1,
+        }
+    }
+
+    fn accepted_arity(&self, function: &F) -> RangeInclusive<usize> {
+        match function.as_ref() {
+            "min" | "max" => 1..=usize::MAX,
+            "abs" | "sqrt" | "exp" | "ln" | "log10" | "sin" | "cos" | "tan" | "floor" | "ceil"
+            | "round" | "len" => 1..=1,
+            #[expect(
+                clippy::reversed_empty_ranges,
+                reason = "unknown functions take no arity"
+            )]
+            _ => 1..=0,
+        }
+    }
+
+    fn is_commutative(&self, operator: &O) -> bool
+    where
+        O: Operator,
+    {
+        operator.clone().into() != All::Math(Math::Add) && operator.is_commutative()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Calculator, Value, ValueError, ValueOp};
+    use crate::{
+        eval::{eval_postfix_with_arity, memo::Memo, EvalError},
+        op::{All, Compare, Logical, Math},
+        to_postfix_with_arity, Call, InputToken, OutputToken,
+    };
+
+    #[test]
+    fn logical_operators() {
+        let calc = Calculator;
+        let (t, f) = (Value::Bool(true), Value::Bool(false));
+        assert_eq!(
+            calc.apply(Logical::Xor.into(), &[t.clone(), t.clone()]),
+            Ok(f.clone())
+        );
+        assert_eq!(
+            calc.apply(Logical::And.into(), &[t.clone(), f.clone()]),
+            Ok(f.clone())
+        );
+        assert_eq!(
+            calc.apply(Logical::Or.into(), &[t.clone(), f.clone()]),
+            Ok(t.clone())
+        );
+        assert_eq!(calc.apply(Logical::Not.into(), &[f]), Ok(t));
+    }
+
+    #[test]
+    fn null_propagation() {
+        let calc = Calculator;
+        let (t, f) = (Value::Bool(true), Value::Bool(false));
+        assert_eq!(
+            calc.apply(Math::Add.into(), &[Value::Number(1.), Value::Null]),
+            Ok(Value::Null)
+        );
+        assert_eq!(
+            calc.apply(Compare::Eq.into(), &[Value::Null, Value::Null]),
+            Ok(Value::Bool(true))
+        );
+        assert_eq!(
+            calc.apply(Compare::Lt.into(), &[Value::Null, Value::Number(1.)]),
+            Ok(Value::Null)
+        );
+        assert_eq!(
+            calc.apply(Logical::And.into(), &[Value::Null, f.clone()]),
+            Ok(f)
+        );
+        assert_eq!(
+            calc.apply(Logical::Or.into(), &[Value::Null, t.clone()]),
+            Ok(t.clone())
+        );
+        assert_eq!(
+            calc.apply(Logical::And.into(), &[t, Value::Null]),
+            Ok(Value::Null)
+        );
+    }
+
+    #[test]
+    fn strings_and_lists() {
+        let calc = Calculator;
+        assert_eq!(
+            calc.apply(Math::Add.into(), &["foo".into(), "bar".into()]),
+            Ok("foobar".into())
+        );
+        assert_eq!(
+            calc.apply(Compare::Lt.into(), &["a".into(), "b".into()]),
+            Ok(Value::Bool(true))
+        );
+        assert_eq!(
+            calc.apply(Compare::Eq.into(), &["1".into(), Value::Number(1.)]),
+            Ok(Value::Bool(false))
+        );
+        let list = Value::List(vec![Value::Number(1.), Value::Null]);
+        assert_eq!(
+            calc.apply(Math::Add.into(), &[list.clone(), list.clone()]),
+            Ok(Value::List(vec![
+                Value::Number(1.),
+                Value::Null,
+                Value::Number(1.),
+                Value::Null
+            ]))
+        );
+        assert_eq!(calc.call("len", &[list]), Ok(Value::Number(2.)));
+        assert_eq!(calc.call("len", &["äb".into()]), Ok(Value::Number(2.)));
+    }
+
+    #[test]
+    fn memoized_concatenation() {
+        let value = OutputToken::<_, Call<&str>, _>::Value;
+        let add = || OutputToken::Operator(ValueOp(All::Math(Math::Add)));
+        let mut memo = Memo::new();
+        // "a" + "b"
+        let postfix = [value("a"), value("b"), add()];
+        assert_eq!(memo.eval(&postfix, &mut Calculator), Ok("ab".into()));
+        // "b" + "a"
+        let postfix = [value("b"), value("a"), add()];
+        assert_eq!(memo.eval(&postfix, &mut Calculator), Ok("ba".into()));
+        assert_eq!(memo.hits(), 0);
+        // The evaluator decides, so plain `All` operators are not swapped either
+        let value = OutputToken::<_, Call<&str>, _>::Value;
+        let add = || OutputToken::Operator(All::Math(Math::Add));
+        let mut memo = Memo::new();
+        let postfix = [value("a"), value("b"), add()];
+        assert_eq!(memo.eval(&postfix, &mut Calculator), Ok("ab".into()));
+        let postfix = [value("b"), value("a"), add()];
+        assert_eq!(memo.eval(&postfix, &mut Calculator), Ok("ba".into()));
+        assert_eq!(memo.hits(), 0);
+    }
+
+    #[test]
+    fn type_mismatch() {
+        let args = [Value::Bool(true), Value::Number(1.)];
+        assert_eq!(
+            Calculator.apply(All::Math(Math::Add), &args),
+            Err(ValueError::TypeMismatch {
+                operator: All::Math(Math::Add),
+                args: args.to_vec()
+            })
+        );
+    }
+
+    #[test]
+    fn variadic_max() {
+        // max ( 1 , 5 , 3 )
+        let postfix = to_postfix_with_arity::<_, _, All>([
+            InputToken::Function("max"),
+            InputToken::LeftParen,
+            InputToken::Value(1.),
+            InputToken::ArgSeparator,
+            InputToken::Value(5.),
+            InputToken::ArgSeparator,
+            InputToken::Value(3.),
+            InputToken::RightParen,
+        ]);
+        let Ok(postfix) = postfix else {
+            panic!("Failed to convert")
+        };
+        assert_eq!(
+            eval_postfix_with_arity(postfix, &mut Calculator),
+            Ok(Value::Number(5.))
+        );
+    }
+
+    #[test]
+    fn variadic_arity_mismatch() {
+        // max ( )
+        let postfix = to_postfix_with_arity::<_, _, All>([
+            InputToken::<f64, _, _>::Function("max"),
+            InputToken::LeftParen,
+            InputToken::RightParen,
+        ]);
+        let Ok(postfix) = postfix else {
+            panic!("Failed to convert")
+        };
+        let error = eval_postfix_with_arity(postfix, &mut Calculator);
+        assert_eq!(
+            error,
+            Err(EvalError::ArityMismatch {
+                pos: 0,
+                expected: 1..=usize::MAX,
+                found: 0
+            })
+        );
+        assert_eq!(
+            error.map_err(|error| error.to_string()),
+            Err("Function at position 0 takes at least 1 arguments but 0 were given".to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_function() {
+        let postfix = to_postfix_with_arity::<_, _, All>([
+            InputToken::Function("foo"),
+            InputToken::LeftParen,
+            InputToken::Value(1.),
+            InputToken::RightParen,
+        ]);
+        let Ok(postfix) = postfix else {
+            panic!("Failed to convert")
+        };
+        // The call is rejected before it is made
+        assert_eq!(
+            eval_postfix_with_arity(postfix, &mut Calculator),
+            Err(EvalError::UnknownFunction { pos: 1 })
+        );
+        assert_eq!(
+            Calculator.call("foo", &[Value::Number(1.)]),
+            Err(ValueError::InvalidFunctionCall {
+                name: "foo".to_string(),
+                args: vec![Value::Number(1.)]
+            })
+        );
+    }
+}
@@ -0,0 +1,213 @@
+//! Step by step snapshots of a conversion.
+//!
+//! `walkthrough` converts an expression like `to_postfix` and records the operator stack and the
+//! output after every token. Use it to build visualizations of the shunting yard algorithm or to
+//! generate course material.
+//!
+//! ```rust
+//! use gyard::{
+//!     op::Math,
+//!     walkthrough::{walkthrough, Action, StackEntry},
+//!     InputToken, OutputToken,
+//! };
+//!
+//! // 1 + 2 * 3
+//! let walkthrough = walkthrough([
+//!     InputToken::<_, (), _>::Value(1),
+//!     InputToken::Operator(Math::Add),
+//!     InputToken::Value(2),
+//!     InputToken::Operator(Math::Mul),
+//!     InputToken::Value(3),
+//! ]);
+//! let step = &walkthrough.steps[3];
+//! assert_eq!(step.read, Some(InputToken::Operator(Math::Mul)));
+//! assert_eq!(step.action, Action::PushOperator { popped: 0 });
+//! assert_eq!(step.stack, [StackEntry::Operator(Math::Add), StackEntry::Operator(Math::Mul)]);
+//! assert_eq!(step.output, [OutputToken::Value(1), OutputToken::Value(2)]);
+//!
+//! let last = walkthrough.steps.last().unwrap();
+//! assert_eq!((&last.read, &last.action), (&None, &Action::Finish { popped: 2 }));
+//! assert_eq!(walkthrough.result.unwrap(), last.output);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{growable, Converter, InputToken, Operator, OutputToken, ParenMissmatchError, Stack};
+
+/// An entry of the operator stack
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StackEntry<F, O> {
+    /// A left parenthesis
+    LeftParen {
+        /// The position of the left parenthesis
+        pos: usize,
+    },
+    /// A function waiting for its arguments
+    Function {
+        /// The function
+        function: F,
+        /// The number of arguments seen so far
+        args: usize,
+    },
+    /// An operator waiting for its right operand or for operators with lower precedence
+    Operator(O),
+}
+
+/// What the conversion did with a token. `popped` is the number of tokens moved from the stack
+/// into the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Action {
+    /// The value was appended to the output.
+    Output,
+    /// Operators with higher precedence were moved into the output, then the operator was pushed.
+    PushOperator {
+        /// The number of moved operators
+        popped: usize,
+    },
+    /// The function was pushed.
+    PushFunction,
+    /// The left parenthesis was pushed.
+    OpenParen,
+    /// The operators since the matching left parenthesis and the function of a call were moved
+    /// into the output.
+    CloseParen {
+        /// The number of moved operators and functions
+        popped: usize,
+    },
+    /// The operators of the current argument were moved into the output.
+    Separator {
+        /// The number of moved operators
+        popped: usize,
+    },
+    /// The input ended and the remaining stack was moved into the output.
+    Finish {
+        /// The number of moved operators and functions
+        popped: usize,
+    },
+}
+
+/// A single step of a `Walkthrough`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step<V, F, O> {
+    /// The token read in this step or `None` for the final step
+    pub read: Option<InputToken<V, F, O>>,
+    /// The position of the token or the length of the input for the final step
+    pub pos: usize,
+    /// What the conversion did
+    pub action: Action,
+    /// The operator stack after the step from bottom to top
+    pub stack: Vec<StackEntry<F, O>>,
+    /// The output after the step
+    pub output: Vec<OutputToken<V, F, O>>,
+}
+
+/// The recorded conversion returned by `walkthrough`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Walkthrough<V, F, O> {
+    /// The steps in conversion order. A token that causes an error has no step.
+    pub steps: Vec<Step<V, F, O>>,
+    /// The result of the conversion
+    pub result: Result<Vec<OutputToken<V, F, O>>, ParenMissmatchError>,
+}
+
+/// Converts an infix expression like `to_postfix` and records every step.
+pub fn walkthrough<V, F, O>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+) -> Walkthrough<V, F, O>
+where
+    V: Clone,
+    F: Clone,
+    O: Operator + Clone,
+{
+    let mut converter: Converter<Stack<F, O>> = Converter::new(Stack::default());
+    let mut output = Vec::new();
+    let mut steps = Vec::new();
+    let identity = |func, _| func;
+    let mut end = 0;
+    for (pos, token) in infix.into_iter().enumerate() {
+        end = pos + 1;
+        let before = output.len();
+        if let Err(error) = growable(converter.push(pos, token.clone(), &mut output, &identity)) {
+            return Walkthrough {
+                steps,
+                result: Err(error),
+            };
+        }
+        let popped = output.len() - before;
+        let action = match token {
+            InputToken::Value(_) => Action::Output,
+            InputToken::Operator(_) => Action::PushOperator { popped },
+            InputToken::Function(_) => Action::PushFunction,
+            InputToken::LeftParen => Action::OpenParen,
+            InputToken::RightParen => Action::CloseParen { popped },
+            #[expect(deprecated, reason = "")]
+            InputToken::ArgSeperator | InputToken::ArgSeparator => Action::Separator { popped },
+        };
+        steps.push(Step {
+            read: Some(token),
+            pos,
+            action,
+            stack: converter.stack.snapshot(),
+            output: output.clone(),
+        });
+    }
+    let before = output.len();
+    if let Err(error) = growable(converter.finish(&mut output, &identity)) {
+        return Walkthrough {
+            steps,
+            result: Err(error),
+        };
+    }
+    steps.push(Step {
+        read: None,
+        pos: end,
+        action: Action::Finish {
+            popped: output.len() - before,
+        },
+        stack: Vec::new(),
+        output: output.clone(),
+    });
+    Walkthrough {
+        steps,
+        result: Ok(output),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{walkthrough, Action, StackEntry};
+    use crate::{op::Math, InputToken, ParenMissmatchError};
+
+    #[test]
+    fn function_calls_and_errors() {
+        // max ( 1 , 2 ) )
+        let walkthrough = walkthrough([
+            InputToken::<_, _, Math>::Function("max"),
+            InputToken::LeftParen,
+            InputToken::Value(1),
+            InputToken::ArgSeparator,
+            InputToken::Value(2),
+            InputToken::RightParen,
+            InputToken::RightParen,
+        ]);
+        assert_eq!(
+            walkthrough.steps[4].stack,
+            [
+                StackEntry::Function {
+                    function: "max",
+                    args: 2
+                },
+                StackEntry::LeftParen { pos: 1 }
+            ]
+        );
+        let last = walkthrough.steps.last().expect("steps before the error");
+        assert_eq!(last.action, Action::CloseParen { popped: 1 });
+        assert!(last.stack.is_empty());
+        assert!(matches!(
+            walkthrough.result,
+            Err(ParenMissmatchError::UnexpectedClosing { pos: 6, .. })
+        ));
+    }
+}
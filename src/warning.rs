@@ -0,0 +1,216 @@
+//! Non-fatal warnings for suspicious but valid expressions.
+//!
+//! `to_postfix_with_warnings` converts an expression like `to_postfix` and reports constructs that
+//! are probably not what the author meant, without rejecting the expression.
+//!
+//! ```rust
+//! use gyard::{op::Math, warning::{to_postfix_with_warnings, Warning}, InputToken};
+//!
+//! // sin x * ( ( 2 ) )
+//! let (postfix, warnings) = to_postfix_with_warnings([
+//!     InputToken::Function("sin"),
+//!     InputToken::Value("x"),
+//!     InputToken::Operator(Math::Mul),
+//!     InputToken::LeftParen,
+//!     InputToken::LeftParen,
+//!     InputToken::Value("2"),
+//!     InputToken::RightParen,
+//!     InputToken::RightParen,
+//! ])
+//! .unwrap();
+//! assert_eq!(postfix.len(), 4);
+//! assert_eq!(warnings, [
+//!     Warning::FunctionWithoutParens { pos: 0 },
+//!     Warning::RedundantParens { open_pos: 4, close_pos: 6 },
+//!     Warning::RedundantParens { open_pos: 3, close_pos: 7 },
+//! ]);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{to_postfix, InputToken, Operator, OutputToken, ParenMissmatchError};
+
+type WarningResult<V, F, O> =
+    Result<(Vec<OutputToken<V, F, O>>, Vec<Warning>), ParenMissmatchError>;
+
+/// The nesting depth of parentheses above which `Warning::DeepNesting` is reported
+pub const MAX_NESTING: usize = 8;
+
+/// A suspicious but valid construct
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Warning {
+    /// The function is not followed by a left parenthesis. Its argument ends at the first
+    /// operator, which may be unexpected.
+    FunctionWithoutParens {
+        /// The position of the function
+        pos: usize,
+    },
+    /// The parentheses contain a single value or another pair of parentheses only.
+    RedundantParens {
+        /// The position of the left parenthesis
+        open_pos: usize,
+        /// The position of the right parenthesis
+        close_pos: usize,
+    },
+    /// The parentheses are nested deeper than `MAX_NESTING`.
+    DeepNesting {
+        /// The position of the first left parenthesis above the limit
+        pos: usize,
+        /// The nesting depth at `pos`
+        depth: usize,
+    },
+}
+
+impl core::fmt::Display for Warning {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Warning::FunctionWithoutParens { pos } => {
+                write!(f, "Function at position {pos} is not followed by '('")
+            }
+            Warning::RedundantParens {
+                open_pos,
+                close_pos,
+            } => write!(
+                f,
+                "Parentheses at positions {open_pos} and {close_pos} are redundant"
+            ),
+            Warning::DeepNesting { pos, depth } => {
+                write!(
+                    f,
+                    "Parentheses at position {pos} are nested {depth} levels deep"
+                )
+            }
+        }
+    }
+}
+
+/// A pair of parentheses that is not closed yet
+struct Group {
+    open_pos: usize,
+    /// True if the parentheses belong to a function call
+    call: bool,
+    /// The number of tokens inside of the parentheses
+    tokens: usize,
+    /// True if the last token inside of the parentheses is a value
+    value: bool,
+}
+
+/// Collects warnings while the tokens pass through.
+#[derive(Default)]
+struct Lint {
+    warnings: Vec<Warning>,
+    groups: Vec<Group>,
+    /// The position of the previous token if it is a function
+    function: Option<usize>,
+    /// The positions of the most recently closed parentheses
+    closed: Option<(usize, usize)>,
+}
+
+impl Lint {
+    fn check<V, F, O>(&mut self, pos: usize, token: &InputToken<V, F, O>) {
+        let function = self.function.take();
+        if let (Some(function), false) = (function, matches!(token, InputToken::LeftParen)) {
+            self.warnings
+                .push(Warning::FunctionWithoutParens { pos: function });
+        }
+        if let InputToken::RightParen = token {
+            let Some(group) = self.groups.pop() else {
+                return;
+            };
+            let nested = self.closed == Some((group.open_pos + 1, pos - 1));
+            if !group.call && ((group.tokens == 1 && group.value) || nested) {
+                self.warnings.push(Warning::RedundantParens {
+                    open_pos: group.open_pos,
+                    close_pos: pos,
+                });
+            }
+            self.closed = Some((group.open_pos, pos));
+        }
+        if let Some(group) = self.groups.last_mut() {
+            group.tokens += 1;
+            group.value = matches!(token, InputToken::Value(_));
+        }
+        match token {
+            InputToken::Function(_) => self.function = Some(pos),
+            InputToken::LeftParen => {
+                self.groups.push(Group {
+                    open_pos: pos,
+                    call: function.is_some(),
+                    tokens: 0,
+                    value: false,
+                });
+                if self.groups.len() == MAX_NESTING + 1 {
+                    self.warnings.push(Warning::DeepNesting {
+                        pos,
+                        depth: self.groups.len(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(mut self) -> Vec<Warning> {
+        if let Some(function) = self.function {
+            self.warnings
+                .push(Warning::FunctionWithoutParens { pos: function });
+        }
+        self.warnings
+    }
+}
+
+/// Converts an infix expression like `to_postfix` and returns the warnings for it in the order
+/// they were found. Redundant parentheses are reported at their right parenthesis.
+pub fn to_postfix_with_warnings<V, F, O>(
+    infix: impl IntoIterator<Item = InputToken<V, F, O>>,
+) -> WarningResult<V, F, O>
+where
+    O: Operator,
+{
+    let mut lint = Lint::default();
+    let postfix = to_postfix(infix.into_iter().enumerate().map(|(pos, token)| {
+        lint.check(pos, &token);
+        token
+    }))?;
+    Ok((postfix, lint.finish()))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::{to_postfix_with_warnings, Warning, MAX_NESTING};
+    use crate::{op::Math, InputToken};
+
+    #[test]
+    fn calls_and_nesting() {
+        // f ( x ) + ( ( ... ( 1 + 2 ) ... ) )
+        let mut infix = vec![
+            InputToken::Function("f"),
+            InputToken::LeftParen,
+            InputToken::Value(0),
+            InputToken::RightParen,
+            InputToken::Operator(Math::Add),
+        ];
+        infix.extend((0..=MAX_NESTING).map(|_| InputToken::LeftParen));
+        infix.extend([
+            InputToken::Value(1),
+            InputToken::Operator(Math::Add),
+            InputToken::Value(2),
+        ]);
+        infix.extend((0..=MAX_NESTING).map(|_| InputToken::RightParen));
+        let (_, warnings) = to_postfix_with_warnings(infix).expect("valid expression");
+        assert_eq!(
+            warnings[0],
+            Warning::DeepNesting {
+                pos: 5 + MAX_NESTING,
+                depth: MAX_NESTING + 1
+            }
+        );
+        // Every pair but the innermost one only contains another pair
+        assert_eq!(warnings.len(), 1 + MAX_NESTING);
+    }
+}
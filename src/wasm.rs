@@ -0,0 +1,143 @@
+//! A text based interface for JavaScript.
+//!
+//! With the `wasm` feature the functions of this module are exported with `wasm-bindgen`. They
+//! take an infix expression as text and return the postfix expression as RPN text or as JSON, so
+//! a browser can use the crate without a Rust wrapper.
+//!
+//! Expressions consist of numbers, variables, function calls like `max(a, 2)`, parentheses and
+//! the operators of `op::unicode`, i.e. `+ - * / ** ^ < <= == != >= > && || !` or `×`. A `-`
+//! directly in front of a number is part of the number if a value is expected, i.e. in
+//! `-2 * (3 - -1)`.
+//!
+//! ```rust
+//! use gyard::wasm::{infix_to_json, infix_to_rpn};
+//!
+//! assert_eq!(infix_to_rpn("1 + max(a, 2) * 3").as_deref(), Ok("1 a 2 max 3 * +"));
+//! assert_eq!(
+//!     infix_to_json("max(a, -2)").as_deref(),
+//!     Ok(r#"[{"value":"a"},{"value":"-2"},{"arity":2,"function":"max"}]"#)
+//! );
+//! assert_eq!(
+//!     infix_to_rpn("1 + $"),
+//!     Err("Unexpected character '$' at offset 4".to_string())
+//! );
+//! ```
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    op::{
+        unicode::{symbol_prefix, Symbol},
+        All,
+    },
+    rpn::Rpn,
+    to_postfix, to_postfix_with_arity, InputToken, OutputToken,
+};
+
+type Token = InputToken<String, String, All>;
+
+/// Converts an infix expression into the RPN text format of `rpn::Rpn`.
+#[wasm_bindgen(js_name = infixToRpn)]
+pub fn infix_to_rpn(expression: &str) -> Result<String, String> {
+    let postfix = to_postfix(tokenize(expression)?).map_err(|error| error.to_string())?;
+    Ok(Rpn(&postfix).to_string())
+}
+
+/// Converts an infix expression into a JSON array of postfix tokens. Values are written as
+/// `{"value": "1"}`, operators as `{"operator": "+"}` and function calls as
+/// `{"function": "max", "arity": 2}`.
+#[wasm_bindgen(js_name = infixToJson)]
+pub fn infix_to_json(expression: &str) -> Result<String, String> {
+    let postfix =
+        to_postfix_with_arity(tokenize(expression)?).map_err(|error| error.to_string())?;
+    let tokens = postfix
+        .into_iter()
+        .map(|token| match token {
+            OutputToken::Value(value) => json!({ "value": value }),
+            OutputToken::Function(call) => {
+                json!({ "function": call.function, "arity": call.arity })
+            }
+            OutputToken::Operator(operator) => json!({ "operator": operator.to_string() }),
+        })
+        .collect();
+    Ok(Value::Array(tokens).to_string())
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while let Some(c) = expression[pos..].chars().next() {
+        let rest = &expression[pos..];
+        if c.is_whitespace() {
+            pos += c.len_utf8();
+            continue;
+        }
+        let expects_value = matches!(
+            tokens.last(),
+            None | Some(InputToken::LeftParen | InputToken::ArgSeparator | InputToken::Operator(_))
+        );
+        let negative = c == '-'
+            && expects_value
+            && rest[1..].starts_with(|c: char| c.is_ascii_digit() || c == '.');
+        let (token, len) = if c.is_ascii_digit() || c == '.' || negative {
+            let len = rest[1..]
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .map_or(rest.len(), |len| len + 1);
+            (InputToken::Value(rest[..len].to_string()), len)
+        } else if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let name = rest[..len].to_string();
+            if rest[len..].trim_start().starts_with('(') {
+                (InputToken::Function(name), len)
+            } else {
+                (InputToken::Value(name), len)
+            }
+        } else {
+            match c {
+                '(' => (InputToken::LeftParen, 1),
+                ')' => (InputToken::RightParen, 1),
+                ',' => (InputToken::ArgSeparator, 1),
+                _ => match symbol_prefix(rest) {
+                    Some((Symbol::Operator(operator), len)) => {
+                        (InputToken::Operator(operator), len)
+                    }
+                    Some((Symbol::Function(function), len)) => {
+                        (InputToken::Function(function.to_string()), len)
+                    }
+                    None => return Err(format!("Unexpected character '{c}' at offset {pos}")),
+                },
+            }
+        };
+        tokens.push(token);
+        pos += len;
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{infix_to_json, infix_to_rpn};
+
+    #[test]
+    fn operators_and_errors() {
+        assert_eq!(
+            infix_to_rpn("-1.5 - x ** 2 <= 3 && !b || f()").as_deref(),
+            Ok("-1.5 x 2 ** - 3 <= b ! && f ||")
+        );
+        assert_eq!(
+            infix_to_json("2 ^ y").as_deref(),
+            Ok(r#"[{"value":"2"},{"value":"y"},{"operator":"^"}]"#)
+        );
+        assert_eq!(infix_to_rpn("2 × √(y)").as_deref(), Ok("2 y sqrt *"));
+        assert!(infix_to_rpn("(1 + 2").is_err());
+    }
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "derive")]
+
+use gyard::{op::Logical, InputToken, IntoInputToken};
+
+#[derive(IntoInputToken)]
+#[expect(dead_code, reason = "Parentheses ignore their fields")]
+#[token(value = i64, operator = Logical)]
+enum Token {
+    #[token(value)]
+    Number { value: i32 },
+    #[token(value = 1)]
+    True,
+    #[token(operator)]
+    Op(Logical),
+    #[token(left_paren)]
+    Open(usize),
+    #[token(right_paren)]
+    Close { pos: usize },
+}
+
+#[test]
+fn converts_all_variant_kinds() {
+    let tokens = [
+        Token::Open(0),
+        Token::Number { value: 2 },
+        Token::Op(Logical::Or),
+        Token::True,
+        Token::Close { pos: 4 },
+    ];
+    let infix: Vec<InputToken<i64, (), Logical>> = tokens.into_iter().map(Into::into).collect();
+    assert_eq!(
+        infix,
+        [
+            InputToken::LeftParen,
+            InputToken::Value(2),
+            InputToken::Operator(Logical::Or),
+            InputToken::Value(1),
+            InputToken::RightParen,
+        ]
+    );
+}